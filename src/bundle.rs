@@ -0,0 +1,235 @@
+use std::collections::BTreeMap;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use crate::storages;
+
+use chrono::{DateTime, FixedOffset};
+use serde::{Serialize, Deserialize};
+
+
+pub const FORMAT: &str = "unist-bundle";
+/// Bumped whenever a bundle could no longer be read by an older unist
+pub const VERSION: u32 = 1;
+
+/// Everything unist keeps on a machine, as one JSON document
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Bundle {
+    pub format: String,
+    pub version: u32,
+    pub created_at: String,
+    /// By part (`tasks`, `config`), any of them may be missing
+    pub files: BTreeMap<String, BundledFile>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BundledFile {
+    /// File name it was packed from, the extension tells the tasks format
+    pub name: String,
+    pub content: String,
+}
+
+/// Where each part lives on this machine
+pub struct Locations {
+    pub tasks: PathBuf,
+    pub config: Option<PathBuf>,
+}
+
+impl Locations {
+    fn parts(&self) -> Vec<(&'static str, &Path)> {
+        let mut parts = vec![("tasks", self.tasks.as_path())];
+        if let Some(config) = &self.config {
+            parts.push(("config", config.as_path()));
+        }
+        parts
+    }
+}
+
+/// A file an import is about to write
+#[derive(Debug)]
+pub struct Restore {
+    pub part: String,
+    pub target: PathBuf,
+    pub content: String,
+    /// Something different is already there
+    pub overwrites: bool,
+}
+
+impl Bundle {
+    /// Packs the parts that exist, skipping the rest
+    pub fn pack(locations: &Locations, now: &DateTime<FixedOffset>) -> io::Result<Self> {
+        let mut files = BTreeMap::new();
+        for (part, path) in locations.parts() {
+            if !path.is_file() {
+                continue;
+            }
+            let name = path.file_name().map(|x| x.to_string_lossy().to_string()).unwrap_or_default();
+            files.insert(part.to_string(), BundledFile { name, content: fs::read_to_string(path)? });
+        }
+        Ok(Self {
+            format: FORMAT.to_string(),
+            version: VERSION,
+            created_at: now.to_rfc3339(),
+            files,
+        })
+    }
+
+    pub fn to_json(&self) -> String {
+        let mut content = serde_json::to_string_pretty(self).unwrap();
+        content.push('\n');
+        content
+    }
+
+    pub fn from_json(content: &str) -> io::Result<Self> {
+        let bundle = serde_json::from_str::<Self>(content)
+            .map_err(|e| io::Error::other(format!("Not a unist bundle: {e}")))?;
+        if bundle.format != FORMAT {
+            return Err(io::Error::other(format!("Not a unist bundle: the format is \"{}\"", bundle.format)));
+        }
+        if bundle.version > VERSION {
+            return Err(io::Error::other(format!(
+                "The bundle is version {}, this unist reads up to {VERSION}. Update unist first.",
+                bundle.version,
+            )));
+        }
+        Ok(bundle)
+    }
+
+    /// What importing would write, without touching anything.
+    /// Tasks packed in another format than the local file are converted.
+    pub fn plan_import(&self, locations: &Locations) -> io::Result<Vec<Restore>> {
+        let mut restores = vec![];
+        for (part, target) in locations.parts() {
+            let Some(file) = self.files.get(part) else { continue };
+            let content = match part {
+                "tasks" => convert_tasks(file, target)?,
+                _ => file.content.clone(),
+            };
+            // A freshly prepared, still empty tasks file has nothing to lose
+            let existing = fs::read_to_string(target).unwrap_or_default();
+            restores.push(Restore {
+                part: part.to_string(),
+                target: target.to_path_buf(),
+                overwrites: !existing.trim().is_empty() && existing != content,
+                content,
+            });
+        }
+        Ok(restores)
+    }
+}
+
+/// The bundled tasks as the file at `target` would store them
+fn convert_tasks(file: &BundledFile, target: &Path) -> io::Result<String> {
+    let extension = |path: &Path| path.extension().map(|x| x.to_ascii_lowercase());
+    if extension(Path::new(&file.name)) == extension(target) {
+        return Ok(file.content.clone());
+    }
+
+    let dir = tempfile::tempdir()?;
+    let source = dir.path().join(&file.name);
+    fs::write(&source, &file.content)?;
+    let tasks = storages::open(source).read_without_ids()?;
+
+    let converted = dir.path().join(target.file_name().unwrap_or("tasks.toml".as_ref()));
+    storages::open(converted.clone()).write(&tasks)?;
+    fs::read_to_string(converted)
+}
+
+/// Writes the restores the way the storages write, each file replaced at once after a copy
+/// of what was there went to `<file>.bak`. Returns where the copies went.
+pub fn apply(restores: &[Restore]) -> io::Result<Vec<PathBuf>> {
+    let mut backups = vec![];
+    for restore in restores {
+        if let Some(dir) = restore.target.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        if restore.target.is_file() {
+            backups.push(match restore.part.as_str() {
+                "tasks" => storages::open(restore.target.clone()).backup()?,
+                _ => storages::backup_file(&restore.target)?,
+            });
+        }
+        storages::write_atomically(&restore.target, &restore.content)?;
+    }
+    Ok(backups)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const GOLDEN: &str = include_str!("../tests/fixtures/golden.toml");
+
+    fn now() -> DateTime<FixedOffset> {
+        DateTime::parse_from_rfc3339("2025-05-12T09:00:00+02:00").unwrap()
+    }
+
+    fn locations(dir: &Path, tasks: &str) -> Locations {
+        Locations {
+            tasks: dir.join("data").join(tasks),
+            config: Some(dir.join("config").join("config.toml")),
+        }
+    }
+
+    #[test]
+    fn round_trip_to_another_machine() {
+        let old = tempfile::tempdir().unwrap();
+        let from = locations(old.path(), "tasks.toml");
+        fs::create_dir_all(from.tasks.parent().unwrap()).unwrap();
+        fs::write(&from.tasks, GOLDEN).unwrap();
+        fs::create_dir_all(from.config.as_ref().unwrap().parent().unwrap()).unwrap();
+        fs::write(from.config.as_ref().unwrap(), "[ui]\nfocus_count = 5\n").unwrap();
+
+        let json = Bundle::pack(&from, &now()).unwrap().to_json();
+        let bundle = Bundle::from_json(&json).unwrap();
+        assert_eq!((bundle.version, bundle.files.len()), (VERSION, 2));
+
+        let new = tempfile::tempdir().unwrap();
+        let to = locations(new.path(), "tasks.toml");
+        let restores = bundle.plan_import(&to).unwrap();
+        assert!(restores.iter().all(|x| !x.overwrites));
+        assert_eq!(apply(&restores).unwrap(), Vec::<PathBuf>::new());
+        assert_eq!(fs::read_to_string(&to.tasks).unwrap(), GOLDEN);
+        assert_eq!(fs::read_to_string(to.config.as_ref().unwrap()).unwrap(), "[ui]\nfocus_count = 5\n");
+
+        // Importing again changes nothing, something else there would be overwritten
+        assert!(bundle.plan_import(&to).unwrap().iter().all(|x| !x.overwrites));
+        fs::write(to.config.as_ref().unwrap(), "[ui]\n").unwrap();
+        let restores = bundle.plan_import(&to).unwrap();
+        assert_eq!(restores.iter().filter(|x| x.overwrites).map(|x| x.part.as_str()).collect::<Vec<_>>(), ["config"]);
+
+        // What was there is kept aside
+        let backups = apply(&restores).unwrap();
+        assert_eq!(backups.len(), 2);
+        assert_eq!(fs::read_to_string(&backups[1]).unwrap(), "[ui]\n");
+        assert_eq!(fs::read_to_string(to.config.as_ref().unwrap()).unwrap(), "[ui]\nfocus_count = 5\n");
+    }
+
+    #[test]
+    fn partial_bundles_and_other_formats_import_cleanly() {
+        let old = tempfile::tempdir().unwrap();
+        let from = locations(old.path(), "tasks.toml");
+        fs::create_dir_all(from.tasks.parent().unwrap()).unwrap();
+        fs::write(&from.tasks, GOLDEN).unwrap();
+        let bundle = Bundle::from_json(&Bundle::pack(&from, &now()).unwrap().to_json()).unwrap();
+        assert_eq!(bundle.files.keys().collect::<Vec<_>>(), ["tasks"]);
+
+        let new = tempfile::tempdir().unwrap();
+        let to = locations(new.path(), "tasks.json");
+        apply(&bundle.plan_import(&to).unwrap()).unwrap();
+        assert!(!to.config.as_ref().unwrap().exists());
+        let tasks = storages::open(to.tasks.clone()).read().unwrap();
+        assert_eq!(tasks, storages::open(from.tasks.clone()).read().unwrap());
+    }
+
+    #[test]
+    fn newer_or_foreign_documents_are_refused() {
+        let bundle = |format: &str, version| format!(r#"{{"format": "{format}", "version": {version}, "created_at": "", "files": {{}}}}"#);
+
+        assert!(Bundle::from_json(&bundle(FORMAT, VERSION)).is_ok());
+        assert!(Bundle::from_json(&bundle(FORMAT, VERSION + 1)).unwrap_err().to_string().contains("Update unist"));
+        assert!(Bundle::from_json(&bundle("something-else", 1)).is_err());
+        assert!(Bundle::from_json("[tasks]").is_err());
+    }
+}
@@ -0,0 +1,199 @@
+use std::io::{self, stdin, Read};
+
+use chrono::{DateTime, FixedOffset, Local, NaiveTime};
+use clap::{Args, ValueEnum};
+
+use super::{task_line, warn_day_overloads};
+use crate::config::Config;
+use crate::readers::EditorTaskReader;
+use crate::storages::TaskStorage;
+use crate::uni::dates::parse_deadline;
+use crate::uni::task::{assign_ids, Task};
+
+
+#[derive(ValueEnum, Clone, Copy, Default)]
+pub enum StdinFormat {
+    /// One task per line: subject, name, deadline and description between separators
+    #[default]
+    Lines,
+    /// Documents as the editor shows a task, front matter between --- lines
+    Yaml,
+    /// Documents as the editor shows a task, front matter between +++ lines
+    Toml,
+}
+
+#[derive(Args)]
+pub struct AddArgs {
+    /// Tasks as "Subject | Name | deadline | description", the last two may be left out
+    pub tasks: Vec<String>,
+    /// Read the tasks from standard input
+    #[arg(long)]
+    pub stdin: bool,
+    #[arg(long, value_enum, default_value_t, requires = "stdin")]
+    pub stdin_format: StdinFormat,
+    /// Between the fields of a task line, the configured one by default
+    #[arg(long)]
+    pub separator: Option<String>,
+    /// Add the valid tasks even when others are not; by default nothing is added then
+    #[arg(long)]
+    pub skip_invalid: bool,
+}
+
+/// A task line, the deadline as `parse_deadline` takes it
+pub fn parse_line(
+    line: &str,
+    separator: &str,
+    now: &DateTime<FixedOffset>,
+    default_time: NaiveTime,
+) -> Result<Task, String> {
+    let mut fields = line.splitn(4, separator).map(str::trim);
+    let subject = fields.next().unwrap_or_default();
+    let name = fields.next().unwrap_or_default();
+    if subject.is_empty() || name.is_empty() {
+        return Err(format!("Expected at least \"Subject {separator} Name\""));
+    }
+    let time = match fields.next().filter(|x| !x.is_empty()) {
+        Some(deadline) => Some(parse_deadline(deadline, now, default_time)?),
+        None => None,
+    };
+    Ok(Task {
+        subject: subject.to_string(),
+        name: name.to_string(),
+        time,
+        description: fields.next().unwrap_or_default().to_string(),
+        created_at: Some(*now),
+        updated_at: Some(*now),
+        ..Task::default()
+    })
+}
+
+/// Parsed tasks numbered by line or document; blank lines and `#` comments are skipped
+fn parse_input(args: &AddArgs, input: &str, config: &Config, now: &DateTime<FixedOffset>) -> Vec<(usize, Result<Task, String>)> {
+    let separator = args.separator.as_deref().unwrap_or(&config.add.separator);
+    let lines = |lines: Vec<&str>| {
+        lines
+            .into_iter()
+            .enumerate()
+            .filter(|(_, x)| !x.trim().is_empty() && !x.trim_start().starts_with('#'))
+            .map(|(i, x)| (i + 1, parse_line(x, separator, now, config.deadline_time())))
+            .collect()
+    };
+    let documents = |delimiter| {
+        EditorTaskReader::from_str_documents(input, delimiter, config.deadline_time())
+            .into_iter()
+            .enumerate()
+            .map(|(i, x)| {
+                let stamped = |task| Task { created_at: Some(*now), updated_at: Some(*now), ..task };
+                (i + 1, x.map(stamped))
+            })
+            .collect()
+    };
+    match (args.stdin, args.stdin_format) {
+        (false, _) => lines(args.tasks.iter().map(String::as_str).collect()),
+        (true, StdinFormat::Lines) => lines(input.lines().collect()),
+        (true, StdinFormat::Yaml) => documents("---"),
+        (true, StdinFormat::Toml) => documents("+++"),
+    }
+}
+
+/// Adds every task in one write, or none of them when one is invalid
+pub fn run(storage: &dyn TaskStorage, args: &AddArgs, config: &Config) -> io::Result<()> {
+    if args.stdin != args.tasks.is_empty() {
+        return Err(io::Error::other("Give the tasks either as arguments or with --stdin"));
+    }
+    let mut input = String::new();
+    if args.stdin {
+        stdin().read_to_string(&mut input)?;
+    }
+    let now = Local::now().fixed_offset();
+    let parsed = parse_input(args, &input, config, &now);
+
+    let mut tasks = storage.read()?;
+    let original = tasks.clone();
+    let before = tasks.len();
+    let mut invalid = 0;
+    for (number, result) in &parsed {
+        match result {
+            Ok(task) => tasks.push(task.clone()),
+            Err(error) => {
+                invalid += 1;
+                eprintln!("{number}: {error}");
+            },
+        }
+    }
+    if invalid > 0 && !args.skip_invalid {
+        return Err(io::Error::other(format!(
+            "{invalid} of {} task(s) invalid, nothing added; --skip-invalid adds the rest",
+            parsed.len()
+        )));
+    }
+
+    assign_ids(&mut tasks);
+    let names = config.ui.date_names();
+    let added = parsed.iter().filter(|(_, x)| x.is_ok()).map(|(number, _)| number);
+    for (number, task) in added.zip(&tasks[before..]) {
+        println!("{number}: added {}", task_line(task, &config.ui.date_display_format, &names, &config.ui.display_zone()).trim_start());
+    }
+    if tasks.len() > before {
+        warn_day_overloads(&original, &tasks, config);
+        storage.write(&tasks)?;
+    }
+    println!("{} task(s) added, {invalid} skipped", tasks.len() - before);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use chrono::TimeDelta;
+
+    use crate::uni::dates::END_OF_DAY;
+
+    fn now() -> DateTime<FixedOffset> {
+        DateTime::parse_from_rfc3339("2025-05-12T09:00:00+02:00").unwrap()
+    }
+
+    #[test]
+    fn lines_take_optional_deadline_and_description() {
+        let task = parse_line("Algorithms | HW5 | +6d | read chapters 7-8 | twice", "|", &now(), END_OF_DAY).unwrap();
+        assert_eq!((task.subject.as_str(), task.name.as_str()), ("Algorithms", "HW5"));
+        assert_eq!(task.time, Some(now() + TimeDelta::days(6)));
+        assert_eq!(task.description, "read chapters 7-8 | twice");
+        assert_eq!(task.created_at, Some(now()));
+
+        let bare = parse_line("Physics;Lab", ";", &now(), END_OF_DAY).unwrap();
+        assert_eq!((bare.time, bare.description.as_str()), (None, ""));
+
+        assert!(parse_line("Physics", "|", &now(), END_OF_DAY).is_err());
+        assert_eq!(parse_line("Physics | Lab | soon", "|", &now(), END_OF_DAY).unwrap_err(), "Unknown date \"soon\"");
+    }
+
+    #[test]
+    fn input_is_numbered_by_line_or_document() {
+        let args = |format| AddArgs {
+            tasks: vec![],
+            stdin: true,
+            stdin_format: format,
+            separator: None,
+            skip_invalid: false,
+        };
+        let config = Config::default();
+
+        let lines = "# syllabus\nAlgo | HW1 | 2025-05-20\n\nAlgo | HW2 | whenever\n";
+        let parsed = parse_input(&args(StdinFormat::Lines), lines, &config, &now());
+        assert_eq!(parsed.iter().map(|(x, _)| *x).collect::<Vec<_>>(), [2, 4]);
+        assert!(parsed[0].1.is_ok() && parsed[1].1.is_err());
+
+        let documents = "---\nname: HW1\nsubject: Algo\ntime: 2025-05-20\n---\nRead\n---\nname: HW2\nsubject: Algo\n---\n";
+        let parsed = parse_input(&args(StdinFormat::Yaml), documents, &config, &now());
+        let tasks = parsed.into_iter().map(|(_, x)| x.unwrap()).collect::<Vec<_>>();
+        assert_eq!(tasks[0].description, "Read");
+        assert!(tasks[0].time.is_some() && tasks[1].time.is_none());
+        assert_eq!(tasks[1].created_at, Some(now()));
+
+        let toml = "+++\nname = \"HW1\"\nsubject = \"Algo\"\ntime = \"next week\"\n+++\n";
+        let parsed = parse_input(&args(StdinFormat::Toml), toml, &config, &now());
+        assert_eq!(parsed[0].1.as_ref().unwrap_err(), "Unknown deadline \"next week\"");
+    }
+}
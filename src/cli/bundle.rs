@@ -0,0 +1,78 @@
+use std::fs;
+use std::io::{self, stdin, IsTerminal};
+use std::path::PathBuf;
+
+use chrono::Local;
+use clap::{Args, Subcommand};
+
+use super::ask_with_prefix;
+use crate::bundle::{self, Bundle, Locations};
+
+
+#[derive(Args)]
+pub struct BundleArgs {
+    #[command(subcommand)]
+    pub action: BundleAction,
+}
+
+#[derive(Subcommand)]
+pub enum BundleAction {
+    /// Pack the tasks and the config into one file
+    Export {
+        path: PathBuf,
+    },
+    /// Unpack a bundle into this machine's data and config directories
+    Import {
+        path: PathBuf,
+        /// Overwrite existing files without asking
+        #[arg(long, short)]
+        yes: bool,
+    },
+}
+
+pub fn run(args: &BundleArgs, locations: &Locations) -> io::Result<()> {
+    match &args.action {
+        BundleAction::Export { path } => {
+            let bundle = Bundle::pack(locations, &Local::now().fixed_offset())?;
+            fs::write(path, bundle.to_json())?;
+            let parts = bundle.files.keys().cloned().collect::<Vec<_>>();
+            println!("Packed {} into {}", parts.join(" and "), path.display());
+            Ok(())
+        },
+        BundleAction::Import { path, yes } => import(path, *yes, locations),
+    }
+}
+
+fn import(path: &PathBuf, yes: bool, locations: &Locations) -> io::Result<()> {
+    let bundle = Bundle::from_json(&fs::read_to_string(path)?)?;
+    let restores = bundle.plan_import(locations)?;
+    if restores.is_empty() {
+        println!("The bundle is empty, nothing to import.");
+        return Ok(());
+    }
+
+    let overwritten = restores.iter().filter(|x| x.overwrites).collect::<Vec<_>>();
+    if !overwritten.is_empty() && !yes {
+        println!("Importing replaces:");
+        for restore in &overwritten {
+            println!("  {} ({})", restore.target.display(), restore.part);
+        }
+        if !stdin().is_terminal() {
+            return Err(io::Error::other("Refusing to overwrite without a terminal, pass --yes"));
+        }
+        let answer = ask_with_prefix("Overwrite them? (y/N): ");
+        if !matches!(answer.trim().to_lowercase().as_str(), "y" | "yes") {
+            println!("Nothing imported.");
+            return Ok(());
+        }
+    }
+
+    let backups = bundle::apply(&restores)?;
+    for restore in &restores {
+        println!("Imported {} to {}", restore.part, restore.target.display());
+    }
+    for backup in &backups {
+        println!("Previous version kept at {}", backup.display());
+    }
+    Ok(())
+}
@@ -0,0 +1,55 @@
+use std::io;
+
+use chrono::Local;
+use clap::Args;
+
+use crate::config::{CalendarTaskFilter, Config};
+use crate::storages::TaskStorage;
+use crate::uni::calendar::Calendar;
+use crate::uni::policy::StatusPolicy;
+
+
+#[derive(Args)]
+pub struct CalendarArgs {
+    /// List the month's tasks under the grid
+    #[arg(long)]
+    pub agenda: bool,
+    /// Which tasks the grid counts and the agenda lists,
+    /// `calendar_include` in the config when not given
+    #[arg(long, value_name = "FILTER")]
+    pub calendar_include: Option<CalendarTaskFilter>,
+    /// Keep completed tasks, short for `--calendar-include completed`
+    #[arg(long, conflicts_with = "calendar_include")]
+    pub all: bool,
+    /// Show ISO week numbers, also on with `calendar_week_numbers` in the config
+    #[arg(long)]
+    pub week_numbers: bool,
+    /// End the agenda with starred tasks that have no deadline,
+    /// also on with `calendar_anytime` in the config
+    #[arg(long, requires = "agenda")]
+    pub anytime: bool,
+}
+
+pub fn run(storage: &dyn TaskStorage, args: &CalendarArgs, config: &Config) -> io::Result<()> {
+    let tasks = storage.read()?;
+    let filter = match args.all {
+        true => CalendarTaskFilter::Completed,
+        false => args.calendar_include.unwrap_or(config.ui.calendar_include),
+    };
+    let calendar = Calendar::new(Local::now(), &tasks)
+        .with_filter(filter)
+        .with_week_numbers(args.week_numbers || config.ui.calendar_week_numbers)
+        .with_anytime(args.anytime || config.ui.calendar_anytime)
+        .with_status_symbols(config.ui.status_symbols)
+        .with_names(config.ui.date_names())
+        .with_policy(StatusPolicy::from_config(config))
+        .with_day_limit(config.validation.max_tasks_per_day)
+        .with_abbreviations(config.subject_abbrev.clone());
+
+    calendar.render();
+    if args.agenda {
+        println!();
+        calendar.render_agenda();
+    }
+    Ok(())
+}
@@ -0,0 +1,91 @@
+use std::io;
+
+use chrono::Local;
+use clap::Args;
+
+use super::select::Selection;
+use super::{ask_with_prefix, task_line};
+use crate::config::Config;
+use crate::storages::TaskStorage;
+
+
+#[derive(Args)]
+pub struct DeleteArgs {
+    #[command(flatten)]
+    pub selection: Selection,
+    /// Only print what would be removed
+    #[arg(long)]
+    pub dry_run: bool,
+    /// Do not ask for confirmation
+    #[arg(long, short)]
+    pub yes: bool,
+}
+
+pub fn run(storage: &dyn TaskStorage, args: &DeleteArgs, config: &Config) -> io::Result<()> {
+    if args.selection.is_empty() {
+        return Err(io::Error::other("Refusing to delete without any selection"));
+    }
+
+    let mut tasks = storage.read()?;
+    let now = Local::now().fixed_offset();
+    let doomed = args.selection
+        .select_exact(&tasks, &now, &config.subject_abbrev)
+        .iter()
+        .map(|x| x.id)
+        .collect::<Vec<_>>();
+
+    if doomed.is_empty() {
+        println!("No tasks match.");
+        return Ok(());
+    }
+
+    println!("{} task(s) to remove:", doomed.len());
+    let names = config.ui.date_names();
+    for task in tasks.iter().filter(|x| doomed.contains(&x.id)) {
+        println!("{}", task_line(task, &config.ui.date_display_format, &names, &config.ui.display_zone()));
+    }
+
+    if args.dry_run {
+        return Ok(());
+    }
+
+    if !args.yes {
+        let answer = ask_with_prefix("Remove them? (y/N): ");
+        if !matches!(answer.trim().to_lowercase().as_str(), "y" | "yes") {
+            println!("Nothing removed.");
+            return Ok(());
+        }
+    }
+
+    // Tasks only in memory, like the demo ones, have no file to keep a copy of
+    let backup = storage.path().map(|_| storage.backup()).transpose()?;
+    tasks.retain(|x| !doomed.contains(&x.id));
+    storage.write(&tasks)?;
+    match backup {
+        Some(backup) => println!("Removed {} task(s), backup at {}", doomed.len(), backup.display()),
+        None => println!("Removed {} task(s)", doomed.len()),
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::storages::MemoryStorage;
+    use crate::uni::task::Task;
+
+    #[test]
+    fn tasks_without_a_file_are_deleted_without_a_backup() {
+        let task = |id, subject: &str| Task { id, subject: subject.to_string(), ..Task::default() };
+        let storage = MemoryStorage::with_tasks(vec![task(1, "Algo"), task(2, "Physics")]);
+        let args = DeleteArgs {
+            selection: Selection { subject: Some("Algo".to_string()), ..Selection::default() },
+            dry_run: false,
+            yes: true,
+        };
+
+        // The demo's storage, which used to fail the backup before anything was removed
+        assert!(run(&storage, &args, &Config::default()).is_ok());
+    }
+}
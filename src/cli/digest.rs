@@ -0,0 +1,112 @@
+use std::env;
+use std::io::{self, stdout, IsTerminal};
+use std::process;
+
+use chrono::{DateTime, FixedOffset, Local};
+use clap::Args;
+
+use crate::config::Config;
+use crate::storages::TaskStorage;
+use crate::uni::digest::Digest;
+use crate::uni::policy::StatusPolicy;
+
+
+#[derive(Args)]
+pub struct DigestArgs {
+    /// Send the summary as a desktop notification instead of printing it
+    #[arg(long)]
+    pub notify: bool,
+    /// Tasks listed per section before the rest is only counted
+    #[arg(long, default_value_t = 5)]
+    pub max_per_section: usize,
+}
+
+/// Headline with the counts, then each non-empty section.
+/// `color` makes the headings bold and the overdue one red.
+pub fn format_digest(digest: &Digest, now: &DateTime<FixedOffset>, max_per_section: usize, color: bool) -> String {
+    if digest.is_empty() {
+        return "Nothing due today or tomorrow, nothing starred.".to_string();
+    }
+
+    let sections = digest.sections();
+    let headline = sections
+        .iter()
+        .filter(|(_, tasks)| !tasks.is_empty())
+        .map(|(title, tasks)| format!("{} {}", tasks.len(), title.to_lowercase()))
+        .collect::<Vec<_>>()
+        .join(" · ");
+
+    let mut lines = vec![headline];
+    for (title, tasks) in sections.iter().filter(|(_, tasks)| !tasks.is_empty()) {
+        let heading = match (color, *title) {
+            (false, _) => title.to_string(),
+            (true, "Overdue") => format!("\x1b[1;31m{title}\x1b[0m"),
+            (true, _) => format!("\x1b[1m{title}\x1b[0m"),
+        };
+        lines.push(String::new());
+        lines.push(heading);
+        for task in tasks.iter().take(max_per_section) {
+            lines.push(format!("  • {}: {} ({})", task.subject, task.name, task.delta_at(now)));
+        }
+        if tasks.len() > max_per_section {
+            lines.push(format!("  … and {} more", tasks.len() - max_per_section));
+        }
+    }
+    lines.join("\n")
+}
+
+/// https://no-color.org: any non-empty value turns colors off
+fn use_color() -> bool {
+    let no_color = env::var_os("NO_COLOR").is_some_and(|x| !x.is_empty());
+    !no_color && stdout().is_terminal()
+}
+
+fn notify(body: &str) -> io::Result<()> {
+    let status = process::Command::new("notify-send")
+        .args(["--app-name=unist", "unist", body])
+        .status()
+        .map_err(|e| io::Error::other(format!("Could not run notify-send: {e}")))?;
+    if !status.success() {
+        return Err(io::Error::other(format!("notify-send failed: {status}")));
+    }
+    Ok(())
+}
+
+pub fn run(storage: &dyn TaskStorage, args: &DigestArgs, config: &Config) -> io::Result<()> {
+    let tasks = storage.read()?;
+    let now = Local::now().fixed_offset();
+    let digest = Digest::new(&tasks, &now, &StatusPolicy::from_config(config));
+
+    if args.notify {
+        notify(&format_digest(&digest, &now, args.max_per_section, false))
+    } else {
+        println!("{}", format_digest(&digest, &now, args.max_per_section, use_color()));
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::uni::task::Task;
+
+    use chrono::TimeDelta;
+
+    #[test]
+    fn sections_are_capped() {
+        let now = DateTime::parse_from_rfc3339("2025-05-12T09:00:00+02:00").unwrap();
+        let tasks = (1..=4)
+            .map(|x| Task {
+                name: format!("HW{x}"),
+                subject: "Algo".to_string(),
+                time: Some(now - TimeDelta::hours(x)),
+                ..Task::default()
+            })
+            .collect::<Vec<_>>();
+
+        let text = format_digest(&Digest::new(&tasks, &now, &StatusPolicy::default()), &now, 2, false);
+        assert!(text.starts_with("4 overdue\n\nOverdue\n  • Algo: HW4"));
+        assert!(text.ends_with("  … and 2 more"));
+        assert!(!text.contains('\x1b'));
+    }
+}
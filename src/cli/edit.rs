@@ -0,0 +1,50 @@
+use std::io::{self, stdin, IsTerminal};
+
+use chrono::Local;
+use clap::Args;
+
+use super::{confirm_past_deadline, warn_day_overloads};
+use super::select::find_task;
+use crate::config::Config;
+use crate::readers::{self, TaskReader};
+use crate::storages::TaskStorage;
+use crate::uni::validation;
+
+
+#[derive(Args)]
+pub struct EditArgs {
+    /// Task id or (part of) its name
+    pub task: String,
+    /// Accept a deadline in the past without asking
+    #[arg(long)]
+    pub allow_past: bool,
+}
+
+pub fn run(storage: &dyn TaskStorage, reader: &dyn TaskReader, args: &EditArgs, config: &Config) -> io::Result<()> {
+    let mut tasks = storage.read()?;
+    let before = tasks.clone();
+    let id = find_task(&tasks, &args.task).map_err(io::Error::other)?.id;
+    let task = tasks.iter_mut().find(|x| x.id == id).unwrap();
+
+    let original = task.clone();
+    readers::edit(reader, task, config).map_err(|_| io::Error::other("Edit aborted, nothing saved"))?;
+
+    let now = Local::now().fixed_offset();
+    if let Some(warning) = validation::past_deadline_warning(original.time, task.time, &now, &config.validation) {
+        if !confirm_past_deadline(&warning, args.allow_past, stdin().is_terminal())? {
+            task.time = original.time;
+        }
+    }
+
+    if *task == original {
+        println!("No changes.");
+        return Ok(());
+    }
+    task.touch(now);
+    let summary = format!("{}: {}", task.subject, task.name);
+    warn_day_overloads(&before, &tasks, config);
+    // The editor may have been open a while, long enough for another change to the file
+    storage.write_if_unchanged(&tasks, storage.fingerprint())?;
+    println!("Saved {summary}");
+    Ok(())
+}
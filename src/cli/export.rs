@@ -0,0 +1,61 @@
+use std::fs;
+use std::io::{self, stdout, Write};
+use std::path::Path;
+
+use chrono::{Local, TimeDelta};
+use clap::{Args, ValueEnum};
+
+use super::select::Selection;
+use crate::config::Config;
+use crate::storages::TaskStorage;
+use crate::uni::dates::parse_duration;
+use crate::uni::export::{export, ExportFormat, ExportOptions};
+
+
+#[derive(ValueEnum, Clone, Copy)]
+pub enum ExportFormatArg {
+    Markdown,
+    Csv,
+    Plain,
+    /// A printable page with the deadlines by month
+    Html,
+}
+
+#[derive(Args)]
+pub struct ExportArgs {
+    /// File to write, or - for standard output
+    pub path: String,
+    /// Format to write; guessed from the extension by default, plain text for -
+    #[arg(long, value_enum)]
+    pub format: Option<ExportFormatArg>,
+    /// Export completed tasks too, which are left out by default
+    #[arg(long)]
+    pub include_completed: bool,
+    /// Export the tasks completed this recently, e.g. 7d
+    #[arg(long, value_parser = parse_duration)]
+    pub completed_since: Option<TimeDelta>,
+    #[command(flatten)]
+    pub selection: Selection,
+}
+
+pub fn run(storage: &dyn TaskStorage, args: &ExportArgs, config: &Config) -> io::Result<()> {
+    let tasks = storage.read()?;
+    let now = Local::now().fixed_offset();
+    let format = match args.format {
+        Some(ExportFormatArg::Markdown) => ExportFormat::Markdown,
+        Some(ExportFormatArg::Csv) => ExportFormat::Csv,
+        Some(ExportFormatArg::Plain) => ExportFormat::Plain,
+        Some(ExportFormatArg::Html) => ExportFormat::Html,
+        None => ExportFormat::from_path(Path::new(&args.path)),
+    };
+    let options = ExportOptions { include_completed: args.include_completed, completed_since: args.completed_since };
+
+    let selected = args.selection.select(&tasks, &now, &config.subject_abbrev);
+    let (content, count) = export(selected, format, &options, now, &config.ui.date_display_format, &config.ui.date_names(), &config.ui.display_zone(), &config.subject_colors);
+    if args.path == "-" {
+        return stdout().write_all(content.as_bytes());
+    }
+    fs::write(&args.path, content)?;
+    println!("Exported {count} task(s) to {}", args.path);
+    Ok(())
+}
@@ -0,0 +1,85 @@
+use std::fs;
+use std::io::{self, stdin, Read};
+use std::process;
+
+use chrono::Local;
+use clap::{Args, ValueEnum};
+
+use super::warn_day_overloads;
+use crate::config::Config;
+use crate::storages::TaskStorage;
+use crate::uni::ics;
+use crate::uni::import::{import, ImportRules, Source};
+
+
+#[derive(ValueEnum, Clone, Copy, Default)]
+pub enum ImportFormat {
+    /// Any iCalendar file, the first category of an event being its subject
+    #[default]
+    Ics,
+    /// Moodle or Canvas export, course codes mapped with [import.course_map]
+    Moodle,
+}
+
+#[derive(Args)]
+pub struct ImportArgs {
+    /// Calendar file, http(s) URL, or - for standard input
+    pub source: String,
+    #[arg(long, value_enum, default_value_t)]
+    pub format: ImportFormat,
+}
+
+/// The calendar text; URLs are fetched with curl, which every platform ships nowadays
+fn fetch(source: &str) -> io::Result<String> {
+    if source == "-" {
+        let mut text = String::new();
+        stdin().read_to_string(&mut text)?;
+        return Ok(text);
+    }
+    if !source.starts_with("http://") && !source.starts_with("https://") {
+        return fs::read_to_string(source);
+    }
+    let output = process::Command::new("curl")
+        .args(["--fail", "--silent", "--show-error", "--location", source])
+        .output()
+        .map_err(|x| io::Error::other(format!("Could not run curl to fetch {source}: {x}")))?;
+    if !output.status.success() {
+        return Err(io::Error::other(format!(
+            "Could not fetch {source}: {}",
+            String::from_utf8_lossy(&output.stderr).trim()
+        )));
+    }
+    String::from_utf8(output.stdout).map_err(io::Error::other)
+}
+
+pub fn run(storage: &dyn TaskStorage, args: &ImportArgs, config: &Config) -> io::Result<()> {
+    let rules = ImportRules::from_config(&config.import).map_err(io::Error::other)?;
+    let events = ics::parse(&fetch(&args.source)?, config.deadline_time());
+    let source = match args.format {
+        ImportFormat::Ics => Source::Ics,
+        ImportFormat::Moodle => Source::Moodle,
+    };
+
+    let mut tasks = storage.read()?;
+    let original = tasks.clone();
+    let report = import(&mut tasks, &events, &rules, source, &Local::now().fixed_offset());
+    if report.imported + report.updated > 0 {
+        warn_day_overloads(&original, &tasks, config);
+        storage.write(&tasks)?;
+    }
+    println!(
+        "{} event(s): {} imported, {} updated, {} skipped, {} unmapped",
+        events.len(),
+        report.imported,
+        report.updated,
+        report.skipped,
+        report.unmapped.values().sum::<usize>()
+    );
+    if !report.unmapped.is_empty() {
+        println!("Course codes missing from [import.course_map]:");
+        for (code, count) in &report.unmapped {
+            println!("  {code} ({count} event(s))");
+        }
+    }
+    Ok(())
+}
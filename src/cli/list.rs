@@ -0,0 +1,38 @@
+use std::io;
+
+use chrono::Local;
+use clap::Args;
+
+use super::select::Selection;
+use super::{format_task_views, task_line, OutputFormat};
+use crate::config::Config;
+use crate::storages::TaskStorage;
+use crate::uni::policy::StatusPolicy;
+
+
+#[derive(Args)]
+pub struct ListArgs {
+    #[command(flatten)]
+    pub selection: Selection,
+    #[arg(long, value_enum, default_value_t)]
+    pub format: OutputFormat,
+}
+
+pub fn run(storage: &dyn TaskStorage, args: &ListArgs, config: &Config) -> io::Result<()> {
+    let tasks = storage.read()?;
+    let now = Local::now().fixed_offset();
+
+    let selected = args.selection.select(&tasks, &now, &config.subject_abbrev);
+    if let OutputFormat::Text = args.format {
+        let names = config.ui.date_names();
+        for task in selected {
+            println!("{}", task_line(task, &config.ui.date_display_format, &names, &config.ui.display_zone()));
+        }
+        return Ok(());
+    }
+    let json = format_task_views(selected, args.format, &now, &StatusPolicy::from_config(config))?;
+    if !json.is_empty() {
+        println!("{json}");
+    }
+    Ok(())
+}
@@ -0,0 +1,136 @@
+use std::io;
+use std::path::{Path, PathBuf};
+
+use clap::{Args, ValueEnum};
+
+use super::{ask_with_prefix, warn_day_overloads};
+use crate::config::Config;
+use crate::storages::{self, TaskStorage};
+use crate::uni::merge::{self, Prefer};
+use crate::uni::task::Task;
+
+
+#[derive(ValueEnum, Clone, Copy)]
+pub enum PreferArg {
+    Ours,
+    Theirs,
+    Newer,
+}
+
+#[derive(Args)]
+pub struct MergeArgs {
+    /// The tasks file to merge into the current one
+    pub other: PathBuf,
+    /// Resolve conflicts without asking
+    #[arg(long, value_enum)]
+    pub prefer: Option<PreferArg>,
+}
+
+fn ask_theirs(ours: &Task, theirs: &Task) -> bool {
+    println!("Conflicting versions of \"{}: {}\":", ours.subject, ours.name);
+    println!("  ours:   {:?}", ours);
+    println!("  theirs: {:?}", theirs);
+    let answer = ask_with_prefix("Keep (o)urs or (t)heirs? (O/t): ");
+    matches!(answer.trim().to_lowercase().as_str(), "t" | "theirs")
+}
+
+/// Tasks of the file merged in, an error naming it when it can't be read
+fn read_other(path: &Path) -> io::Result<Vec<Task>> {
+    // A storage reads a missing file as one without tasks
+    if !path.is_file() {
+        return Err(io::Error::new(io::ErrorKind::NotFound, format!("Could not merge {}: no such file", path.display())));
+    }
+    storages::open(path.to_path_buf())
+        .read_without_ids()
+        .map_err(|x| io::Error::new(x.kind(), format!("Could not merge {}: {x}", path.display())))
+}
+
+pub fn run(storage: &dyn TaskStorage, args: &MergeArgs, config: &Config) -> io::Result<()> {
+    let mut tasks = storage.read()?;
+    let before = tasks.clone();
+    let theirs = read_other(&args.other)?;
+
+    let (prefer, interactive) = match args.prefer {
+        Some(PreferArg::Ours) => (Prefer::Ours, false),
+        Some(PreferArg::Theirs) => (Prefer::Theirs, false),
+        Some(PreferArg::Newer) => (Prefer::Newer, false),
+        None => (Prefer::Newer, true),
+    };
+
+    let report = merge::merge(&mut tasks, theirs, prefer, |ours, theirs| {
+        interactive && ask_theirs(ours, theirs)
+    });
+
+    if report.added + report.updated > 0 {
+        warn_day_overloads(&before, &tasks, config);
+        storage.write(&tasks)?;
+    }
+    println!(
+        "Merged {}: {} added, {} updated, {} skipped",
+        args.other.display(),
+        report.added,
+        report.updated,
+        report.skipped
+    );
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::fs;
+
+    use chrono::{DateTime, TimeDelta};
+
+    use crate::storages::TomlStorage;
+    use crate::uni::anchors::Anchor;
+
+    #[test]
+    fn unreadable_files_are_an_error_naming_them() {
+        let dir = tempfile::tempdir().unwrap();
+        for (file, content) in [("garbled.toml", "[[tasks]]\nname =\n"), ("garbled.json", "{{")] {
+            let path = dir.path().join(file);
+            fs::write(&path, content).unwrap();
+            let error = read_other(&path).unwrap_err().to_string();
+            assert!(error.starts_with(&format!("Could not merge {}", path.display())), "{error}");
+        }
+        assert!(read_other(&dir.path().join("missing.toml")).is_err());
+    }
+
+    #[test]
+    fn anchors_follow_their_tasks_into_our_numbering() {
+        let at = |time| Some(DateTime::parse_from_rfc3339(time).unwrap());
+        let task = |id, subject: &str, name: &str| Task { id, subject: subject.to_string(), name: name.to_string(), ..Task::default() };
+        let anchored = |id, subject, name, anchor, days| Task {
+            anchor: Some(Anchor { task: anchor, offset: TimeDelta::days(days) }),
+            ..task(id, subject, name)
+        };
+        let dir = tempfile::tempdir().unwrap();
+        let ours = TomlStorage::new(dir.path().join("ours.toml"));
+        ours.write(&vec![
+            Task { time: at("2025-06-30T18:00:00+02:00"), ..task(1, "Algo", "Submission") },
+            anchored(2, "Algo", "Outline", 1, -2),
+        ]).unwrap();
+        let other = dir.path().join("theirs.toml");
+        TomlStorage::new(other.clone()).write(&vec![
+            Task { time: at("2025-07-15T12:00:00+02:00"), ..task(1, "Thesis", "Final") },
+            anchored(2, "Thesis", "Draft", 1, -7),
+            Task { time: at("2025-06-30T18:00:00+02:00"), ..task(5, "Algo", "Submission") },
+            anchored(6, "Algo", "Outline", 5, -2),
+        ]).unwrap();
+
+        let args = MergeArgs { other, prefer: Some(PreferArg::Theirs) };
+        run(&ours, &args, &Config::default()).unwrap();
+
+        let tasks = TomlStorage::new(dir.path().join("ours.toml")).read().unwrap();
+        let names = tasks.iter().map(|x| (x.id, x.name.as_str())).collect::<Vec<_>>();
+        assert_eq!(names, [(1, "Submission"), (2, "Outline"), (3, "Final"), (4, "Draft")]);
+        // The draft follows the thesis it was anchored to, not our task 1
+        assert_eq!(tasks[3].anchor.unwrap().task, 3);
+        assert_eq!(tasks[3].time, at("2025-07-08T12:00:00+02:00"));
+        // Unchanged anchored tasks are no update
+        assert_eq!(tasks[1].anchor.unwrap().task, 1);
+        assert_eq!(tasks[1].time, at("2025-06-28T18:00:00+02:00"));
+    }
+}
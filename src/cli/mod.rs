@@ -0,0 +1,280 @@
+pub mod add;
+pub mod bundle;
+pub mod calendar;
+pub mod delete;
+pub mod digest;
+pub mod edit;
+pub mod export;
+pub mod import;
+pub mod list;
+pub mod merge;
+pub mod open_task;
+pub mod prompt;
+pub mod report;
+pub mod select;
+pub mod shift;
+pub mod show;
+pub mod status;
+pub mod validate;
+
+use std::io::{self, stdin, stdout, BufRead, Write};
+use std::path::PathBuf;
+
+use chrono::{DateTime, FixedOffset};
+use clap::{Parser, Subcommand, ValueEnum};
+
+use crate::config::Config;
+use crate::constants;
+use crate::storages::{self, Damaged, TaskStorage};
+use crate::uni::detail::TaskView;
+use crate::uni::policy::StatusPolicy;
+use crate::uni::validation;
+use crate::uni::{dates::{format_date, DisplayZone}, demo, locale::DateNames, task::Task};
+use add::AddArgs;
+use bundle::BundleArgs;
+use calendar::CalendarArgs;
+use delete::DeleteArgs;
+use digest::DigestArgs;
+use edit::EditArgs;
+use export::ExportArgs;
+use import::ImportArgs;
+use list::ListArgs;
+use merge::MergeArgs;
+use open_task::OpenTaskArgs;
+use report::ReportArgs;
+use shift::ShiftArgs;
+use show::ShowArgs;
+use status::StatusArgs;
+use validate::ValidateArgs;
+
+
+#[derive(Parser)]
+#[command(name = "unist", about = "Keep track of university tasks")]
+pub struct Cli {
+    /// Tasks file to use instead of the one in the data directory
+    #[arg(long, global = true)]
+    pub file: Option<PathBuf>,
+    /// Skip the introduction shown when the tasks file is new
+    #[arg(long)]
+    pub no_onboarding: bool,
+    /// Work on generated tasks kept in memory, nothing is written to disk
+    #[arg(long, global = true)]
+    pub demo: bool,
+    /// Seed for the demo tasks
+    #[arg(long, global = true, requires = "demo", default_value_t = demo::DEFAULT_SEED)]
+    pub seed: u64,
+    /// Open the list on one subject, or the subject this abbreviates
+    #[arg(long)]
+    pub subject: Option<String>,
+    /// Open the list on the tasks mentioning this
+    #[arg(long)]
+    pub search: Option<String>,
+    /// Open in the urgent view
+    #[arg(long)]
+    pub urgent: bool,
+    /// Open with the task of this id selected
+    #[arg(long)]
+    pub select: Option<u64>,
+    /// Go on with the tasks recovered from a damaged tasks file without asking
+    #[arg(long, global = true)]
+    pub force: bool,
+    #[command(subcommand)]
+    pub command: Option<Command>,
+}
+
+#[derive(Subcommand)]
+pub enum Command {
+    /// Check the tasks for likely data-entry mistakes
+    Validate(ValidateArgs),
+    /// Print the tasks, optionally narrowed down
+    List(ListArgs),
+    /// Add tasks given as arguments or piped in, all of them or none
+    Add(AddArgs),
+    /// Remove the selected tasks
+    Delete(DeleteArgs),
+    /// Print a single task in full
+    Show(ShowArgs),
+    /// Open a single task in the editor
+    Edit(EditArgs),
+    /// Merge another tasks file into this one
+    Merge(MergeArgs),
+    /// Add the deadlines of a calendar export, updating the ones imported before
+    Import(ImportArgs),
+    /// Write the tasks as Markdown, CSV or plain text, completed ones left out by default
+    Export(ExportArgs),
+    /// Move the deadlines of a subject's incomplete tasks
+    Shift(ShiftArgs),
+    /// Summarize what is overdue, due soon and starred
+    Digest(DigestArgs),
+    /// Show this month's calendar
+    Calendar(CalendarArgs),
+    /// Move the tasks and the config to another machine
+    Bundle(BundleArgs),
+    /// Count what was completed today, is due soon and is overdue; exits with 2 when something is overdue, 1 on an error
+    Status(StatusArgs),
+    /// Sum up the time tracked with the focus timer
+    Report(ReportArgs),
+    /// Open the list on one task, given by id or as a unist://task/<id> link
+    OpenTask(OpenTaskArgs),
+    /// Print a desktop entry making unist:// links open their task
+    DesktopEntry,
+    /// Work through numbered menus instead of the full-screen UI, e.g. over a serial line
+    Prompt,
+}
+
+#[derive(ValueEnum, Clone, Copy, Default)]
+pub enum OutputFormat {
+    #[default]
+    Text,
+    Json,
+    /// One JSON object per line, for `jq` and `xargs` pipelines
+    JsonLines,
+}
+
+/// Tasks as `TaskView`s: an array for json, one line each for json-lines. Text is the
+/// caller's to format, so it gets nothing.
+pub fn format_task_views<'a>(
+    tasks: impl IntoIterator<Item = &'a Task>,
+    format: OutputFormat,
+    now: &DateTime<FixedOffset>,
+    policy: &StatusPolicy,
+) -> serde_json::Result<String> {
+    let views = tasks.into_iter().map(|x| TaskView::new(x, now, policy));
+    match format {
+        OutputFormat::Text => Ok(String::new()),
+        OutputFormat::Json => serde_json::to_string_pretty(&views.collect::<Vec<_>>()),
+        OutputFormat::JsonLines => Ok(views
+            .map(|x| serde_json::to_string(&x))
+            .collect::<serde_json::Result<Vec<_>>>()?
+            .join("\n")),
+    }
+}
+
+pub fn ask_with_prefix(prefix: &str) -> String {
+    print!("{}", prefix);
+    stdout().flush().expect(constants::FLUSH_ERROR);
+
+    let mut buf = "".to_owned();
+    stdin()
+        .lock()
+        .read_line(&mut buf)
+        .expect(constants::STDIN_ERROR);
+
+    buf
+}
+
+/// Settles a past-deadline warning: `--allow-past` accepts it, a terminal gets asked,
+/// anything else is refused
+pub fn confirm_past_deadline(warning: &str, allow_past: bool, interactive: bool) -> io::Result<bool> {
+    if allow_past {
+        return Ok(true);
+    }
+    if !interactive {
+        return Err(io::Error::other(format!("{warning}, pass --allow-past to keep it")));
+    }
+    let answer = ask_with_prefix(&format!("{warning} — keep it? (y/N): "));
+    Ok(matches!(answer.trim().to_lowercase().as_str(), "y" | "yes"))
+}
+
+/// Checks the tasks file reads before a command works on it. A damaged one is set aside with
+/// the tasks recovered from it in its place, after `--force` or a yes on a terminal; anything
+/// else is refused. Other errors are left for the command to report.
+pub fn recover_damaged(storage: &dyn TaskStorage, force: bool, interactive: bool, now: DateTime<FixedOffset>) -> io::Result<()> {
+    let Err(error) = storage.read() else { return Ok(()) };
+    let Ok(damaged) = Damaged::take(error) else { return Ok(()) };
+    eprintln!("{damaged}\n{}", damaged.summary());
+    if !force {
+        if !interactive {
+            return Err(io::Error::other("Nothing changed, pass --force to continue with the recovered tasks"));
+        }
+        let answer = ask_with_prefix("Continue with the recovered tasks, keeping the damaged file aside? (y/N): ");
+        if !matches!(answer.trim().to_lowercase().as_str(), "y" | "yes") {
+            return Err(io::Error::other("Nothing changed"));
+        }
+    }
+    let kept = storages::set_aside(storage, &damaged, now)?;
+    eprintln!("The damaged file is kept as {}", kept.display());
+    Ok(())
+}
+
+/// Warns on stderr about each day the change from `before` to `after` took over
+/// `max_tasks_per_day`, see `validation::day_overloads`
+pub fn warn_day_overloads(before: &[Task], after: &[Task], config: &Config) {
+    let names = config.ui.date_names();
+    for overload in validation::day_overloads_between(before, after, &config.validation) {
+        eprintln!("Warning: {}", overload.describe(&names));
+    }
+}
+
+/// One-line summary used by the listing commands
+pub fn task_line(task: &Task, date_format: &str, names: &DateNames, zone: &DisplayZone) -> String {
+    let mark = match (task.complete, task.starred) {
+        (true, _) => "✓",
+        (false, true) => "*",
+        (false, false) => " ",
+    };
+    let when = match task.time {
+        Some(time) => format!("{}, {}", format_date(&time, date_format, names, zone), task.delta()),
+        None => task.delta(),
+    };
+    format!("{:>4} {} {}: {} ({})", task.id, mark, task.subject, task.name, when)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use chrono::TimeDelta;
+
+    #[test]
+    fn json_formats_share_one_schema() {
+        let now = DateTime::parse_from_rfc3339("2025-05-12T09:00:00+02:00").unwrap();
+        let tasks = [
+            Task { id: 1, name: "HW1".to_string(), time: Some(now + TimeDelta::hours(2)), ..Task::default() },
+            Task { id: 2, name: "Lab".to_string(), time: None, updated_at: Some(now), ..Task::default() },
+        ];
+        let policy = StatusPolicy::default();
+
+        let lines = format_task_views(&tasks, OutputFormat::JsonLines, &now, &policy).unwrap();
+        let lines = lines.lines().map(|x| serde_json::from_str::<serde_json::Value>(x).unwrap()).collect::<Vec<_>>();
+        let array = format_task_views(&tasks, OutputFormat::Json, &now, &policy).unwrap();
+        assert_eq!(serde_json::from_str::<serde_json::Value>(&array).unwrap(), serde_json::Value::Array(lines.clone()));
+
+        assert_eq!(lines[0]["time"], "2025-05-12T11:00:00+02:00");
+        assert_eq!(lines[0]["status"], "due today");
+        assert_eq!(lines[0]["delta_seconds"], 7200);
+        assert!(lines[0].get("updated_at").is_none());
+        assert_eq!(lines[1]["time"], serde_json::Value::Null);
+        assert_eq!(lines[1]["updated_at"], "2025-05-12T09:00:00+02:00");
+    }
+
+    #[test]
+    fn past_deadlines_need_a_flag_without_a_terminal() {
+        let warning = "Deadline is 142 days in the past";
+
+        assert!(confirm_past_deadline(warning, true, false).unwrap());
+        let error = confirm_past_deadline(warning, false, false).unwrap_err();
+        assert!(error.to_string().contains("--allow-past"));
+    }
+
+    #[test]
+    fn damaged_files_are_only_replaced_when_asked_to() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("tasks.toml");
+        let truncated = include_str!("../../tests/fixtures/truncated.toml");
+        std::fs::write(&path, truncated).unwrap();
+        let storage = storages::TomlStorage::new(path.clone());
+        let now = DateTime::parse_from_rfc3339("2025-03-01T12:00:00+01:00").unwrap();
+
+        let error = recover_damaged(&storage, false, false, now).unwrap_err();
+        assert!(error.to_string().contains("--force"));
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), truncated);
+
+        recover_damaged(&storage, true, false, now).unwrap();
+        assert_eq!(storage.read().unwrap().len(), 2);
+        assert!(dir.path().join("tasks.toml.corrupt-20250301-120000").exists());
+        // Nothing left to recover
+        recover_damaged(&storage, false, false, now).unwrap();
+    }
+}
+
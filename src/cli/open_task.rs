@@ -0,0 +1,116 @@
+use std::io;
+use std::path::Path;
+
+use clap::Args;
+
+use crate::storages::TaskStorage;
+
+
+/// Scheme of the links `open-task` is given, as in `unist://task/7`
+pub const SCHEME: &str = "unist";
+
+#[derive(Args)]
+pub struct OpenTaskArgs {
+    /// Task id, or a link to it like unist://task/7
+    #[arg(value_parser = parse_link)]
+    pub task: u64,
+}
+
+/// The id a `unist://task/<id>` link or a bare id points at
+pub fn parse_link(link: &str) -> Result<u64, String> {
+    let id = match link.strip_prefix(&format!("{SCHEME}://")) {
+        Some(rest) => rest
+            .strip_prefix("task/")
+            .ok_or_else(|| format!("{link} is not a task link, expected {SCHEME}://task/<id>"))?
+            // Some launchers append a slash
+            .trim_end_matches('/'),
+        None => link.trim_start_matches('#'),
+    };
+    id.parse().map_err(|_| format!("{id:?} is not a task id"))
+}
+
+/// The id to open the list on, once it is known to be there
+pub fn resolve(storage: &dyn TaskStorage, args: &OpenTaskArgs) -> io::Result<u64> {
+    let tasks = storage.read()?;
+    match tasks.iter().any(|x| x.id == args.task) {
+        true => Ok(args.task),
+        false => Err(io::Error::other(format!("No task #{}", args.task))),
+    }
+}
+
+/// A desktop entry handling `unist://` links by running `exe` in a terminal, on `file` when
+/// given. Installed as `~/.local/share/applications/unist.desktop`, it is registered with
+/// `xdg-mime default unist.desktop x-scheme-handler/unist`.
+pub fn desktop_entry(exe: &Path, file: Option<&Path>) -> String {
+    let mut command = vec![quote(&exe.to_string_lossy())];
+    if let Some(file) = file {
+        command.push("--file".to_string());
+        command.push(quote(&file.to_string_lossy()));
+    }
+    command.extend(["open-task".to_string(), "%u".to_string()]);
+    format!(
+        "[Desktop Entry]\n\
+        Type=Application\n\
+        Name=unist\n\
+        Comment=Open a task of unist\n\
+        Exec={}\n\
+        Terminal=true\n\
+        NoDisplay=true\n\
+        MimeType=x-scheme-handler/{SCHEME};\n",
+        command.join(" "),
+    )
+}
+
+/// An `Exec` argument as the desktop entry specification wants it, quoted when it has to be
+fn quote(argument: &str) -> String {
+    let plain = |c: char| c.is_alphanumeric() || "/-_.+,:@".contains(c);
+    if !argument.is_empty() && argument.chars().all(plain) {
+        return argument.to_string();
+    }
+    let mut quoted = String::from('"');
+    for c in argument.chars() {
+        if matches!(c, '"' | '`' | '$' | '\\') {
+            quoted.push('\\');
+        }
+        quoted.push(c);
+    }
+    quoted.push('"');
+    // The whole value is unescaped once more when the entry is read
+    quoted.replace('\\', "\\\\")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::storages::MemoryStorage;
+    use crate::uni::task::Task;
+
+    #[test]
+    fn links_and_bare_ids_point_at_a_task() {
+        assert_eq!(parse_link("unist://task/7"), Ok(7));
+        assert_eq!(parse_link("unist://task/7/"), Ok(7));
+        assert_eq!(parse_link("7"), Ok(7));
+        assert_eq!(parse_link("#7"), Ok(7));
+        assert!(parse_link("unist://subject/7").unwrap_err().contains("not a task link"));
+        assert!(parse_link("unist://task/HW1").unwrap_err().contains("\"HW1\" is not a task id"));
+    }
+
+    #[test]
+    fn missing_tasks_are_refused_before_opening() {
+        let storage = MemoryStorage::with_tasks(vec![Task { id: 3, ..Task::default() }]);
+        assert_eq!(resolve(&storage, &OpenTaskArgs { task: 3 }).unwrap(), 3);
+        let error = resolve(&storage, &OpenTaskArgs { task: 4 }).unwrap_err();
+        assert_eq!(error.to_string(), "No task #4");
+    }
+
+    #[test]
+    fn desktop_entries_quote_what_needs_it() {
+        let entry = desktop_entry(Path::new("/usr/bin/unist"), None);
+        assert!(entry.contains("\nExec=/usr/bin/unist open-task %u\n"));
+        assert!(entry.contains("\nMimeType=x-scheme-handler/unist;\n"));
+
+        let entry = desktop_entry(Path::new("/opt/my apps/unist"), Some(Path::new("/home/me/$tasks.toml")));
+        assert!(entry.contains(r#"Exec="/opt/my apps/unist" --file "/home/me/\\$tasks.toml" open-task %u"#));
+    }
+}
@@ -0,0 +1,14 @@
+use std::io;
+
+use crate::config::Config;
+use crate::storages::TaskStorage;
+use crate::uni::todo::Todo;
+
+
+/// Numbered menus on plain stdin and stdout, for when the full-screen UI cannot run
+pub fn run(storage: &dyn TaskStorage, config: &Config) -> io::Result<()> {
+    let mut todo = Todo::new(storage, config)?;
+    todo.panic_lookup();
+    todo.run();
+    Ok(())
+}
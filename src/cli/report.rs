@@ -0,0 +1,27 @@
+use std::io;
+
+use clap::Args;
+
+use crate::storages::TaskStorage;
+use crate::uni::report::TimeReport;
+
+
+#[derive(Args)]
+pub struct ReportArgs {
+    /// Time tracked with the focus timer, per subject and per week
+    #[arg(long, required = true)]
+    pub time: bool,
+    /// Comma-separated instead of aligned tables
+    #[arg(long)]
+    pub csv: bool,
+}
+
+pub fn run(storage: &dyn TaskStorage, args: &ReportArgs) -> io::Result<()> {
+    let tasks = storage.read_without_ids()?;
+    let report = TimeReport::new(&tasks);
+    match args.csv {
+        true => print!("{}", report.csv()),
+        false => println!("{}", report.table()),
+    }
+    Ok(())
+}
@@ -0,0 +1,184 @@
+use chrono::{DateTime, FixedOffset, TimeDelta};
+use clap::Args;
+
+use crate::config::SubjectAbbrev;
+use crate::uni::dates::parse_duration;
+use crate::uni::query::TaskQuery;
+use crate::uni::task::Task;
+
+
+/// Task selection shared by the commands working on a subset of tasks.
+/// Every given predicate has to match.
+#[derive(Args, Default, Debug)]
+pub struct Selection {
+    /// Only tasks of this subject, or of the subject this abbreviates. Case and surrounding
+    /// spaces don't count, except for delete, which takes the subject exactly as stored.
+    #[arg(long)]
+    pub subject: Option<String>,
+    /// Only completed tasks
+    #[arg(long)]
+    pub completed: bool,
+    /// Only incomplete tasks past their deadline
+    #[arg(long)]
+    pub overdue: bool,
+    /// Only starred tasks
+    #[arg(long)]
+    pub starred: bool,
+    /// Only tasks with these ids (comma-separated or repeated)
+    #[arg(long = "id", value_delimiter = ',')]
+    pub ids: Vec<u64>,
+    /// Only tasks whose name matches a glob ('*' and '?')
+    #[arg(long)]
+    pub name: Option<String>,
+    /// Only tasks mentioning this in the subject, name or description
+    #[arg(long)]
+    pub text: Option<String>,
+    /// Only tasks whose deadline passed more than this long ago, e.g. 60d
+    #[arg(long, value_parser = parse_duration)]
+    pub older_than: Option<TimeDelta>,
+}
+
+impl Selection {
+    pub fn is_empty(&self) -> bool {
+        self.subject.is_none()
+            && !self.completed
+            && !self.overdue
+            && !self.starred
+            && self.ids.is_empty()
+            && self.name.is_none()
+            && self.text.is_none()
+            && self.older_than.is_none()
+    }
+
+    /// The selection as a query over `tasks`
+    pub fn query<'a>(&self, tasks: &'a [Task], now: &DateTime<FixedOffset>, abbrev: &SubjectAbbrev) -> TaskQuery<'a> {
+        let mut query = TaskQuery::new(tasks, *now);
+        if let Some(subject) = &self.subject {
+            query = query.subject(abbrev.expand(subject));
+        }
+        if self.completed {
+            query = query.complete(true);
+        }
+        if self.overdue {
+            query = query.complete(false).due_before(*now);
+        }
+        if self.starred {
+            query = query.starred(true);
+        }
+        if !self.ids.is_empty() {
+            query = query.ids(&self.ids);
+        }
+        if let Some(name) = self.name.clone() {
+            query = query.matching(move |x| glob_match(&name, &x.name));
+        }
+        if let Some(text) = &self.text {
+            query = query.text_contains(text);
+        }
+        if let Some(older_than) = self.older_than {
+            query = query.due_before(*now - older_than);
+        }
+        query
+    }
+
+    pub fn select<'a>(&self, tasks: &'a [Task], now: &DateTime<FixedOffset>, abbrev: &SubjectAbbrev) -> Vec<&'a Task> {
+        self.query(tasks, now, abbrev).iter().collect()
+    }
+
+    /// As `select`, with the subject matching exactly, case and spaces included. What
+    /// destructive commands go by, so "algo" removes nothing of "Algo".
+    pub fn select_exact<'a>(&self, tasks: &'a [Task], now: &DateTime<FixedOffset>, abbrev: &SubjectAbbrev) -> Vec<&'a Task> {
+        let mut query = self.query(tasks, now, abbrev);
+        if let Some(subject) = &self.subject {
+            let subject = abbrev.expand(subject).to_string();
+            query = query.matching(move |x| x.subject == subject);
+        }
+        query.iter().collect()
+    }
+}
+
+/// Case-insensitive glob supporting '*' (any run) and '?' (any single character)
+pub fn glob_match(pattern: &str, text: &str) -> bool {
+    let pattern = pattern.to_lowercase().chars().collect::<Vec<_>>();
+    let text = text.to_lowercase().chars().collect::<Vec<_>>();
+
+    let (mut p, mut t) = (0, 0);
+    let mut backtrack: Option<(usize, usize)> = None;
+    while t < text.len() {
+        match pattern.get(p) {
+            Some('*') => {
+                backtrack = Some((p, t));
+                p += 1;
+            },
+            Some(&c) if c == '?' || c == text[t] => {
+                p += 1;
+                t += 1;
+            },
+            _ => match backtrack {
+                // Let the last star swallow one more character
+                Some((star, matched)) => {
+                    p = star + 1;
+                    t = matched + 1;
+                    backtrack = Some((star, matched + 1));
+                },
+                None => return false,
+            },
+        }
+    }
+
+    pattern[p..].iter().all(|&c| c == '*')
+}
+
+/// Resolves a task by id, then exact name, then name substring (all case-insensitive).
+/// Errors list the candidates when the query is ambiguous.
+pub fn find_task<'a>(tasks: &'a [Task], query: &str) -> Result<&'a Task, String> {
+    if let Ok(id) = query.parse::<u64>() {
+        if let Some(task) = tasks.iter().find(|x| x.id == id) {
+            return Ok(task);
+        }
+    }
+
+    let query = query.to_lowercase();
+    let exact = tasks
+        .iter()
+        .filter(|x| x.name.to_lowercase() == query)
+        .collect::<Vec<_>>();
+    let candidates = match exact.is_empty() {
+        true => tasks
+            .iter()
+            .filter(|x| x.name.to_lowercase().contains(&query))
+            .collect::<Vec<_>>(),
+        false => exact,
+    };
+
+    match candidates.as_slice() {
+        [] => Err(format!("No task matches \"{query}\"")),
+        [task] => Ok(task),
+        _ => {
+            let mut message = format!("\"{query}\" is ambiguous, use an id:");
+            for task in candidates {
+                message += &format!("\n  {}: {}: {}", task.id, task.subject, task.name);
+            }
+            Err(message)
+        },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn only_destructive_selections_match_the_subject_exactly() {
+        let task = |id, subject: &str| Task { id, subject: subject.to_string(), ..Task::default() };
+        let tasks = vec![task(1, "Algo"), task(2, "algo "), task(3, "Physics")];
+        let now = DateTime::parse_from_rfc3339("2025-03-01T12:00:00+01:00").unwrap();
+        let abbrev = SubjectAbbrev::default();
+        let selection = Selection { subject: Some("Algo".to_string()), ..Selection::default() };
+        let ids = |tasks: Vec<&Task>| tasks.iter().map(|x| x.id).collect::<Vec<_>>();
+
+        assert_eq!(ids(selection.select(&tasks, &now, &abbrev)), [1, 2]);
+        assert_eq!(ids(selection.select_exact(&tasks, &now, &abbrev)), [1]);
+        let lower = Selection { subject: Some("algo".to_string()), ..Selection::default() };
+        assert!(lower.select_exact(&tasks, &now, &abbrev).is_empty());
+    }
+}
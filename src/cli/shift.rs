@@ -0,0 +1,88 @@
+use std::io::{self, stdin, IsTerminal};
+
+use chrono::{Local, TimeDelta};
+use clap::Args;
+
+use super::{ask_with_prefix, confirm_past_deadline, warn_day_overloads};
+use crate::config::Config;
+use crate::storages::TaskStorage;
+use crate::uni::dates::{format_date, parse_duration};
+use crate::uni::shift::{apply_shift, plan_shift};
+use crate::uni::validation::past_deadline_warning;
+
+
+#[derive(Args)]
+pub struct ShiftArgs {
+    /// Subject whose incomplete tasks move, abbreviations work too
+    #[arg(long)]
+    pub subject: String,
+    /// Signed duration, e.g. +7d or -1w2d
+    #[arg(allow_hyphen_values = true, value_parser = parse_duration)]
+    pub by: TimeDelta,
+    /// Only print what would change
+    #[arg(long)]
+    pub dry_run: bool,
+    /// Do not ask for confirmation
+    #[arg(long, short)]
+    pub yes: bool,
+    /// Accept deadlines ending up in the past without asking
+    #[arg(long)]
+    pub allow_past: bool,
+}
+
+pub fn run(storage: &dyn TaskStorage, args: &ShiftArgs, config: &Config) -> io::Result<()> {
+    let mut tasks = storage.read()?;
+    let shifts = plan_shift(&tasks, config.subject_abbrev.expand(&args.subject), args.by);
+
+    if shifts.is_empty() {
+        println!("No open tasks with a deadline in {}.", args.subject);
+        return Ok(());
+    }
+
+    let now = Local::now().fixed_offset();
+    let date_format = &config.ui.date_display_format;
+    let names = config.ui.date_names();
+    let zone = config.ui.display_zone();
+    let mut in_past = 0;
+    println!("{} deadline(s) to shift:", shifts.len());
+    for shift in &shifts {
+        let task = tasks.iter().find(|x| x.id == shift.id).unwrap();
+        let warning = past_deadline_warning(Some(shift.before), Some(shift.after), &now, &config.validation);
+        in_past += warning.is_some() as usize;
+        println!(
+            "{:>4} {}: {} → {}{}",
+            task.id,
+            task.name,
+            format_date(&shift.before, date_format, &names, &zone),
+            format_date(&shift.after, date_format, &names, &zone),
+            warning.map(|x| format!(" ({x})")).unwrap_or_default(),
+        );
+    }
+
+    if args.dry_run {
+        return Ok(());
+    }
+
+    if in_past > 0 {
+        let warning = format!("{in_past} deadline(s) would end up in the past");
+        if !confirm_past_deadline(&warning, args.allow_past, !args.yes && stdin().is_terminal())? {
+            println!("Nothing changed.");
+            return Ok(());
+        }
+    }
+
+    if !args.yes {
+        let answer = ask_with_prefix("Shift them? (y/N): ");
+        if !matches!(answer.trim().to_lowercase().as_str(), "y" | "yes") {
+            println!("Nothing changed.");
+            return Ok(());
+        }
+    }
+
+    let before = tasks.clone();
+    apply_shift(&mut tasks, &shifts, now);
+    warn_day_overloads(&before, &tasks, config);
+    storage.write(&tasks)?;
+    println!("Shifted {} deadline(s)", shifts.len());
+    Ok(())
+}
@@ -0,0 +1,41 @@
+use std::io;
+
+use chrono::Local;
+use clap::Args;
+use crossterm::terminal;
+
+use super::select::find_task;
+use super::OutputFormat;
+use crate::config::Config;
+use crate::storages::TaskStorage;
+use crate::uni::detail::{format_detail, TaskView};
+use crate::uni::policy::StatusPolicy;
+
+
+#[derive(Args)]
+pub struct ShowArgs {
+    /// Task id or (part of) its name
+    pub task: String,
+    #[arg(long, value_enum, default_value_t)]
+    pub format: OutputFormat,
+}
+
+pub fn run(storage: &dyn TaskStorage, args: &ShowArgs, config: &Config) -> io::Result<()> {
+    let tasks = storage.read()?;
+    let now = Local::now().fixed_offset();
+    let task = find_task(&tasks, &args.task).map_err(io::Error::other)?;
+    let policy = StatusPolicy::from_config(config);
+
+    match args.format {
+        OutputFormat::Text => {
+            let width = terminal::size().map(|(x, _)| x as usize).unwrap_or(80);
+            println!("{}", format_detail(task, &now, width, &config.ui.date_display_format, &config.ui.date_names(), &config.ui.display_zone(), &policy));
+        },
+        OutputFormat::Json => {
+            let json = serde_json::to_string_pretty(&TaskView::new(task, &now, &policy))?;
+            println!("{json}");
+        },
+        OutputFormat::JsonLines => println!("{}", serde_json::to_string(&TaskView::new(task, &now, &policy))?),
+    }
+    Ok(())
+}
@@ -0,0 +1,120 @@
+use std::io::{self, stdout, Write};
+
+use chrono::{DateTime, FixedOffset, Local, TimeDelta};
+use clap::Args;
+
+use crate::config::{Config, StatusConfig};
+use crate::storages::TaskStorage;
+use crate::uni::dates::{local_date, parse_duration};
+use crate::uni::query::TaskQuery;
+use crate::uni::task::Task;
+
+
+/// Exit code when something is overdue, kept apart from the 1 every command exits with on
+/// an error so a prompt script can tell the two apart
+pub const OVERDUE_EXIT_CODE: i32 = 2;
+
+#[derive(Args)]
+pub struct StatusArgs {
+    /// One token per non-zero count for a shell prompt, e.g. "✓3 ⚑2 ⏰1"
+    #[arg(long)]
+    pub compact: bool,
+    /// Leave out the trailing newline
+    #[arg(short = 'n')]
+    pub no_newline: bool,
+    /// How far ahead counts as due soon, e.g. 24h; the panic threshold by default
+    #[arg(long, value_parser = parse_duration)]
+    pub threshold: Option<TimeDelta>,
+}
+
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct StatusCounts {
+    pub completed_today: usize,
+    pub due_soon: usize,
+    pub overdue: usize,
+}
+
+impl StatusCounts {
+    pub fn new(tasks: &[Task], now: &DateTime<FixedOffset>, threshold: TimeDelta) -> Self {
+        let query = || TaskQuery::new(tasks, *now);
+        let today = local_date(now);
+        Self {
+            completed_today: query()
+                .complete(true)
+                .matching(move |x| x.completed_at.is_some_and(|t| local_date(&t) == today))
+                .count(),
+            due_soon: query().complete(false).due_after(*now).due_before(*now + threshold).count(),
+            overdue: query().complete(false).due_before(*now).count(),
+        }
+    }
+
+    /// Empty when there is nothing to show, so the prompt stays clean
+    pub fn compact(&self, glyphs: &StatusConfig) -> String {
+        [
+            (&glyphs.completed, self.completed_today),
+            (&glyphs.due_soon, self.due_soon),
+            (&glyphs.overdue, self.overdue),
+        ]
+            .into_iter()
+            .filter(|(_, count)| *count > 0)
+            .map(|(glyph, count)| format!("{glyph}{count}"))
+            .collect::<Vec<_>>()
+            .join(" ")
+    }
+
+    pub fn long(&self) -> String {
+        format!(
+            "{} completed today · {} due soon · {} overdue",
+            self.completed_today, self.due_soon, self.overdue
+        )
+    }
+}
+
+/// Prints the counts, returns whether anything is overdue
+pub fn run(storage: &dyn TaskStorage, args: &StatusArgs, config: &Config) -> io::Result<bool> {
+    let tasks = storage.read_without_ids()?;
+    let now = Local::now().fixed_offset();
+    let threshold = args.threshold.unwrap_or(TimeDelta::hours(config.ui.panic_threshold_hours));
+    let counts = StatusCounts::new(&tasks, &now, threshold);
+
+    let text = match args.compact {
+        true => counts.compact(&config.status),
+        false => counts.long(),
+    };
+    let mut stdout = stdout();
+    match args.no_newline {
+        true => write!(stdout, "{text}")?,
+        false => writeln!(stdout, "{text}")?,
+    }
+    // The exit code may follow right away
+    stdout.flush()?;
+    Ok(counts.overdue > 0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use chrono::TimeZone;
+
+    #[test]
+    fn counts_and_compact_tokens() {
+        let now = Local.with_ymd_and_hms(2025, 5, 12, 9, 0, 0).unwrap().fixed_offset();
+        let task = |hours: i64, complete| Task {
+            time: Some(now + TimeDelta::hours(hours)),
+            complete,
+            completed_at: complete.then_some(now - TimeDelta::hours(1)),
+            ..Task::default()
+        };
+        let tasks = vec![task(-3, false), task(5, false), task(30, false), task(100, false), task(2, true)];
+
+        let counts = StatusCounts::new(&tasks, &now, TimeDelta::hours(48));
+        assert_eq!(counts, StatusCounts { completed_today: 1, due_soon: 2, overdue: 1 });
+        assert_eq!(counts.compact(&StatusConfig::default()), "✓1 ⚑2 ⏰1");
+        assert_eq!(StatusCounts::new(&tasks, &now, TimeDelta::hours(6)).due_soon, 1);
+
+        let nothing = StatusCounts::new(&tasks[3..4], &now, TimeDelta::hours(48));
+        assert_eq!(nothing.compact(&StatusConfig::default()), "");
+        assert_eq!(nothing.long(), "0 completed today · 0 due soon · 0 overdue");
+    }
+}
@@ -0,0 +1,190 @@
+use std::io;
+
+use clap::Args;
+use serde::Serialize;
+
+use super::OutputFormat;
+use crate::config::Config;
+use crate::storages::TaskStorage;
+use crate::ui::colors::custom_color;
+use crate::uni::anchors::{self, BrokenAnchor};
+use crate::uni::task::Task;
+use crate::uni::validation;
+
+
+#[derive(Args)]
+pub struct ValidateArgs {
+    /// json and json-lines both print the report as a single object
+    #[arg(long, value_enum, default_value_t)]
+    pub format: OutputFormat,
+}
+
+/// Everything `validate` finds, the JSON output as is
+#[derive(Serialize)]
+pub struct Report {
+    /// The tasks file checked, None for the demo tasks
+    pub file: Option<String>,
+    /// Same as the exit code: no conflicts and no duplicates that look like mistakes
+    pub clean: bool,
+    /// Open tasks without a description, not a problem
+    pub open_stubs: usize,
+    /// Ids of each pair of same-subject tasks due too close together
+    pub conflicts: Vec<[u64; 2]>,
+    pub duplicates: Vec<Duplicate>,
+    /// Ids of tasks with a color that can't be read, drawn in their status color instead
+    pub invalid_colors: Vec<u64>,
+    /// Relative deadlines that can't be worked out, those tasks have none
+    pub broken_anchors: Vec<BrokenAnchor>,
+}
+
+#[derive(Serialize)]
+pub struct Duplicate {
+    /// Oldest first
+    pub ids: Vec<u64>,
+    /// The deadlines differ, as with a homework due every week
+    pub possibly_intentional: bool,
+}
+
+impl Report {
+    pub fn new(tasks: &[Task], config: &Config) -> Self {
+        let conflicts = validation::find_deadline_conflicts(tasks, &config.validation);
+        let duplicates = validation::find_duplicates(tasks);
+        let invalid_colors: Vec<u64> = invalid_colors(tasks).map(|x| x.id).collect();
+        let broken_anchors = anchors::resolve(&mut tasks.to_vec());
+        Self {
+            file: None,
+            clean: conflicts.is_empty()
+                && duplicates.iter().all(|x| x.possibly_intentional)
+                && invalid_colors.is_empty()
+                && broken_anchors.is_empty(),
+            open_stubs: tasks.iter().filter(|x| !x.complete && x.is_stub()).count(),
+            conflicts: conflicts.iter().map(|x| [tasks[x.first].id, tasks[x.second].id]).collect(),
+            duplicates: duplicates
+                .into_iter()
+                .map(|x| Duplicate { ids: x.ids, possibly_intentional: x.possibly_intentional })
+                .collect(),
+            invalid_colors,
+            broken_anchors,
+        }
+    }
+}
+
+fn invalid_colors(tasks: &[Task]) -> impl Iterator<Item = &Task> {
+    tasks.iter().filter(|x| x.color.is_some() && custom_color(x).is_none())
+}
+
+/// Prints every problem found, returns whether the tasks are clean.
+/// Duplicates with different deadlines are listed but don't count as a problem,
+/// neither do open tasks without a description, which are only counted.
+pub fn run(storage: &dyn TaskStorage, args: &ValidateArgs, config: &Config) -> io::Result<bool> {
+    let tasks = storage.read()?;
+    if !matches!(args.format, OutputFormat::Text) {
+        let report = Report { file: storage.path().map(|x| x.display().to_string()), ..Report::new(&tasks, config) };
+        let json = match args.format {
+            OutputFormat::Json => serde_json::to_string_pretty(&report)?,
+            _ => serde_json::to_string(&report)?,
+        };
+        println!("{json}");
+        return Ok(report.clean);
+    }
+
+    let conflicts = validation::find_deadline_conflicts(&tasks, &config.validation);
+    let duplicates = validation::find_duplicates(&tasks);
+    let stubs = tasks.iter().filter(|x| !x.complete && x.is_stub()).count();
+    if let Some(path) = storage.path() {
+        println!("Checked {} task(s) in {}", tasks.len(), path.display());
+    }
+    if stubs > 0 {
+        println!("{stubs} open task(s) without a description, `:filter stubs` in the UI lists them.");
+    }
+
+    let colors: Vec<&Task> = invalid_colors(&tasks).collect();
+    let broken = anchors::resolve(&mut tasks.clone());
+    if conflicts.is_empty() && duplicates.is_empty() && colors.is_empty() && broken.is_empty() {
+        println!("No problems found.");
+        return Ok(true);
+    }
+
+    if !conflicts.is_empty() {
+        println!(
+            "Deadline conflicts (same subject, due within {} minutes):",
+            config.validation.conflict_window_minutes
+        );
+        for conflict in conflicts.iter() {
+            println!("  {}", conflict.describe(&tasks));
+        }
+    }
+    if !duplicates.is_empty() {
+        println!("Duplicate tasks (same subject and name):");
+        for group in duplicates.iter() {
+            let ids = group.ids.iter().map(u64::to_string).collect::<Vec<_>>();
+            println!("  {} [ids {}]", group.describe(&tasks), ids.join(", "));
+        }
+    }
+    if !colors.is_empty() {
+        println!("Colors that can't be read, hex like \"#b16286\" or a name like magenta:");
+        for task in &colors {
+            println!("  {}: {} has \"{}\" [id {}]", task.subject, task.name, task.color.as_deref().unwrap_or_default(), task.id);
+        }
+    }
+    if !broken.is_empty() {
+        println!("Relative deadlines that can't be worked out:");
+        for anchor in broken.iter() {
+            if let Some(task) = tasks.iter().find(|x| x.id == anchor.id) {
+                let rule = task.anchor.map(|x| x.describe(&tasks)).unwrap_or_default();
+                println!("  {}: {} is {rule}, but {} [id {}]", task.subject, task.name, anchor.problem.describe(), task.id);
+            }
+        }
+    }
+    Ok(conflicts.is_empty() && duplicates.iter().all(|x| x.possibly_intentional) && colors.is_empty() && broken.is_empty())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use chrono::{DateTime, TimeDelta};
+
+    #[test]
+    fn report_names_tasks_by_id() {
+        let now = DateTime::parse_from_rfc3339("2025-05-12T09:00:00+02:00").unwrap();
+        let task = |id, name: &str, minutes| Task {
+            id,
+            subject: "Algo".to_string(),
+            name: name.to_string(),
+            description: "About it".to_string(),
+            time: Some(now + TimeDelta::minutes(minutes)),
+            ..Task::default()
+        };
+        let tasks = vec![task(7, "HW1", 0), task(3, "HW2", 10), task(9, "HW2", 10)];
+
+        let report = Report::new(&tasks, &Config::default());
+        assert_eq!(report.conflicts, [[7, 3], [7, 9], [3, 9]]);
+        assert_eq!(report.duplicates.len(), 1);
+        assert!(!report.clean);
+        assert_eq!(report.open_stubs, 0);
+        assert!(report.invalid_colors.is_empty());
+        assert!(report.broken_anchors.is_empty());
+    }
+
+    #[test]
+    fn unreadable_colors_are_reported() {
+        let task = |id, color: &str| Task { id, color: Some(color.to_string()), ..Task::default() };
+        let report = Report::new(&[task(1, "#b16286"), task(2, "thesis"), task(3, "blue")], &Config::default());
+        assert_eq!(report.invalid_colors, [2]);
+        assert!(!report.clean);
+    }
+
+    #[test]
+    fn broken_anchors_are_reported() {
+        use crate::uni::anchors::{Anchor, AnchorProblem};
+
+        let anchored = |id, task| Task { id, anchor: Some(Anchor { task, offset: TimeDelta::days(-1) }), ..Task::default() };
+        let report = Report::new(&[anchored(1, 9), anchored(2, 3), Task { id: 3, time: None, ..Task::default() }], &Config::default());
+        assert_eq!(
+            report.broken_anchors,
+            [BrokenAnchor { id: 1, problem: AnchorProblem::Missing }, BrokenAnchor { id: 2, problem: AnchorProblem::Undated }]
+        );
+        assert!(!report.clean);
+    }
+}
@@ -0,0 +1,422 @@
+use std::collections::BTreeMap;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use crate::constants;
+use crate::uni::dates::{self, DisplayZone};
+use crate::uni::locale::{DateNames, NameOverrides};
+use crate::uni::task::Task;
+
+use chrono::{DateTime, FixedOffset, NaiveTime, TimeDelta};
+use clap::ValueEnum;
+use serde::{Serialize, Deserialize};
+
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Config {
+    pub storage: StorageConfig,
+    pub ui: UiConfig,
+    pub validation: ValidationConfig,
+    pub subject_abbrev: SubjectAbbrev,
+    pub subject_colors: SubjectColors,
+    pub status: StatusConfig,
+    pub add: AddConfig,
+    pub import: ImportConfig,
+    /// Markdown file seeding the description of new tasks, `~` is expanded.
+    /// `{subject}`, `{name}` and `{date}` are filled in.
+    pub new_task_template: Option<PathBuf>,
+    /// "HH:MM" a deadline given only as a day falls on, 23:59 when unset
+    pub default_deadline_time: Option<String>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct StorageConfig {
+    /// Tasks file overriding the XDG data directory, `~` is expanded
+    pub file: Option<PathBuf>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct UiConfig {
+    /// How many of the most urgent tasks focus mode keeps on screen
+    pub focus_count: usize,
+    /// Countdown to the nearest deadline in the Tasks title
+    pub show_next_deadline: bool,
+    /// Greet with a short introduction when the tasks file is new
+    pub onboarding: bool,
+    /// Extra emphasis for tasks more than a day overdue
+    pub overdue_emphasis: OverdueEmphasis,
+    /// Colors the terminal shows: "truecolor", "256" or "16", found out from COLORTERM and TERM when unset
+    pub color_depth: Option<ColorDepth>,
+    /// "standard", or "monochrome" for no colors at all, only text styles and status symbols
+    pub theme: Theme,
+    /// How the selected task stands out: "reversed", or "marker" for the `>` and bold text
+    pub selection: SelectionStyle,
+    /// Filters switched on together by `f`
+    pub filter_preset: Filters,
+    /// strftime format of every absolute date shown, CLI included
+    pub date_display_format: String,
+    /// Zone absolute dates are shown in: "local", "original" for the offset each was
+    /// written with, or an IANA name like "Europe/Berlin"
+    pub display_timezone: String,
+    /// Language of month and weekday names: "en", "de", "fr", or "system" to follow LC_TIME
+    pub locale: String,
+    /// Own month and weekday names in place of the locale's
+    pub date_names: NameOverrides,
+    /// How tasks are edited
+    pub reader: ReaderKind,
+    /// Front matter the editor document is written with, either is read back
+    pub editor_front_matter: FrontMatter,
+    /// Comments in the front matter explaining the fields
+    pub editor_help_comments: bool,
+    /// Column the description is wrapped at in the editor, for editors without soft wrap.
+    /// The lines are joined again on saving.
+    pub editor_wrap_width: Option<usize>,
+    /// ISO week numbers in front of each calendar row
+    pub calendar_week_numbers: bool,
+    /// Starred tasks without a deadline close the agenda in an "Anytime" section
+    pub calendar_anytime: bool,
+    /// Tasks the calendar grid, the agenda and the date picker count and list: "incomplete",
+    /// "recently-completed" or "completed"
+    pub calendar_include: CalendarTaskFilter,
+    /// Incomplete tasks due within this many hours are shown as panicking
+    pub panic_threshold_hours: i64,
+    /// Open tasks without a deadline untouched for this many days panic too
+    pub escalate_after_days: Option<i64>,
+    /// Tasks due today go above the overdue ones
+    pub today_first: bool,
+    /// Where tasks without a deadline gather under their own header: "bottom" or "top"
+    pub undated_position: UndatedPosition,
+    /// Clicking a column header sorts by it, at the cost of the terminal's own text selection
+    pub mouse: bool,
+    /// The weekly review goes through incomplete tasks untouched for this many days
+    pub review_stale_days: i64,
+    /// Status markers and text styles on top of the colors, for color blindness
+    /// and monochrome terminals
+    pub status_symbols: bool,
+    /// Length of the focus timer started with `T`
+    pub focus_timer_minutes: i64,
+}
+
+/// Which tasks the list hides
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Filters {
+    pub hide_completed: bool,
+    pub hide_no_deadline: bool,
+    /// Keep only panicking and starred tasks
+    pub only_urgent: bool,
+    pub only_starred: bool,
+    /// Keep only tasks whose description is still to be written
+    pub only_stubs: bool,
+    /// Keep only tasks of this subject, ignoring case
+    pub subject: Option<String>,
+    /// Keep only tasks mentioning this in the subject, name or description, ignoring case
+    pub text: Option<String>,
+}
+
+impl Filters {
+    pub fn is_empty(&self) -> bool {
+        *self == Self::default()
+    }
+}
+
+/// Which tasks the calendar is about, the same for each of its views
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, ValueEnum)]
+#[serde(rename_all = "kebab-case")]
+pub enum CalendarTaskFilter {
+    /// Open tasks only
+    #[default]
+    Incomplete,
+    /// Open tasks and those completed within the last week
+    RecentlyCompleted,
+    /// Completed tasks too, however long ago
+    Completed,
+}
+
+impl CalendarTaskFilter {
+    const RECENT_DAYS: i64 = 7;
+
+    /// Completed tasks from before `completed_at` was recorded are never recent
+    pub fn includes(&self, task: &Task, now: &DateTime<FixedOffset>) -> bool {
+        match self {
+            _ if !task.complete => true,
+            Self::Incomplete => false,
+            Self::RecentlyCompleted => task.completed_at.is_some_and(|x| *now - x <= TimeDelta::days(Self::RECENT_DAYS)),
+            Self::Completed => true,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum OverdueEmphasis {
+    #[default]
+    Blink,
+    Reversed,
+    None,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum UndatedPosition {
+    #[default]
+    Bottom,
+    Top,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Theme {
+    #[default]
+    Standard,
+    /// For e-ink and other terminals where colors barely differ
+    Monochrome,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SelectionStyle {
+    #[default]
+    Reversed,
+    Marker,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ColorDepth {
+    #[serde(rename = "truecolor")]
+    TrueColor,
+    #[serde(rename = "256")]
+    Ansi256,
+    /// The Linux console and other minimal terminals
+    #[serde(rename = "16")]
+    Ansi16,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ReaderKind {
+    /// `$EDITOR` on a Markdown file with front matter
+    #[default]
+    Editor,
+    /// A form inside the terminal, for when there is no usable editor
+    Form,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum FrontMatter {
+    /// Between `---` lines
+    #[default]
+    Yaml,
+    /// Between `+++` lines
+    Toml,
+}
+
+impl Default for UiConfig {
+    fn default() -> Self {
+        Self {
+            focus_count: 3,
+            show_next_deadline: true,
+            onboarding: true,
+            overdue_emphasis: OverdueEmphasis::default(),
+            color_depth: None,
+            theme: Theme::default(),
+            selection: SelectionStyle::default(),
+            filter_preset: Filters { hide_completed: true, ..Filters::default() },
+            date_display_format: dates::DEFAULT_DISPLAY_FORMAT.to_string(),
+            display_timezone: "local".to_string(),
+            locale: "en".to_string(),
+            date_names: NameOverrides::default(),
+            reader: ReaderKind::default(),
+            editor_front_matter: FrontMatter::default(),
+            editor_help_comments: true,
+            editor_wrap_width: None,
+            calendar_week_numbers: false,
+            calendar_anytime: false,
+            calendar_include: CalendarTaskFilter::default(),
+            panic_threshold_hours: constants::PANIC_THRESHOLD_HOURS,
+            escalate_after_days: None,
+            today_first: false,
+            undated_position: UndatedPosition::default(),
+            mouse: false,
+            review_stale_days: 7,
+            status_symbols: false,
+            focus_timer_minutes: 25,
+        }
+    }
+}
+
+impl UiConfig {
+    /// Month and weekday names everything shows dates with
+    pub fn date_names(&self) -> DateNames {
+        // Same precedence as the C library
+        let system = ["LC_ALL", "LC_TIME", "LANG"]
+            .into_iter()
+            .filter_map(|x| std::env::var(x).ok())
+            .find(|x| !x.is_empty());
+        DateNames::resolve(&self.locale, system.as_deref(), &self.date_names)
+    }
+
+    pub fn display_zone(&self) -> DisplayZone {
+        DisplayZone::resolve(&self.display_timezone)
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ValidationConfig {
+    /// Same-subject incomplete tasks due closer than this are reported as conflicts
+    pub conflict_window_minutes: i64,
+    /// Newly set deadlines further in the past than this need a confirmation
+    pub past_deadline_margin_minutes: i64,
+    /// Ask before adding a task that looks like an existing one
+    pub warn_duplicates: bool,
+    /// Names this many edits apart still count as the same task
+    pub duplicate_max_distance: usize,
+    /// Warn when a change leaves more incomplete tasks than this due on one day,
+    /// and mark such days in the calendar
+    pub max_tasks_per_day: Option<usize>,
+}
+
+impl Default for ValidationConfig {
+    fn default() -> Self {
+        Self {
+            conflict_window_minutes: 60,
+            past_deadline_margin_minutes: 60,
+            warn_duplicates: true,
+            duplicate_max_distance: 2,
+            max_tasks_per_day: None,
+        }
+    }
+}
+
+/// Glyphs in front of each count of `unist status --compact`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct StatusConfig {
+    pub completed: String,
+    pub due_soon: String,
+    pub overdue: String,
+}
+
+impl Default for StatusConfig {
+    fn default() -> Self {
+        Self {
+            completed: "✓".to_string(),
+            due_soon: "⚑".to_string(),
+            overdue: "⏰".to_string(),
+        }
+    }
+}
+
+/// `unist add` task lines, e.g. "Algorithms | HW5 | +6d | read chapters 7-8"
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct AddConfig {
+    /// Between subject, name, deadline and description
+    pub separator: String,
+}
+
+impl Default for AddConfig {
+    fn default() -> Self {
+        Self { separator: "|".to_string() }
+    }
+}
+
+/// `unist import` of calendar exports. Patterns are regular expressions.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ImportConfig {
+    /// Course code to subject, `"CS-401-2025S" = "Algorithms"`; events of other courses are not imported
+    pub course_map: BTreeMap<String, String>,
+    /// Finds the course code at the start of a SUMMARY when the event has no CATEGORIES,
+    /// the first group being the code. The whole match is cut off.
+    pub course_pattern: String,
+    /// Cut out of every SUMMARY, e.g. the " is due" Moodle adds
+    pub cleanup: Vec<String>,
+    /// Events whose SUMMARY matches one of these are not deadlines and are skipped
+    pub ignore: Vec<String>,
+}
+
+impl Default for ImportConfig {
+    fn default() -> Self {
+        Self {
+            course_map: BTreeMap::new(),
+            course_pattern: r"^\[?([A-Z][A-Z0-9]*(?:-[A-Z0-9]+)+)\]?:?\s*".to_string(),
+            cleanup: vec![r"\s+(is due|closes)$".to_string()],
+            ignore: vec![r"\s+opens$".to_string()],
+        }
+    }
+}
+
+/// Short names for long subjects in the table, `"Introduction to Distributed Systems" = "IDS"`.
+/// The full name stays everywhere a task is shown whole.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct SubjectAbbrev(BTreeMap<String, String>);
+
+impl SubjectAbbrev {
+    /// The short name, or the subject itself when it has none
+    pub fn abbreviate<'a>(&'a self, subject: &'a str) -> &'a str {
+        self.0.get(subject).map_or(subject, String::as_str)
+    }
+
+    /// The subject a typed abbreviation (case-insensitive) stands for, anything else as typed
+    pub fn expand<'a>(&'a self, typed: &'a str) -> &'a str {
+        let typed = typed.trim();
+        self.0
+            .iter()
+            .find(|(_, short)| short.to_lowercase() == typed.to_lowercase())
+            .map_or(typed, |(subject, _)| subject.as_str())
+    }
+}
+
+/// Own accents for subjects, `"Algorithms" = "#83a598"` or a name like "magenta".
+/// The rest get one picked from their name.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct SubjectColors(BTreeMap<String, String>);
+
+impl SubjectColors {
+    pub fn get(&self, subject: &str) -> Option<&str> {
+        self.0.get(subject).map(String::as_str)
+    }
+}
+
+impl Config {
+    pub fn path() -> Option<PathBuf> {
+        dirs::config_dir().map(|dir| dir.join("unist").join("config.toml"))
+    }
+
+    pub fn load() -> io::Result<Self> {
+        match Self::path() {
+            Some(path) if path.exists() => Self::from_file(&path),
+            _ => Ok(Self::default()),
+        }
+    }
+
+    pub fn from_file(path: &Path) -> io::Result<Self> {
+        let content = fs::read_to_string(path)?;
+        let config: Self = toml::from_str(&content)
+            .map_err(|e| io::Error::other(format!("Could not parse the config {}: {}", path.display(), e)))?;
+        if let Some(time) = &config.default_deadline_time {
+            NaiveTime::parse_from_str(time, "%H:%M").map_err(|_| io::Error::other(format!(
+                "Could not parse the config {}: default_deadline_time \"{time}\" is not HH:MM",
+                path.display(),
+            )))?;
+        }
+        Ok(config)
+    }
+
+    /// Time of day for deadlines given only as a day
+    pub fn deadline_time(&self) -> NaiveTime {
+        self.default_deadline_time
+            .as_deref()
+            .and_then(|x| NaiveTime::parse_from_str(x, "%H:%M").ok())
+            .unwrap_or(dates::END_OF_DAY)
+    }
+}
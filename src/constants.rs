@@ -1,6 +1,18 @@
-pub static DAYS_LEFT: i32 = 2;
-pub static TABLE_PATH: &str = ".local/state/unist/";
-pub static TABLE_NAME: &str = "kek.toml";
+/// Default for deadlines closer than this many hours counting as panic
+pub static PANIC_THRESHOLD_HOURS: i64 = 48;
+pub static DATA_DIR: &str = "unist";
+pub static TABLE_NAME: &str = "tasks.toml";
+/// Earlier default locations, relative to the home directory
+pub static LEGACY_TABLES: [&str; 2] = [".local/state/unist/kek.toml", ".unitodo/unist_table.toml"];
+pub static TICK_RATE_MS: u64 = 1000;
+/// How long each status bar message stays up
+pub static STATUS_MESSAGE_SECS: i64 = 4;
+/// Unsaved changes are journaled at most this often
+pub static JOURNAL_INTERVAL_MS: u64 = 2000;
+/// How many changes back `u` can go
+pub static UNDO_LIMIT: usize = 50;
+/// How much of a description the description pane shows, in bytes; the task keeps all of it
+pub static DESCRIPTION_RENDER_LIMIT: usize = 16 * 1024;
 pub static SEPARATOR: &str =        "------------------------------";
 pub static FLUSH_ERROR: &str = "Could not flush to the standard output";
 pub static STDIN_ERROR: &str = "Could not read from the standard input";
@@ -1,70 +1,195 @@
-use std::fs::{self, File};
-use std::io::{self, stdin, stdout, BufRead, Error, Write};
-use std::path::Path;
+use std::env;
+use std::io::{self, stdout, IsTerminal};
+use std::path::{Path, PathBuf};
+use std::process;
+use std::sync::Arc;
+use std::sync::atomic::AtomicBool;
 
 
+mod bundle;
+mod cli;
+mod config;
 mod constants;
+mod paths;
 mod readers;
 mod storages;
 mod ui;
 mod uni;
 
-use ui::{
-    app::App,
-    panes::{DescriptionPane, TasksPane},
-};
+use ui::app::{App, Startup};
+
+use ui::colors::{Ansi16, Ansi256, Degraded, MonochromeTaskColors, StandardTaskColors, TaskColors};
+use storages::{MemoryStorage, TaskStorage};
+use cli::{Cli, Command};
+use config::{ColorDepth, Config, Theme};
+use uni::demo;
+
+use chrono::Local;
 
-use readers::EditorTaskReader;
-use ui::colors::StandardTaskColors;
-use storages::TomlStorage;
+use clap::Parser;
+use ratatui::crossterm::{
+    event::{DisableMouseCapture, EnableMouseCapture},
+    ExecutableCommand,
+};
+#[cfg(unix)]
+use signal_hook::consts::{SIGHUP, SIGTERM};
 
 use dirs::{self, home_dir};
 
-// use uni::{todo::Todo, task::Task};
-fn ask_with_prefix(prefix: &str) -> String {
-    print!("{}", prefix);
-    stdout().flush().expect(constants::FLUSH_ERROR);
+/// Resolves the tasks file, also telling whether it is being created just now
+fn table_path(cli: &Cli, config: &Config) -> io::Result<(PathBuf, bool)> {
+    let home = home_dir();
+    let xdg_data_home = env::var("XDG_DATA_HOME").ok();
+    let path = paths::resolve_tasks_path(
+        cli.file.as_deref(),
+        config.storage.file.as_deref(),
+        xdg_data_home.as_deref(),
+        home.as_deref(),
+    )?;
 
-    let mut buf = "".to_owned();
-    stdin()
-        .lock()
-        .read_line(&mut buf)
-        .expect(constants::STDIN_ERROR);
+    // Only the default location takes over the old ones
+    let explicit = cli.file.is_some() || config.storage.file.is_some();
+    if let (false, Some(home)) = (explicit, &home) {
+        if let Some(legacy) = paths::migrate_legacy(&path, home)? {
+            println!("Moved the tasks from {} to {}", legacy.display(), path.display());
+        }
+    }
 
-    buf
+    let first_run = !path.exists();
+    paths::prepare(&path)?;
+    Ok((path, first_run))
 }
 
 fn main() -> io::Result<()> {
-    let mut dir = match home_dir() {
-        Some(dir) => dir,
-        None => return Err(Error::other("Could not find a home directory")),
+    let cli = Cli::parse();
+    let config = Config::load()?;
+    let startup = Startup {
+        subject: cli.subject.clone(),
+        search: cli.search.clone(),
+        urgent: cli.urgent,
+        select: cli.select,
     };
 
-    dir.push(Path::new(constants::TABLE_PATH));
+    // Only needs to know how unist is started
+    if let Some(Command::DesktopEntry) = &cli.command {
+        print!("{}", cli::open_task::desktop_entry(&env::current_exe()?, cli.file.as_deref()));
+        return Ok(());
+    }
 
-    // prologue
-    if !dir.exists() {
-        println!("Creating dir");
-        fs::create_dir(&dir)?;
-        println!("Created dir");
+    if cli.demo {
+        let tasks = demo::generate(cli.seed, Local::now().fixed_offset());
+        return dispatch(cli.command, Box::new(MemoryStorage::with_tasks(tasks)), config, None, startup);
     }
 
-    dir.push(Path::new(constants::TABLE_NAME));
-    if !dir.exists() {
-        File::create(&dir)?;
+    let (path, first_run) = table_path(&cli, &config)?;
+    // Works on the files themselves rather than on the tasks in them
+    if let Some(Command::Bundle(args)) = &cli.command {
+        if let Err(error) = cli::bundle::run(args, &bundle::Locations { tasks: path, config: Config::path() }) {
+            eprintln!("{error}");
+            process::exit(1);
+        }
+        return Ok(());
+    }
+    let storage = storages::open(path.clone());
+    // The UI asks the same once it has read the file
+    if cli.command.is_some() {
+        if let Err(error) = cli::recover_damaged(storage.as_ref(), cli.force, io::stdin().is_terminal(), Local::now().fixed_offset()) {
+            eprintln!("{error}");
+            process::exit(1);
+        }
     }
+    let onboarding = first_run && config.ui.onboarding && !cli.no_onboarding;
+    dispatch(cli.command, storage, config, onboarding.then_some(path.as_path()), startup)
+}
 
-    println!("Loading tasks from {}...", constants::TABLE_NAME);
+/// Runs the command, or the UI without one. `onboarding` is the new tasks file to introduce,
+/// `startup` how the UI opens.
+fn dispatch(command: Option<Command>, storage: Box<dyn TaskStorage + Send>, config: Config, onboarding: Option<&Path>, startup: Startup) -> io::Result<()> {
+    let result = match command {
+        Some(Command::Validate(args)) => cli::validate::run(storage.as_ref(), &args, &config).map(|clean| {
+            if !clean {
+                process::exit(1);
+            }
+        }),
+        Some(Command::List(args)) => cli::list::run(storage.as_ref(), &args, &config),
+        Some(Command::Add(args)) => cli::add::run(storage.as_ref(), &args, &config),
+        Some(Command::Delete(args)) => cli::delete::run(storage.as_ref(), &args, &config),
+        Some(Command::Show(args)) => cli::show::run(storage.as_ref(), &args, &config),
+        Some(Command::Edit(args)) => cli::edit::run(storage.as_ref(), readers::from_config(&config).as_ref(), &args, &config),
+        Some(Command::Merge(args)) => cli::merge::run(storage.as_ref(), &args, &config),
+        Some(Command::Import(args)) => cli::import::run(storage.as_ref(), &args, &config),
+        Some(Command::Export(args)) => cli::export::run(storage.as_ref(), &args, &config),
+        Some(Command::Shift(args)) => cli::shift::run(storage.as_ref(), &args, &config),
+        Some(Command::Digest(args)) => cli::digest::run(storage.as_ref(), &args, &config),
+        Some(Command::Calendar(args)) => cli::calendar::run(storage.as_ref(), &args, &config),
+        Some(Command::Report(args)) => cli::report::run(storage.as_ref(), &args),
+        Some(Command::Prompt) => cli::prompt::run(storage.as_ref(), &config),
+        Some(Command::Status(args)) => cli::status::run(storage.as_ref(), &args, &config).map(|overdue| {
+            if overdue {
+                process::exit(cli::status::OVERDUE_EXIT_CODE);
+            }
+        }),
+        Some(Command::Bundle(_)) => Err(io::Error::other("Bundles are made of files, the demo tasks have none")),
+        Some(Command::DesktopEntry) => Ok(()),
+        // Checked up front, so a stale link gets an error rather than a list opened on nothing
+        Some(Command::OpenTask(args)) => match cli::open_task::resolve(storage.as_ref(), &args) {
+            Ok(id) => return tui(storage, config, None, Startup { select: Some(id), ..startup }),
+            Err(error) => Err(error),
+        },
+        None => return tui(storage, config, onboarding, startup),
+    };
 
+    if let Err(error) = result {
+        eprintln!("{error}");
+        process::exit(1);
+    }
+    Ok(())
+}
+
+/// The UI in the colors the config and the terminal allow
+fn tui(storage: Box<dyn TaskStorage + Send>, config: Config, onboarding: Option<&Path>, startup: Startup) -> io::Result<()> {
+    if config.ui.theme == Theme::Monochrome {
+        // Without colors, the symbols and text styles are all there is to tell tasks apart
+        let mut config = config;
+        config.ui.status_symbols = true;
+        return run_tui::<MonochromeTaskColors>(storage, config, onboarding, startup);
+    }
+    match config.ui.color_depth.unwrap_or_else(ColorDepth::detect) {
+        ColorDepth::TrueColor => run_tui::<StandardTaskColors>(storage, config, onboarding, startup),
+        ColorDepth::Ansi256 => run_tui::<Degraded<StandardTaskColors, Ansi256>>(storage, config, onboarding, startup),
+        ColorDepth::Ansi16 => run_tui::<Degraded<StandardTaskColors, Ansi16>>(storage, config, onboarding, startup),
+    }
+}
+
+fn run_tui<T: TaskColors>(storage: Box<dyn TaskStorage + Send>, config: Config, onboarding: Option<&Path>, startup: Startup) -> io::Result<()> {
+    let mouse = config.ui.mouse;
+    // The terminal comes up at once, a slow file shows a splash until it is read
+    let mut app = App::<T>::builder()
+        .with_config(config)
+        .with_startup(startup)
+        .with_onboarding(onboarding.map(Path::to_path_buf))
+        .build_loading(storage);
     let mut terminal = ratatui::init();
     terminal.clear()?;
-    let mut app = App::<StandardTaskColors, EditorTaskReader, TomlStorage>::new(
-        Box::new(TasksPane::new()),
-        Box::new(DescriptionPane::default()),
-        dir.clone(),
-    )?;
+    if mouse {
+        stdout().execute(EnableMouseCapture)?;
+    }
+    // Closing the terminal emulator or `kill` must not leave it in raw mode or lose the changes
+    let terminate = Arc::new(AtomicBool::new(false));
+    #[cfg(unix)]
+    for signal in [SIGTERM, SIGHUP] {
+        signal_hook::flag::register(signal, Arc::clone(&terminate))?;
+    }
+    app.terminate_on(terminate);
     let app_result = app.run(terminal);
+    if mouse {
+        stdout().execute(DisableMouseCapture)?;
+    }
     ratatui::restore();
+    // Left on stdout, the terminal only shows it once the UI is gone
+    if let Some(summary) = app.loaded_summary() {
+        println!("{summary}");
+    }
     app_result?;
 
     // if app.should_save() {
@@ -0,0 +1,139 @@
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use crate::constants;
+
+
+/// Where the tasks live, in order of precedence:
+/// `--file`, the config, `$XDG_DATA_HOME/unist/`, `~/.local/share/unist/`.
+/// The environment is passed in so the order can be checked without touching it.
+pub fn resolve_tasks_path(
+    flag: Option<&Path>,
+    config: Option<&Path>,
+    xdg_data_home: Option<&str>,
+    home: Option<&Path>,
+) -> io::Result<PathBuf> {
+    if let Some(path) = flag.or(config) {
+        return Ok(expand_tilde(path, home));
+    }
+
+    // The spec says relative values are invalid and should be ignored
+    let data_home = match xdg_data_home.map(PathBuf::from).filter(|x| x.is_absolute()) {
+        Some(dir) => dir,
+        None => home
+            .ok_or_else(|| io::Error::other("Could not find a home directory"))?
+            .join(".local/share"),
+    };
+    Ok(data_home.join(constants::DATA_DIR).join(constants::TABLE_NAME))
+}
+
+pub fn expand_tilde(path: &Path, home: Option<&Path>) -> PathBuf {
+    match (path.strip_prefix("~"), home) {
+        (Ok(rest), Some(home)) => home.join(rest),
+        _ => path.to_path_buf(),
+    }
+}
+
+/// `path` for people to read, the home directory shortened to `~`
+pub fn display_path(path: &Path, home: Option<&Path>) -> String {
+    match home.and_then(|x| path.strip_prefix(x).ok()) {
+        Some(rest) => Path::new("~").join(rest).display().to_string(),
+        None => path.display().to_string(),
+    }
+}
+
+/// Copies the tasks from an old default location if the new one is still empty.
+/// A note is left behind, returns the file migrated from.
+pub fn migrate_legacy(target: &Path, home: &Path) -> io::Result<Option<PathBuf>> {
+    if target.exists() {
+        return Ok(None);
+    }
+
+    let Some(legacy) = constants::LEGACY_TABLES
+        .iter()
+        .map(|x| home.join(x))
+        .find(|x| x.is_file())
+    else {
+        return Ok(None);
+    };
+
+    if let Some(dir) = target.parent() {
+        fs::create_dir_all(dir)?;
+    }
+    fs::copy(&legacy, target)?;
+
+    let mut note = legacy.clone().into_os_string();
+    note.push(".MOVED");
+    fs::write(
+        note,
+        format!("unist now keeps the tasks in {}\nThis copy is no longer read or written.\n", target.display()),
+    )?;
+    Ok(Some(legacy))
+}
+
+/// Makes sure the directories of the tasks file exist. The file itself is left to the
+/// first save, so a first run can still tell it is one.
+pub fn prepare(path: &Path) -> io::Result<()> {
+    match path.parent() {
+        Some(dir) => fs::create_dir_all(dir),
+        None => Ok(()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const HOME: &str = "/home/student";
+
+    fn resolve(flag: Option<&str>, config: Option<&str>, xdg: Option<&str>) -> PathBuf {
+        resolve_tasks_path(
+            flag.map(Path::new),
+            config.map(Path::new),
+            xdg,
+            Some(Path::new(HOME)),
+        ).unwrap()
+    }
+
+    #[test]
+    fn resolution_order() {
+        let expected = |x: &str| PathBuf::from(x);
+        assert_eq!(resolve(None, None, None), expected("/home/student/.local/share/unist/tasks.toml"));
+        assert_eq!(resolve(None, None, Some("/data")), expected("/data/unist/tasks.toml"));
+        assert_eq!(resolve(None, None, Some("relative")), expected("/home/student/.local/share/unist/tasks.toml"));
+        assert_eq!(resolve(None, Some("~/uni.toml"), Some("/data")), expected("/home/student/uni.toml"));
+        assert_eq!(resolve(Some("/tmp/a.toml"), Some("~/uni.toml"), Some("/data")), expected("/tmp/a.toml"));
+    }
+
+    #[test]
+    fn preparing_leaves_the_file_to_the_first_save() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("data/unist/tasks.toml");
+        prepare(&path).unwrap();
+        assert!(path.parent().unwrap().is_dir());
+        assert!(!path.exists());
+    }
+
+    #[test]
+    fn home_is_shortened_for_display() {
+        let home = Some(Path::new(HOME));
+        assert_eq!(display_path(Path::new("/home/student/.local/share/unist/tasks.toml"), home), "~/.local/share/unist/tasks.toml");
+        assert_eq!(display_path(Path::new("/home/studentx/tasks.toml"), home), "/home/studentx/tasks.toml");
+        assert_eq!(display_path(Path::new("/tmp/a.toml"), None), "/tmp/a.toml");
+    }
+
+    #[test]
+    fn legacy_file_is_copied_once() {
+        let home = tempfile::tempdir().unwrap();
+        let legacy = home.path().join(constants::LEGACY_TABLES[0]);
+        fs::create_dir_all(legacy.parent().unwrap()).unwrap();
+        fs::write(&legacy, "[[tasks]]\n").unwrap();
+        let target = home.path().join("share/unist/tasks.toml");
+
+        assert_eq!(migrate_legacy(&target, home.path()).unwrap(), Some(legacy.clone()));
+        assert_eq!(fs::read_to_string(&target).unwrap(), "[[tasks]]\n");
+        assert!(legacy.exists());
+        assert_eq!(migrate_legacy(&target, home.path()).unwrap(), None);
+    }
+}
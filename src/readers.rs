@@ -1,16 +1,36 @@
-use std::io::{Read, Seek, Write};
-use chrono::{DateTime, FixedOffset, Local};
+use std::borrow::Cow;
+use std::io::{BufWriter, Read, Seek, Write};
+use chrono::{DateTime, FixedOffset, Local, NaiveDate, NaiveTime, TimeDelta};
 use std::io::SeekFrom;
 use std::io;
 
+use crate::config::{Config, FrontMatter, ReaderKind};
+use crate::ui::form::FormReader;
+use crate::uni::anchors::Anchor;
+use crate::uni::dates::{deadline_on, format_date, format_duration};
+use crate::uni::reflow;
 use crate::uni::task::Task;
+use crate::uni::template::new_description_or_none;
 
 use serde::{Serialize, Deserialize};
-use matter::matter;
 
 
 pub trait TaskReader {
-    fn read(template: &Task) -> Result<Task, ()>;
+    fn read(&self, template: &Task, config: &Config) -> Result<Task, ()>;
+}
+
+/// Replaces `task` with the reader's result, leaving it untouched on failure
+pub fn edit(reader: &dyn TaskReader, task: &mut Task, config: &Config) -> Result<(), ()> {
+    *task = reader.read(task, config)?;
+    Ok(())
+}
+
+/// The reader the config asks for
+pub fn from_config(config: &Config) -> Box<dyn TaskReader> {
+    match config.ui.reader {
+        ReaderKind::Editor => Box::new(EditorTaskReader),
+        ReaderKind::Form => Box::new(FormReader),
+    }
 }
 
 
@@ -18,22 +38,51 @@ pub trait TaskReader {
 pub struct TaskMetadata {
     pub name: String,
     pub subject: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
     pub time: Option<String>,
+    #[serde(default)]
     pub complete: bool,
+    #[serde(default)]
     pub starred: bool,
+    /// In place of the status color, left out when there is none
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub color: Option<String>,
+    /// Id of the task the deadline follows, `time` is left out then
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub relative_to: Option<u64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub offset: Option<String>,
 }
 
 impl TaskMetadata {
     const DATE_FORMAT: &str = "%Y-%m-%d %H:%M %z";
+    /// Comment lines on top of the fields, both front matter formats take `#`
+    const HELP: &str = "\
+        # name, subject: text\n\
+        # time: deadline as YYYY-MM-DD HH:MM +ZZZZ, e.g. 2025-05-12 18:00 +0200,\n\
+        #   or the day alone for the default deadline time\n\
+        # complete, starred: true or false\n\
+        # color: optional, e.g. \"#b16286\" or magenta, in place of the status color\n\
+        # relative_to, offset: in place of time, e.g. 12 and -7d for a week before task 12\n\
+        # The description goes below the closing line. Saving unchanged keeps the task\n\
+        # as it was, emptying the file cancels the edit.\n";
 
     fn format_time(time: DateTime<FixedOffset>) -> String {
         format!("{}", time.format(Self::DATE_FORMAT))
     }
 
-    fn parse_time(time: Option<String>) -> Result<DateTime<FixedOffset>, ()> {
+    /// A day alone falls on `default_time` in the local zone
+    fn parse_time_exact(time: &str, default_time: NaiveTime) -> Option<DateTime<FixedOffset>> {
+        DateTime::parse_from_str(time, Self::DATE_FORMAT).ok().or_else(|| {
+            let day = NaiveDate::parse_from_str(time.trim(), "%Y-%m-%d").ok()?;
+            deadline_on(day, None, default_time, &Local)
+        })
+    }
+
+    /// As `parse_time_exact`, with now for anything unreadable
+    fn parse_time(time: Option<String>, default_time: NaiveTime) -> Result<DateTime<FixedOffset>, ()> {
         match time {
-            Some(time) => Ok(DateTime::parse_from_str(&time, Self::DATE_FORMAT)
-                .unwrap_or(Local::now().fixed_offset())),
+            Some(time) => Ok(Self::parse_time_exact(&time, default_time).unwrap_or(Local::now().fixed_offset())),
             None => Err(()),
         }
     }
@@ -42,9 +91,15 @@ impl TaskMetadata {
         Self {
             name: task.name.clone(),
             subject: task.subject.clone(),
-            time: Some(Self::format_time(task.time.clone().unwrap_or(Local::now().fixed_offset()))),
+            time: match task.anchor {
+                Some(_) => None,
+                None => Some(Self::format_time(task.time.unwrap_or(Local::now().fixed_offset()))),
+            },
             complete: task.complete,
             starred: task.starred,
+            color: task.color.clone(),
+            relative_to: task.anchor.map(|x| x.task),
+            offset: task.anchor.map(|x| format_duration(x.offset)),
         }
     }
 }
@@ -52,40 +107,153 @@ impl TaskMetadata {
 pub struct EditorTaskReader;
 
 impl EditorTaskReader {
-    fn to_task(task_proxy: TaskMetadata, description: String) -> Result<Task, ()> {
+    fn to_task(task_proxy: TaskMetadata, description: String, default_time: NaiveTime) -> Result<Task, ()> {
+        let anchor = Anchor::from_fields(task_proxy.relative_to, task_proxy.offset.as_deref(), task_proxy.time.as_deref()).map_err(|_| ())?;
         Ok(Task {
+            id: 0,
             name: task_proxy.name,
             subject: task_proxy.subject,
-            time: TaskMetadata::parse_time(task_proxy.time).map(|x| x.fixed_offset()).ok(),
+            time: TaskMetadata::parse_time(task_proxy.time, default_time).map(|x| x.fixed_offset()).ok(),
             description,
             complete: task_proxy.complete,
             starred: task_proxy.starred,
+            created_at: None,
+            completed_at: None,
+            updated_at: None,
+            time_spent: TimeDelta::zero(),
+            color: task_proxy.color,
+            anchor,
         })
     }
 
-    fn task_to_string(task: &Task) -> String {
+    /// The document the editor opens, written as it goes: a description of megabytes is
+    /// not copied on the way to the file
+    fn write_task(task: &Task, config: &Config, out: &mut dyn Write) -> io::Result<()> {
         let metadata = TaskMetadata::from_task(task);
-        let metadata_str = serde_yaml::to_string(&metadata).unwrap();
-        format!("---\n{}\n---\n{}", metadata_str, &task.description)
-    }
-
-    fn from_str_task(task: &str) -> Result<Task, ()> {
-        matter(task)
-            .ok_or(())
-            .map(|(metadata, description)| (serde_yaml::from_str::<TaskMetadata>(&metadata), description))
-            .and_then(|(metadata, description)| {
-                metadata
-                    .map_err(|_| ())
-                    .and_then(|metadata| Self::to_task(metadata, description))
+        let (delimiter, metadata_str) = match config.ui.editor_front_matter {
+            FrontMatter::Yaml => ("---", serde_yaml::to_string(&metadata).unwrap()),
+            FrontMatter::Toml => ("+++", toml::to_string(&metadata).unwrap()),
+        };
+        // Only a hint, the time field above is what gets parsed back
+        let mut weekday = match task.time {
+            Some(time) => format!("# Due {}\n", format_date(&time, &config.ui.date_display_format, &config.ui.date_names(), &config.ui.display_zone())),
+            None => String::new(),
+        };
+        if config.ui.editor_help_comments {
+            weekday += TaskMetadata::HELP;
+        }
+        // An empty description starts from the template, if one is configured
+        let today = Local::now().date_naive();
+        let description = match task.description.is_empty() {
+            true => Cow::Owned(new_description_or_none(config, &task.subject, &task.name, today).unwrap_or_default()),
+            false => Cow::Borrowed(task.description.as_str()),
+        };
+        let description = match config.ui.editor_wrap_width {
+            Some(width) => Cow::Owned(reflow::wrap(&description, width)),
+            None => description,
+        };
+        write!(out, "{delimiter}\n{weekday}{metadata_str}\n{delimiter}\n")?;
+        out.write_all(description.as_bytes())?;
+        // Editors like to end files with a newline, parsing takes it off again
+        out.write_all(b"\n")
+    }
+
+    #[cfg(test)]
+    fn task_to_string(task: &Task, config: &Config) -> String {
+        let mut document = Vec::new();
+        Self::write_task(task, config, &mut document).unwrap();
+        String::from_utf8(document).unwrap()
+    }
+
+    /// Front matter and description of a document. The first line and the next one like it
+    /// delimit the front matter, the description after them is kept exactly as written
+    /// except for the one newline every document ends with.
+    fn split_front_matter(document: &str) -> Option<(&str, &str)> {
+        let (opening, rest) = document.split_once('\n')?;
+        let delimiter = opening.trim_end();
+        if delimiter != "---" && delimiter != "+++" {
+            return None;
+        }
+        let mut offset = 0;
+        for line in rest.split_inclusive('\n') {
+            if line.trim_end() == delimiter {
+                let description = &rest[offset + line.len()..];
+                let description = description.strip_suffix("\r\n").or(description.strip_suffix('\n'));
+                return Some((&rest[..offset], description.unwrap_or(&rest[offset + line.len()..])));
+            }
+            offset += line.len();
+        }
+        None
+    }
+
+    /// The delimiters tell the format, but TOML written between `---` lines is fine too
+    fn parse_metadata(task: &str, metadata: &str) -> Result<TaskMetadata, ()> {
+        let yaml = || serde_yaml::from_str::<TaskMetadata>(metadata).map_err(|_| ());
+        let toml = || toml::from_str::<TaskMetadata>(metadata).map_err(|_| ());
+        match task.trim_start().starts_with("+++") {
+            true => toml().or_else(|_| yaml()),
+            false => yaml().or_else(|_| toml()),
+        }
+    }
+
+    fn from_str_task(task: &str, default_time: NaiveTime) -> Result<Task, ()> {
+        let (metadata, description) = Self::split_front_matter(task).ok_or(())?;
+        let metadata = Self::parse_metadata(task, metadata)?;
+        Self::to_task(metadata, description.to_string(), default_time)
+    }
+
+    /// Tasks of documents written one after another, each as the editor shows a task.
+    /// Every front matter line starts the next document, so descriptions may not contain
+    /// one. Unlike the editor, a deadline that can't be read is an error.
+    pub fn from_str_documents(text: &str, delimiter: &str, default_time: NaiveTime) -> Vec<Result<Task, String>> {
+        let mut parts = text.split_inclusive('\n').fold(vec![String::new()], |mut parts, line| {
+            if line.trim_end() == delimiter {
+                parts.push(String::new());
+            }
+            parts.last_mut().unwrap().push_str(line);
+            parts
+        });
+        let ignored = parts.remove(0);
+        if !ignored.trim().is_empty() {
+            return vec![Err(format!("Expected a {delimiter} line before \"{}\"", ignored.trim()))];
+        }
+
+        // Front matter and description alternate
+        parts
+            .chunks(2)
+            .map(|x| {
+                let document = x.concat();
+                let (metadata, description) = Self::split_front_matter(&document).ok_or("Front matter is not closed")?;
+                let metadata = Self::parse_metadata(&document, metadata).map_err(|_| "Could not read the front matter")?;
+                if let Some(time) = metadata.time.as_deref().filter(|x| TaskMetadata::parse_time_exact(x, default_time).is_none()) {
+                    return Err(format!("Unknown deadline \"{time}\""));
+                }
+                Self::to_task(metadata, description.to_string(), default_time).map_err(|_| "Invalid task".to_string())
             })
+            .collect()
+    }
+
+    /// The description as the editor left it, with wrapped lines joined again when wrapping
+    /// is on. A description that only differs in how its lines break stays as it was.
+    fn settle_description(template: &Task, description: String, config: &Config) -> String {
+        if config.ui.editor_wrap_width.is_none() {
+            return description;
+        }
+        let description = reflow::unwrap(&description);
+        match reflow::unwrap(&template.description) == description {
+            true => template.description.clone(),
+            false => description,
+        }
     }
 
-    fn _read(template: &Task) -> Result<String, io::Error> {
-        let template = Self::task_to_string(template);
+    fn _read(template: &Task, config: &Config) -> Result<String, io::Error> {
         let mut file = tempfile::Builder::new()
             .suffix(".md")
             .tempfile()?;
-        file.write_all(template.as_bytes())?;
+        let mut writer = BufWriter::new(&mut file);
+        Self::write_task(template, config, &mut writer)?;
+        writer.flush()?;
+        drop(writer);
         file.seek(SeekFrom::Start(0))?;
 
         edit::edit_file(&file)?;
@@ -99,9 +267,218 @@ impl EditorTaskReader {
 
 
 impl TaskReader for EditorTaskReader {
-    fn read(template: &Task) -> Result<Task, ()> {
-        Self::_read(template)
+    fn read(&self, template: &Task, config: &Config) -> Result<Task, ()> {
+        Self::_read(template, config)
             .map_err(|_| ())
-            .and_then(|x| Self::from_str_task(&x))
+            .and_then(|x| Self::from_str_task(&x, config.deadline_time()))
+            .map(|x| {
+                // Bookkeeping fields are not part of the document
+                let complete = x.complete;
+                let mut task = Task {
+                    description: Self::settle_description(template, x.description.clone(), config),
+                    id: template.id,
+                    complete: template.complete,
+                    created_at: template.created_at,
+                    completed_at: template.completed_at,
+                    updated_at: template.updated_at,
+                    time_spent: template.time_spent,
+                    ..x
+                };
+                task.set_complete(complete);
+                task
+            })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::uni::dates::END_OF_DAY;
+
+    #[test]
+    fn weekday_comment_is_not_parsed_back() {
+        let task = Task {
+            name: "HW1".to_string(),
+            subject: "Algo".to_string(),
+            description: "Notes".to_string(),
+            time: Some(DateTime::parse_from_rfc3339("2025-05-12T18:00:00+02:00").unwrap()),
+            ..Task::default()
+        };
+
+        let mut config = Config::default();
+        config.ui.display_timezone = "original".to_string();
+        let document = EditorTaskReader::task_to_string(&task, &config);
+        assert!(document.contains("# Due Mon 12 May 2025 18:00"));
+
+        let parsed = EditorTaskReader::from_str_task(&document, END_OF_DAY).unwrap();
+        assert_eq!(parsed.time, task.time);
+        assert_eq!(parsed.description, "Notes");
+    }
+
+    fn round_trip(front_matter: FrontMatter) -> (String, Task) {
+        let task = Task {
+            name: "HW1".to_string(),
+            subject: "Algo".to_string(),
+            description: "Notes".to_string(),
+            time: Some(DateTime::parse_from_rfc3339("2025-05-12T18:00:00+02:00").unwrap()),
+            starred: true,
+            color: Some("#b16286".to_string()),
+            ..Task::default()
+        };
+        let mut config = Config::default();
+        config.ui.editor_front_matter = front_matter;
+
+        let document = EditorTaskReader::task_to_string(&task, &config);
+        let parsed = EditorTaskReader::from_str_task(&document, END_OF_DAY).unwrap();
+        assert_eq!((&parsed.name, &parsed.subject, parsed.time, parsed.starred), (&task.name, &task.subject, task.time, true));
+        assert_eq!(parsed.description, "Notes");
+        assert_eq!(parsed.color, task.color);
+        (document, parsed)
+    }
+
+    #[test]
+    fn relative_deadline_takes_the_place_of_time() {
+        let task = Task {
+            name: "Draft".to_string(),
+            time: Some(DateTime::parse_from_rfc3339("2025-05-05T18:00:00+02:00").unwrap()),
+            anchor: Some(Anchor { task: 12, offset: TimeDelta::days(-7) }),
+            ..Task::default()
+        };
+
+        let document = EditorTaskReader::task_to_string(&task, &Config::default());
+        assert!(document.contains("\nrelative_to: 12\noffset: -7d\n"));
+        assert!(!document.contains("\ntime:"));
+        let parsed = EditorTaskReader::from_str_task(&document, END_OF_DAY).unwrap();
+        assert_eq!(parsed.anchor, task.anchor);
+        assert_eq!(parsed.time, None);
+    }
+
+    #[test]
+    fn both_front_matter_formats_round_trip() {
+        let (yaml, _) = round_trip(FrontMatter::Yaml);
+        assert!(yaml.starts_with("---\n") && yaml.contains("\nname: HW1\n"));
+        let (toml, _) = round_trip(FrontMatter::Toml);
+        assert!(toml.starts_with("+++\n") && toml.contains("\nname = \"HW1\"\n"));
+    }
+
+    #[test]
+    fn descriptions_round_trip_byte_for_byte() {
+        let pieces = ["", "\n", "\n\n", "  ", "\t", "\r\n", "---", "text", "# Heading", "\u{a0}"];
+        let mut config = Config::default();
+
+        for front_matter in [FrontMatter::Yaml, FrontMatter::Toml] {
+            config.ui.editor_front_matter = front_matter;
+            for a in pieces {
+                for b in pieces {
+                    for c in pieces {
+                        let description = format!("{a}{b}text{c}{a}");
+                        let task = Task { name: "HW1".to_string(), description, ..Task::new_now() };
+
+                        let document = EditorTaskReader::task_to_string(&task, &config);
+                        let parsed = EditorTaskReader::from_str_task(&document, END_OF_DAY).unwrap();
+                        assert_eq!(parsed.description, task.description, "{document:?}");
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn final_newline_from_the_editor_is_not_part_of_the_description() {
+        let fields = "name: HW1\nsubject: Algo\ncomplete: false\nstarred: false\n";
+        let with_newline = EditorTaskReader::from_str_task(&format!("---\n{fields}---\nNotes\n"), END_OF_DAY).unwrap();
+        let without = EditorTaskReader::from_str_task(&format!("---\n{fields}---\nNotes"), END_OF_DAY).unwrap();
+        let crlf = format!("---\n{fields}---\nNotes\n").replace('\n', "\r\n");
+        let crlf = EditorTaskReader::from_str_task(&crlf, END_OF_DAY).unwrap();
+
+        assert_eq!(with_newline.description, "Notes");
+        assert_eq!(without.description, "Notes");
+        assert_eq!(crlf.description, "Notes");
+        assert!(EditorTaskReader::from_str_task("", END_OF_DAY).is_err());
+    }
+
+    #[test]
+    fn delimiter_lines_in_the_description_are_kept() {
+        let description = "Intro\n\n---\n\nname: Not the name\nsubject: Nor this\n----\n+++\ntime = \"2025\"\n---";
+        let task = Task { name: "HW1".to_string(), subject: "Algo".to_string(), ..Task::new_now() };
+        let mut config = Config::default();
+
+        for front_matter in [FrontMatter::Yaml, FrontMatter::Toml] {
+            config.ui.editor_front_matter = front_matter;
+            // At the very start of the description too, right after the closing line
+            for description in [description.to_string(), format!("---\n{description}")] {
+                let task = Task { description: description.clone(), ..task.clone() };
+
+                let document = EditorTaskReader::task_to_string(&task, &config);
+                let parsed = EditorTaskReader::from_str_task(&document, END_OF_DAY).unwrap();
+                assert_eq!((parsed.name.as_str(), parsed.subject.as_str()), ("HW1", "Algo"));
+                assert_eq!(parsed.description, description);
+            }
+        }
+    }
+
+    #[test]
+    fn wrapped_descriptions_are_joined_back() {
+        let description = "Read chapters seven and eight before the lab.\n- bring the notes\n```\nlet long_line = 0;\n```";
+        let task = Task { name: "HW1".to_string(), description: description.to_string(), ..Task::new_now() };
+        let mut config = Config::default();
+        config.ui.editor_wrap_width = Some(16);
+
+        let document = EditorTaskReader::task_to_string(&task, &config);
+        assert!(document.ends_with("\nRead chapters\nseven and eight\nbefore the lab.\n- bring the\n  notes\n```\nlet long_line = 0;\n```\n"));
+        let parsed = EditorTaskReader::from_str_task(&document, END_OF_DAY).unwrap();
+        assert_eq!(EditorTaskReader::settle_description(&task, parsed.description, &config), description);
+
+        // Broken by hand before, saving unchanged keeps it that way
+        let by_hand = Task { description: "One\ntwo".to_string(), ..task.clone() };
+        assert_eq!(EditorTaskReader::settle_description(&by_hand, "One two".to_string(), &config), "One\ntwo");
+        assert_eq!(EditorTaskReader::settle_description(&by_hand, "One\nthree".to_string(), &config), "One three");
+    }
+
+    #[test]
+    fn help_comments_stay_out_of_the_task() {
+        let task = Task { name: "HW1".to_string(), description: "Notes".to_string(), ..Task::new_now() };
+        let mut config = Config::default();
+
+        for front_matter in [FrontMatter::Yaml, FrontMatter::Toml] {
+            config.ui.editor_front_matter = front_matter;
+            let document = EditorTaskReader::task_to_string(&task, &config);
+            assert!(document.contains("# time: deadline as YYYY-MM-DD HH:MM +ZZZZ"));
+
+            let parsed = EditorTaskReader::from_str_task(&document, END_OF_DAY).unwrap();
+            assert_eq!(parsed.description, "Notes");
+            assert!(!format!("{parsed:?}").contains("YYYY"));
+        }
+
+        config.ui.editor_help_comments = false;
+        assert!(!EditorTaskReader::task_to_string(&task, &config).contains("YYYY"));
+    }
+
+    #[test]
+    fn toml_between_yaml_delimiters_and_unknown_keys_are_accepted() {
+        let toml_in_yaml = "---\nname = \"HW1\"\nsubject = \"Algo\"\ntime = \"2025-05-12 18:00 +0200\"\n\
+            complete = false\nstarred = false\npriority = 3\n---\nNotes";
+        let task = EditorTaskReader::from_str_task(toml_in_yaml, END_OF_DAY).unwrap();
+        assert_eq!((task.name.as_str(), task.description.as_str()), ("HW1", "Notes"));
+        assert_eq!(task.time, Some(DateTime::parse_from_rfc3339("2025-05-12T18:00:00+02:00").unwrap()));
+
+        // Keys the document does not know about are ignored, in either format
+        let yaml = "---\nname: HW1\nsubject: Algo\ntime: 2025-05-12 18:00 +0200\ncomplete: true\nstarred: false\n\
+            priority: 3\n---\nNotes";
+        assert!(EditorTaskReader::from_str_task(yaml, END_OF_DAY).unwrap().complete);
+        assert!(EditorTaskReader::from_str_task("---\nname: HW1\n---\nNotes", END_OF_DAY).is_err());
+    }
+
+    #[test]
+    fn a_day_alone_in_the_front_matter_takes_the_default_time() {
+        let nine = NaiveTime::from_hms_opt(9, 0, 0).unwrap();
+        let document = |time| format!("---\nname: HW1\nsubject: Algo\ntime: {time}\ncomplete: false\nstarred: false\n---\n");
+        let local = |task: Task| task.time.unwrap().with_timezone(&Local).naive_local().to_string();
+
+        assert_eq!(local(EditorTaskReader::from_str_task(&document("2025-05-12"), nine).unwrap()), "2025-05-12 09:00:00");
+        assert_eq!(local(EditorTaskReader::from_str_task(&document("2025-05-12"), END_OF_DAY).unwrap()), "2025-05-12 23:59:00");
+        let typed = EditorTaskReader::from_str_task(&document("2025-05-12 18:00 +0200"), nine).unwrap();
+        assert_eq!(typed.time, Some(DateTime::parse_from_rfc3339("2025-05-12T18:00:00+02:00").unwrap()));
     }
 }
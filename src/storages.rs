@@ -1,102 +1,916 @@
-use std::io;
-use std::{fs, path::PathBuf};
+use std::cell::{Cell, RefCell};
+use std::fmt;
+use std::hash::{DefaultHasher, Hash, Hasher};
+use std::io::{self, Write};
+use std::{fs, path::{Path, PathBuf}};
 
-use crate::uni::task::Task;
+use crate::uni::anchors::{self, Anchor};
+use crate::uni::dates::format_duration;
+use crate::uni::task::{assign_ids, Task};
 
-use chrono::DateTime;
+use chrono::{DateTime, FixedOffset, TimeDelta};
 use serde::{Serialize, Deserialize};
 
 
-pub trait TaskStorage: Sized + Clone + 'static {
-    fn new(path: PathBuf) -> Self;
+/// Where the tasks live. Object safe, so the backend can be picked at runtime.
+pub trait TaskStorage {
     fn should_save(&self, tasks: &Vec<Task>) -> bool;
-    fn read(&self) -> Result<Vec<Task>, io::Error>;
+    /// Tasks exactly as stored, those from older files keep id 0
+    fn read_without_ids(&self) -> Result<Vec<Task>, io::Error>;
+    fn read(&self) -> Result<Vec<Task>, io::Error> {
+        let mut tasks = self.read_without_ids()?;
+        assign_ids(&mut tasks);
+        anchors::resolve(&mut tasks);
+        Ok(tasks)
+    }
     fn write(&self, tasks: &Vec<Task>) -> Result<(), io::Error>;
+    /// Fingerprint of the file as last read or written, None for tasks only in memory
+    fn fingerprint(&self) -> Option<Fingerprint> {
+        None
+    }
+    /// `write`, unless the file no longer has the `expected` fingerprint because something
+    /// else wrote it since. That fails with a `WriteConflict`. Without a fingerprint it just writes.
+    fn write_if_unchanged(&self, tasks: &Vec<Task>, _expected: Option<Fingerprint>) -> Result<(), io::Error> {
+        self.write(tasks)
+    }
+    /// Keeps a copy of the current contents aside, returns where it went
+    fn backup(&self) -> Result<PathBuf, io::Error>;
+    /// Unsaved tasks kept next to the real ones in case the app dies before saving
+    fn write_journal(&self, _tasks: &Vec<Task>) -> Result<(), io::Error> {
+        Ok(())
+    }
+    /// The journaled tasks, when the journal is newer than the saved ones
+    fn read_journal(&self) -> Result<Option<Vec<Task>>, io::Error> {
+        Ok(None)
+    }
+    fn discard_journal(&self) -> Result<(), io::Error> {
+        Ok(())
+    }
+    /// The file the tasks are kept in, None for tasks only in memory
+    fn path(&self) -> Option<&Path> {
+        None
+    }
+}
+
+/// Hash of the file's contents, tells whether it changed since it was read
+pub type Fingerprint = u64;
+
+/// Why `write_if_unchanged` refused to write, wrapped in the `io::Error` it returns
+#[derive(Debug)]
+pub struct WriteConflict {
+    pub path: PathBuf,
+}
+
+impl WriteConflict {
+    pub fn is(error: &io::Error) -> bool {
+        error.get_ref().is_some_and(|x| x.is::<WriteConflict>())
+    }
+}
+
+impl fmt::Display for WriteConflict {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} was changed by something else since it was read", self.path.display())
+    }
+}
+
+impl std::error::Error for WriteConflict {}
+
+/// Why `read` could not load a file, with every task that could still be read from it.
+/// Wrapped in the `io::Error` `read` returns, see `set_aside`.
+#[derive(Debug)]
+pub struct Damaged {
+    pub path: PathBuf,
+    pub error: String,
+    pub recovered: Vec<Task>,
+    /// Entries that could not be read
+    pub lost: usize,
+}
+
+impl Damaged {
+    /// The damage `error` is about, or `error` itself when it is about something else
+    pub fn take(error: io::Error) -> Result<Damaged, io::Error> {
+        match error.get_ref().is_some_and(|x| x.is::<Damaged>()) {
+            true => Ok(*error.into_inner().unwrap().downcast::<Damaged>().unwrap()),
+            false => Err(error),
+        }
+    }
+
+    /// "3 of 5 tasks recovered, 2 lost"
+    pub fn summary(&self) -> String {
+        let total = self.recovered.len() + self.lost;
+        format!("{} of {total} task(s) recovered, {} lost", self.recovered.len(), self.lost)
+    }
+}
+
+impl fmt::Display for Damaged {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} is damaged: {}", self.path.display(), self.error)
+    }
+}
+
+impl std::error::Error for Damaged {}
+
+/// Keeps the damaged file as `<file>.corrupt-<timestamp>` and puts the tasks recovered from
+/// it in its place, returns where the original went. `read` works again afterwards.
+pub fn set_aside(storage: &dyn TaskStorage, damaged: &Damaged, now: DateTime<FixedOffset>) -> Result<PathBuf, io::Error> {
+    let mut corrupt = damaged.path.clone().into_os_string();
+    corrupt.push(format!(".corrupt-{}", now.format("%Y%m%d-%H%M%S")));
+    let corrupt = PathBuf::from(corrupt);
+    fs::copy(&damaged.path, &corrupt)?;
+    storage.write(&damaged.recovered)?;
+    Ok(corrupt)
+}
+
+fn fingerprint(content: &str) -> Fingerprint {
+    let mut hasher = DefaultHasher::new();
+    content.hash(&mut hasher);
+    hasher.finish()
+}
+
+/// Fails with a `WriteConflict` unless the file still has the `expected` fingerprint.
+/// A file removed meanwhile has nothing left to lose, the write goes ahead.
+fn check_unchanged(path: &Path, expected: Option<Fingerprint>) -> Result<(), io::Error> {
+    let Some(expected) = expected else { return Ok(()) };
+    match fs::read_to_string(path) {
+        Ok(content) if fingerprint(&content) != expected => {
+            Err(io::Error::other(WriteConflict { path: path.to_path_buf() }))
+        },
+        Err(error) if error.kind() != io::ErrorKind::NotFound => Err(error),
+        _ => Ok(()),
+    }
+}
+
+/// The file's contents, empty when it isn't there yet: it is created on the first save
+fn read_file(path: &Path) -> Result<String, io::Error> {
+    match fs::read_to_string(path) {
+        Err(error) if error.kind() == io::ErrorKind::NotFound => Ok(String::new()),
+        result => result,
+    }
+}
+
+/// Picks the backend from the file extension: `.json` gets JSON, anything else TOML
+pub fn open(path: PathBuf) -> Box<dyn TaskStorage + Send> {
+    match path.extension().and_then(|x| x.to_str()) {
+        Some(extension) if extension.eq_ignore_ascii_case("json") => Box::new(JsonStorage::new(path)),
+        _ => Box::new(TomlStorage::new(path)),
+    }
+}
+
+/// Undoes what a trip through other tools does to the file:
+/// a UTF-8 BOM, CRLF line endings and NUL padding at the end
+fn normalize(content: &str) -> String {
+    content
+        .strip_prefix('\u{feff}')
+        .unwrap_or(content)
+        .replace("\r\n", "\n")
+        .trim_end_matches('\0')
+        .to_string()
+}
+
+/// Canonical entries: tasks in id (creation) order regardless of how the UI sorts them
+fn canonical_entries(tasks: &[Task]) -> Tasks {
+    let mut entries = tasks.iter().map(TaskEntry::from_task).collect::<Vec<_>>();
+    entries.sort_by_key(|x| x.id);
+    Tasks { tasks: entries }
+}
+
+/// Canonical entries as last read or written, what `should_save` compares against
+/// instead of going back to the file
+#[derive(Clone, Default)]
+struct Snapshot(RefCell<Option<Tasks>>);
+
+impl Snapshot {
+    /// Tasks as `read` hands them out, with ids
+    fn record(&self, tasks: &[Task]) {
+        let mut tasks = tasks.to_vec();
+        assign_ids(&mut tasks);
+        *self.0.borrow_mut() = Some(canonical_entries(&tasks));
+    }
+
+    /// Nothing read or written yet counts as a change
+    fn differs(&self, tasks: &[Task]) -> bool {
+        self.0.borrow().as_ref().is_none_or(|saved| *saved != canonical_entries(tasks))
+    }
+}
+
+fn parse_entries(entries: Tasks) -> Result<Vec<Task>, String> {
+    entries.tasks
+        .into_iter()
+        .map(|x| {
+            let name = x.name.clone();
+            x.to_task().map_err(|_| format!("Could not parse the task {name}"))
+        })
+        .collect()
 }
 
+/// Every `[[tasks]]` entry of a file that doesn't parse as a whole that still parses on its
+/// own, and how many did not. A file cut off mid-write loses its last entry, a garbled one
+/// the entries with garbage in them. A `[[tasks]]` line inside a multiline string is part of
+/// the string, not a new entry.
+fn recover_entries(content: &str) -> (Vec<Task>, usize) {
+    let mut blocks: Vec<String> = vec![];
+    let mut open = None;
+    for line in content.lines() {
+        match (open.is_none() && line.trim() == "[[tasks]]", blocks.last_mut()) {
+            (true, _) => blocks.push(String::new()),
+            // Anything before the first entry is no task
+            (false, None) => (),
+            (false, Some(block)) => {
+                block.push_str(line);
+                block.push('\n');
+            },
+        }
+        open = multiline_string_after(line, open);
+    }
+    let mut recovered = vec![];
+    let mut lost = 0;
+    for block in blocks {
+        match toml::from_str::<TaskEntry>(&block).map(TaskEntry::to_task) {
+            Ok(Ok(task)) => recovered.push(task),
+            _ => lost += 1,
+        }
+    }
+    (recovered, lost)
+}
+
+/// The delimiter of the multiline string still open at the end of `line`, given the one open
+/// at its start. Single-line strings and comments are skipped, so quotes in them don't count.
+fn multiline_string_after(line: &str, mut open: Option<&'static str>) -> Option<&'static str> {
+    let mut rest = line;
+    while !rest.is_empty() {
+        if let Some(delimiter) = open {
+            // Only basic strings have escapes
+            let end = match delimiter {
+                r#"""""# => find_unescaped(rest, delimiter),
+                _ => rest.find(delimiter),
+            };
+            let Some(end) = end else { return open };
+            // Up to two quotes right before the delimiter still belong to the string
+            let quote = &delimiter[..1];
+            let extra = rest[end + delimiter.len()..].chars().take(2).take_while(|x| quote.starts_with(*x)).count();
+            rest = &rest[end + delimiter.len() + extra..];
+            open = None;
+        } else if let Some(delimiter) = [r#"""""#, "'''"].into_iter().find(|x| rest.starts_with(x)) {
+            rest = &rest[delimiter.len()..];
+            open = Some(delimiter);
+        } else if let Some(after) = rest.strip_prefix('"') {
+            rest = find_unescaped(after, "\"").map_or("", |end| &after[end + 1..]);
+        } else if let Some(after) = rest.strip_prefix('\'') {
+            rest = after.find('\'').map_or("", |end| &after[end + 1..]);
+        } else if rest.starts_with('#') {
+            break;
+        } else {
+            let next = rest.chars().next().map_or(1, char::len_utf8);
+            rest = &rest[next..];
+        }
+    }
+    open
+}
+
+/// Where `pattern` first appears in `text` other than right after a backslash
+fn find_unescaped(text: &str, pattern: &str) -> Option<usize> {
+    let mut escaped = false;
+    for (index, c) in text.char_indices() {
+        if !escaped && text[index..].starts_with(pattern) {
+            return Some(index);
+        }
+        escaped = !escaped && c == '\\';
+    }
+    None
+}
+
+/// Write next to the target and rename over it, so a crash never leaves half a file.
+/// The file keeps the permissions it had, a new one gets the temporary file's.
+pub fn write_atomically(path: &Path, content: &str) -> Result<(), io::Error> {
+    let dir = path.parent().unwrap_or(Path::new("."));
+    let mut file = tempfile::NamedTempFile::new_in(dir)?;
+    if let Ok(metadata) = fs::metadata(path) {
+        file.as_file().set_permissions(metadata.permissions())?;
+    }
+    file.write_all(content.as_bytes())?;
+    file.persist(path).map_err(|e| e.error)?;
+    Ok(())
+}
+
+/// Copies the file to `<file>.bak`, returns where
+pub fn backup_file(path: &Path) -> Result<PathBuf, io::Error> {
+    let mut backup = path.to_path_buf().into_os_string();
+    backup.push(".bak");
+    let backup = PathBuf::from(backup);
+    fs::copy(path, &backup)?;
+    Ok(backup)
+}
+
+fn journal_file(path: &Path) -> PathBuf {
+    let mut journal = path.to_path_buf().into_os_string();
+    journal.push(".journal");
+    PathBuf::from(journal)
+}
+
+/// The journal, unless it is missing or the file was saved after it was written
+fn fresh_journal(path: &Path) -> Option<PathBuf> {
+    let journal = journal_file(path);
+    let written = fs::metadata(&journal).and_then(|x| x.modified()).ok()?;
+    match fs::metadata(path).and_then(|x| x.modified()) {
+        // Equal times happen on coarse filesystems, better to ask once too often
+        Ok(saved) if saved > written => None,
+        _ => Some(journal),
+    }
+}
 
-#[derive(Serialize, Deserialize)]
+fn remove_journal(path: &Path) -> Result<(), io::Error> {
+    match fs::remove_file(journal_file(path)) {
+        Err(error) if error.kind() != io::ErrorKind::NotFound => Err(error),
+        _ => Ok(()),
+    }
+}
+
+
+#[derive(Clone, PartialEq, Serialize, Deserialize)]
 pub struct TaskEntry {
+    #[serde(default)]
+    pub id: u64,
     pub name: String,
     pub description: String,
     pub subject: String,
     pub time: Option<String>,
     pub complete: bool,
     pub starred: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub created_at: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub completed_at: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub updated_at: Option<String>,
+    /// Whole minutes
+    #[serde(default, skip_serializing_if = "is_zero")]
+    pub time_spent: i64,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub color: Option<String>,
+    /// Id of the task the deadline follows, in place of `time`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub relative_to: Option<u64>,
+    /// Added to that task's deadline, as in "-7d"
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub offset: Option<String>,
+}
+
+fn is_zero(minutes: &i64) -> bool {
+    *minutes == 0
 }
 
-#[derive(Serialize, Deserialize)]
+#[derive(Clone, PartialEq, Serialize, Deserialize)]
 struct Tasks {
+    // A freshly created file is empty
+    #[serde(default)]
     tasks: Vec<TaskEntry>
 }
 
 impl TaskEntry {
     fn from_task(task: &Task) -> Self {
         Self {
+            id: task.id,
             name: task.name.clone(),
             description: task.description.clone(),
             subject: task.subject.clone(),
-            time: task.time.map(|x| x.to_rfc3339()),
+            // The resolved deadline would go stale as soon as the anchor moves
+            time: task.time.filter(|_| task.anchor.is_none()).map(|x| x.to_rfc3339()),
             complete: task.complete,
             starred: task.starred,
+            created_at: task.created_at.map(|x| x.to_rfc3339()),
+            completed_at: task.completed_at.map(|x| x.to_rfc3339()),
+            updated_at: task.updated_at.map(|x| x.to_rfc3339()),
+            time_spent: task.time_spent.num_minutes(),
+            color: task.color.clone(),
+            relative_to: task.anchor.map(|x| x.task),
+            offset: task.anchor.map(|x| format_duration(x.offset)),
         }
     }
-    fn to_task(self) -> Result<Task, ()> {
-        let time = match self.time {
+
+    fn parse_time(time: Option<String>) -> Result<Option<DateTime<FixedOffset>>, ()> {
+        match time {
             Some(time) => {
-                Some(DateTime::parse_from_rfc3339(&time).map_err(|_| ())?)
+                Ok(Some(DateTime::parse_from_rfc3339(&time).map_err(|_| ())?))
             }
-            None => None
-        };
+            None => Ok(None)
+        }
+    }
+
+    fn to_task(self) -> Result<Task, ()> {
+        let anchor = Anchor::from_fields(self.relative_to, self.offset.as_deref(), self.time.as_deref()).map_err(|_| ())?;
+        let time = Self::parse_time(self.time)?;
         Ok(Task {
+            id: self.id,
             name: self.name,
             description: self.description,
             subject: self.subject,
             time,
             complete: self.complete,
             starred: self.starred,
+            created_at: Self::parse_time(self.created_at)?,
+            completed_at: Self::parse_time(self.completed_at)?,
+            updated_at: Self::parse_time(self.updated_at)?,
+            time_spent: TimeDelta::try_minutes(self.time_spent).ok_or(())?,
+            color: self.color,
+            anchor,
         })
     }
 }
 
 #[derive(Clone)]
 pub struct TomlStorage {
-    path: PathBuf
+    path: PathBuf,
+    saved: Snapshot,
+    on_disk: Cell<Option<Fingerprint>>,
 }
 
 impl TomlStorage {
-    fn dump<'a>(&self, tasks: &Vec<Task>) -> String {
-        let tasks = Tasks {
-            tasks: tasks.iter().map(TaskEntry::from_task).collect()
-        };
-        toml::to_string(&tasks).unwrap()
+    pub fn new(path: PathBuf) -> Self {
+        Self {
+            path,
+            saved: Snapshot::default(),
+            on_disk: Cell::new(None),
+        }
+    }
+
+    /// Canonical file contents, keys in the `TaskEntry` field order
+    fn dump(&self, tasks: &[Task]) -> String {
+        toml::to_string(&canonical_entries(tasks)).unwrap()
     }
 }
 
 impl TaskStorage for TomlStorage {
-    fn new(path: PathBuf) -> Self {
+    fn read_without_ids(&self) -> Result<Vec<Task>, io::Error> {
+        let raw = read_file(&self.path)?;
+        let content = normalize(&raw);
+        let parsed = toml::from_str::<Tasks>(&content).map_err(|x| x.message().to_string());
+        let tasks = match parsed.and_then(parse_entries) {
+            Ok(tasks) => tasks,
+            Err(error) => {
+                let (recovered, lost) = recover_entries(&content);
+                return Err(io::Error::other(Damaged { path: self.path.clone(), error, recovered, lost }));
+            },
+        };
+        self.saved.record(&tasks);
+        self.on_disk.set(Some(fingerprint(&raw)));
+        Ok(tasks)
+    }
+
+    fn should_save(&self, tasks: &Vec<Task>) -> bool {
+        // Compare canonical forms, so a file written by hand or an older version doesn't count as a change
+        self.saved.differs(tasks)
+    }
+
+    fn write(&self, tasks: &Vec<Task>) -> Result<(), std::io::Error> {
+        let content = self.dump(tasks);
+        write_atomically(&self.path, &content)?;
+        self.saved.record(tasks);
+        self.on_disk.set(Some(fingerprint(&content)));
+        Ok(())
+    }
+
+    fn fingerprint(&self) -> Option<Fingerprint> {
+        self.on_disk.get()
+    }
+
+    fn write_if_unchanged(&self, tasks: &Vec<Task>, expected: Option<Fingerprint>) -> Result<(), std::io::Error> {
+        check_unchanged(&self.path, expected)?;
+        self.write(tasks)
+    }
+
+    fn backup(&self) -> Result<PathBuf, std::io::Error> {
+        backup_file(&self.path)
+    }
+
+    fn write_journal(&self, tasks: &Vec<Task>) -> Result<(), std::io::Error> {
+        write_atomically(&journal_file(&self.path), &self.dump(tasks))
+    }
+
+    fn read_journal(&self) -> Result<Option<Vec<Task>>, std::io::Error> {
+        let Some(journal) = fresh_journal(&self.path) else { return Ok(None) };
+        TomlStorage::new(journal).read().map(Some)
+    }
+
+    fn discard_journal(&self) -> Result<(), std::io::Error> {
+        remove_journal(&self.path)
+    }
+
+    fn path(&self) -> Option<&Path> {
+        Some(&self.path)
+    }
+}
+
+/// Same entries as `TomlStorage`, as pretty-printed JSON
+#[derive(Clone)]
+pub struct JsonStorage {
+    path: PathBuf,
+    saved: Snapshot,
+    on_disk: Cell<Option<Fingerprint>>,
+}
+
+impl JsonStorage {
+    pub fn new(path: PathBuf) -> Self {
         Self {
-            path
+            path,
+            saved: Snapshot::default(),
+            on_disk: Cell::new(None),
         }
     }
 
+    fn dump(&self, tasks: &[Task]) -> String {
+        let mut content = serde_json::to_string_pretty(&canonical_entries(tasks)).unwrap();
+        content.push('\n');
+        content
+    }
+}
 
-    fn read(&self) -> Result<Vec<Task>, std::io::Error> {
-        let content = fs::read_to_string(&self.path)?;
-        let task_entries = toml::from_str::<Tasks>(&content).expect(&format!("Could not parse the file: {}", self.path.display()));
-        let mut tasks = vec![];
-        for task_entry in task_entries.tasks {
-            let name = task_entry.name.clone();
-            tasks.push(task_entry.to_task().expect(&format!("Could not parse the task {}", name)));
+impl TaskStorage for JsonStorage {
+    fn read_without_ids(&self) -> Result<Vec<Task>, io::Error> {
+        let raw = read_file(&self.path)?;
+        let content = normalize(&raw);
+        // A missing or freshly created file is empty, which isn't valid JSON
+        let tasks = match content.trim().is_empty() {
+            true => vec![],
+            false => serde_json::from_str::<Tasks>(&content)
+                .map_err(|x| x.to_string())
+                .and_then(parse_entries)
+                .map_err(|x| io::Error::other(format!("Could not parse {}: {x}", self.path.display())))?,
         };
+        self.saved.record(&tasks);
+        self.on_disk.set(Some(fingerprint(&raw)));
         Ok(tasks)
     }
 
     fn should_save(&self, tasks: &Vec<Task>) -> bool {
-        fs::read_to_string(&self.path).unwrap() != self.dump(tasks)
+        self.saved.differs(tasks)
     }
 
     fn write(&self, tasks: &Vec<Task>) -> Result<(), std::io::Error> {
-        fs::write(&self.path, self.dump(tasks))
+        let content = self.dump(tasks);
+        write_atomically(&self.path, &content)?;
+        self.saved.record(tasks);
+        self.on_disk.set(Some(fingerprint(&content)));
+        Ok(())
+    }
+
+    fn fingerprint(&self) -> Option<Fingerprint> {
+        self.on_disk.get()
+    }
+
+    fn write_if_unchanged(&self, tasks: &Vec<Task>, expected: Option<Fingerprint>) -> Result<(), std::io::Error> {
+        check_unchanged(&self.path, expected)?;
+        self.write(tasks)
+    }
+
+    fn backup(&self) -> Result<PathBuf, std::io::Error> {
+        backup_file(&self.path)
+    }
+
+    fn write_journal(&self, tasks: &Vec<Task>) -> Result<(), std::io::Error> {
+        write_atomically(&journal_file(&self.path), &self.dump(tasks))
+    }
+
+    fn read_journal(&self) -> Result<Option<Vec<Task>>, std::io::Error> {
+        let Some(journal) = fresh_journal(&self.path) else { return Ok(None) };
+        JsonStorage::new(journal).read().map(Some)
+    }
+
+    fn discard_journal(&self) -> Result<(), std::io::Error> {
+        remove_journal(&self.path)
+    }
+
+    fn path(&self) -> Option<&Path> {
+        Some(&self.path)
+    }
+}
+
+/// Keeps the tasks it was given and never writes them anywhere.
+/// Backs the demo mode and the UI tests.
+#[derive(Clone, Default)]
+pub struct MemoryStorage {
+    tasks: Vec<Task>
+}
+
+impl MemoryStorage {
+    pub fn with_tasks(tasks: Vec<Task>) -> Self {
+        Self {
+            tasks
+        }
+    }
+}
+
+impl TaskStorage for MemoryStorage {
+    fn read_without_ids(&self) -> Result<Vec<Task>, std::io::Error> {
+        Ok(self.tasks.clone())
+    }
+
+    fn should_save(&self, _tasks: &Vec<Task>) -> bool {
+        false
+    }
+
+    fn write(&self, _tasks: &Vec<Task>) -> Result<(), std::io::Error> {
+        Ok(())
+    }
+
+    fn backup(&self) -> Result<PathBuf, std::io::Error> {
+        Err(io::Error::other("Tasks in memory have no file to back up"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const GOLDEN: &str = include_str!("../tests/fixtures/golden.toml");
+    const TRUNCATED: &str = include_str!("../tests/fixtures/truncated.toml");
+    const GARBLED: &str = include_str!("../tests/fixtures/garbled.toml");
+    const MULTILINE: &str = include_str!("../tests/fixtures/multiline.toml");
+
+    fn storage_with(content: &str) -> (tempfile::TempDir, TomlStorage) {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("tasks.toml");
+        fs::write(&path, content).unwrap();
+        (dir, TomlStorage::new(path))
+    }
+
+    #[test]
+    fn untouched_file_round_trips_byte_for_byte() {
+        let (_dir, storage) = storage_with(GOLDEN);
+        let tasks = storage.read().unwrap();
+
+        assert!(!storage.should_save(&tasks));
+        storage.write(&tasks).unwrap();
+        assert_eq!(fs::read_to_string(&storage.path).unwrap(), GOLDEN);
+    }
+
+    #[test]
+    fn foreign_line_endings_and_bom_survive_a_load_save_cycle() {
+        let windows = format!("\u{feff}{}", GOLDEN.replace('\n', "\r\n"));
+        for content in [windows.clone(), format!("{windows}\0\0\0"), GOLDEN.replace('\n', "\r\n")] {
+            let (_dir, storage) = storage_with(&content);
+            let tasks = storage.read().unwrap();
+
+            assert_eq!(tasks, storage_with(GOLDEN).1.read().unwrap());
+            assert!(!storage.should_save(&tasks));
+            storage.write(&tasks).unwrap();
+            assert_eq!(fs::read_to_string(&storage.path).unwrap(), GOLDEN);
+        }
+    }
+
+    #[test]
+    fn ui_order_does_not_leak_into_the_file() {
+        let (_dir, storage) = storage_with(GOLDEN);
+        let mut tasks = storage.read().unwrap();
+        tasks.reverse();
+
+        assert!(!storage.should_save(&tasks));
+        assert_eq!(storage.dump(&tasks), GOLDEN);
+    }
+
+    #[test]
+    fn any_single_field_change_needs_saving() {
+        let (_dir, storage) = storage_with(GOLDEN);
+        let tasks = storage.read().unwrap();
+        let later = || DateTime::parse_from_rfc3339("2030-01-01T00:00:00+00:00").ok();
+        let changes: [&dyn Fn(&mut Task); 10] = [
+            &|x| x.name.push('!'),
+            &|x| x.description.push(' '),
+            &|x| x.subject.push('!'),
+            &|x| x.time = later(),
+            &|x| x.complete = !x.complete,
+            &|x| x.starred = !x.starred,
+            &|x| x.created_at = later(),
+            &|x| x.completed_at = later(),
+            &|x| x.updated_at = later(),
+            &|x| x.time_spent += TimeDelta::minutes(1),
+        ];
+
+        for change in changes {
+            let mut changed = tasks.clone();
+            change(&mut changed[0]);
+            assert!(storage.should_save(&changed));
+        }
+    }
+
+    #[test]
+    fn should_save_does_not_go_back_to_the_file() {
+        let (_dir, storage) = storage_with(GOLDEN);
+        assert!(storage.should_save(&vec![]));
+        let mut tasks = storage.read().unwrap();
+
+        fs::remove_file(&storage.path).unwrap();
+        assert!(!storage.should_save(&tasks));
+
+        tasks.pop();
+        assert!(storage.should_save(&tasks));
+        storage.write(&tasks).unwrap();
+        assert!(!storage.should_save(&tasks));
+    }
+
+    #[test]
+    fn time_spent_is_kept_in_minutes() {
+        let (_dir, storage) = storage_with(GOLDEN);
+        let mut tasks = storage.read().unwrap();
+        tasks[0].time_spent = TimeDelta::minutes(90);
+
+        assert!(storage.dump(&tasks).contains("time_spent = 90\n"));
+        storage.write(&tasks).unwrap();
+        assert_eq!(storage.read().unwrap()[0].time_spent, TimeDelta::minutes(90));
+        assert_eq!(storage.dump(&tasks[1..]).matches("time_spent").count(), 0);
+    }
+
+    #[test]
+    fn color_is_kept_only_when_set() {
+        let (_dir, storage) = storage_with(GOLDEN);
+        let mut tasks = storage.read().unwrap();
+        tasks[0].color = Some("#b16286".to_string());
+
+        assert!(storage.dump(&tasks).contains("color = \"#b16286\"\n"));
+        storage.write(&tasks).unwrap();
+        assert_eq!(storage.read().unwrap()[0].color.as_deref(), Some("#b16286"));
+        assert_eq!(storage.dump(&tasks[1..]).matches("color").count(), 0);
+    }
+
+    #[test]
+    fn anchored_deadline_is_kept_as_its_rule() {
+        let (_dir, storage) = storage_with(GOLDEN);
+        let mut tasks = storage.read().unwrap();
+        tasks[1].anchor = Some(Anchor { task: tasks[0].id, offset: TimeDelta::days(-7) });
+
+        let dump = storage.dump(&tasks);
+        assert!(dump.contains("relative_to = 1\noffset = \"-7d\"\n"));
+        storage.write(&tasks).unwrap();
+        let read = storage.read().unwrap();
+        assert_eq!(read[1].anchor, tasks[1].anchor);
+        assert_eq!(read[1].time, tasks[0].time.map(|x| x - TimeDelta::days(7)));
+        assert_eq!(storage.dump(&read), dump);
+    }
+
+    #[test]
+    fn anchors_that_cannot_resolve_do_not_stop_the_load() {
+        let (_dir, storage) = storage_with(GOLDEN);
+        let mut tasks = storage.read().unwrap();
+        tasks[1].anchor = Some(Anchor { task: tasks[0].id, offset: TimeDelta::days(-1_000_000_000) });
+        storage.write(&tasks).unwrap();
+        assert!(fs::read_to_string(&storage.path).unwrap().contains("offset = \"-1000000000d\"\n"));
+        let read = storage.read().unwrap();
+        assert!(read[1].anchor.is_some() && read[1].time.is_none());
+
+        // A time next to the anchor is refused, not quietly dropped
+        let content = fs::read_to_string(&storage.path).unwrap()
+            .replace("relative_to = 1\n", &format!("time = \"{}\"\nrelative_to = 1\n", tasks[0].time.unwrap().to_rfc3339()));
+        fs::write(&storage.path, content).unwrap();
+        let error = storage.read().unwrap_err();
+        assert_eq!(Damaged::take(error).unwrap().lost, 1);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn saving_keeps_the_permissions_of_the_file() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let (_dir, storage) = storage_with(GOLDEN);
+        fs::set_permissions(&storage.path, fs::Permissions::from_mode(0o644)).unwrap();
+        let tasks = storage.read().unwrap();
+        storage.write(&tasks).unwrap();
+        assert_eq!(fs::metadata(&storage.path).unwrap().permissions().mode() & 0o777, 0o644);
+    }
+
+    #[test]
+    fn json_file_holds_the_same_tasks() {
+        let (dir, toml) = storage_with(GOLDEN);
+        let tasks = toml.read().unwrap();
+        let path = dir.path().join("tasks.json");
+        fs::write(&path, "").unwrap();
+
+        let json = open(path.clone());
+        assert!(json.read().unwrap().is_empty());
+        json.write(&tasks).unwrap();
+        assert!(fs::read_to_string(&path).unwrap().starts_with("{\n  \"tasks\": ["));
+        assert_eq!(json.read().unwrap(), tasks);
+        assert!(!json.should_save(&tasks));
+    }
+
+    #[test]
+    fn writes_refuse_to_clobber_a_file_changed_since_read() {
+        let (dir, toml) = storage_with(GOLDEN);
+        let json_path = dir.path().join("tasks.json");
+        fs::write(&json_path, "").unwrap();
+        let json = open(json_path.clone());
+        for (storage, path) in [(&toml as &dyn TaskStorage, toml.path.clone()), (json.as_ref(), json_path)] {
+            let mut tasks = storage.read().unwrap();
+            let read = storage.fingerprint();
+            assert!(read.is_some());
+
+            // Another instance saves in between
+            let external = fs::read_to_string(&path).unwrap() + "\n";
+            fs::write(&path, &external).unwrap();
+            tasks.push(Task { id: 9, ..Task::default() });
+            let error = storage.write_if_unchanged(&tasks, read).unwrap_err();
+            assert!(WriteConflict::is(&error));
+            assert_eq!(fs::read_to_string(&path).unwrap(), external);
+
+            // Without a fingerprint it is a plain write, after which the new one holds
+            storage.write_if_unchanged(&tasks, None).unwrap();
+            assert_ne!(storage.fingerprint(), read);
+            storage.write_if_unchanged(&tasks, storage.fingerprint()).unwrap();
+            fs::remove_file(&path).unwrap();
+            storage.write_if_unchanged(&tasks, storage.fingerprint()).unwrap();
+        }
+        assert!(!WriteConflict::is(&io::Error::other("disk full")));
+    }
+
+    #[test]
+    fn journal_outlives_a_crash_until_saved() {
+        let (dir, storage) = storage_with(GOLDEN);
+        let mut tasks = storage.read().unwrap();
+        assert!(storage.read_journal().unwrap().is_none());
+
+        tasks[0].starred = !tasks[0].starred;
+        storage.write_journal(&tasks).unwrap();
+        assert!(dir.path().join("tasks.toml.journal").exists());
+        assert_eq!(storage.read_journal().unwrap(), Some(tasks.clone()));
+        // Journaling is not saving
+        assert!(storage.should_save(&tasks));
+
+        storage.write(&tasks).unwrap();
+        storage.discard_journal().unwrap();
+        assert!(storage.read_journal().unwrap().is_none());
+        assert!(!dir.path().join("tasks.toml.journal").exists());
+        storage.discard_journal().unwrap();
+    }
+
+    #[test]
+    fn a_truncated_file_keeps_every_complete_entry() {
+        let (dir, storage) = storage_with(TRUNCATED);
+        let damaged = Damaged::take(storage.read().unwrap_err()).unwrap();
+        assert_eq!(damaged.path, storage.path);
+        assert!(!damaged.error.is_empty());
+        assert_eq!(damaged.summary(), "2 of 3 task(s) recovered, 1 lost");
+        let golden = storage_with(GOLDEN).1.read().unwrap();
+        assert_eq!(damaged.recovered, golden[..2]);
+
+        // The original is kept as it was, the recovered tasks take its place
+        let now = DateTime::parse_from_rfc3339("2025-03-01T12:00:00+01:00").unwrap();
+        let kept = set_aside(&storage, &damaged, now).unwrap();
+        assert_eq!(kept, dir.path().join("tasks.toml.corrupt-20250301-120000"));
+        assert_eq!(fs::read_to_string(&kept).unwrap(), TRUNCATED);
+        assert_eq!(storage.read().unwrap(), golden[..2]);
+    }
+
+    #[test]
+    fn garbled_entries_are_lost_on_their_own() {
+        // A deadline that is no RFC 3339 and a line that is no TOML, in two different entries
+        let (_dir, storage) = storage_with(GARBLED);
+        let damaged = Damaged::take(storage.read().unwrap_err()).unwrap();
+        assert_eq!(damaged.recovered.iter().map(|x| x.name.as_str()).collect::<Vec<_>>(), ["Essay"]);
+        assert_eq!(damaged.lost, 2);
+
+        // Only the bad deadline, the file parses but its task doesn't
+        let (_dir, storage) = storage_with(&GOLDEN.replace("2025-05-12T18:00:00+02:00", "2025-05-12 18:00"));
+        let damaged = Damaged::take(storage.read().unwrap_err()).unwrap();
+        assert_eq!(damaged.error, "Could not parse the task HW1");
+        assert_eq!(damaged.summary(), "2 of 3 task(s) recovered, 1 lost");
+    }
+
+    #[test]
+    fn headers_inside_multiline_strings_start_no_entry() {
+        let (_dir, storage) = storage_with(MULTILINE);
+        let damaged = Damaged::take(storage.read().unwrap_err()).unwrap();
+        assert_eq!(damaged.summary(), "2 of 3 task(s) recovered, 1 lost");
+        assert_eq!(damaged.recovered[0].description, "Hand in the config, it starts with\n[[tasks]]\nand a \"quoted\" \"\"\" line");
+        assert_eq!(damaged.recovered[1].description, "  [[tasks]]\n");
+        assert_eq!(damaged.recovered[1].subject, "Literature");
+
+        assert_eq!(multiline_string_after(r#"a = 'x"""' # """"#, None), None);
+        assert_eq!(multiline_string_after(r#"a = """x\""""#, None), Some(r#"""""#));
+        assert_eq!(multiline_string_after(r#"a = """x"""""#, None), None);
+        assert_eq!(multiline_string_after("'''", Some(r#"""""#)), Some(r#"""""#));
+    }
+
+    #[test]
+    fn a_missing_file_has_no_tasks_until_the_first_save() {
+        let dir = tempfile::tempdir().unwrap();
+        for name in ["tasks.toml", "tasks.json"] {
+            let path = dir.path().join(name);
+            let storage = open(path.clone());
+            assert!(storage.read().unwrap().is_empty());
+            assert!(!path.exists());
+
+            storage.write_if_unchanged(&vec![Task::example()], storage.fingerprint()).unwrap();
+            assert_eq!(storage.read().unwrap().len(), 1);
+        }
+    }
+
+    #[test]
+    fn other_read_errors_are_not_damage() {
+        let dir = tempfile::tempdir().unwrap();
+        let unreadable = TomlStorage::new(dir.path().to_path_buf()).read().unwrap_err();
+        assert!(Damaged::take(unreadable).is_err());
+
+        let path = dir.path().join("tasks.json");
+        fs::write(&path, "{\"tasks\": [").unwrap();
+        let error = open(path).read().unwrap_err();
+        assert!(error.to_string().starts_with("Could not parse"), "{error}");
     }
 }
@@ -1,20 +1,48 @@
 use std::io;
+use std::collections::BTreeMap;
 use std::cmp::Ordering;
 use std::marker::PhantomData;
 use std::path::{Path, PathBuf};
-use std::io::stdout;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering as AtomicOrdering};
+use std::sync::mpsc::{self, Receiver, TryRecvError};
+use std::thread;
+use std::io::{stdout, Write};
+use std::time::{Duration, Instant};
 
-use crate::uni::task::{Task, TaskStatus};
-use crate::readers::{TaskReader, EditorTaskReader};
-use crate::storages::{TaskStorage, TomlStorage};
-use super::panes::Pane;
+use crate::config::{Config, Filters, SelectionStyle, UndatedPosition};
+use crate::constants;
+use crate::paths;
+use crate::uni::anchors::{self, BrokenAnchor};
+use crate::uni::task::{assign_ids, Task, TaskStatus};
+use crate::uni::clock::Clock;
+use crate::uni::query::TaskQuery;
+use crate::uni::ordering::{self, Bucket};
+use crate::uni::policy::StatusPolicy;
+use crate::uni::template;
+use crate::uni::validation::{self, DeadlineConflict};
+use crate::readers::{self, TaskReader};
+use crate::storages::{self, Damaged, Fingerprint, MemoryStorage, TaskStorage, WriteConflict};
+use super::panes::{DescriptionPane, Pane, TasksPane};
 use super::colors::{TaskColors, StandardTaskColors};
-use super::popups::{ClosurePopup, Popup, PopupAction};
+use super::commands;
+use super::popups::{self, ClosurePopup, ExportPopup, InputPopup, Popup, PopupAction};
+use super::quick_add::QuickAddPopup;
+use super::paste_import::PasteImportPopup;
+use super::date_picker::{DatePicker, DatePickerPopup, OnPick};
+use super::dedup::DedupPopup;
+use super::finder::FinderPopup;
+use super::messages::StatusMessages;
+use super::review::{Review, ReviewAction};
+use super::timer::FocusTimer;
+use crate::uni::dates::{format_spent, local_date};
 
+use chrono::{DateTime, FixedOffset, NaiveDate, TimeDelta};
 use ratatui::prelude::*;
 use ratatui::DefaultTerminal;
+use ratatui::widgets::{Block, BorderType, Clear, Padding, Paragraph};
 use ratatui::crossterm::{
-    event::{self, Event, KeyCode, KeyEvent, KeyEventKind},
+    event::{self, Event, KeyCode, KeyEvent, KeyEventKind, KeyModifiers},
     terminal::{
         EnterAlternateScreen,
         LeaveAlternateScreen,
@@ -27,7 +55,106 @@ use ratatui::crossterm::{
 pub struct Data {
     pub index: Option<usize>,
     pub tasks: Vec<Task>,
-    pub filter_zen: bool,
+    pub filters: Filters,
+    pub view: Option<View>,
+    pub config: Config,
+    pub conflicts: Vec<DeadlineConflict>,
+    /// Anchored tasks left without a deadline, see `anchors::resolve`
+    pub broken_anchors: Vec<BrokenAnchor>,
+    /// Incomplete tasks due each day as of the last validation, None before the first
+    day_counts: Option<BTreeMap<NaiveDate, usize>>,
+    pub focus: Option<Focus>,
+    pub clock: Clock,
+    pub sort_mode: SortMode,
+    /// For the status bar, anything with a `&mut Data` may add to it
+    pub messages: StatusMessages,
+    /// The tasks file, None for tasks only in memory
+    pub location: Option<PathBuf>,
+    /// The task template could not be read and the user was told so
+    template_failed: bool,
+    /// Snapshots of `tasks` before each undoable change, oldest first
+    history: Vec<Vec<Task>>,
+}
+
+/// Column the list is ordered by, the deadline unless picked otherwise
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum SortColumn {
+    /// Starred first
+    Starred,
+    Subject,
+    Name,
+    #[default]
+    Deadline,
+    /// Most recently changed first, has no column of its own
+    Updated,
+}
+
+impl SortColumn {
+    /// Short name for the title badge
+    pub fn label(&self) -> &'static str {
+        match self {
+            SortColumn::Starred => "star",
+            SortColumn::Subject => "subject",
+            SortColumn::Name => "name",
+            SortColumn::Deadline => "due",
+            SortColumn::Updated => "last change",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct SortMode {
+    pub column: SortColumn,
+    pub descending: bool,
+}
+
+impl SortMode {
+    pub fn arrow(&self) -> &'static str {
+        match self.descending {
+            true => "▼",
+            false => "▲",
+        }
+    }
+}
+
+/// Named filter combination taking over the list until it is toggled off
+#[derive(Debug)]
+pub struct View {
+    pub name: &'static str,
+    /// Filters to go back to
+    previous: Filters,
+}
+
+impl View {
+    pub const URGENT: (&'static str, Filters) = ("URGENT", Filters {
+        hide_completed: true,
+        hide_no_deadline: false,
+        only_urgent: true,
+        only_starred: false,
+        only_stubs: false,
+        subject: None,
+        text: None,
+    });
+}
+
+/// How the list opens, given on the command line. It goes into the same filters and
+/// selection the keys change, so it is undone in the session like anything else.
+#[derive(Debug, Clone, Default)]
+pub struct Startup {
+    pub subject: Option<String>,
+    pub search: Option<String>,
+    /// In the urgent view
+    pub urgent: bool,
+    /// Task id to select
+    pub select: Option<u64>,
+}
+
+/// Temporary view restricted to the most urgent tasks
+#[derive(Default, Debug)]
+pub struct Focus {
+    /// Positions in `tasks`, most urgent first
+    pub tasks: Vec<usize>,
+    previous_index: Option<usize>,
 }
 
 impl Data {
@@ -36,30 +163,178 @@ impl Data {
             0 => None,
             _ => Some(0),
         };
-        Self { index, tasks, filter_zen: false }
+        Self { index, tasks, ..Default::default() }
+    }
+
+    pub fn with_config(tasks: Vec<Task>, config: Config) -> Self {
+        let mut data = Self { config, ..Self::new(tasks) };
+        data.validate();
+        data
+    }
+
+    /// Conflicts refer to positions in `tasks`, so this has to rerun after any reordering.
+    /// Deadlines following another task's are brought up to date first.
+    pub fn validate(&mut self) {
+        self.broken_anchors = anchors::resolve(&mut self.tasks);
+        self.conflicts = validation::find_deadline_conflicts(&self.tasks, &self.config.validation);
+        self.check_day_budget();
+    }
+
+    /// Warns about days whatever changed since the last validation took over the limit,
+    /// so every way of adding tasks or moving deadlines is covered. The tasks as loaded are
+    /// taken as they are.
+    fn check_day_budget(&mut self) {
+        if self.config.validation.max_tasks_per_day.is_none() {
+            return;
+        }
+        let counts = validation::open_tasks_per_day(&self.tasks);
+        if let Some(before) = &self.day_counts {
+            let names = self.config.ui.date_names();
+            for overload in validation::day_overloads(before, &counts, &self.config.validation) {
+                self.messages.error(overload.describe(&names));
+            }
+        }
+        self.day_counts = Some(counts);
+    }
+
+    pub fn in_conflict(&self, index: usize) -> bool {
+        self.conflicts.iter().any(|x| x.involves(index))
     }
 
     fn sort(&mut self) {
+        // Anchored deadlines as of now, whatever changed since the last frame
+        self.broken_anchors = anchors::resolve(&mut self.tasks);
+        let now = self.now();
+        let today = TaskQuery::new(&[], now).due_today(true);
+        let today_first = self.config.ui.today_first;
+        let by_deadline = |task1: &Task, task2: &Task| {
+            if today_first && task1.time.is_some() && task2.time.is_some() {
+                let today = today.matches(task2).cmp(&today.matches(task1));
+                if today != Ordering::Equal {
+                    return today;
+                }
+            }
+            ordering::compare(task1, task2, &now)
+        };
+        let mode = self.sort_mode;
+        let undated_first = self.config.ui.undated_position == UndatedPosition::Top;
+        // The default order's buckets, undated ones moved up if asked to
+        let group = |task: &Task| match Bucket::of(task, &now) {
+            Bucket::Undated if undated_first => 0,
+            Bucket::Overdue | Bucket::Upcoming => 1,
+            Bucket::Undated => 2,
+            Bucket::Completed => 3,
+        };
         self.tasks.sort_by(|task1, task2| {
-            if task1.complete && !task2.complete {
-                return Ordering::Greater;
-            } else if !task1.complete && task2.complete {
-                return Ordering::Less;
+            // Undated and completed tasks keep to their own buckets whatever the column
+            let group = group(task1).cmp(&group(task2));
+            if group != Ordering::Equal {
+                return group;
             }
 
-            if task1.time.is_some() && task2.time.is_none() {
-                return Ordering::Less;
-            } else if task1.time.is_none() && task2.time.is_some() {
-                return Ordering::Greater;
-            } else if task1.time.is_none() && task2.time.is_none() {
-                return Ordering::Equal;
+            let by_column = match mode.column {
+                SortColumn::Starred => task2.starred.cmp(&task1.starred),
+                SortColumn::Subject => task1.subject.to_lowercase().cmp(&task2.subject.to_lowercase()),
+                SortColumn::Name => task1.name.to_lowercase().cmp(&task2.name.to_lowercase()),
+                SortColumn::Deadline => by_deadline(task1, task2),
+                SortColumn::Updated => task2.last_updated().cmp(&task1.last_updated()),
+            };
+            let by_column = if mode.descending { by_column.reverse() } else { by_column };
+            by_column.then_with(|| by_deadline(task1, task2))
+        });
+        self.validate();
+        self.refresh_focus();
+    }
+
+    /// Ascending, then descending, then back to the deadline order
+    pub fn cycle_sort(&mut self, column: SortColumn) {
+        self.sort_mode = match self.sort_mode {
+            SortMode { column: current, descending: false } if current == column => SortMode { column, descending: true },
+            SortMode { column: current, descending: true } if current == column => SortMode::default(),
+            _ => SortMode { column, descending: false },
+        };
+        self.sort();
+    }
+
+    pub fn now(&self) -> DateTime<FixedOffset> {
+        self.clock.now()
+    }
+
+    /// What the list is narrowed or ordered by, for the title. Anything changing how
+    /// the list looks adds its badge here rather than to the pane.
+    pub fn title_badges(&self) -> Vec<String> {
+        let mut badges = vec![];
+        if let Some(view) = &self.view {
+            badges.push(view.name.to_string());
+        }
+        if self.filters.only_starred {
+            badges.push("★".to_string());
+        }
+        if self.filters.only_stubs {
+            badges.push("✎".to_string());
+        }
+        if let Some(subject) = &self.filters.subject {
+            badges.push(subject.clone());
+        }
+        if let Some(text) = &self.filters.text {
+            badges.push(format!("“{text}”"));
+        }
+        if self.sort_mode != SortMode::default() {
+            badges.push(format!("sort: {} {}", self.sort_mode.column.label(), self.sort_mode.arrow()));
+        }
+        badges
+    }
+
+    /// Distinct subjects, spelled as first seen
+    pub fn subjects(&self) -> Vec<String> {
+        let mut subjects: Vec<String> = vec![];
+        for task in &self.tasks {
+            let subject = task.subject.trim();
+            if !subject.is_empty() && !subjects.iter().any(|x| x.to_lowercase() == subject.to_lowercase()) {
+                subjects.push(subject.to_string());
             }
+        }
+        subjects.sort();
+        subjects
+    }
 
-            task1.time
-                .unwrap()
-                .partial_cmp(&task2.time.unwrap())
-                .expect("Could not perform the comparison")
-        });
+    /// Incomplete tasks the given one is likely a duplicate of
+    pub fn find_similar(&self, task: &Task) -> Vec<&Task> {
+        validation::find_similar(&self.tasks, task, &self.config.validation)
+    }
+
+    pub fn status_policy(&self) -> StatusPolicy {
+        StatusPolicy::from_config(&self.config)
+    }
+
+    /// Status under the configured policy
+    pub fn status(&self, task: &Task, now: &DateTime<FixedOffset>) -> TaskStatus {
+        self.status_policy().status(task, now)
+    }
+
+    pub fn is_visible(&self, index: usize, task: &Task) -> bool {
+        if let Some(focus) = &self.focus {
+            if !focus.tasks.contains(&index) {
+                return false;
+            }
+        }
+        let urgent = task.starred || self.status(task, &self.now()).is_urgent();
+        let hidden = (self.filters.hide_completed && task.complete)
+            || (self.filters.hide_no_deadline && task.time.is_none())
+            || (self.filters.only_urgent && !urgent)
+            || (self.filters.only_starred && !task.starred)
+            || (self.filters.only_stubs && !task.is_stub())
+            || self.filters.subject.as_ref().is_some_and(|x| !task.subject.trim().eq_ignore_ascii_case(x))
+            || self.filters.text.as_ref().is_some_and(|x| !TaskQuery::new(&[], self.now()).text_contains(x).matches(task));
+        !hidden
+    }
+
+    /// Visible tasks together with their positions in `tasks`
+    pub fn visible(&self) -> impl Iterator<Item = (usize, &Task)> {
+        self.tasks
+            .iter()
+            .enumerate()
+            .filter(|(i, x)| self.is_visible(*i, x))
     }
 
     pub fn iter(&self) -> DataIterator {
@@ -73,25 +348,278 @@ impl Data {
         self.iter().collect::<Vec<_>>().len()
     }
 
-    pub fn get(&self, index: usize) -> Option<&Task> {
-        self.iter().collect::<Vec<_>>().get(index).map(|x| *x)
+    /// Position in `tasks` of the selected task. `index` counts the visible tasks only,
+    /// so anything reaching into `tasks` for the selection goes through here. None with
+    /// nothing selected, nothing visible, or an index past the visible tasks.
+    pub fn selected_underlying_index(&self) -> Option<usize> {
+        self.visible().nth(self.index?).map(|(i, _)| i)
+    }
+
+    pub fn selected(&self) -> Option<&Task> {
+        self.selected_underlying_index().map(|x| &self.tasks[x])
+    }
+
+    /// Edits made through it are not stamped, `touch` the underlying index for that
+    #[cfg_attr(not(test), allow(dead_code))]
+    pub fn selected_mut(&mut self) -> Option<&mut Task> {
+        let i = self.selected_underlying_index()?;
+        self.tasks.get_mut(i)
+    }
+
+    /// Every task, measured against the current time and the configured status policy
+    pub fn query(&self) -> TaskQuery<'_> {
+        TaskQuery::new(&self.tasks, self.now()).policy(self.status_policy())
+    }
+
+    /// Nearest upcoming incomplete deadline among the visible tasks
+    pub fn next_deadline(&self) -> Option<&Task> {
+        // Sorted, so the first one still in the future is the nearest
+        let upcoming = self.query()
+            .complete(false)
+            .status([TaskStatus::Panic, TaskStatus::DueToday, TaskStatus::Normal])
+            .due_after(self.now());
+        self.iter().find(|x| upcoming.matches(x))
+    }
+
+    /// Remembers the tasks as they are, so the change that follows can be undone as a whole
+    pub fn checkpoint(&mut self) {
+        self.history.push(self.tasks.clone());
+        if self.history.len() > constants::UNDO_LIMIT {
+            self.history.remove(0);
+        }
+    }
+
+    /// Goes back to the last checkpoint, false when there is none
+    pub fn undo(&mut self) -> bool {
+        let Some(tasks) = self.history.pop() else { return false };
+        self.tasks = tasks;
+        self.clamp_index();
+        true
+    }
+
+    /// Description for a new task from the configured template, None without one. A template
+    /// that can't be read is an error once, after that new tasks quietly go without.
+    pub fn template_description(&mut self, subject: &str, name: &str) -> Result<Option<String>, String> {
+        if self.template_failed {
+            return Ok(None);
+        }
+        let today = local_date(&self.now());
+        template::new_description(&self.config, subject, name, today).map_err(|error| {
+            self.template_failed = true;
+            error.to_string()
+        })
+    }
+
+    /// Moves the selection to the task with `id`, leaving any view, focus or filter that
+    /// hides it. False when there is no such task.
+    pub fn select(&mut self, id: u64) -> bool {
+        let Some(position) = self.tasks.iter().position(|x| x.id == id) else { return false };
+        if !self.is_visible(position, &self.tasks[position]) {
+            self.view = None;
+            self.focus = None;
+            self.filters = Filters::default();
+        }
+        let index = self.visible().position(|(i, _)| i == position);
+        self.index = index;
+        true
+    }
+
+    /// Every new task goes through here to get an id
+    pub fn add(&mut self, task: Task) {
+        self.add_all(vec![task]);
+    }
+
+    /// Adds `tasks` as a single undoable change
+    pub fn add_all(&mut self, tasks: Vec<Task>) {
+        self.checkpoint();
+        for task in tasks {
+            self.tasks.push(task);
+            self.touch(self.tasks.len() - 1);
+        }
+        assign_ids(&mut self.tasks);
+        if self.index.is_none() {
+            self.index = Some(0);
+        }
+    }
+
+    /// Drops every task with one of `ids` as a single undoable change
+    pub fn remove_tasks(&mut self, ids: &[u64]) {
+        self.checkpoint();
+        self.tasks.retain(|x| !ids.contains(&x.id));
+        self.clamp_index();
+        self.validate();
+    }
+
+    /// Marks the task at `idx` as changed just now. Every edit of a task goes through
+    /// here or `update`.
+    pub fn touch(&mut self, idx: usize) {
+        let now = self.now();
+        if let Some(task) = self.tasks.get_mut(idx) {
+            task.touch(now);
+        }
+    }
+
+    /// Changes the task with `id` and touches it, false when it is gone
+    pub fn update(&mut self, id: u64, change: impl FnOnce(&mut Task)) -> bool {
+        let Some(idx) = self.tasks.iter().position(|x| x.id == id) else { return false };
+        change(&mut self.tasks[idx]);
+        self.touch(idx);
+        true
     }
 
     pub fn toggle_task_status(&mut self) {
-        if let Some(i) = self.index {
-            self.tasks[i].complete = !self.tasks[i].complete;
+        if let Some(i) = self.selected_underlying_index() {
+            self.checkpoint();
+            let complete = self.tasks[i].complete;
+            self.tasks[i].set_complete(!complete);
+            self.touch(i);
+            if !complete {
+                // By the app's clock, like the rest of its stamps
+                self.tasks[i].completed_at = Some(self.now());
+                let task = &self.tasks[i];
+                self.messages.info(format!("Done: {} — {} 🎉", task.subject, task.name));
+            }
         }
     }
 
     pub fn toggle_task_star(&mut self) {
-        if let Some(i) = self.index {
+        if let Some(i) = self.selected_underlying_index() {
+            self.checkpoint();
             self.tasks[i].starred = !self.tasks[i].starred;
+            self.touch(i);
+        }
+    }
+
+    /// Switches the configured preset on, or every filter off when it is already on
+    pub fn apply_filter_preset(&mut self) {
+        let preset = self.config.ui.filter_preset.clone();
+        self.filters = match self.filters == preset {
+            true => Filters::default(),
+            false => preset,
+        };
+        self.clamp_index();
+    }
+
+    /// Enters the view, or leaves it restoring exactly the filters from before
+    pub fn toggle_view(&mut self, (name, filters): (&'static str, Filters)) {
+        // Views narrow down within the subject and the search the list is on
+        let filters = Filters { subject: self.filters.subject.clone(), text: self.filters.text.clone(), ..filters };
+        self.filters = match self.view.take() {
+            Some(view) if view.name == name => view.previous,
+            Some(view) => {
+                self.view = Some(View { name, previous: view.previous });
+                filters
+            },
+            None => {
+                self.view = Some(View { name, previous: self.filters.clone() });
+                filters
+            },
+        };
+        self.index = Some(0);
+        self.clamp_index();
+    }
+
+    /// "Loaded 37 tasks from ~/.local/share/unist/tasks.toml (2 overdue)", shown on the way
+    /// in and by `:file`
+    pub fn summary(&self) -> String {
+        let count = self.tasks.len();
+        let plural = if count == 1 { "" } else { "s" };
+        let from = match &self.location {
+            Some(path) => format!("from {}", paths::display_path(path, dirs::home_dir().as_deref())),
+            None => "kept in memory".to_string(),
+        };
+        let overdue = match self.query().complete(false).due_before(self.now()).count() {
+            0 => String::new(),
+            overdue => format!(" ({overdue} overdue)"),
+        };
+        format!("Loaded {count} task{plural} {from}{overdue}")
+    }
+
+    /// Every filter off, any view left
+    pub fn clear_filters(&mut self) {
+        self.view = None;
+        self.filters = Filters::default();
+        self.clamp_index();
+    }
+
+    /// Opens the list as `startup` asks, after sorting it the way the first draw would.
+    /// A subject or task the tasks don't have is reported and left out.
+    pub fn start_with(&mut self, startup: &Startup) {
+        self.sort();
+        if let Some(typed) = &startup.subject {
+            let subject = self.config.subject_abbrev.expand(typed).to_string();
+            match self.subjects().into_iter().find(|x| x.eq_ignore_ascii_case(&subject)) {
+                Some(subject) => self.filters.subject = Some(subject),
+                None => self.messages.error(format!("No tasks in {subject}, showing every subject")),
+            }
+        }
+        if let Some(search) = startup.search.as_deref().map(str::trim).filter(|x| !x.is_empty()) {
+            self.filters.text = Some(search.to_string());
+        }
+        if startup.urgent {
+            self.toggle_view(View::URGENT);
+        }
+        self.clamp_index();
+        if let Some(id) = startup.select {
+            if !self.select(id) {
+                self.messages.error(format!("No task #{id} to select"));
+            }
+        }
+    }
+
+    pub fn toggle_hide_completed(&mut self) {
+        self.filters.hide_completed = !self.filters.hide_completed;
+        self.clamp_index();
+    }
+
+    pub fn toggle_hide_no_deadline(&mut self) {
+        self.filters.hide_no_deadline = !self.filters.hide_no_deadline;
+        self.clamp_index();
+    }
+
+    pub fn toggle_only_starred(&mut self) {
+        self.filters.only_starred = !self.filters.only_starred;
+        self.clamp_index();
+    }
+
+    pub fn toggle_only_stubs(&mut self) {
+        self.filters.only_stubs = !self.filters.only_stubs;
+        self.clamp_index();
+    }
+
+    /// Nearest incomplete deadlines, overdue ones naturally come first
+    fn refresh_focus(&mut self) {
+        if self.focus.is_none() {
+            return;
+        }
+        let mut urgent = self.query()
+            .complete(false)
+            .matching(|x| x.time.is_some())
+            .indices()
+            .collect::<Vec<_>>();
+        urgent.sort_by_key(|&i| self.tasks[i].time);
+        urgent.truncate(self.config.ui.focus_count);
+        if let Some(focus) = self.focus.as_mut() {
+            focus.tasks = urgent;
         }
     }
 
-    pub fn toggle_filter_zen(&mut self) {
-        self.filter_zen = !self.filter_zen;
-        let current_len = self.iter().collect::<Vec<_>>().len();
+    pub fn toggle_focus(&mut self) {
+        match self.focus.take() {
+            Some(focus) => {
+                self.index = focus.previous_index;
+            },
+            None => {
+                self.focus = Some(Focus { previous_index: self.index, ..Default::default() });
+                self.refresh_focus();
+                self.index = Some(0);
+            },
+        }
+        self.clamp_index();
+    }
+
+    fn clamp_index(&mut self) {
+        let current_len = self.len();
         self.index = self.index.and_then(|x|
             if current_len == 0 {None}
             else {Some(x.min(current_len - 1))}
@@ -113,11 +641,9 @@ impl<'a> Iterator for DataIterator<'a> {
     fn next(&mut self) -> Option<Self::Item> {
         self.index += 1;
         self.data
-            .tasks
-            .iter()
-            .filter(|x| !(matches!(x.get_status_now(), TaskStatus::Zen) && self.data.filter_zen))
-            .skip(self.index - 1)
-            .next()
+            .visible()
+            .nth(self.index - 1)
+            .map(|(_, x)| x)
     }
 }
 
@@ -129,97 +655,824 @@ enum CurrentPane {
     Right,
 }
 
-pub struct App<'a, T = StandardTaskColors, R =  EditorTaskReader, S = TomlStorage> 
+pub struct App<'a, T = StandardTaskColors> 
 where
     T: TaskColors,
-    R: TaskReader,
-    S: TaskStorage,
 {
     pub data: Data,
     current_pane: CurrentPane,
     left_pane: Box<dyn Pane<T>>,
     right_pane: Box<dyn Pane<T>>,
     current_popup: Option<Box<dyn Popup<T> + 'a>>,
-    storage: S,
+    /// Takes over the panes until it is done
+    review: Option<Review<T>>,
+    timer: Option<FocusTimer>,
+    /// Written only through `persist`
+    storage: Box<dyn TaskStorage>,
+    /// The file as it was before this session's first save is kept next to it
+    backed_up: bool,
     exit: bool,
-    _reader_marker: PhantomData<R>
+    /// The editor needs the terminal, so it runs between frames rather than inside the key handler
+    edit_requested: bool,
+    resized: bool,
+    /// Set from a signal handler when the app has to go without asking
+    terminate: Arc<AtomicBool>,
+    reader: Box<dyn TaskReader>,
+    /// Tasks as of the last journal check, the journal is only touched when they change
+    journaled: Vec<Task>,
+    journal_checked: Instant,
+    /// Tasks still on their way from the storage, see `AppBuilder::build_loading`
+    loading: Option<Loading>,
+    /// `Data::summary` as of when the tasks came in, for stdout once the terminal is back
+    loaded_summary: Option<String>,
 }
 
-impl<'a, T: TaskColors, R: TaskReader, S: TaskStorage> App<'a, T, R, S> where
-{
-    pub fn new(left_pane: Box<dyn Pane<T>>, right_pane: Box<dyn Pane<T>>, path: PathBuf) -> io::Result<Self> {
-        let storage = S::new(path);
-        let tasks = storage.read()?;
-        Ok(Self {
-            data: Data::new(tasks),
+/// The storage handed back by the thread that read it, with what it read
+type Loaded = (Box<dyn TaskStorage + Send>, io::Result<Vec<Task>>);
+
+/// Until the tasks are in there is nothing to act on, only q works
+enum Loading {
+    Pending {
+        receiver: Receiver<Loaded>,
+        startup: Startup,
+        onboarding: Option<PathBuf>,
+    },
+    /// The file did not parse, waiting for y to go on with what could be recovered from it
+    Damaged {
+        storage: Box<dyn TaskStorage + Send>,
+        damaged: Damaged,
+        startup: Startup,
+        onboarding: Option<PathBuf>,
+    },
+    /// Nothing to show or save, only to leave
+    Failed,
+}
+
+/// Puts an `App` together. Anything left out gets what the full-screen UI uses: the tasks
+/// and description panes, tasks kept in memory, the reader the config asks for, the default
+/// config and the system clock. New knobs come as further `with_*` methods.
+pub struct AppBuilder<T: TaskColors = StandardTaskColors> {
+    left_pane: Option<Box<dyn Pane<T>>>,
+    right_pane: Option<Box<dyn Pane<T>>>,
+    storage: Box<dyn TaskStorage>,
+    reader: Option<Box<dyn TaskReader>>,
+    config: Config,
+    clock: Clock,
+    startup: Startup,
+    onboarding: Option<PathBuf>,
+}
+
+impl<T: TaskColors> Default for AppBuilder<T> {
+    fn default() -> Self {
+        Self {
+            left_pane: None,
+            right_pane: None,
+            storage: Box::new(MemoryStorage::default()),
+            reader: None,
+            config: Config::default(),
+            clock: Clock::default(),
+            startup: Startup::default(),
+            onboarding: None,
+        }
+    }
+}
+
+impl<T: TaskColors> AppBuilder<T> {
+    /// Colors of another theme. Panes are made for one theme, so any given before are dropped.
+    #[cfg_attr(not(test), allow(dead_code))]
+    pub fn with_theme<U: TaskColors>(self) -> AppBuilder<U> {
+        AppBuilder {
+            left_pane: None,
+            right_pane: None,
+            storage: self.storage,
+            reader: self.reader,
+            config: self.config,
+            clock: self.clock,
+            startup: self.startup,
+            onboarding: self.onboarding,
+        }
+    }
+
+    #[cfg_attr(not(test), allow(dead_code))]
+    pub fn with_panes(self, left: Box<dyn Pane<T>>, right: Box<dyn Pane<T>>) -> Self {
+        Self { left_pane: Some(left), right_pane: Some(right), ..self }
+    }
+
+    #[cfg_attr(not(test), allow(dead_code))]
+    pub fn with_storage(self, storage: Box<dyn TaskStorage>) -> Self {
+        Self { storage, ..self }
+    }
+
+    #[cfg_attr(not(test), allow(dead_code))]
+    pub fn with_reader(self, reader: Box<dyn TaskReader>) -> Self {
+        Self { reader: Some(reader), ..self }
+    }
+
+    pub fn with_config(self, config: Config) -> Self {
+        Self { config, ..self }
+    }
+
+    #[cfg_attr(not(test), allow(dead_code))]
+    pub fn with_clock(self, clock: Clock) -> Self {
+        Self { clock, ..self }
+    }
+
+    /// Filters and selection to open the list with
+    pub fn with_startup(self, startup: Startup) -> Self {
+        Self { startup, ..self }
+    }
+
+    /// New tasks file to introduce once `build_loading` has the tasks
+    pub fn with_onboarding(self, onboarding: Option<PathBuf>) -> Self {
+        Self { onboarding, ..self }
+    }
+
+    /// Reads the tasks from the storage
+    #[cfg_attr(not(test), allow(dead_code))]
+    pub fn build<'a>(self) -> io::Result<App<'a, T>> {
+        let tasks = self.storage.read()?;
+        Ok(self.assemble(tasks))
+    }
+
+    /// Brings the UI up without waiting for the tasks. They are read from `storage` on another
+    /// thread while a splash shows, and swapped in by `App::check_loading` once they arrive.
+    /// Then the app greets as `main` used to after `build`: onboarding or recovery, duplicates.
+    pub fn build_loading<'a>(mut self, storage: Box<dyn TaskStorage + Send>) -> App<'a, T> {
+        let location = storage.path().map(Path::to_path_buf);
+        let (sender, receiver) = mpsc::channel();
+        thread::spawn(move || {
+            let tasks = storage.read();
+            // Nobody is waiting any more when the app was quit meanwhile
+            let _ = sender.send((storage, tasks));
+        });
+        let loading = Loading::Pending {
+            receiver,
+            startup: std::mem::take(&mut self.startup),
+            onboarding: self.onboarding.take(),
+        };
+        let mut app = Self { storage: Box::new(MemoryStorage::default()), ..self }.assemble(vec![]);
+        app.data.location = location;
+        app.loading = Some(loading);
+        app
+    }
+
+    fn assemble<'a>(self, tasks: Vec<Task>) -> App<'a, T> {
+        let left_pane = self.left_pane.unwrap_or_else(|| Box::new(TasksPane::new()));
+        let right_pane = self.right_pane.unwrap_or_else(|| Box::new(DescriptionPane::default()));
+        let reader = self.reader.unwrap_or_else(|| readers::from_config(&self.config));
+        let mut data = Data {
+            clock: self.clock,
+            location: self.storage.path().map(Path::to_path_buf),
+            ..Data::with_config(tasks.clone(), self.config)
+        };
+        data.start_with(&self.startup);
+        App {
+            journaled: tasks,
+            journal_checked: Instant::now(),
+            data,
             current_pane: CurrentPane::Left,
             left_pane,
             right_pane,
             current_popup: None,
-            storage,
+            review: None,
+            timer: None,
+            storage: self.storage,
+            backed_up: false,
             exit: false,
-            _reader_marker: PhantomData,
-        })
+            edit_requested: false,
+            resized: false,
+            terminate: Arc::new(AtomicBool::new(false)),
+            reader,
+            loading: None,
+            loaded_summary: None,
+        }
+    }
+}
+
+impl<'a, T: TaskColors> App<'a, T> where
+{
+    pub fn builder() -> AppBuilder<T> {
+        AppBuilder::default()
     }
 
     pub fn run(&mut self, mut terminal: DefaultTerminal) -> io::Result<()> {
-        while !self.exit {
+        while !self.is_exiting() {
+            self.check_loading();
             terminal.draw(|frame| self.draw(frame))?;
-            self.handle_events(&mut terminal)?;
+            self.handle_events()?;
+            if self.tick() {
+                // The bell gets through the alternate screen
+                stdout().write_all(b"\x07")?;
+                stdout().flush()?;
+            }
+            if self.check_termination() {
+                break;
+            }
+            if self.take_resize() {
+                // Whatever was drawn for the old size is garbage now
+                terminal.clear()?;
+            }
+            if self.journal_checked.elapsed() >= Duration::from_millis(constants::JOURNAL_INTERVAL_MS) {
+                self.update_journal();
+            }
+            if self.take_edit_request() {
+                stdout().execute(LeaveAlternateScreen)?;
+                self.edit().unwrap();
+                stdout().execute(EnterAlternateScreen)?;
+                terminal.clear()?;
+            }
         }
         Ok(())
     }
 
-    fn draw(&mut self, frame: &mut Frame) {
+    /// Swaps the tasks in once the reading thread is done, or says why it failed
+    pub fn check_loading(&mut self) {
+        let Some(Loading::Pending { receiver, .. }) = &self.loading else { return };
+        let received = match receiver.try_recv() {
+            Err(TryRecvError::Empty) => return,
+            Ok(loaded) => Some(loaded),
+            // The thread panicked
+            Err(TryRecvError::Disconnected) => None,
+        };
+        let Some(Loading::Pending { startup, onboarding, .. }) = self.loading.take() else { return };
+        match received {
+            Some((storage, Ok(tasks))) => self.loaded(storage, tasks, &startup, onboarding.as_deref()),
+            Some((storage, Err(error))) => match Damaged::take(error) {
+                Ok(damaged) => self.offer_damaged(storage, damaged, startup, onboarding),
+                Err(error) => self.load_failed(&error.to_string()),
+            },
+            None => self.load_failed("the tasks file could not be read"),
+        }
+    }
+
+    /// "Loaded 37 tasks from …" once the tasks are in, None while they aren't
+    pub fn loaded_summary(&self) -> Option<&str> {
+        self.loaded_summary.as_deref()
+    }
+
+    fn load_failed(&mut self, error: &str) {
+        self.loading = Some(Loading::Failed);
+        let text = format!("Could not load the tasks: {error}\n\nq quits");
+        self.current_popup = Some(Box::new(ClosurePopup::message(text)));
+    }
+
+    /// Asks whether to go on with the tasks that could be read from a damaged file
+    fn offer_damaged(&mut self, storage: Box<dyn TaskStorage + Send>, damaged: Damaged, startup: Startup, onboarding: Option<PathBuf>) {
+        let name = damaged.path.file_name().unwrap_or_default().to_string_lossy();
+        let text = format!(
+            "{damaged}\n\n{}. Continue with them? The damaged file is kept as {name}.corrupt-<time>.\n\ny continues · q quits",
+            damaged.summary(),
+        );
+        self.current_popup = Some(Box::new(ClosurePopup::message(text)));
+        self.loading = Some(Loading::Damaged { storage, damaged, startup, onboarding });
+    }
+
+    /// Sets the damaged file aside and opens the list on what was recovered from it
+    fn recover_damaged(&mut self) {
+        let Some(Loading::Damaged { storage, damaged, startup, onboarding }) = self.loading.take() else { return };
+        let recovered = storages::set_aside(storage.as_ref(), &damaged, self.data.now())
+            .and_then(|kept| Ok((kept, storage.read()?)));
+        match recovered {
+            Ok((kept, tasks)) => {
+                self.current_popup = None;
+                self.loaded(storage, tasks, &startup, onboarding.as_deref());
+                self.data.messages.error(format!("{}, the damaged file is kept as {}", damaged.summary(), kept.display()));
+            },
+            Err(error) => self.load_failed(&format!("could not recover them: {error}")),
+        }
+    }
+
+    /// The tasks are in: the list opens as `startup` asks, with the summary in the status bar
+    fn loaded(&mut self, storage: Box<dyn TaskStorage>, tasks: Vec<Task>, startup: &Startup, onboarding: Option<&Path>) {
+        self.data = Data {
+            clock: self.data.clock,
+            location: self.data.location.take(),
+            ..Data::with_config(tasks.clone(), self.data.config.clone())
+        };
+        self.data.start_with(startup);
+        self.loaded_summary = Some(self.data.summary());
+        self.data.messages.info(self.data.summary());
+        self.storage = storage;
+        self.journaled = tasks;
+        match onboarding {
+            Some(path) => self.show_onboarding(path),
+            None => self.offer_recovery(),
+        }
+        self.show_duplicates();
+    }
+
+    /// Stops the app once `flag` is set, e.g. by `signal_hook::flag::register`
+    pub fn terminate_on(&mut self, flag: Arc<AtomicBool>) {
+        self.terminate = flag;
+    }
+
+    /// On a termination request, journals unsaved changes and exits without asking
+    pub fn check_termination(&mut self) -> bool {
+        if !self.terminate.load(AtomicOrdering::Relaxed) {
+            return false;
+        }
+        self.pause_timer();
+        // Nobody is left to answer a save prompt, the journal is recovered on the next start
+        if self.storage.should_save(&self.data.tasks) {
+            let _ = self.storage.write_journal(&self.data.tasks);
+        }
+        self.exit = true;
+        true
+    }
+
+    pub fn is_exiting(&self) -> bool {
+        self.exit
+    }
+
+    /// Whether `e` was pressed since the last call
+    pub fn take_edit_request(&mut self) -> bool {
+        std::mem::take(&mut self.edit_requested)
+    }
+
+    pub fn draw(&mut self, frame: &mut Frame) {
+        if let Some(loading) = &self.loading {
+            if matches!(loading, Loading::Pending { .. }) {
+                self.render_splash(frame);
+            }
+            if let Some(popup) = &self.current_popup {
+                popup.render(frame, frame.area());
+            }
+            return;
+        }
+
+        let [main, status_bar] = Layout::vertical([
+            Constraint::Fill(1),
+            Constraint::Length(1),
+        ]).areas(frame.area());
         let chunks = Layout::horizontal([
             Constraint::Percentage(50),
             Constraint::Percentage(50),
-        ]).split(frame.area());
+        ]).split(main);
 
         self.data.sort();
 
-        let left_active = matches!(self.current_pane, CurrentPane::Left);
-        self.current_popup = if let Some(popup) = self.current_popup.take() {
+        match self.review.as_mut() {
+            Some(review) => review.render(frame, main, &self.data),
+            None => {
+                let left_active = matches!(self.current_pane, CurrentPane::Left);
+                self.left_pane.render(frame, chunks[0], &self.data, left_active);
+                self.right_pane.render(frame, chunks[1], &self.data, !left_active);
+            },
+        }
+        self.render_status_bar(frame, status_bar);
+
+        if self.data.focus.is_some() && self.review.is_none() {
+            self.render_focus(frame);
+        }
+
+        // On top of a full frame, so a redraw after a resize leaves no stale background
+        if let Some(popup) = &self.current_popup {
             popup.render(frame, frame.area());
-            Some(popup)
-        } else {
-            None
+        }
+    }
+
+    fn render_splash(&self, frame: &mut Frame) {
+        let text = match &self.data.location {
+            Some(path) => format!("Loading tasks from {}…", paths::display_path(path, dirs::home_dir().as_deref())),
+            None => "Loading tasks…".to_string(),
         };
-        if self.current_popup.is_some() {
-            return
+        let [_, middle, _] = Layout::vertical([
+            Constraint::Fill(1),
+            Constraint::Length(1),
+            Constraint::Fill(1),
+        ]).areas(frame.area());
+        frame.render_widget(Line::from(text).centered().dim(), middle);
+    }
+
+    fn render_status_bar(&self, frame: &mut Frame, area: Rect) {
+        // A message takes the place of the filters while it is up
+        let line = match self.data.messages.current() {
+            Some(message) if message.error => Line::from(format!(" {}", message.text)).fg(T::error()),
+            Some(message) => Line::from(format!(" {}", message.text)).fg(T::highlight_desc()),
+            None => self.status_line(),
+        };
+        frame.render_widget(line, area);
+        let mut right = vec![];
+        if let Some(timer) = &self.timer {
+            right.push(Span::from(timer.label(self.data.now())).fg(T::highlight_desc()));
+            right.push(Span::raw("  "));
         }
+        // Where the selection is in a long list
+        if let Some(i) = self.data.index.filter(|_| self.data.len() > 0) {
+            right.push(Span::raw(format!("{}/{}  ", i + 1, self.data.len())));
+        }
+        right.push(Span::from("? help ").dim());
+        frame.render_widget(Line::from(right).right_aligned(), area);
+    }
 
-        self.left_pane.render(frame, chunks[0], &self.data, left_active);
-        self.right_pane.render(frame, chunks[1], &self.data, !left_active);
+    /// Filter switches and counts
+    fn status_line(&self) -> Line<'static> {
+        let filters = &self.data.filters;
+        let indicator = |key: &str, label: &str, active: bool| {
+            let span = Span::from(format!(" {key} {label} "));
+            match active {
+                true => span.reversed(),
+                false => span.dim(),
+            }
+        };
+        Line::from(vec![
+            indicator("C", "completed", filters.hide_completed),
+            Span::raw(" "),
+            indicator("U", "undated", filters.hide_no_deadline),
+            Span::raw(" "),
+            indicator("*", "starred", filters.only_starred),
+            Span::raw(format!("  ★ {}", self.data.query().starred(true).count())),
+            Span::raw(match filters.is_empty() {
+                true => "  all tasks shown".to_string(),
+                false => format!("  {} hidden", self.data.tasks.len() - self.data.len()),
+            }),
+        ])
     }
 
-    fn handle_events(&mut self, terminal: &mut DefaultTerminal) -> io::Result<()> {
-        match event::read()? {
+    fn render_focus(&self, frame: &mut Frame) {
+        let area = frame.area();
+        frame.buffer_mut().set_style(area, Style::default().add_modifier(Modifier::DIM));
+
+        let now = self.data.now();
+        let overdue = self.data.config.ui.overdue_emphasis.modifier();
+        let symbols = self.data.config.ui.status_symbols;
+        let mut lines = vec![];
+        for (i, task) in self.data.iter().enumerate() {
+            let selected = self.data.index == Some(i);
+            let marker = match (selected, self.data.config.ui.selection) {
+                (true, SelectionStyle::Marker) => "> ",
+                _ => "",
+            };
+            let mut name = Line::from(format!("{marker}{}", task.name()))
+                .centered()
+                .style(T::task_style(task, &self.data.status(task, &now), task.get_delta(&now), overdue, symbols))
+                .bold();
+            if selected { name = name.add_modifier(self.data.config.ui.selection.modifier()) };
+
+            lines.push(name);
+            lines.push(Line::from(task.delta_at(&now)).centered());
+            lines.push(Line::default());
+        }
+        if lines.is_empty() {
+            lines.push(Line::from("Nothing urgent").centered());
+        }
+
+        let block = Block::bordered()
+            .border_type(BorderType::Rounded)
+            .border_style(T::active_border())
+            .title(Line::from(" Focus ").bold().centered())
+            .padding(Padding::uniform(1));
+
+        let area = popups::centered_rect(60, 50, area);
+        frame.render_widget(Clear, area);
+        frame.render_widget(Paragraph::new(lines).block(block), area);
+    }
+
+    fn handle_events(&mut self) -> io::Result<()> {
+        // Wake up periodically even without input so countdowns stay live
+        match event::poll(Duration::from_millis(constants::TICK_RATE_MS)) {
+            Ok(true) => (),
+            // A signal cut the wait short, `check_termination` takes it from here
+            Err(error) if error.kind() == io::ErrorKind::Interrupted => return Ok(()),
+            result => { result?; return Ok(()); },
+        }
+        self.handle_event(event::read()?)
+    }
+
+    pub fn handle_event(&mut self, event: Event) -> io::Result<()> {
+        match event {
             Event::Key(key_event) if key_event.kind == KeyEventKind::Press => {
-                self.handle_key_event(terminal, key_event)?
+                self.handle_key_event(key_event)?
             }
+            // Popups take no clicks, and only the list has anything to click
+            Event::Mouse(mouse_event) if self.current_popup.is_none() && self.loading.is_none() => {
+                self.left_pane.handle_mouse_event(mouse_event, &mut self.data)
+            },
+            Event::Resize(_, _) => self.resized = true,
             _ => ()
         }
         Ok(())
     }
 
-    pub fn save(&self) -> io::Result<()> {
-        self.storage.write(&self.data.tasks)
+    /// Whether the terminal changed size since the last call
+    pub fn take_resize(&mut self) -> bool {
+        std::mem::take(&mut self.resized)
+    }
+
+    /// The one way the app writes the tasks file. The storage keeps the canonical order and
+    /// renames over the old file, the first save of a session backs that file up, and the
+    /// journal goes since nothing is unsaved any more. Fails with a `WriteConflict` when
+    /// something else wrote the file since it was read or saved.
+    pub fn persist(&mut self) -> io::Result<()> {
+        let expected = self.storage.fingerprint();
+        self.write_tasks(expected)
+    }
+
+    fn write_tasks(&mut self, expected: Option<Fingerprint>) -> io::Result<()> {
+        if !self.backed_up {
+            // Best effort, a new file or tasks kept in memory have nothing to back up
+            let _ = self.storage.backup();
+            self.backed_up = true;
+        }
+        self.storage.write_if_unchanged(&self.data.tasks, expected)?;
+        self.storage.discard_journal()?;
+        self.journaled = self.data.tasks.clone();
+        Ok(())
+    }
+
+    /// Saves and leaves, or stays with the reason when the save fails
+    fn save_and_exit(&mut self) -> Option<Box<dyn Popup<T> + 'a>> {
+        match self.persist() {
+            Ok(()) => {
+                self.exit = true;
+                None
+            },
+            Err(error) if WriteConflict::is(&error) => Some(Box::new(Self::conflict_popup(&error, true))),
+            Err(error) => Some(Box::new(ClosurePopup::message(format!("Could not save: {error}")))),
+        }
+    }
+
+    /// Asks what to do about a tasks file written by something else since it was read
+    fn conflict_popup(error: &io::Error, exit: bool) -> ClosurePopup<T> {
+        let text = format!(
+            "{error}.\n\n\
+            r    reload it, dropping your unsaved changes (u brings them back)\n\
+            o    overwrite it with yours\n\
+            Esc  keep editing"
+        );
+        ClosurePopup {
+            payload: Box::new(move |_data: &mut Data, key_event: &KeyEvent| {
+                match key_event.code {
+                    KeyCode::Char('r') => PopupAction::Reload,
+                    _ => PopupAction::Overwrite { exit },
+                }
+            }),
+            text,
+            confirmation: Box::new(|key_event: &KeyEvent| {
+                [KeyCode::Char('r'), KeyCode::Char('o')].contains(&key_event.code)
+            }),
+            cancellation: Box::new(|key_event: &KeyEvent| {key_event.code == KeyCode::Esc}),
+            _marker: PhantomData,
+        }
+    }
+
+    /// Replaces the tasks with what the file holds now, undoably
+    fn reload(&mut self) -> io::Result<()> {
+        let tasks = self.storage.read()?;
+        self.data.checkpoint();
+        self.data.tasks = tasks.clone();
+        self.data.clamp_index();
+        self.data.validate();
+        self.journaled = tasks;
+        Ok(())
+    }
+
+    /// Journals unsaved changes made since the last call, or drops the journal once there are none
+    pub fn update_journal(&mut self) {
+        self.journal_checked = Instant::now();
+        if self.journaled == self.data.tasks {
+            return;
+        }
+        self.journaled = self.data.tasks.clone();
+        // Only a safety net, failing to write it must not get in the way
+        let _ = match self.storage.should_save(&self.data.tasks) {
+            true => self.storage.write_journal(&self.data.tasks),
+            false => self.storage.discard_journal(),
+        };
+    }
+
+    /// Asks whether to pick up where a session that never saved left off
+    pub fn offer_recovery(&mut self) {
+        let Ok(Some(tasks)) = self.storage.read_journal() else { return };
+        if !self.storage.should_save(&tasks) {
+            let _ = self.storage.discard_journal();
+            return;
+        }
+        // What the journal holds, so the next journal check drops it unless the tasks are taken
+        self.journaled = tasks.clone();
+        let popup = ClosurePopup {
+            payload: Box::new(move |data: &mut Data, key_event: &KeyEvent| {
+                if matches!(key_event.code, KeyCode::Char('y') | KeyCode::Enter) {
+                    data.checkpoint();
+                    data.tasks = tasks.clone();
+                    data.clamp_index();
+                    data.validate();
+                }
+                PopupAction::Close
+            }),
+            text: "The last session ended with unsaved changes. Recover them? (y/n)".to_string(),
+            confirmation: Box::new(|key_event: &KeyEvent| {
+                [KeyCode::Enter, KeyCode::Char('y'), KeyCode::Char('n')].contains(&key_event.code)
+            }),
+            cancellation: Box::new(|key_event: &KeyEvent| {key_event.code == KeyCode::Esc}),
+            _marker: PhantomData,
+        };
+        self.current_popup = Some(Box::new(popup));
     }
 
-    fn edit(&mut self) -> Result<(), ()> {
-        if let Some(old_task) = self.data.index.and_then(|x| self.data.tasks.get(x)) {
-            let new_task = R::read(&old_task)?;
-            self.data.tasks[self.data.index.unwrap()] = new_task;
+    /// Warns once about tasks sharing a subject and a name, unless something else is already shown
+    pub fn show_duplicates(&mut self) {
+        let groups = validation::find_duplicates(&self.data.tasks);
+        if groups.is_empty() || self.current_popup.is_some() {
+            return;
+        }
+        let mut text = "These tasks share a subject and a name:\n\n".to_string();
+        for group in &groups {
+            text.push_str(&format!("  {}\n", group.describe(&self.data.tasks)));
+        }
+        text.push_str("\nd  pick which to keep\nEsc  leave them");
+        let popup = ClosurePopup {
+            payload: Box::new(move |data: &mut Data, _key_event: &KeyEvent| {
+                PopupAction::Open(Box::new(DedupPopup::new(&groups, &data.tasks, &data.config.ui.date_names(), &data.config.ui.display_zone())))
+            }),
+            text,
+            confirmation: Box::new(|key_event: &KeyEvent| {key_event.code == KeyCode::Char('d')}),
+            cancellation: Box::new(|key_event: &KeyEvent| {[KeyCode::Esc, KeyCode::Enter].contains(&key_event.code)}),
+            _marker: PhantomData,
         };
+        self.current_popup = Some(Box::new(popup));
+    }
+
+    /// Runs the reader on the selected task
+    pub fn edit(&mut self) -> Result<(), ()> {
+        let Some(original) = self.data.selected().cloned() else { return Ok(()) };
+        // Suspended until the reader returns, the tasks may have moved on by then
+        let edited = self.reader.read(&original, &self.data.config)?;
+        self.write_back(&original, edited);
         Ok(())
     }
 
+    /// Puts the edited version of `original` back on the task with its id, wherever that is now.
+    /// When it is gone, asks whether to add the edited version as a new task.
+    pub fn write_back(&mut self, original: &Task, edited: Task) {
+        self.data.clamp_index();
+        let Some(task) = self.data.tasks.iter().find(|x| x.id == original.id) else {
+            self.current_popup = Some(Box::new(Self::vanished_popup(original, edited)));
+            return;
+        };
+        let previous = task.time;
+        // An anchored deadline is not written down but resolved, the same anchor keeps it
+        let time = match edited.anchor {
+            Some(_) if edited.anchor == task.anchor => task.time,
+            _ => edited.time,
+        };
+        let edited = Task { id: original.id, time, ..edited };
+        // Saving the editor without a change is not a change
+        if *task != edited {
+            self.data.update(original.id, |task| *task = edited);
+        }
+        self.data.validate();
+        if let Some(broken) = self.data.broken_anchors.iter().find(|x| x.id == original.id) {
+            self.data.messages.error(format!("No deadline: {}", broken.problem.describe()));
+        }
+
+        let now = self.data.clock.now();
+        let time = self.data.tasks.iter().find(|x| x.id == original.id).and_then(|x| x.time);
+        if let Some(warning) = validation::past_deadline_warning(previous, time, &now, &self.data.config.validation) {
+            self.current_popup = Some(Box::new(Self::past_deadline_popup(original.id, previous, warning)));
+        }
+    }
+
+    /// Re-adds an edit whose task was removed meanwhile on y, drops it on n
+    fn vanished_popup(original: &Task, edited: Task) -> ClosurePopup<T> {
+        ClosurePopup {
+            payload: Box::new(move |data: &mut Data, key_event: &KeyEvent| {
+                if matches!(key_event.code, KeyCode::Char('y') | KeyCode::Enter) {
+                    data.add(Task { id: 0, ..edited.clone() });
+                }
+                PopupAction::Close
+            }),
+            text: format!(
+                "\"{}: {}\" was removed while it was being edited. Add the edited version as a new task? (y/n)",
+                original.subject, original.name,
+            ),
+            confirmation: Box::new(|key_event: &KeyEvent| {
+                [KeyCode::Enter, KeyCode::Char('y'), KeyCode::Char('n')].contains(&key_event.code)
+            }),
+            cancellation: Box::new(|key_event: &KeyEvent| {key_event.code == KeyCode::Esc}),
+            _marker: PhantomData,
+        }
+    }
+
+    /// Sets the deadline of the selected task from the month grid
+    fn pick_deadline(&mut self) {
+        let Some(task) = self.data.selected() else { return };
+        let (id, previous) = (task.id, task.time);
+        let picker = DatePicker::new(previous, self.data.now(), self.data.config.deadline_time(), &self.data.tasks)
+            .with_status_symbols(self.data.config.ui.status_symbols)
+            .with_names(self.data.config.ui.date_names())
+            .with_policy(self.data.status_policy())
+            .with_day_limit(self.data.config.validation.max_tasks_per_day)
+            .with_filter(self.data.config.ui.calendar_include);
+        let on_pick: OnPick<T> = Box::new(move |data: &mut Data, time| {
+            data.checkpoint();
+            // A deadline picked by hand no longer follows another task's
+            data.update(id, |task| {
+                task.time = Some(time);
+                task.anchor = None;
+            });
+            data.validate();
+            match validation::past_deadline_warning(previous, Some(time), &data.now(), &data.config.validation) {
+                Some(warning) => PopupAction::Open(Box::new(Self::past_deadline_popup(id, previous, warning))),
+                None => PopupAction::Close,
+            }
+        });
+        self.current_popup = Some(Box::new(DatePickerPopup::new(picker, on_pick)));
+    }
+
+    /// Starts the focus timer on the selected task, pauses or resumes it when it already
+    /// runs on that task, and moves it over from any other task
+    fn toggle_timer(&mut self) {
+        let now = self.data.now();
+        let selected = self.data.selected().cloned();
+        match (self.timer.as_mut(), selected) {
+            (Some(timer), Some(task)) if timer.id != task.id => {
+                timer.pause(&mut self.data);
+                self.timer = Some(FocusTimer::start(&task, self.timer_length(), now));
+            },
+            (Some(timer), _) if timer.is_running() => timer.pause(&mut self.data),
+            (Some(timer), _) => timer.resume(now),
+            (None, Some(task)) => self.timer = Some(FocusTimer::start(&task, self.timer_length(), now)),
+            (None, None) => (),
+        }
+    }
+
+    fn timer_length(&self) -> TimeDelta {
+        TimeDelta::minutes(self.data.config.ui.focus_timer_minutes)
+    }
+
+    /// Banks the running focus timer into its task
+    fn pause_timer(&mut self) {
+        if let Some(timer) = self.timer.as_mut() {
+            timer.pause(&mut self.data);
+        }
+    }
+
+    /// Moves the status bar messages on and ends the focus timer once it ran out,
+    /// true when it just did
+    pub fn tick(&mut self) -> bool {
+        let now = self.data.now();
+        self.data.messages.expire(now);
+        let Some(mut timer) = self.timer.take_if(|x| x.is_done(now)) else { return false };
+        timer.pause(&mut self.data);
+        let spent = self.data.tasks.iter().find(|x| x.id == timer.id).map_or(TimeDelta::zero(), |x| x.time_spent);
+        let text = format!("Time is up on \"{}\", {} spent on it so far.", timer.name, format_spent(spent));
+        self.current_popup.get_or_insert_with(|| Box::new(ClosurePopup::message(text)));
+        true
+    }
+
+    /// Starts the weekly review, or says there is nothing to go through
+    fn start_review(&mut self) -> Option<Box<dyn Popup<T>>> {
+        let days = self.data.config.ui.review_stale_days;
+        self.review = Review::new(&self.data, days);
+        match self.review {
+            Some(_) => None,
+            None => Some(Box::new(ClosurePopup::message(format!("Nothing has been left alone for {days} days.")))),
+        }
+    }
+
+    /// Keeps the new deadline on y, puts the previous one back on n
+    fn past_deadline_popup(id: u64, previous: Option<DateTime<FixedOffset>>, warning: String) -> ClosurePopup<T> {
+        ClosurePopup {
+            payload: Box::new(move |data: &mut Data, key_event: &KeyEvent| {
+                if key_event.code == KeyCode::Char('n') {
+                    data.update(id, |task| task.time = previous);
+                }
+                PopupAction::Close
+            }),
+            text: format!("{warning} — keep it? (y/n)"),
+            confirmation: Box::new(|key_event: &KeyEvent| {
+                [KeyCode::Enter, KeyCode::Char('y'), KeyCode::Char('n')].contains(&key_event.code)
+            }),
+            cancellation: Box::new(|_key_event: &KeyEvent| false),
+            _marker: PhantomData,
+        }
+    }
+
     fn add_default(&mut self) {
-        self.data.tasks.push(Task::default())
+        let mut task = Task::placeholder_now(&self.data.clock);
+        match self.data.template_description(&task.subject, &task.name) {
+            Ok(Some(description)) => task.description = description,
+            Ok(None) => (),
+            Err(error) => self.current_popup = Some(Box::new(ClosurePopup::message(error))),
+        }
+        self.data.add(task);
     }
 
-    fn handle_key_event(&mut self, terminal: &mut DefaultTerminal, key_event: KeyEvent) -> io::Result<()> {
+    pub fn handle_key_event(&mut self, key_event: KeyEvent) -> io::Result<()> {
+        // Raw mode turns Ctrl-C into a key, which quits from anywhere, popups included
+        if key_event.code == KeyCode::Char('c') && key_event.modifiers.contains(KeyModifiers::CONTROL) {
+            self.current_popup = None;
+            return self.exit();
+        }
+        // Nothing to act on before the tasks are in, only to leave or take what was recovered
+        if self.loading.is_some() {
+            match key_event.code {
+                KeyCode::Char('y') if matches!(self.loading, Some(Loading::Damaged { .. })) => self.recover_damaged(),
+                code => self.exit = code == KeyCode::Char('q'),
+            }
+            return Ok(());
+        }
         let mut should_stop = false;
         self.current_popup = match self.current_popup.take() {
             // Popup exists
@@ -228,20 +1481,56 @@ impl<'a, T: TaskColors, R: TaskReader, S: TaskStorage> App<'a, T, R, S> where
                 // Popup persists
                 match popup.handle_key_event(&key_event, &mut self.data) {
                     PopupAction::Exit => {
+                        // Leaving without saving, so whatever was journaled is not wanted
+                        self.storage.discard_journal()?;
                         self.exit = true;
                         None
                     },
+                    PopupAction::SaveAndExit => self.save_and_exit(),
                     PopupAction::Close => {
                         None
                     },
                     PopupAction::None => {
                         Some(popup)
-                    }
+                    },
+                    PopupAction::Open(next) => {
+                        Some(next)
+                    },
+                    PopupAction::Review => self.start_review(),
+                    PopupAction::Reload => match self.reload() {
+                        Ok(()) => {
+                            self.data.messages.info("Reloaded");
+                            None
+                        },
+                        Err(error) => Some(Box::new(ClosurePopup::message(format!("Could not reload: {error}")))),
+                    },
+                    PopupAction::Overwrite { exit } => match self.write_tasks(None) {
+                        Ok(()) => {
+                            self.exit = exit;
+                            self.data.messages.info("Saved");
+                            None
+                        },
+                        Err(error) => Some(Box::new(ClosurePopup::message(format!("Could not save: {error}")))),
+                    },
                 }
             },
             None => None,
         };
         if should_stop {return Ok(())}
+
+        if let Some(review) = self.review.as_mut() {
+            match review.handle_key_event(&key_event, &mut self.data) {
+                ReviewAction::None => (),
+                ReviewAction::Open(popup) => self.current_popup = Some(popup),
+                ReviewAction::Done => {
+                    let summary = review.summary();
+                    self.review = None;
+                    self.data.clamp_index();
+                    self.current_popup = Some(Box::new(ClosurePopup::message(summary)));
+                },
+            }
+            return Ok(());
+        }
         // dbg!(format!("{}", self.current_popup.is_none().to_string()));
 
         match key_event.code {
@@ -256,19 +1545,45 @@ impl<'a, T: TaskColors, R: TaskReader, S: TaskStorage> App<'a, T, R, S> where
                 self.left_pane.leave();
                 self.current_pane = CurrentPane::Right;
             },
-            KeyCode::Char('f') => self.data.toggle_filter_zen(),
-            KeyCode::Char('w') => { self.save().unwrap(); },
-            KeyCode::Char('e') => {
-                stdout().execute(LeaveAlternateScreen)?;
-                // disable_raw_mode()?;
-                self.edit().unwrap();
-                stdout().execute(EnterAlternateScreen)?;
-                // enable_raw_mode()?;
-                terminal.clear()?;
+            KeyCode::Char('f') if !key_event.modifiers.contains(KeyModifiers::CONTROL) => self.data.apply_filter_preset(),
+            KeyCode::Char('C') => self.data.toggle_hide_completed(),
+            KeyCode::Char('U') => self.data.toggle_hide_no_deadline(),
+            KeyCode::Char('*') => self.data.toggle_only_starred(),
+            KeyCode::Char('a') | KeyCode::Char(' ') => self.data.toggle_view(View::URGENT),
+            KeyCode::Char('Z') => self.data.toggle_focus(),
+            KeyCode::Char('w') => match self.persist() {
+                Ok(()) => self.data.messages.info("Saved"),
+                Err(error) if WriteConflict::is(&error) => {
+                    self.current_popup = Some(Box::new(Self::conflict_popup(&error, false)));
+                },
+                Err(error) => self.data.messages.error(format!("Could not save: {error}")),
+            },
+            KeyCode::Char('e') if key_event.modifiers.contains(KeyModifiers::CONTROL) => {
+                self.current_popup = Some(Box::new(ExportPopup::new()));
+            },
+            KeyCode::Char('e') => self.edit_requested = true,
+            KeyCode::Char('D') => self.pick_deadline(),
+            KeyCode::Char('R') => self.current_popup = self.start_review(),
+            KeyCode::Char('T') => self.toggle_timer(),
+            KeyCode::Char('p') if key_event.modifiers.contains(KeyModifiers::CONTROL) => {
+                self.current_popup = Some(Box::new(FinderPopup::new(&self.data)));
             },
             KeyCode::Char('p') => {
                 self.add_default();
             }
+            KeyCode::Char('n') => {
+                self.current_popup = Some(Box::new(QuickAddPopup::new(self.data.subjects())));
+            },
+            KeyCode::Char('v') if key_event.modifiers.contains(KeyModifiers::CONTROL) => {
+                self.current_popup = Some(Box::new(PasteImportPopup::new(self.data.subjects())));
+            },
+            KeyCode::Char('v') => self.show_conflicts(),
+            KeyCode::Char('u') if !key_event.modifiers.contains(KeyModifiers::CONTROL) => { self.data.undo(); },
+            KeyCode::Char(':') => {
+                let palette = InputPopup::new("Command", ":".to_string(), Box::new(commands::run));
+                self.current_popup = Some(Box::new(palette));
+            },
+            KeyCode::Char('?') => self.show_help(),
             _ => {
                 self.current_popup = match self.current_pane {
                     CurrentPane::Left => self.left_pane.handle_key_event(key_event, &mut self.data),
@@ -279,19 +1594,97 @@ impl<'a, T: TaskColors, R: TaskReader, S: TaskStorage> App<'a, T, R, S> where
         Ok(())
     }
 
+    fn show_conflicts(&mut self) {
+        let text = if self.data.conflicts.is_empty() {
+            "No deadline conflicts found.".to_string()
+        } else {
+            let mut lines = vec!["Same-subject tasks due close to each other:".to_string()];
+            lines.extend(self.data.conflicts.iter().map(|x| x.describe(&self.data.tasks)));
+            lines.join("\n")
+        };
+        self.current_popup = Some(Box::new(ClosurePopup::message(text)));
+    }
+
+    fn show_help(&mut self) {
+        let text = [
+            "h / l    switch between panes",
+            "j / k    move down / up",
+            "g / G    first / last task",
+            "Ctrl-d / Ctrl-u  half a page down / up, Ctrl-f / Ctrl-b a whole page",
+            "H / M / L top / middle / bottom of the screen",
+            "p        add a task",
+            "n        quick add with subject completion",
+            "Ctrl-v   paste a list, a task per line, also :paste-import",
+            "Ctrl-p   find a task by subject, name or description",
+            "e        edit the selected task",
+            "D        pick the deadline on a calendar",
+            "c        toggle complete",
+            "s        toggle star",
+            "d        delete the selected task, also from the description or the task view",
+            "Enter / K show the whole task",
+            "i        toggle row numbers",
+            "1-5      sort by star, subject, name, deadline, last change; again to flip",
+            "f        apply the filter preset",
+            "C        hide completed tasks",
+            "U        hide tasks without a deadline",
+            "*        starred tasks only",
+            "a / Space urgent and starred tasks only",
+            "Z        focus on the most urgent tasks",
+            "v        list deadline conflicts",
+            "R        review tasks untouched for a while, also :review",
+            "T        focus timer on the selected task; again to pause",
+            "u        undo",
+            ":        command, e.g. :shift Algorithms +7d, :time for the time tracked,",
+            "         :filter stubs for tasks still without a description,",
+            "         :filter clear to show everything again,",
+            "         :today-done for what was completed today, :file for the tasks file",
+            "Ctrl-e   export the visible tasks",
+            "w        save",
+            "q        quit, also Ctrl-c",
+        ].join("\n");
+        self.current_popup = Some(Box::new(ClosurePopup::message(text)));
+    }
+
+    /// Greets a new user, offering to create an example task
+    pub fn show_onboarding(&mut self, path: &Path) {
+        let text = format!(
+            "Welcome to unist!\n\nYour tasks will be saved to {}\n\n\
+            p  add a task\n\
+            Ctrl-v  paste a list of deadlines, e.g. from a syllabus\n\
+            e  edit the selected task\n\
+            ?  show all keys\n\n\
+            Create an example task? (y/n)",
+            path.display()
+        );
+        let popup = ClosurePopup {
+            payload: Box::new(|data: &mut Data, key_event: &KeyEvent| {
+                if matches!(key_event.code, KeyCode::Char('y') | KeyCode::Enter) {
+                    data.add(Task::example());
+                }
+                PopupAction::Close
+            }),
+            text,
+            confirmation: Box::new(|key_event: &KeyEvent| {
+                [KeyCode::Enter, KeyCode::Char('y'), KeyCode::Char('n')].contains(&key_event.code)
+            }),
+            cancellation: Box::new(|key_event: &KeyEvent| {key_event.code == KeyCode::Esc}),
+            _marker: PhantomData,
+        };
+        self.current_popup = Some(Box::new(popup));
+    }
+
     fn exit(&mut self) -> io::Result<()> {
+        // Quitting is no reason to lose the minutes worked
+        self.pause_timer();
         if !self.storage.should_save(&self.data.tasks) {
+            self.storage.discard_journal()?;
             self.exit = true;
             return Ok(());
         }
-        let storage = self.storage.clone();
         let popup = ClosurePopup {
-            payload: Box::new(move |data: &mut Data, key_event: &KeyEvent| {
+            payload: Box::new(|_data: &mut Data, key_event: &KeyEvent| {
                 match key_event.code {
-                    KeyCode::Char('y') | KeyCode::Enter => {
-                        storage.write(&data.tasks).unwrap();
-                        PopupAction::Exit
-                    },
+                    KeyCode::Char('y') | KeyCode::Enter => PopupAction::SaveAndExit,
                     KeyCode::Char('n') => PopupAction::Exit,
                     _ => PopupAction::None,
                 }
@@ -0,0 +1,82 @@
+use unicode_width::UnicodeWidthStr;
+
+use super::panes::truncate;
+
+
+/// Pane title with compact badges for the state the pane is in, e.g.
+/// "Tasks [URGENT][★][sort: name ▼] — next: Algo HW1 in 1d".
+/// What does not fit goes from the right: the note, then badge by badge.
+#[derive(Debug, Default, Clone)]
+pub struct TitleBadges {
+    title: String,
+    badges: Vec<String>,
+    /// Plain text after the badges, the first to go
+    note: Option<String>,
+}
+
+impl TitleBadges {
+    pub fn new(title: impl Into<String>) -> Self {
+        Self { title: title.into(), ..Self::default() }
+    }
+
+    pub fn with_badge(mut self, badge: impl Into<String>) -> Self {
+        self.badges.push(badge.into());
+        self
+    }
+
+    pub fn with_badges(self, badges: impl IntoIterator<Item = String>) -> Self {
+        badges.into_iter().fold(self, Self::with_badge)
+    }
+
+    pub fn with_note(self, note: Option<String>) -> Self {
+        TitleBadges { note, ..self }
+    }
+
+    fn compose(&self, badges: usize) -> String {
+        let badges = self.badges[..badges].iter().map(|x| format!("[{x}]")).collect::<String>();
+        match badges.is_empty() {
+            true => self.title.clone(),
+            false => format!("{} {badges}", self.title),
+        }
+    }
+
+    /// The title in at most `width` columns
+    pub fn render(&self, width: usize) -> String {
+        let all = self.compose(self.badges.len());
+        if let Some(note) = &self.note {
+            let text = format!("{all} {note}");
+            if text.width() <= width {
+                return text;
+            }
+        }
+        if all.width() <= width {
+            return all;
+        }
+        // An ellipsis tells that badges were left out
+        (0..self.badges.len())
+            .rev()
+            .map(|x| format!("{} …", self.compose(x)))
+            .find(|x| x.width() <= width)
+            .unwrap_or_else(|| truncate(&self.title, width))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn badges_go_from_the_right_when_narrow() {
+        let title = TitleBadges::new("Tasks")
+            .with_badge("URGENT")
+            .with_badges(["★".to_string(), "sort: name ▼".to_string()])
+            .with_note(Some("— next: Algo HW1 in 1d".to_string()));
+
+        assert_eq!(title.render(80), "Tasks [URGENT][★][sort: name ▼] — next: Algo HW1 in 1d");
+        assert_eq!(title.render(40), "Tasks [URGENT][★][sort: name ▼]");
+        assert_eq!(title.render(20), "Tasks [URGENT][★] …");
+        assert_eq!(title.render(10), "Tasks …");
+        assert_eq!(title.render(4), "Tas…");
+        assert_eq!(TitleBadges::new("Tasks").render(80), "Tasks");
+    }
+}
@@ -1,28 +1,439 @@
+use std::env;
+use std::marker::PhantomData;
+
+use crate::config::{ColorDepth, OverdueEmphasis, SelectionStyle, SubjectColors};
 use crate::uni::task::{Task, TaskStatus};
 
-use ratatui::style::{Color, palette::tailwind};
+use chrono::TimeDelta;
+use ratatui::style::{Color, Modifier, Style, palette::tailwind};
 
 pub trait TaskColors: 'static {
     fn highlight_table() -> Color { Color::Gray }
     fn highlight_desc() -> Color { Color::Gray }
     fn highlight_border() -> Color { Color::Gray }
+    /// Badge of the tasks due today
+    fn today_accent() -> Color { Color::Cyan }
+    /// Behind the selected entry of the description pane
+    fn active_entry_bg() -> Color { Color::DarkGray }
+    /// Text of the selected entry, the header keeps its own color
+    fn active_entry_fg() -> Color { Color::Reset }
+    /// Error lines of the status bar and popups
+    fn error() -> Color { Color::Red }
+    /// Days of the deadline picker with a task or two due
+    fn busy_day() -> Color { Color::Yellow }
+    /// Days of the deadline picker with more
+    fn packed_day() -> Color { Color::Red }
+    /// Days of the deadline picker with more open tasks than `max_tasks_per_day`
+    fn overloaded_day() -> Color { Color::Magenta }
+
+    /// The selected entry of the description pane
+    fn active_entry() -> Style {
+        Style::default().bg(Self::active_entry_bg()).fg(Self::active_entry_fg())
+    }
+
+    /// Border of the pane keys go to
+    fn active_border() -> Style {
+        Style::default().fg(Self::highlight_border())
+    }
 
     #[allow(unused)]
-    fn task_color(_status: &Task) -> Color;
+    fn task_color(status: &TaskStatus) -> Color;
+
+    /// Accent of a subject, the same one every run
+    fn subject_accent(subject: &str) -> Color {
+        pick_accent(&SUBJECT_PALETTE, subject)
+    }
+
+    /// The configured color of the subject, `subject_accent` when it has none or an unreadable one
+    fn subject_color(subject: &str, overrides: &SubjectColors) -> Color {
+        overrides
+            .get(subject)
+            .and_then(|x| x.parse().ok())
+            .unwrap_or_else(|| Self::subject_accent(subject))
+    }
+
+    /// The color the task asks for in place of its status color, see `custom_color`
+    fn task_override(task: &Task) -> Option<Color> {
+        custom_color(task)
+    }
+
+    /// Whole style of a task row. Panicking tasks escalate the longer they are overdue:
+    /// plain when still due, bold within the first day, bold plus `overdue` after that.
+    /// With `symbols` the status also shows without colors: panicking bold, zen dim.
+    fn task_style(task: &Task, status: &TaskStatus, delta: Option<TimeDelta>, overdue: Modifier, symbols: bool) -> Style {
+        let style = Style::default().fg(Self::task_override(task).unwrap_or_else(|| Self::task_color(status)));
+        let style = match (symbols, status) {
+            (true, TaskStatus::Panic | TaskStatus::DueToday) => style.add_modifier(Modifier::BOLD),
+            (true, TaskStatus::Zen) => style.add_modifier(Modifier::DIM),
+            _ => style,
+        };
+        match (status, delta) {
+            (TaskStatus::Panic, Some(delta)) if delta <= -TimeDelta::days(1) => {
+                style.add_modifier(Modifier::BOLD | overdue)
+            },
+            (TaskStatus::Panic, Some(delta)) if delta < TimeDelta::zero() => {
+                style.add_modifier(Modifier::BOLD)
+            },
+            _ => style,
+        }
+    }
 }
 // #b8bb26
 
+/// Accents of the default theme, the red kept for panicking tasks
+const SUBJECT_PALETTE: [Color; 6] = [Color::Cyan, Color::Magenta, Color::Yellow, Color::Blue, Color::Green, Color::LightCyan];
+
+/// A color of `palette` chosen by the subject's FNV-1a hash. The standard hasher
+/// may change between Rust releases, which would repaint every subject.
+fn pick_accent(palette: &[Color], subject: &str) -> Color {
+    let hash = subject
+        .trim()
+        .to_lowercase()
+        .bytes()
+        .fold(0xcbf29ce484222325_u64, |hash, x| (hash ^ x as u64).wrapping_mul(0x100000001b3));
+    palette[(hash % palette.len() as u64) as usize]
+}
+
+/// The task's own color, None without one or with one that can't be read
+pub fn custom_color(task: &Task) -> Option<Color> {
+    task.color.as_deref()?.parse().ok()
+}
+
+/// Marker telling the status apart without colors: "✓" complete, "!!" overdue, "!" panicking
+/// or due today
+pub fn status_symbol(task: &Task, status: &TaskStatus, delta: Option<TimeDelta>) -> &'static str {
+    match (task.complete, status, delta) {
+        (true, _, _) => "✓",
+        (false, TaskStatus::Panic, Some(delta)) if delta < TimeDelta::zero() => "!!",
+        (false, TaskStatus::Panic | TaskStatus::DueToday, _) => "!",
+        _ => "",
+    }
+}
+
+/// The 16 ANSI colors as xterm shows them by default
+const ANSI: [(Color, (u8, u8, u8)); 16] = [
+    (Color::Black, (0, 0, 0)),
+    (Color::Red, (205, 0, 0)),
+    (Color::Green, (0, 205, 0)),
+    (Color::Yellow, (205, 205, 0)),
+    (Color::Blue, (0, 0, 238)),
+    (Color::Magenta, (205, 0, 205)),
+    (Color::Cyan, (0, 205, 205)),
+    (Color::Gray, (229, 229, 229)),
+    (Color::DarkGray, (127, 127, 127)),
+    (Color::LightRed, (255, 0, 0)),
+    (Color::LightGreen, (0, 255, 0)),
+    (Color::LightYellow, (255, 255, 0)),
+    (Color::LightBlue, (92, 92, 255)),
+    (Color::LightMagenta, (255, 0, 255)),
+    (Color::LightCyan, (0, 255, 255)),
+    (Color::White, (255, 255, 255)),
+];
+
+/// What an indexed color looks like: the ANSI ones, the 6×6×6 cube, then the grays
+fn indexed_rgb(index: u8) -> (u8, u8, u8) {
+    let level = |x: u8| if x == 0 { 0 } else { 55 + 40 * x };
+    match index {
+        0..=15 => ANSI[index as usize].1,
+        16..=231 => {
+            let x = index - 16;
+            (level(x / 36), level(x / 6 % 6), level(x % 6))
+        },
+        _ => {
+            let gray = 8 + 10 * (index - 232);
+            (gray, gray, gray)
+        },
+    }
+}
+
+/// `color` for a web page, as "#rrggbb"
+pub fn css_color(color: Color) -> String {
+    let (r, g, b) = match color {
+        Color::Rgb(r, g, b) => (r, g, b),
+        Color::Indexed(index) => indexed_rgb(index),
+        // The terminal's own default has no value, text is black on paper
+        named => ANSI.iter().find(|(x, _)| *x == named).map_or((0, 0, 0), |(_, rgb)| *rgb),
+    };
+    format!("#{r:02x}{g:02x}{b:02x}")
+}
+
+fn distance((r1, g1, b1): (u8, u8, u8), (r2, g2, b2): (u8, u8, u8)) -> i32 {
+    let d = |a: u8, b: u8| (a as i32 - b as i32).pow(2);
+    d(r1, r2) + d(g1, g2) + d(b1, b2)
+}
+
+impl ColorDepth {
+    /// From COLORTERM and TERM; 16 colors unless either promises more
+    pub fn detect() -> Self {
+        Self::from_env(env::var("COLORTERM").ok().as_deref(), env::var("TERM").ok().as_deref())
+    }
+
+    fn from_env(colorterm: Option<&str>, term: Option<&str>) -> Self {
+        match (colorterm, term) {
+            (Some("truecolor" | "24bit"), _) => ColorDepth::TrueColor,
+            (_, Some(term)) if term.contains("256color") => ColorDepth::Ansi256,
+            _ => ColorDepth::Ansi16,
+        }
+    }
+
+    /// The nearest color the terminal has. Named colors are ANSI already and stay.
+    pub fn degrade(self, color: Color) -> Color {
+        let rgb = match color {
+            Color::Rgb(r, g, b) => (r, g, b),
+            Color::Indexed(index) if self == ColorDepth::Ansi16 => indexed_rgb(index),
+            _ => return color,
+        };
+        match self {
+            ColorDepth::TrueColor => color,
+            // Not the first 16, they follow the terminal's theme
+            ColorDepth::Ansi256 => (16..=255).min_by_key(|&x| distance(indexed_rgb(x), rgb)).map_or(color, Color::Indexed),
+            ColorDepth::Ansi16 => ANSI.iter().min_by_key(|(_, x)| distance(*x, rgb)).map_or(color, |(x, _)| *x),
+        }
+    }
+
+    pub fn degrade_style(self, style: Style) -> Style {
+        Style {
+            fg: style.fg.map(|x| self.degrade(x)),
+            bg: style.bg.map(|x| self.degrade(x)),
+            ..style
+        }
+    }
+}
+
+/// Palette a `Degraded` theme is brought down to
+pub trait Depth: 'static {
+    const DEPTH: ColorDepth;
+}
+
+pub struct Ansi256;
+impl Depth for Ansi256 {
+    const DEPTH: ColorDepth = ColorDepth::Ansi256;
+}
+
+pub struct Ansi16;
+impl Depth for Ansi16 {
+    const DEPTH: ColorDepth = ColorDepth::Ansi16;
+}
+
+/// Theme `T` with every color it gives out degraded to `D`
+pub struct Degraded<T, D>(PhantomData<(T, D)>);
+
+impl<T: TaskColors, D: Depth> TaskColors for Degraded<T, D> {
+    fn highlight_table() -> Color { D::DEPTH.degrade(T::highlight_table()) }
+    fn highlight_desc() -> Color { D::DEPTH.degrade(T::highlight_desc()) }
+    fn highlight_border() -> Color { D::DEPTH.degrade(T::highlight_border()) }
+    fn today_accent() -> Color { D::DEPTH.degrade(T::today_accent()) }
+    fn active_entry_bg() -> Color { D::DEPTH.degrade(T::active_entry_bg()) }
+    fn active_entry_fg() -> Color { D::DEPTH.degrade(T::active_entry_fg()) }
+    fn error() -> Color { D::DEPTH.degrade(T::error()) }
+    fn busy_day() -> Color { D::DEPTH.degrade(T::busy_day()) }
+    fn packed_day() -> Color { D::DEPTH.degrade(T::packed_day()) }
+    fn overloaded_day() -> Color { D::DEPTH.degrade(T::overloaded_day()) }
+    fn active_entry() -> Style { D::DEPTH.degrade_style(T::active_entry()) }
+    fn active_border() -> Style { D::DEPTH.degrade_style(T::active_border()) }
+
+    fn task_color(status: &TaskStatus) -> Color { D::DEPTH.degrade(T::task_color(status)) }
+    fn subject_accent(subject: &str) -> Color { D::DEPTH.degrade(T::subject_accent(subject)) }
+    fn subject_color(subject: &str, overrides: &SubjectColors) -> Color { D::DEPTH.degrade(T::subject_color(subject, overrides)) }
+    fn task_override(task: &Task) -> Option<Color> { T::task_override(task).map(|x| D::DEPTH.degrade(x)) }
+
+    fn task_style(task: &Task, status: &TaskStatus, delta: Option<TimeDelta>, overdue: Modifier, symbols: bool) -> Style {
+        D::DEPTH.degrade_style(T::task_style(task, status, delta, overdue, symbols))
+    }
+}
+
+impl OverdueEmphasis {
+    pub fn modifier(&self) -> Modifier {
+        match self {
+            OverdueEmphasis::Blink => Modifier::SLOW_BLINK,
+            OverdueEmphasis::Reversed => Modifier::REVERSED,
+            OverdueEmphasis::None => Modifier::empty(),
+        }
+    }
+}
+
+impl SelectionStyle {
+    /// Added to the selected row; the marker is drawn either way
+    pub fn modifier(&self) -> Modifier {
+        match self {
+            SelectionStyle::Reversed => Modifier::REVERSED,
+            SelectionStyle::Marker => Modifier::BOLD,
+        }
+    }
+}
+
 pub struct StandardTaskColors;
 impl TaskColors for StandardTaskColors {
     fn highlight_table() -> Color { tailwind::GRAY.c600 }
     fn highlight_desc() -> Color { Color::from_u32(0xfabd2f) }
     fn highlight_border() -> Color { Color::Rgb(142, 192, 124) }
+    fn today_accent() -> Color { Color::Rgb(131, 165, 152) }
+    fn active_entry_bg() -> Color { tailwind::GRAY.c700 }
+
+    fn subject_accent(subject: &str) -> Color {
+        // Gruvbox, as the rest
+        const PALETTE: [Color; 6] = [
+            Color::Rgb(131, 165, 152),
+            Color::Rgb(211, 134, 155),
+            Color::Rgb(142, 192, 124),
+            Color::Rgb(250, 189, 47),
+            Color::Rgb(184, 187, 38),
+            Color::Rgb(69, 133, 136),
+        ];
+        pick_accent(&PALETTE, subject)
+    }
 
-    fn task_color(task: &Task) -> Color {
-        match task.get_status_now() {
+    fn task_color(status: &TaskStatus) -> Color {
+        match status {
             TaskStatus::Panic => Color::Rgb(251, 73, 52),
+            TaskStatus::DueToday => Color::Rgb(254, 128, 25),
             TaskStatus::Normal => Color::White,
             TaskStatus::Zen => Color::from_u32(0x6b7280),
         }
     }
 }
+
+/// No colors, every state told apart by text styles: panicking bold, overdue bold and
+/// underlined, due today underlined, zen dim
+pub struct MonochromeTaskColors;
+impl TaskColors for MonochromeTaskColors {
+    fn highlight_table() -> Color { Color::Reset }
+    fn highlight_desc() -> Color { Color::Reset }
+    fn highlight_border() -> Color { Color::Reset }
+    fn today_accent() -> Color { Color::Reset }
+    fn active_entry_bg() -> Color { Color::Reset }
+    fn active_entry_fg() -> Color { Color::Reset }
+    fn error() -> Color { Color::Reset }
+    fn busy_day() -> Color { Color::Reset }
+    fn packed_day() -> Color { Color::Reset }
+    fn overloaded_day() -> Color { Color::Reset }
+
+    fn active_entry() -> Style { Style::default().add_modifier(Modifier::REVERSED) }
+    fn active_border() -> Style { Style::default().add_modifier(Modifier::BOLD) }
+
+    fn task_color(_status: &TaskStatus) -> Color { Color::Reset }
+    fn subject_accent(_subject: &str) -> Color { Color::Reset }
+    fn subject_color(_subject: &str, _overrides: &SubjectColors) -> Color { Color::Reset }
+    fn task_override(_task: &Task) -> Option<Color> { None }
+
+    fn task_style(_task: &Task, status: &TaskStatus, delta: Option<TimeDelta>, overdue: Modifier, _symbols: bool) -> Style {
+        let modifier = match (status, delta) {
+            (TaskStatus::Panic, Some(delta)) if delta <= -TimeDelta::days(1) => Modifier::BOLD | Modifier::UNDERLINED | overdue,
+            (TaskStatus::Panic, Some(delta)) if delta < TimeDelta::zero() => Modifier::BOLD | Modifier::UNDERLINED,
+            (TaskStatus::Panic, _) => Modifier::BOLD,
+            (TaskStatus::DueToday, _) => Modifier::UNDERLINED,
+            (TaskStatus::Normal, _) => Modifier::empty(),
+            (TaskStatus::Zen, _) => Modifier::DIM,
+        };
+        Style::default().add_modifier(modifier)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn style(hours: i64) -> Style {
+        let status = TaskStatus::Panic;
+        StandardTaskColors::task_style(&Task::default(), &status, Some(TimeDelta::hours(hours)), Modifier::SLOW_BLINK, false)
+    }
+
+    #[test]
+    fn overdue_tasks_escalate() {
+        let red = StandardTaskColors::task_color(&TaskStatus::Panic);
+
+        assert_eq!(style(5), Style::default().fg(red));
+        assert_eq!(style(-1), Style::default().fg(red).add_modifier(Modifier::BOLD));
+        assert_eq!(style(-30), Style::default().fg(red).add_modifier(Modifier::BOLD | Modifier::SLOW_BLINK));
+    }
+
+    #[test]
+    fn own_color_replaces_the_status_color_only() {
+        let task = |color: &str| Task { color: Some(color.to_string()), ..Task::default() };
+        let overdue = |task: &Task| StandardTaskColors::task_style(task, &TaskStatus::Panic, Some(TimeDelta::hours(-30)), Modifier::SLOW_BLINK, false);
+
+        let thesis = overdue(&task("#b16286"));
+        assert_eq!(thesis.fg, Some(Color::Rgb(177, 98, 134)));
+        assert_eq!(thesis.add_modifier, Modifier::BOLD | Modifier::SLOW_BLINK);
+        assert_eq!(overdue(&task("magenta")).fg, Some(Color::Magenta));
+        // Unreadable, so the status decides
+        assert_eq!(overdue(&task("thesis purple")).fg, Some(StandardTaskColors::task_color(&TaskStatus::Panic)));
+        assert_eq!(Degraded::<StandardTaskColors, Ansi16>::task_override(&task("#ff0000")), Some(Color::LightRed));
+        assert_eq!(MonochromeTaskColors::task_override(&task("#b16286")), None);
+    }
+
+    #[test]
+    fn colors_degrade_to_the_nearest_the_terminal_has() {
+        type Console = Degraded<StandardTaskColors, Ansi16>;
+        assert_eq!(Console::task_color(&TaskStatus::Panic), Color::LightRed);
+        assert_eq!(Console::task_color(&TaskStatus::DueToday), Color::Yellow);
+        assert_eq!(Console::task_color(&TaskStatus::Zen), Color::DarkGray);
+        assert_eq!(Console::task_color(&TaskStatus::Normal), Color::White);
+        assert_eq!(Console::active_entry_bg(), Color::DarkGray);
+        assert_eq!(ColorDepth::Ansi16.degrade(Color::Indexed(196)), Color::LightRed);
+
+        assert_eq!(Degraded::<StandardTaskColors, Ansi256>::task_color(&TaskStatus::Panic), Color::Indexed(203));
+        assert_eq!(ColorDepth::Ansi256.degrade(Color::Indexed(3)), Color::Indexed(3));
+        assert_eq!(ColorDepth::TrueColor.degrade(Color::Rgb(1, 2, 3)), Color::Rgb(1, 2, 3));
+    }
+
+    #[test]
+    fn subjects_keep_their_accent_unless_configured() {
+        type Theme = StandardTaskColors;
+        let overrides: SubjectColors = toml::from_str("Algorithms = \"#ff0000\"\nPhysics = \"no such color\"").unwrap();
+
+        assert_eq!(Theme::subject_accent("Databases"), Theme::subject_accent(" databases"));
+        assert_eq!(pick_accent(&SUBJECT_PALETTE, "Databases"), Color::Blue);
+        assert_eq!(Theme::subject_color("Algorithms", &overrides), Color::Rgb(255, 0, 0));
+        assert_eq!(Theme::subject_color("Physics", &overrides), Theme::subject_accent("Physics"));
+        assert_eq!(Degraded::<Theme, Ansi16>::subject_color("Algorithms", &overrides), Color::LightRed);
+    }
+
+    #[test]
+    fn monochrome_styles_differ_for_every_state() {
+        let style = |status, hours| MonochromeTaskColors::task_style(&Task::default(), &status, Some(TimeDelta::hours(hours)), Modifier::SLOW_BLINK, false);
+        let styles = [
+            style(TaskStatus::Panic, -30),
+            style(TaskStatus::Panic, -1),
+            style(TaskStatus::Panic, 5),
+            style(TaskStatus::DueToday, 5),
+            style(TaskStatus::Normal, 100),
+            style(TaskStatus::Zen, 100),
+        ];
+        for (i, x) in styles.iter().enumerate() {
+            assert_eq!((x.fg, x.bg), (None, None));
+            assert!(styles[i + 1..].iter().all(|y| y != x), "{x:?}");
+        }
+        // The selection has to show on top of any of them
+        assert!(styles.iter().all(|x| !x.add_modifier.contains(SelectionStyle::Reversed.modifier())));
+    }
+
+    #[test]
+    fn depth_follows_the_environment() {
+        assert_eq!(ColorDepth::from_env(Some("truecolor"), Some("xterm-256color")), ColorDepth::TrueColor);
+        assert_eq!(ColorDepth::from_env(None, Some("tmux-256color")), ColorDepth::Ansi256);
+        assert_eq!(ColorDepth::from_env(None, Some("linux")), ColorDepth::Ansi16);
+        assert_eq!(ColorDepth::from_env(None, None), ColorDepth::Ansi16);
+    }
+
+    #[test]
+    fn symbols_tell_the_status_apart_without_colors() {
+        let task = |complete| Task { complete, ..Task::default() };
+        let symbol = |complete, status, hours| status_symbol(&task(complete), &status, Some(TimeDelta::hours(hours)));
+
+        assert_eq!(symbol(true, TaskStatus::Zen, -5), "✓");
+        assert_eq!(symbol(false, TaskStatus::Panic, -5), "!!");
+        assert_eq!(symbol(false, TaskStatus::Panic, 5), "!");
+        assert_eq!(symbol(false, TaskStatus::DueToday, 20), "!");
+        assert_eq!(symbol(false, TaskStatus::Normal, 100), "");
+
+        let zen = StandardTaskColors::task_style(&task(true), &TaskStatus::Zen, None, Modifier::empty(), true);
+        assert!(zen.add_modifier.contains(Modifier::DIM));
+        let panic = StandardTaskColors::task_style(&task(false), &TaskStatus::Panic, Some(TimeDelta::hours(5)), Modifier::empty(), true);
+        assert!(panic.add_modifier.contains(Modifier::BOLD));
+        let today = StandardTaskColors::task_style(&task(false), &TaskStatus::DueToday, Some(TimeDelta::hours(20)), Modifier::empty(), true);
+        assert!(today.add_modifier.contains(Modifier::BOLD));
+        let plain = StandardTaskColors::task_style(&task(false), &TaskStatus::DueToday, Some(TimeDelta::hours(20)), Modifier::empty(), false);
+        assert!(!plain.add_modifier.contains(Modifier::BOLD));
+    }
+}
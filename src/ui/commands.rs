@@ -0,0 +1,164 @@
+use std::fs;
+use std::marker::PhantomData;
+use std::path::Path;
+
+use super::app::Data;
+use super::colors::TaskColors;
+use super::paste_import::PasteImportPopup;
+use super::popups::{ClosurePopup, PopupAction};
+use crate::paths::expand_tilde;
+use crate::uni::dates::{format_date, local_date, parse_duration};
+use crate::uni::export::{self, ExportFormat, ExportOptions};
+use crate::uni::report::TimeReport;
+use crate::uni::shift::{apply_shift, plan_shift};
+use crate::uni::validation::past_deadline_warning;
+
+use ratatui::crossterm::event::{KeyCode, KeyEvent};
+
+
+/// Runs one line typed into the command palette
+pub fn run<T: TaskColors>(data: &mut Data, line: &str) -> PopupAction<T> {
+    let (command, rest) = line.split_once(' ').unwrap_or((line, ""));
+    let result = match command {
+        "shift" => shift(data, rest.trim()),
+        "export" => return export(data, rest.trim(), &ExportOptions::default()),
+        "review" => return PopupAction::Review,
+        "filter" => return filter(data, rest.trim()),
+        "time" => return PopupAction::Open(Box::new(ClosurePopup::message(TimeReport::new(&data.tasks).table()))),
+        "today-done" => return PopupAction::Open(Box::new(ClosurePopup::message(done_today(data)))),
+        "file" => return PopupAction::Open(Box::new(ClosurePopup::message(data.summary()))),
+        "paste-import" => return PopupAction::Open(Box::new(PasteImportPopup::new(data.subjects()))),
+        "" => return PopupAction::Close,
+        _ => Err(format!("Unknown command \"{command}\"")),
+    };
+    match result {
+        Ok(popup) => PopupAction::Open(Box::new(popup)),
+        Err(error) => PopupAction::Open(Box::new(ClosurePopup::message(error))),
+    }
+}
+
+/// Tasks completed today by `completed_at`, the latest first
+fn done_today(data: &Data) -> String {
+    let now = data.now();
+    let mut done = data.tasks
+        .iter()
+        .filter_map(|x| Some((x.completed_at?, x)))
+        .filter(|(time, task)| task.complete && local_date(time) == local_date(&now))
+        .collect::<Vec<_>>();
+    if done.is_empty() {
+        return "Nothing completed today yet.".to_string();
+    }
+    done.sort_by_key(|(time, _)| std::cmp::Reverse(*time));
+
+    let mut lines = vec![format!("Completed today: {}", done.len()), String::new()];
+    lines.extend(done.iter().map(|(time, task)| format!("{}  {} — {}", time.format("%H:%M"), task.subject, task.name)));
+    lines.join("\n")
+}
+
+/// `filter stubs` toggles showing only the tasks still without a description,
+/// `filter clear` switches every filter off
+fn filter<T: TaskColors>(data: &mut Data, args: &str) -> PopupAction<T> {
+    match args {
+        "stubs" => {
+            data.toggle_only_stubs();
+            PopupAction::Close
+        },
+        "clear" => {
+            data.clear_filters();
+            PopupAction::Close
+        },
+        _ => PopupAction::Open(Box::new(ClosurePopup::message("Usage: filter stubs|clear".to_string()))),
+    }
+}
+
+/// `shift <subject> <duration>`, the subject may contain spaces
+fn shift<T: TaskColors>(data: &Data, args: &str) -> Result<ClosurePopup<T>, String> {
+    let usage = "Usage: shift <subject> <duration>, e.g. shift Algorithms +7d";
+    let (subject, duration) = args.rsplit_once(' ').ok_or(usage)?;
+    let delta = parse_duration(duration)?;
+    let subject = data.config.subject_abbrev.expand(subject);
+
+    let shifts = plan_shift(&data.tasks, subject, delta);
+    if shifts.is_empty() {
+        return Ok(ClosurePopup::message(format!("No open tasks with a deadline in {subject}.")));
+    }
+
+    let now = data.now();
+    let date_format = &data.config.ui.date_display_format;
+    let names = data.config.ui.date_names();
+    let zone = data.config.ui.display_zone();
+    let mut lines = vec![format!("Shift {} deadline(s) in {subject} by {duration}?", shifts.len()), String::new()];
+    for shift in &shifts {
+        let name = data.tasks.iter().find(|x| x.id == shift.id).map_or("", |x| x.name.as_str());
+        let warning = past_deadline_warning(Some(shift.before), Some(shift.after), &now, &data.config.validation);
+        lines.push(format!(
+            "{name}: {} → {}{}",
+            format_date(&shift.before, date_format, &names, &zone),
+            format_date(&shift.after, date_format, &names, &zone),
+            warning.map(|x| format!(" ({x})")).unwrap_or_default(),
+        ));
+    }
+    lines.push(String::new());
+    lines.push("(y/n)".to_string());
+
+    Ok(ClosurePopup {
+        text: lines.join("\n"),
+        payload: Box::new(move |data: &mut Data, key_event: &KeyEvent| {
+            if matches!(key_event.code, KeyCode::Char('y') | KeyCode::Enter) {
+                data.checkpoint();
+                let now = data.now();
+                apply_shift(&mut data.tasks, &shifts, now);
+            }
+            PopupAction::Close
+        }),
+        confirmation: Box::new(|key_event: &KeyEvent| {
+            [KeyCode::Enter, KeyCode::Char('y'), KeyCode::Char('n')].contains(&key_event.code)
+        }),
+        cancellation: Box::new(|key_event: &KeyEvent| {key_event.code == KeyCode::Esc}),
+        _marker: PhantomData,
+    })
+}
+
+/// Writes the visible tasks, in the order shown, to `path`. The format follows the extension.
+pub fn export<T: TaskColors>(data: &mut Data, path: &str, options: &ExportOptions) -> PopupAction<T> {
+    if path.is_empty() {
+        return PopupAction::Close;
+    }
+    let path = expand_tilde(Path::new(path), dirs::home_dir().as_deref());
+    let (content, count) = export::export(
+        data.visible().map(|(_, x)| x),
+        ExportFormat::from_path(&path),
+        options,
+        data.now(),
+        &data.config.ui.date_display_format,
+        &data.config.ui.date_names(),
+        &data.config.ui.display_zone(),
+        &data.config.subject_colors,
+    );
+
+    if !path.exists() {
+        return PopupAction::Open(Box::new(write_export(&path, &content, count)));
+    }
+    PopupAction::Open(Box::new(ClosurePopup {
+        text: format!("{} already exists. Overwrite it? (y/n)", path.display()),
+        payload: Box::new(move |_data: &mut Data, key_event: &KeyEvent| {
+            if matches!(key_event.code, KeyCode::Char('y') | KeyCode::Enter) {
+                PopupAction::Open(Box::new(write_export(&path, &content, count)))
+            } else {
+                PopupAction::Close
+            }
+        }),
+        confirmation: Box::new(|key_event: &KeyEvent| {
+            [KeyCode::Enter, KeyCode::Char('y'), KeyCode::Char('n')].contains(&key_event.code)
+        }),
+        cancellation: Box::new(|key_event: &KeyEvent| {key_event.code == KeyCode::Esc}),
+        _marker: PhantomData,
+    }))
+}
+
+fn write_export<T: TaskColors>(path: &Path, content: &str, count: usize) -> ClosurePopup<T> {
+    match fs::write(path, content) {
+        Ok(()) => ClosurePopup::message(format!("Exported {count} task(s) to {}", path.display())),
+        Err(error) => ClosurePopup::message(format!("Could not export to {}: {error}", path.display())),
+    }
+}
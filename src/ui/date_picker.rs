@@ -0,0 +1,462 @@
+use std::collections::BTreeMap;
+use std::marker::PhantomData;
+
+use super::app::Data;
+use super::colors::TaskColors;
+use super::popups::{fitted_rect, Popup, PopupAction};
+use super::textarea::TextArea;
+use crate::config::CalendarTaskFilter;
+use crate::uni::calendar::{tasks_by_day, Calendar, DaySummary};
+use crate::uni::dates::deadline_on;
+use crate::uni::format::status_span;
+use crate::uni::locale::DateNames;
+use crate::uni::policy::StatusPolicy;
+use crate::uni::task::Task;
+
+use chrono::{DateTime, Datelike, Days, FixedOffset, Months, NaiveDate, NaiveTime};
+use ratatui::prelude::*;
+use ratatui::crossterm::event::{KeyCode, KeyEvent};
+use ratatui::widgets::{Block, BorderType, Clear, Padding, Paragraph};
+
+
+/// What a key did to the picker
+pub enum Pick {
+    Done(DateTime<FixedOffset>),
+    Cancelled,
+    Pending,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Step {
+    Day,
+    Time,
+}
+
+/// Month grid to choose a day from, then the time on that day. Embedded by whatever
+/// needs a deadline, `DatePickerPopup` is the standalone version.
+pub struct DatePicker {
+    cursor: NaiveDate,
+    now: DateTime<FixedOffset>,
+    offset: FixedOffset,
+    time: TextArea,
+    /// Taken when the time is left blank
+    default_time: NaiveTime,
+    step: Step,
+    /// Tasks due on each day of the shown month, completed ones included
+    month: BTreeMap<NaiveDate, Vec<Task>>,
+    /// Tasks the heat of a day and its summary are about
+    filter: CalendarTaskFilter,
+    error: Option<String>,
+    /// Busy days are told apart by text style too, not only by color
+    symbols: bool,
+    names: DateNames,
+    policy: StatusPolicy,
+    /// Days with more open tasks due stand out as overloaded
+    day_limit: Option<usize>,
+}
+
+impl DatePicker {
+    /// Grid lines: month, weekdays and up to six weeks
+    const GRID: u16 = 8;
+
+    /// Starts on `initial` when there is one, otherwise today at `default_time`
+    pub fn new(
+        initial: Option<DateTime<FixedOffset>>,
+        now: DateTime<FixedOffset>,
+        default_time: NaiveTime,
+        tasks: &[Task],
+    ) -> Self {
+        let start = initial.unwrap_or(now);
+        let time = initial.map_or(default_time, |x| x.time()).format("%H:%M").to_string();
+        let mut picker = Self {
+            cursor: start.date_naive(),
+            now,
+            offset: *start.offset(),
+            time: TextArea::single_line(&time),
+            default_time,
+            step: Step::Day,
+            month: BTreeMap::new(),
+            filter: CalendarTaskFilter::default(),
+            error: None,
+            symbols: false,
+            names: DateNames::default(),
+            policy: StatusPolicy::default(),
+            day_limit: None,
+        };
+        picker.load_month(tasks);
+        picker
+    }
+
+    pub fn with_status_symbols(self, symbols: bool) -> Self {
+        Self { symbols, ..self }
+    }
+
+    pub fn with_names(self, names: DateNames) -> Self {
+        Self { names, ..self }
+    }
+
+    pub fn with_policy(self, policy: StatusPolicy) -> Self {
+        Self { policy, ..self }
+    }
+
+    pub fn with_day_limit(self, day_limit: Option<usize>) -> Self {
+        Self { day_limit, ..self }
+    }
+
+    pub fn with_filter(self, filter: CalendarTaskFilter) -> Self {
+        Self { filter, ..self }
+    }
+
+    fn load_month(&mut self, tasks: &[Task]) {
+        let (year, month) = (self.cursor.year(), self.cursor.month());
+        self.month = tasks_by_day(tasks, year, month, CalendarTaskFilter::Completed, &self.now).dated
+            .into_iter()
+            .map(|(day, tasks)| (day, tasks.into_iter().cloned().collect()))
+            .collect();
+    }
+
+    /// Open tasks due on `date`
+    fn due_on(&self, date: NaiveDate) -> usize {
+        self.month.get(&date).map_or(0, |x| x.iter().filter(|x| !x.complete).count())
+    }
+
+    /// Tasks due on `date` the filter lets in
+    fn shown_on(&self, date: NaiveDate) -> Vec<&Task> {
+        self.month
+            .get(&date)
+            .map(|x| x.iter().filter(|x| self.filter.includes(x, &self.now)).collect())
+            .unwrap_or_default()
+    }
+
+    fn summary(&self) -> DaySummary {
+        DaySummary::new(&self.shown_on(self.cursor), self.cursor, &self.now, &self.policy)
+    }
+
+    /// Moves the cursor, loading the tasks again when it lands in another month
+    fn go(&mut self, cursor: Option<NaiveDate>, tasks: &[Task]) {
+        let Some(cursor) = cursor else { return };
+        let month_changed = (cursor.year(), cursor.month()) != (self.cursor.year(), self.cursor.month());
+        self.cursor = cursor;
+        if month_changed {
+            self.load_month(tasks);
+        }
+    }
+
+    pub fn handle_key_event(&mut self, key_event: &KeyEvent, tasks: &[Task]) -> Pick {
+        self.error = None;
+        if self.step == Step::Time {
+            match key_event.code {
+                KeyCode::Esc => self.step = Step::Day,
+                KeyCode::Enter => {
+                    let time = match self.time.text().trim() {
+                        "" => Ok(None),
+                        text => NaiveTime::parse_from_str(text, "%H:%M").map(Some),
+                    };
+                    match time {
+                        Ok(time) => match deadline_on(self.cursor, time, self.default_time, &self.offset) {
+                            Some(time) => return Pick::Done(time),
+                            None => self.error = Some("No such moment".to_string()),
+                        },
+                        Err(_) => self.error = Some("Expected the time as HH:MM".to_string()),
+                    }
+                },
+                _ => { self.time.handle_key_event(key_event); },
+            }
+            return Pick::Pending;
+        }
+
+        let cursor = self.cursor;
+        match key_event.code {
+            KeyCode::Char('h') | KeyCode::Left => self.go(cursor.checked_sub_days(Days::new(1)), tasks),
+            KeyCode::Char('l') | KeyCode::Right => self.go(cursor.checked_add_days(Days::new(1)), tasks),
+            KeyCode::Char('k') | KeyCode::Up => self.go(cursor.checked_sub_days(Days::new(7)), tasks),
+            KeyCode::Char('j') | KeyCode::Down => self.go(cursor.checked_add_days(Days::new(7)), tasks),
+            // The day is clamped to the length of the month
+            KeyCode::Char('[') => self.go(cursor.checked_sub_months(Months::new(1)), tasks),
+            KeyCode::Char(']') => self.go(cursor.checked_add_months(Months::new(1)), tasks),
+            KeyCode::Enter => self.step = Step::Time,
+            KeyCode::Esc => return Pick::Cancelled,
+            _ => (),
+        }
+        Pick::Pending
+    }
+
+    /// Lines needed by `render`
+    pub fn height(&self) -> u16 {
+        // Grid, a blank line, the day summary, the time box and the hint
+        Self::GRID + 1 + 1 + 3 + 1
+    }
+
+    fn day_style<T: TaskColors>(&self, date: NaiveDate) -> Style {
+        // Only open tasks overload a day, whatever else the heat counts
+        let overloaded = self.day_limit.is_some_and(|x| self.due_on(date) > x);
+        let style = match (self.shown_on(date).len(), self.symbols) {
+            _ if overloaded => match self.symbols {
+                true => Style::new().fg(T::overloaded_day()).bold().underlined().italic(),
+                false => Style::new().fg(T::overloaded_day()),
+            },
+            (0, _) => Style::new(),
+            (1 | 2, false) => Style::new().fg(T::busy_day()),
+            (1 | 2, true) => Style::new().fg(T::busy_day()).bold(),
+            (_, false) => Style::new().fg(T::packed_day()),
+            (_, true) => Style::new().fg(T::packed_day()).bold().underlined(),
+        };
+        let style = if date == self.now.date_naive() { style.underlined() } else { style };
+        if date == self.cursor { style.reversed().bold() } else { style }
+    }
+
+    fn grid<T: TaskColors>(&self) -> Vec<Line<'static>> {
+        let (year, month) = (self.cursor.year(), self.cursor.month());
+        let (first_day, num_days) = Calendar::get_month_info_ym(year, month);
+
+        // Days are as wide as the widest weekday heading
+        let day_width = self.names.day_width();
+        let mut lines = vec![
+            Line::from(format!("{} {year}", self.names.month_short(month))).bold().centered(),
+            Line::from(self.names.weekday_headings().trim_end().to_string()).dim().centered(),
+        ];
+        let mut week = vec![Span::raw(" ".repeat((day_width + 1) * first_day as usize))];
+        for day in 1..=num_days {
+            let date = NaiveDate::from_ymd_opt(year, month, day).unwrap();
+            week.push(Span::styled(format!("{day:>day_width$}"), self.day_style::<T>(date)));
+            week.push(Span::raw(" "));
+            if (day + first_day) % 7 == 0 || day == num_days {
+                // Pad the last week so the columns stay put when centered
+                let width = week.iter().map(|x| x.width()).sum::<usize>();
+                week.push(Span::raw(" ".repeat((7 * (day_width + 1)).saturating_sub(width))));
+                lines.push(Line::from(std::mem::take(&mut week)).centered());
+            }
+        }
+        lines
+    }
+
+    pub fn render<T: TaskColors>(&self, frame: &mut Frame, area: Rect) {
+        let [grid, _, summary, time, hint] = Layout::vertical([
+            Constraint::Length(Self::GRID),
+            Constraint::Length(1),
+            Constraint::Length(1),
+            Constraint::Length(3),
+            Constraint::Length(1),
+        ]).areas(area);
+
+        frame.render_widget(Paragraph::new(self.grid::<T>()), grid);
+
+        let day = self.summary();
+        let mut spans = vec![Span::from(day.heading(&self.names)).fg(T::highlight_desc())];
+        for (i, (text, status)) in day.parts().into_iter().enumerate() {
+            if i > 0 {
+                spans.push(Span::from(", ").fg(T::highlight_desc()));
+            }
+            spans.push(match status {
+                Some(status) => status_span::<T>(text, &status),
+                None => Span::from(text).fg(T::highlight_desc()),
+            });
+        }
+        frame.render_widget(Paragraph::new(Line::from(spans).centered()), summary);
+
+        let focused = self.step == Step::Time;
+        let block = Block::bordered().border_type(BorderType::Rounded).title(" Time ");
+        let block = match focused {
+            true => block.border_style(T::active_border()),
+            false => block.dim(),
+        };
+        self.time.render(frame, time, block, focused);
+
+        let hint_line = match (&self.error, self.step) {
+            (Some(error), _) => Line::from(error.as_str()).fg(T::error()),
+            (None, Step::Day) => Line::from("hjkl day · [ ] month · Enter time · Esc cancel").dim(),
+            (None, Step::Time) => Line::from("Enter accept · Esc back to the day").dim(),
+        };
+        frame.render_widget(Paragraph::new(hint_line.centered()), hint);
+    }
+}
+
+/// What a `DatePickerPopup` does with the chosen deadline
+pub type OnPick<T> = Box<dyn FnMut(&mut Data, DateTime<FixedOffset>) -> PopupAction<T>>;
+
+pub struct DatePickerPopup<T: TaskColors> {
+    picker: DatePicker,
+    on_pick: OnPick<T>,
+    _marker: PhantomData<T>,
+}
+
+impl<T: TaskColors> DatePickerPopup<T> {
+    pub fn new(picker: DatePicker, on_pick: OnPick<T>) -> Self {
+        Self {
+            picker,
+            on_pick,
+            _marker: PhantomData,
+        }
+    }
+}
+
+impl<T: TaskColors> Popup<T> for DatePickerPopup<T> {
+    fn size(&self) -> (u16, u16) {
+        (50, 90)
+    }
+    fn title(&self) -> Line<'_> { Line::from(" Deadline ").fg(T::highlight_desc()) }
+    fn paragraph(&self) -> Paragraph<'_> {
+        Paragraph::default()
+    }
+    fn handle_key_event(&mut self, key_event: &KeyEvent, data: &mut Data) -> PopupAction<T> {
+        match self.picker.handle_key_event(key_event, &data.tasks) {
+            Pick::Done(time) => (self.on_pick)(data, time),
+            Pick::Cancelled => PopupAction::Close,
+            Pick::Pending => PopupAction::None,
+        }
+    }
+    fn render(&self, frame: &mut Frame, area: Rect) {
+        let area = fitted_rect(self.size().0.min(area.width), self.picker.height(), self.size().1, area);
+        let popup_block = Block::bordered()
+            .border_type(BorderType::Rounded)
+            .title_alignment(Alignment::Center)
+            .title(self.title())
+            .padding(Padding::uniform(1));
+        let inner = popup_block.inner(area);
+        frame.render_widget(Clear, area);
+        frame.render_widget(popup_block, area);
+        self.picker.render::<T>(frame, inner);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::ui::colors::{MonochromeTaskColors, StandardTaskColors};
+    use crate::uni::dates::END_OF_DAY;
+
+    use chrono::TimeDelta;
+    use ratatui::crossterm::event::KeyModifiers;
+
+    fn press(picker: &mut DatePicker, keys: &str, tasks: &[Task]) -> Pick {
+        let mut pick = Pick::Pending;
+        for c in keys.chars() {
+            let code = match c {
+                '\n' => KeyCode::Enter,
+                c => KeyCode::Char(c),
+            };
+            pick = picker.handle_key_event(&KeyEvent::new(code, KeyModifiers::NONE), tasks);
+        }
+        pick
+    }
+
+    #[test]
+    fn days_and_months_move_and_the_time_defaults_to_the_end_of_the_day() {
+        let now = DateTime::parse_from_rfc3339("2025-01-31T10:00:00+02:00").unwrap();
+        let due = |days| Task { time: Some(now + TimeDelta::days(days)), ..Task::default() };
+        let tasks = vec![due(2), due(2), due(30)];
+        let mut picker = DatePicker::new(None, now, END_OF_DAY, &tasks);
+        assert!(picker.month.is_empty());
+
+        // 31 Jan is clamped to 28 Feb, then three weeks and three days back
+        press(&mut picker, "]kkkhhh", &tasks);
+        assert_eq!(picker.cursor, NaiveDate::from_ymd_opt(2025, 2, 4).unwrap());
+        assert_eq!(picker.due_on(NaiveDate::from_ymd_opt(2025, 2, 2).unwrap()), 2);
+        assert_eq!(picker.summary().heading(&DateNames::default()), "Tue 4 Feb — nothing due");
+
+        let Pick::Done(time) = press(&mut picker, "\n\n", &tasks) else { panic!("Nothing picked") };
+        assert_eq!(time, DateTime::parse_from_rfc3339("2025-02-04T23:59:00+02:00").unwrap());
+    }
+
+    #[test]
+    fn an_existing_deadline_keeps_its_time() {
+        let now = DateTime::parse_from_rfc3339("2025-05-12T10:00:00+02:00").unwrap();
+        let mut picker = DatePicker::new(Some(now + TimeDelta::hours(8)), now, END_OF_DAY, &[]);
+
+        assert!(matches!(press(&mut picker, "l\n", &[]), Pick::Pending));
+        picker.time = TextArea::single_line("25:00");
+        assert!(matches!(press(&mut picker, "\n", &[]), Pick::Pending));
+        assert!(picker.error.is_some());
+
+        let mut picker = DatePicker::new(Some(now + TimeDelta::hours(8)), now, END_OF_DAY, &[]);
+        let Pick::Done(time) = press(&mut picker, "l\n\n", &[]) else { panic!("Nothing picked") };
+        assert_eq!(time, DateTime::parse_from_rfc3339("2025-05-13T18:00:00+02:00").unwrap());
+    }
+
+    #[test]
+    fn configured_default_time_fills_in_for_a_missing_time() {
+        let now = DateTime::parse_from_rfc3339("2025-05-12T10:00:00+02:00").unwrap();
+        let nine = NaiveTime::from_hms_opt(9, 0, 0).unwrap();
+
+        let mut picker = DatePicker::new(None, now, nine, &[]);
+        assert_eq!(picker.time.text(), "09:00");
+        let Pick::Done(time) = press(&mut picker, "l\n\n", &[]) else { panic!("Nothing picked") };
+        assert_eq!(time, DateTime::parse_from_rfc3339("2025-05-13T09:00:00+02:00").unwrap());
+
+        // A cleared time box is the default too, a typed time wins over it
+        let mut picker = DatePicker::new(None, now, nine, &[]);
+        press(&mut picker, "\n", &[]);
+        picker.time = TextArea::single_line("");
+        let Pick::Done(time) = press(&mut picker, "\n", &[]) else { panic!("Nothing picked") };
+        assert_eq!(time, DateTime::parse_from_rfc3339("2025-05-12T09:00:00+02:00").unwrap());
+
+        let mut picker = DatePicker::new(None, now, nine, &[]);
+        press(&mut picker, "\n", &[]);
+        picker.time = TextArea::single_line("14:30");
+        let Pick::Done(time) = press(&mut picker, "\n", &[]) else { panic!("Nothing picked") };
+        assert_eq!(time, DateTime::parse_from_rfc3339("2025-05-12T14:30:00+02:00").unwrap());
+    }
+
+    #[test]
+    fn grid_uses_the_names_and_their_widths() {
+        let now = DateTime::parse_from_rfc3339("2025-03-12T10:00:00+01:00").unwrap();
+        let german = DatePicker::new(None, now, END_OF_DAY, &[]).with_names(DateNames::builtin("de").unwrap());
+        let grid = german.grid::<StandardTaskColors>();
+        assert_eq!(grid[0].to_string(), "Mär 2025");
+        assert_eq!(grid[1].to_string(), "Mo Di Mi Do Fr Sa So");
+
+        let mut names = DateNames::english();
+        names.weekdays_min = names.weekdays_short.clone();
+        let wide = DatePicker::new(None, now, END_OF_DAY, &[]).with_names(names).grid::<StandardTaskColors>();
+        // 1 Mar 2025 is a Saturday, every week as wide as the headings
+        assert_eq!(wide[2].to_string(), format!("{}  1   2 ", " ".repeat(4 * 5)));
+        assert!(wide[2..].iter().all(|x| x.width() == wide[1].width() + 1));
+    }
+
+    #[test]
+    fn days_over_the_limit_stand_out() {
+        let now = DateTime::parse_from_rfc3339("2025-03-12T12:00:00+01:00").unwrap();
+        let due = |days, complete| Task { time: Some(now + TimeDelta::days(days)), complete, ..Task::default() };
+        let tasks = vec![due(1, false), due(1, false), due(1, false), due(2, false), due(2, false), due(2, true)];
+        let picker = DatePicker::new(None, now, END_OF_DAY, &tasks);
+        let fg = |picker: &DatePicker, days| picker.day_style::<StandardTaskColors>((now + TimeDelta::days(days)).date_naive()).fg;
+        assert_eq!(fg(&picker, 1), Some(StandardTaskColors::packed_day()));
+
+        // Completed tasks take no room
+        let picker = picker.with_day_limit(Some(2));
+        assert_eq!(fg(&picker, 1), Some(StandardTaskColors::overloaded_day()));
+        assert_eq!(fg(&picker, 2), Some(StandardTaskColors::busy_day()));
+
+        // The heat comes from the theme, so a colorless one leaves the days colorless
+        let day = (now + TimeDelta::days(1)).date_naive();
+        assert_eq!(picker.day_style::<MonochromeTaskColors>(day).fg, Some(Color::Reset));
+    }
+
+    #[test]
+    fn the_heat_and_the_summary_follow_the_filter() {
+        let now = DateTime::parse_from_rfc3339("2025-03-12T12:00:00+01:00").unwrap();
+        let due = |days, completed_days_ago: Option<i64>| Task {
+            time: Some(now + TimeDelta::days(days)),
+            complete: completed_days_ago.is_some(),
+            completed_at: completed_days_ago.map(|x| now - TimeDelta::days(x)),
+            ..Task::default()
+        };
+        let tasks = vec![due(1, None), due(1, Some(1)), due(1, Some(20)), due(-2, Some(0))];
+        let tomorrow = (now + TimeDelta::days(1)).date_naive();
+        let picker = DatePicker::new(None, now, END_OF_DAY, &tasks).with_day_limit(Some(1));
+        let shown = |picker: &DatePicker| (picker.shown_on(tomorrow).len(), picker.shown_on((now - TimeDelta::days(2)).date_naive()).len());
+
+        assert_eq!(shown(&picker), (1, 0));
+        let picker = picker.with_filter(CalendarTaskFilter::RecentlyCompleted);
+        assert_eq!(shown(&picker), (2, 1));
+        let mut picker = picker.with_filter(CalendarTaskFilter::Completed);
+        assert_eq!(shown(&picker), (3, 1));
+        // Three tasks that day make it packed, but only the one open task counts toward the limit
+        assert_eq!(picker.day_style::<StandardTaskColors>(tomorrow).fg, Some(StandardTaskColors::packed_day()));
+
+        press(&mut picker, "l", &tasks);
+        assert_eq!(picker.summary().total, 3);
+        assert_eq!(picker.summary().done, 2);
+    }
+}
@@ -0,0 +1,148 @@
+use std::marker::PhantomData;
+
+use super::app::Data;
+use super::colors::TaskColors;
+use super::popups::{fitted_rect, Popup, PopupAction};
+use crate::uni::dates::{format_date, DisplayZone};
+use crate::uni::locale::DateNames;
+use crate::uni::task::Task;
+use crate::uni::validation::DuplicateGroup;
+
+use ratatui::prelude::*;
+use ratatui::crossterm::event::{KeyCode, KeyEvent};
+use ratatui::widgets::{Block, BorderType, Clear, Padding, Paragraph};
+
+
+/// One task of a duplicate group and whether it survives
+struct Choice {
+    group: usize,
+    id: u64,
+    /// Id, deadline and state, taken when the popup opens
+    label: String,
+    keep: bool,
+}
+
+/// Every duplicate group with the tasks to keep ticked. Exact copies keep the oldest,
+/// groups with different deadlines keep everything until told otherwise.
+pub struct DedupPopup<T: TaskColors> {
+    headers: Vec<String>,
+    choices: Vec<Choice>,
+    selected: usize,
+    error: Option<String>,
+    _marker: PhantomData<T>,
+}
+
+impl<T: TaskColors> DedupPopup<T> {
+    pub fn new(groups: &[DuplicateGroup], tasks: &[Task], names: &DateNames, zone: &DisplayZone) -> Self {
+        let label = |id: u64| {
+            let Some(task) = tasks.iter().find(|x| x.id == id) else { return format!("#{id}") };
+            let due = task.time.map(|x| format_date(&x, "%a %d %b %H:%M", names, zone)).unwrap_or("no deadline".to_string());
+            let done = if task.complete { ", done" } else { "" };
+            format!("#{id} {due}{done}")
+        };
+        let choices = groups
+            .iter()
+            .enumerate()
+            .flat_map(|(group, x)| x.ids.iter().enumerate().map(move |(i, id)| (group, i, *id, x.possibly_intentional)))
+            .map(|(group, i, id, possibly_intentional)| Choice {
+                group,
+                id,
+                label: label(id),
+                keep: possibly_intentional || i == 0,
+            })
+            .collect();
+        Self {
+            headers: groups.iter().map(|x| x.describe(tasks)).collect(),
+            choices,
+            selected: 0,
+            error: None,
+            _marker: PhantomData,
+        }
+    }
+
+    /// Ids of the tasks not kept, unless that would empty a group
+    fn dropped(&self) -> Result<Vec<u64>, String> {
+        let emptied = (0..self.headers.len())
+            .any(|group| self.choices.iter().filter(|x| x.group == group).all(|x| !x.keep));
+        if emptied {
+            return Err("Keep at least one task of each group".to_string());
+        }
+        Ok(self.choices.iter().filter(|x| !x.keep).map(|x| x.id).collect())
+    }
+
+    fn lines(&self) -> Vec<Line<'_>> {
+        let mut lines = vec![];
+        for (i, choice) in self.choices.iter().enumerate() {
+            if i == 0 || self.choices[i - 1].group != choice.group {
+                lines.push(Line::from(self.headers[choice.group].as_str()).bold());
+            }
+            let mark = if choice.keep { "[keep]" } else { "[drop]" };
+            let line = Line::from(format!("  {mark} {}", choice.label));
+            lines.push(match (i == self.selected, choice.keep) {
+                (true, _) => line.fg(T::highlight_desc()).bold(),
+                (false, true) => line,
+                (false, false) => line.dim(),
+            });
+        }
+        lines
+    }
+}
+
+impl<T: TaskColors> Popup<T> for DedupPopup<T> {
+    fn size(&self) -> (u16, u16) {
+        (70, 90)
+    }
+    fn title(&self) -> Line<'_> { Line::from(" Duplicates ").fg(T::highlight_desc()) }
+    fn paragraph(&self) -> Paragraph<'_> {
+        Paragraph::new(self.lines())
+    }
+    fn handle_key_event(&mut self, key_event: &KeyEvent, data: &mut Data) -> PopupAction<T> {
+        self.error = None;
+        match key_event.code {
+            KeyCode::Esc | KeyCode::Char('q') => return PopupAction::Close,
+            KeyCode::Char('j') | KeyCode::Down => self.selected = (self.selected + 1).min(self.choices.len().saturating_sub(1)),
+            KeyCode::Char('k') | KeyCode::Up => self.selected = self.selected.saturating_sub(1),
+            KeyCode::Char(' ') => {
+                if let Some(choice) = self.choices.get_mut(self.selected) {
+                    choice.keep = !choice.keep;
+                }
+            },
+            KeyCode::Enter => match self.dropped() {
+                Ok(ids) => {
+                    if !ids.is_empty() {
+                        data.remove_tasks(&ids);
+                    }
+                    return PopupAction::Close;
+                },
+                Err(error) => self.error = Some(error),
+            },
+            _ => (),
+        }
+        PopupAction::None
+    }
+    fn render(&self, frame: &mut Frame, area: Rect) {
+        // The groups, a blank line and the footer
+        let lines = (self.headers.len() + self.choices.len()) as u16 + 2;
+        let area = fitted_rect(self.size().0.min(area.width), lines, self.size().1, area);
+        let popup_block = Block::bordered()
+            .border_type(BorderType::Rounded)
+            .title_alignment(Alignment::Center)
+            .title(self.title())
+            .padding(Padding::uniform(1));
+        let inner = popup_block.inner(area);
+        frame.render_widget(Clear, area);
+        frame.render_widget(popup_block, area);
+
+        let [list, _, footer] = Layout::vertical([
+            Constraint::Fill(1),
+            Constraint::Length(1),
+            Constraint::Length(1),
+        ]).areas(inner);
+        frame.render_widget(self.paragraph(), list);
+        let footer_line = match &self.error {
+            Some(error) => Line::from(error.as_str()).fg(T::error()),
+            None => Line::from("jk move · Space keep/drop · Enter remove dropped · Esc cancel").dim(),
+        };
+        frame.render_widget(Paragraph::new(footer_line), footer);
+    }
+}
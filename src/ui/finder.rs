@@ -0,0 +1,276 @@
+use std::marker::PhantomData;
+
+use super::app::Data;
+use super::colors::TaskColors;
+use super::popups::{Popup, PopupAction};
+use super::textarea::TextArea;
+use crate::uni::completion::score;
+use crate::uni::dates::DisplayZone;
+use crate::uni::detail::format_detail;
+use crate::uni::locale::DateNames;
+use crate::uni::policy::StatusPolicy;
+use crate::uni::task::Task;
+
+use chrono::{DateTime, FixedOffset};
+use ratatui::prelude::*;
+use ratatui::crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use ratatui::widgets::{Block, BorderType, Clear, Padding, Paragraph, Wrap};
+
+
+/// Lower is better: title matches before description matches, then `score`
+type Rank = (bool, u8, usize);
+
+/// A task as the finder matches it, lowercased once when the finder opens
+struct Entry {
+    task: Task,
+    title: String,
+    /// Title and description together, so a query may run from one into the other
+    text: String,
+}
+
+impl Entry {
+    fn new(task: &Task) -> Self {
+        let title = format!("{} {}", task.subject, task.name).to_lowercase();
+        let text = format!("{title} {}", task.description.to_lowercase());
+        Self { task: task.clone(), title, text }
+    }
+
+    fn rank(&self, query: &str) -> Option<Rank> {
+        match score(&self.title, query) {
+            Some((kind, skipped, _)) => Some((false, kind, skipped)),
+            None => score(&self.text, query).map(|(kind, skipped, _)| (true, kind, skipped)),
+        }
+    }
+}
+
+/// Every task filtered as the query is typed, best matches first, with the selected one
+/// shown whole next to the list. Enter selects it in the table.
+pub struct FinderPopup<T: TaskColors> {
+    entries: Vec<Entry>,
+    query: TextArea,
+    /// Query `matches` were ranked for
+    ranked: String,
+    /// Positions in `entries`, best first
+    matches: Vec<usize>,
+    selected: usize,
+    now: DateTime<FixedOffset>,
+    date_format: String,
+    names: DateNames,
+    zone: DisplayZone,
+    policy: StatusPolicy,
+    _marker: PhantomData<T>,
+}
+
+impl<T: TaskColors> FinderPopup<T> {
+    pub fn new(data: &Data) -> Self {
+        let entries = data.tasks.iter().map(Entry::new).collect::<Vec<_>>();
+        Self {
+            matches: (0..entries.len()).collect(),
+            entries,
+            query: TextArea::single_line(""),
+            ranked: String::new(),
+            selected: 0,
+            now: data.now(),
+            date_format: data.config.ui.date_display_format.clone(),
+            names: data.config.ui.date_names(),
+            zone: data.config.ui.display_zone(),
+            policy: data.status_policy(),
+            _marker: PhantomData,
+        }
+    }
+
+    /// Ranks the tasks for the current query. Typing on only narrows the matches, so
+    /// then just those are ranked again rather than every task.
+    fn refilter(&mut self) {
+        let query = self.query.text().trim().to_lowercase();
+        if query == self.ranked {
+            return;
+        }
+        let candidates = match query.starts_with(&self.ranked) {
+            true => std::mem::take(&mut self.matches),
+            false => (0..self.entries.len()).collect(),
+        };
+        let mut ranked = candidates
+            .into_iter()
+            .filter_map(|i| self.entries[i].rank(&query).map(|rank| (rank, i)))
+            .collect::<Vec<_>>();
+        // Stable, so equal matches keep the table's order
+        ranked.sort_by_key(|(rank, _)| *rank);
+        self.matches = ranked.into_iter().map(|(_, i)| i).collect();
+        self.ranked = query;
+        self.selected = 0;
+    }
+
+    fn current(&self) -> Option<&Task> {
+        self.matches.get(self.selected).map(|&i| &self.entries[i].task)
+    }
+
+    fn step(&mut self, forward: bool) {
+        let len = self.matches.len();
+        if len > 0 {
+            self.selected = match forward {
+                true => (self.selected + 1) % len,
+                false => (self.selected + len - 1) % len,
+            };
+        }
+    }
+
+    fn list(&self, height: usize) -> Vec<Line<'_>> {
+        // Only the rows on screen, however many tasks match
+        let first = (self.selected + 1).saturating_sub(height);
+        self.matches
+            .iter()
+            .enumerate()
+            .skip(first)
+            .take(height)
+            .map(|(i, &entry)| {
+                let task = &self.entries[entry].task;
+                let line = Line::from(format!(" {} — {}", task.subject, task.name));
+                match (i == self.selected, task.complete) {
+                    (true, _) => line.fg(T::highlight_desc()).bold(),
+                    (false, true) => line.dim(),
+                    (false, false) => line,
+                }
+            })
+            .collect()
+    }
+}
+
+impl<T: TaskColors> Popup<T> for FinderPopup<T> {
+    fn size(&self) -> (u16, u16) {
+        (100, 100)
+    }
+    fn title(&self) -> Line<'_> { Line::from(" Find ").fg(T::highlight_desc()) }
+    fn paragraph(&self) -> Paragraph<'_> {
+        let preview = self.current()
+            .map(|x| format_detail(x, &self.now, usize::MAX, &self.date_format, &self.names, &self.zone, &self.policy))
+            .unwrap_or_default();
+        Paragraph::new(preview).wrap(Wrap { trim: false })
+    }
+    fn handle_key_event(&mut self, key_event: &KeyEvent, data: &mut Data) -> PopupAction<T> {
+        let control = key_event.modifiers.contains(KeyModifiers::CONTROL);
+        match key_event.code {
+            KeyCode::Esc => return PopupAction::Close,
+            KeyCode::Enter => {
+                if let Some(task) = self.current() {
+                    data.select(task.id);
+                }
+                return PopupAction::Close;
+            },
+            KeyCode::Down => self.step(true),
+            KeyCode::Up => self.step(false),
+            KeyCode::Char('n' | 'j') if control => self.step(true),
+            KeyCode::Char('p' | 'k') if control => self.step(false),
+            _ => {
+                if self.query.handle_key_event(key_event) {
+                    self.refilter();
+                }
+            },
+        }
+        PopupAction::None
+    }
+    fn render(&self, frame: &mut Frame, area: Rect) {
+        let popup_block = Block::bordered()
+            .border_type(BorderType::Rounded)
+            .title_alignment(Alignment::Center)
+            .title(self.title())
+            .padding(Padding::horizontal(1));
+        let inner = popup_block.inner(area);
+        frame.render_widget(Clear, area);
+        frame.render_widget(popup_block, area);
+
+        let [left, right] = Layout::horizontal([Constraint::Percentage(45), Constraint::Percentage(55)]).areas(inner);
+        let [input, list, footer] = Layout::vertical([
+            Constraint::Length(3),
+            Constraint::Fill(1),
+            Constraint::Length(1),
+        ]).areas(left);
+
+        let input_block = Block::bordered()
+            .border_type(BorderType::Rounded)
+            .title(format!(" {}/{} ", self.matches.len(), self.entries.len()))
+            .border_style(T::active_border());
+        self.query.render(frame, input, input_block, true);
+        frame.render_widget(Paragraph::new(self.list(list.height as usize)), list);
+        let footer_line = Line::from("↑↓ move · Enter select · Esc close").dim();
+        frame.render_widget(Paragraph::new(footer_line), footer);
+
+        let preview_block = Block::bordered()
+            .border_type(BorderType::Rounded)
+            .padding(Padding::horizontal(1));
+        frame.render_widget(self.paragraph().block(preview_block), right);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::ui::colors::StandardTaskColors;
+
+    fn finder(tasks: &[(&str, &str, &str)]) -> (FinderPopup<StandardTaskColors>, Data) {
+        let tasks = tasks
+            .iter()
+            .enumerate()
+            .map(|(i, (subject, name, description))| Task {
+                id: i as u64 + 1,
+                subject: subject.to_string(),
+                name: name.to_string(),
+                description: description.to_string(),
+                ..Task::default()
+            })
+            .collect();
+        let data = Data::new(tasks);
+        (FinderPopup::new(&data), data)
+    }
+
+    fn type_text(finder: &mut FinderPopup<StandardTaskColors>, data: &mut Data, text: &str) {
+        for c in text.chars() {
+            finder.handle_key_event(&KeyEvent::new(KeyCode::Char(c), KeyModifiers::NONE), data);
+        }
+    }
+
+    fn names(finder: &FinderPopup<StandardTaskColors>) -> Vec<&str> {
+        finder.matches.iter().map(|&i| finder.entries[i].task.name.as_str()).collect()
+    }
+
+    #[test]
+    fn titles_rank_above_descriptions() {
+        let (mut finder, mut data) = finder(&[
+            ("Physics", "Lab report", "Graphs for the pendulum"),
+            ("Algo", "Graph homework", ""),
+            ("Databases", "Quiz", "Chapter on graph queries"),
+        ]);
+        assert_eq!(names(&finder).len(), 3);
+
+        type_text(&mut finder, &mut data, "graph");
+        assert_eq!(names(&finder), ["Graph homework", "Lab report", "Quiz"]);
+        // Subsequence across subject and name
+        for _ in 0.."graph".len() {
+            finder.handle_key_event(&KeyEvent::new(KeyCode::Backspace, KeyModifiers::NONE), &mut data);
+        }
+        type_text(&mut finder, &mut data, "alghw");
+        assert_eq!(names(&finder), ["Graph homework"]);
+    }
+
+    #[test]
+    fn narrowing_and_widening_give_the_same_result_as_from_scratch() {
+        let (mut finder, mut data) = finder(&[
+            ("Algo", "HW1", "sorting"),
+            ("Algo", "HW2", "graphs"),
+            ("Physics", "Lab", "optics"),
+        ]);
+        type_text(&mut finder, &mut data, "algo");
+        assert_eq!(names(&finder), ["HW1", "HW2"]);
+        type_text(&mut finder, &mut data, "s");
+        assert_eq!(names(&finder), ["HW1", "HW2"]);
+        type_text(&mut finder, &mut data, "rt");
+        assert_eq!(names(&finder), ["HW1"]);
+
+        for _ in 0.."algosrt".len() {
+            finder.handle_key_event(&KeyEvent::new(KeyCode::Backspace, KeyModifiers::NONE), &mut data);
+        }
+        type_text(&mut finder, &mut data, "opt");
+        assert_eq!(names(&finder), ["Lab"]);
+    }
+}
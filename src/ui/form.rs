@@ -0,0 +1,360 @@
+use std::io::{self, stdout};
+
+use super::colors::{MonochromeTaskColors, StandardTaskColors, TaskColors};
+use super::textarea::TextArea;
+use crate::config::{Config, Theme};
+use crate::readers::TaskReader;
+use crate::uni::dates::{parse_deadline, END_OF_DAY};
+use crate::uni::task::Task;
+
+use chrono::{DateTime, FixedOffset, Local, NaiveTime};
+use ratatui::prelude::*;
+use ratatui::crossterm::{
+    event::{self, Event, KeyCode, KeyEvent, KeyEventKind, KeyModifiers},
+    terminal::{self, EnterAlternateScreen, LeaveAlternateScreen},
+    ExecutableCommand,
+};
+use ratatui::widgets::{Block, BorderType, Clear, Paragraph};
+
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Field {
+    Name,
+    Subject,
+    Deadline,
+    Starred,
+    Complete,
+    Description,
+}
+
+impl Field {
+    const ORDER: [Field; 6] = [Field::Name, Field::Subject, Field::Deadline, Field::Starred, Field::Complete, Field::Description];
+
+    fn step(self, by: isize) -> Self {
+        let i = Self::ORDER.iter().position(|x| *x == self).unwrap() as isize;
+        Self::ORDER[(i + by).rem_euclid(Self::ORDER.len() as isize) as usize]
+    }
+}
+
+pub enum FormAction {
+    Continue,
+    Accept(Box<Task>),
+    Cancel,
+}
+
+/// Every editable field of a task on one screen
+pub struct TaskForm {
+    template: Task,
+    name: TextArea,
+    subject: TextArea,
+    deadline: TextArea,
+    starred: bool,
+    complete: bool,
+    description: TextArea,
+    focus: Field,
+    error: Option<String>,
+    /// Time of a deadline typed as a day alone
+    default_time: NaiveTime,
+}
+
+impl TaskForm {
+    const DEADLINE_FORMAT: &str = "%Y-%m-%d %H:%M";
+
+    pub fn new(template: &Task) -> Self {
+        let deadline = Self::shown_deadline(template);
+        Self {
+            template: template.clone(),
+            name: TextArea::single_line(&template.name),
+            subject: TextArea::single_line(&template.subject),
+            deadline: TextArea::single_line(&deadline),
+            starred: template.starred,
+            complete: template.complete,
+            description: TextArea::new(&template.description),
+            focus: Field::Name,
+            error: None,
+            default_time: END_OF_DAY,
+        }
+    }
+
+    /// The deadline as the field starts out with it
+    fn shown_deadline(task: &Task) -> String {
+        task.time.map(|x| x.format(Self::DEADLINE_FORMAT).to_string()).unwrap_or_default()
+    }
+
+    pub fn with_default_time(self, default_time: NaiveTime) -> Self {
+        Self { default_time, ..self }
+    }
+
+    /// The edited task, or what is wrong with the input
+    pub fn task(&self, now: &DateTime<FixedOffset>) -> Result<Task, String> {
+        let name = self.name.text().trim().to_string();
+        if name.is_empty() {
+            return Err("The name can't be empty".to_string());
+        }
+        let deadline = self.deadline.text();
+        let time = match deadline.trim() {
+            "" => None,
+            text => Some(parse_deadline(text, now, self.default_time)?),
+        };
+        // A deadline typed by hand no longer follows another task's
+        let anchor = match deadline.trim() == Self::shown_deadline(&self.template) {
+            true => self.template.anchor,
+            false => None,
+        };
+
+        let mut task = Task {
+            name,
+            subject: self.subject.text().trim().to_string(),
+            time,
+            description: self.description.text(),
+            starred: self.starred,
+            anchor,
+            ..self.template.clone()
+        };
+        task.set_complete(self.complete);
+        Ok(task)
+    }
+
+    fn focused_text(&mut self) -> Option<&mut TextArea> {
+        match self.focus {
+            Field::Name => Some(&mut self.name),
+            Field::Subject => Some(&mut self.subject),
+            Field::Deadline => Some(&mut self.deadline),
+            Field::Description => Some(&mut self.description),
+            Field::Starred | Field::Complete => None,
+        }
+    }
+
+    pub fn handle_key_event(&mut self, key_event: &KeyEvent, now: &DateTime<FixedOffset>) -> FormAction {
+        let control = key_event.modifiers.contains(KeyModifiers::CONTROL);
+        match key_event.code {
+            KeyCode::Esc => return FormAction::Cancel,
+            KeyCode::Char('s') if control => match self.task(now) {
+                Ok(task) => return FormAction::Accept(Box::new(task)),
+                Err(error) => self.error = Some(error),
+            },
+            KeyCode::Tab => self.focus = self.focus.step(1),
+            KeyCode::BackTab => self.focus = self.focus.step(-1),
+            KeyCode::Char(' ') if self.focus == Field::Starred => self.starred = !self.starred,
+            KeyCode::Char(' ') if self.focus == Field::Complete => self.complete = !self.complete,
+            _ => {
+                if let Some(text) = self.focused_text() {
+                    text.handle_key_event(key_event);
+                }
+            },
+        }
+        FormAction::Continue
+    }
+
+    fn block<T: TaskColors>(&self, title: &'static str, field: Field) -> Block<'static> {
+        let block = Block::bordered()
+            .border_type(BorderType::Rounded)
+            .title(format!(" {title} "));
+        if self.focus == field {
+            block.border_style(T::active_border())
+        } else {
+            block
+        }
+    }
+
+    fn checkbox<T: TaskColors>(&self, label: &'static str, checked: bool, field: Field) -> Span<'static> {
+        let text = format!("[{}] {label}", if checked { "x" } else { " " });
+        if self.focus == field {
+            Span::raw(text).fg(T::highlight_desc()).bold()
+        } else {
+            Span::raw(text)
+        }
+    }
+
+    pub fn render<T: TaskColors>(&self, frame: &mut Frame, area: Rect) {
+        let outer = Block::bordered()
+            .border_type(BorderType::Rounded)
+            .title_alignment(Alignment::Center)
+            .title(Line::from(" Edit task ").fg(T::highlight_desc()));
+        let inner = outer.inner(area);
+        frame.render_widget(Clear, area);
+        frame.render_widget(outer, area);
+
+        let [name, subject, deadline, flags, description, footer] = Layout::vertical([
+            Constraint::Length(3),
+            Constraint::Length(3),
+            Constraint::Length(3),
+            Constraint::Length(1),
+            Constraint::Fill(1),
+            Constraint::Length(1),
+        ]).areas(inner);
+
+        self.name.render(frame, name, self.block::<T>("Name", Field::Name), self.focus == Field::Name);
+        self.subject.render(frame, subject, self.block::<T>("Subject", Field::Subject), self.focus == Field::Subject);
+        self.deadline.render(
+            frame,
+            deadline,
+            self.block::<T>("Deadline: 2025-05-12 18:00, tomorrow, +3d", Field::Deadline),
+            self.focus == Field::Deadline,
+        );
+        let flags_line = Line::from(vec![
+            Span::raw(" "),
+            self.checkbox::<T>("Starred", self.starred, Field::Starred),
+            Span::raw("   "),
+            self.checkbox::<T>("Complete", self.complete, Field::Complete),
+        ]);
+        frame.render_widget(Paragraph::new(flags_line), flags);
+        self.description.render(
+            frame,
+            description,
+            self.block::<T>("Description", Field::Description),
+            self.focus == Field::Description,
+        );
+
+        let footer_line = match &self.error {
+            Some(error) => Line::from(format!(" {error}")).fg(T::error()),
+            None => Line::from(" Tab next field · Space toggle · Ctrl-s save · Esc cancel").dim(),
+        };
+        frame.render_widget(Paragraph::new(footer_line), footer);
+    }
+}
+
+/// Edits the task in a form drawn in the terminal, no external editor involved
+pub struct FormReader;
+
+impl FormReader {
+    fn run(template: &Task, config: &Config) -> io::Result<Option<Task>> {
+        let mut terminal = Terminal::new(CrosstermBackend::new(stdout()))?;
+        terminal.clear()?;
+        let mut form = TaskForm::new(template).with_default_time(config.deadline_time());
+        loop {
+            terminal.draw(|frame| match config.ui.theme {
+                Theme::Standard => form.render::<StandardTaskColors>(frame, frame.area()),
+                Theme::Monochrome => form.render::<MonochromeTaskColors>(frame, frame.area()),
+            })?;
+            if let Event::Key(key_event) = event::read()? {
+                if key_event.kind != KeyEventKind::Press {
+                    continue;
+                }
+                match form.handle_key_event(&key_event, &Local::now().fixed_offset()) {
+                    FormAction::Continue => (),
+                    FormAction::Accept(task) => return Ok(Some(*task)),
+                    FormAction::Cancel => return Ok(None),
+                }
+            }
+        }
+    }
+}
+
+impl TaskReader for FormReader {
+    fn read(&self, template: &Task, config: &Config) -> Result<Task, ()> {
+        // The CLI comes here straight from a cooked terminal, the UI with raw mode still on
+        let raw = terminal::is_raw_mode_enabled().map_err(|_| ())?;
+        if !raw {
+            terminal::enable_raw_mode().map_err(|_| ())?;
+        }
+        stdout().execute(EnterAlternateScreen).map_err(|_| ())?;
+        let result = Self::run(template, config);
+        stdout().execute(LeaveAlternateScreen).map_err(|_| ())?;
+        if !raw {
+            terminal::disable_raw_mode().map_err(|_| ())?;
+        }
+        result.ok().flatten().ok_or(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use chrono::TimeDelta;
+
+    use crate::uni::anchors::Anchor;
+
+    fn now() -> DateTime<FixedOffset> {
+        DateTime::parse_from_rfc3339("2025-03-01T12:00:00+00:00").unwrap()
+    }
+
+    fn press(form: &mut TaskForm, code: KeyCode, modifiers: KeyModifiers) -> FormAction {
+        form.handle_key_event(&KeyEvent::new(code, modifiers), &now())
+    }
+
+    fn type_text(form: &mut TaskForm, text: &str) {
+        for c in text.chars() {
+            press(form, KeyCode::Char(c), KeyModifiers::NONE);
+        }
+    }
+
+    #[test]
+    fn fields_are_edited_in_turn() {
+        let template = Task {
+            id: 7,
+            name: "HW".to_string(),
+            subject: "Algo".to_string(),
+            description: String::new(),
+            time: Some(now() + TimeDelta::days(1)),
+            ..Task::default()
+        };
+        let mut form = TaskForm::new(&template);
+
+        type_text(&mut form, "1");
+        press(&mut form, KeyCode::Tab, KeyModifiers::NONE);
+        press(&mut form, KeyCode::Tab, KeyModifiers::NONE);
+        for _ in 0..16 {
+            press(&mut form, KeyCode::Backspace, KeyModifiers::NONE);
+        }
+        type_text(&mut form, "tomorrow 09:00");
+        press(&mut form, KeyCode::Tab, KeyModifiers::NONE);
+        type_text(&mut form, " ");
+        press(&mut form, KeyCode::BackTab, KeyModifiers::NONE);
+        press(&mut form, KeyCode::BackTab, KeyModifiers::NONE);
+        press(&mut form, KeyCode::BackTab, KeyModifiers::NONE);
+        press(&mut form, KeyCode::BackTab, KeyModifiers::NONE);
+        type_text(&mut form, "First line");
+        press(&mut form, KeyCode::Enter, KeyModifiers::NONE);
+        type_text(&mut form, "second");
+
+        let FormAction::Accept(task) = press(&mut form, KeyCode::Char('s'), KeyModifiers::CONTROL) else {
+            panic!("The form should accept");
+        };
+        assert_eq!(task.id, 7);
+        assert_eq!(task.name, "HW1");
+        assert_eq!(task.time, Some(DateTime::parse_from_rfc3339("2025-03-02T09:00:00+00:00").unwrap()));
+        assert!(task.starred);
+        assert_eq!(task.description, "First line\nsecond");
+    }
+
+    #[test]
+    fn a_day_alone_takes_the_configured_time() {
+        let template = Task { name: "HW".to_string(), ..Task::default() };
+        let nine = NaiveTime::from_hms_opt(9, 0, 0).unwrap();
+        let mut form = TaskForm::new(&template).with_default_time(nine);
+        form.deadline = TextArea::single_line("2025-03-04");
+        assert_eq!(form.task(&now()).unwrap().time, Some(DateTime::parse_from_rfc3339("2025-03-04T09:00:00+00:00").unwrap()));
+        form.deadline = TextArea::single_line("2025-03-04 18:15");
+        assert_eq!(form.task(&now()).unwrap().time, Some(DateTime::parse_from_rfc3339("2025-03-04T18:15:00+00:00").unwrap()));
+    }
+
+    #[test]
+    fn bad_input_keeps_the_form_open() {
+        let mut form = TaskForm::new(&Task { name: String::new(), ..Task::default() });
+        assert!(matches!(press(&mut form, KeyCode::Char('s'), KeyModifiers::CONTROL), FormAction::Continue));
+        assert_eq!(form.error.as_deref(), Some("The name can't be empty"));
+        assert!(matches!(press(&mut form, KeyCode::Esc, KeyModifiers::NONE), FormAction::Cancel));
+    }
+
+    #[test]
+    fn a_typed_deadline_replaces_the_anchor() {
+        let template = Task {
+            name: "Draft".to_string(),
+            time: Some(now() + TimeDelta::days(3)),
+            anchor: Some(Anchor { task: 1, offset: TimeDelta::days(-7) }),
+            ..Task::default()
+        };
+        let mut form = TaskForm::new(&template);
+        assert_eq!(form.task(&now()).unwrap().anchor, template.anchor);
+
+        form.deadline = TextArea::single_line("2025-03-10 12:00");
+        let task = form.task(&now()).unwrap();
+        assert_eq!(task.anchor, None);
+        assert_eq!(task.time, Some(DateTime::parse_from_rfc3339("2025-03-10T12:00:00+00:00").unwrap()));
+
+        form.deadline = TextArea::single_line("");
+        assert_eq!(form.task(&now()).unwrap(), Task { time: None, anchor: None, ..template });
+    }
+}
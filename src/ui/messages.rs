@@ -0,0 +1,75 @@
+use std::collections::VecDeque;
+
+use chrono::{DateTime, FixedOffset, TimeDelta};
+
+use crate::constants;
+
+
+/// Short-lived line in the status bar, for news that does not merit a popup
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StatusMessage {
+    pub text: String,
+    pub error: bool,
+}
+
+/// Messages shown one after another in the status bar, each for a few seconds
+/// from the moment it comes up
+#[derive(Debug, Default)]
+pub struct StatusMessages {
+    queue: VecDeque<StatusMessage>,
+    /// When the front message came up
+    shown_since: Option<DateTime<FixedOffset>>,
+}
+
+impl StatusMessages {
+    pub fn info(&mut self, text: impl Into<String>) {
+        self.queue.push_back(StatusMessage { text: text.into(), error: false });
+    }
+
+    pub fn error(&mut self, text: impl Into<String>) {
+        self.queue.push_back(StatusMessage { text: text.into(), error: true });
+    }
+
+    /// Drops the front message once its time is up, so the next one comes up
+    pub fn expire(&mut self, now: DateTime<FixedOffset>) {
+        let shown_for = TimeDelta::seconds(constants::STATUS_MESSAGE_SECS);
+        if self.shown_since.is_some_and(|x| now - x >= shown_for) {
+            self.queue.pop_front();
+            self.shown_since = None;
+        }
+        if !self.queue.is_empty() && self.shown_since.is_none() {
+            self.shown_since = Some(now);
+        }
+    }
+
+    pub fn current(&self) -> Option<&StatusMessage> {
+        self.queue.front()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn messages_take_turns() {
+        let start = DateTime::parse_from_rfc3339("2025-05-12T09:00:00+02:00").unwrap();
+        let at = |seconds| start + TimeDelta::seconds(seconds);
+        let mut messages = StatusMessages::default();
+        messages.info("Saved");
+        messages.error("Could not copy");
+
+        messages.expire(at(0));
+        assert_eq!(messages.current().map(|x| x.text.as_str()), Some("Saved"));
+        messages.expire(at(constants::STATUS_MESSAGE_SECS - 1));
+        assert_eq!(messages.current().map(|x| x.text.as_str()), Some("Saved"));
+
+        // The second one gets its full time from when it came up
+        messages.expire(at(constants::STATUS_MESSAGE_SECS));
+        assert!(messages.current().unwrap().error);
+        messages.expire(at(2 * constants::STATUS_MESSAGE_SECS - 1));
+        assert!(messages.current().is_some());
+        messages.expire(at(2 * constants::STATUS_MESSAGE_SECS));
+        assert_eq!(messages.current(), None);
+    }
+}
@@ -1,4 +1,19 @@
 pub mod app;
+pub mod badges;
 pub mod panes;
 pub mod popups;
 pub mod colors;
+pub mod commands;
+pub mod form;
+pub mod quick_add;
+pub mod paste_import;
+pub mod date_picker;
+pub mod dedup;
+pub mod finder;
+pub mod messages;
+pub mod review;
+pub mod timer;
+pub mod textarea;
+
+#[cfg(test)]
+mod tests;
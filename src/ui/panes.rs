@@ -1,16 +1,42 @@
 use std::{borrow::BorrowMut, cell::RefCell};
-use std::marker::PhantomData;
 
-use super::{colors::TaskColors, popups::ClosurePopup};
-use super::app::Data;
-use super::popups::{Popup, PopupAction};
+use super::colors::{status_symbol, TaskColors};
+use super::app::{Data, SortColumn};
+use super::badges::TitleBadges;
+use super::popups::{delete_task, DetailPopup, Popup};
+use crate::uni::dates::{format_ago, format_date, format_spent};
+use crate::constants;
+use crate::uni::policy::StatusPolicy;
 use crate::uni::task::Task;
 
-use unicode_width::UnicodeWidthStr;
+use chrono::{DateTime, FixedOffset, TimeDelta};
+use unicode_width::{UnicodeWidthChar, UnicodeWidthStr};
 use ratatui::{
-    crossterm::event::{self, Event, KeyCode, KeyEvent, KeyEventKind, KeyModifiers}, layout::{Alignment, Constraint, Layout, Rect}, style::{palette::tailwind, Modifier, Style, Stylize}, text::{Line, Span, Text, ToText}, widgets::{block::{Position, Title}, Block, BorderType, Paragraph, Row, Table, TableState, Wrap}, Frame
+    crossterm::event::{self, Event, KeyCode, KeyEvent, KeyEventKind, KeyModifiers, MouseButton, MouseEvent, MouseEventKind}, layout::{Alignment, Constraint, Layout, Position, Rect}, style::{Modifier, Style, Stylize}, text::{Line, Span, Text, ToText}, widgets::{block::Title, Block, BorderType, Cell, Paragraph, Row, Scrollbar, ScrollbarOrientation, ScrollbarState, Table, TableState}, Frame
 };
 
+/// Cuts `text` down to `max_width` columns, marking the cut with an ellipsis
+pub fn truncate(text: &str, max_width: usize) -> String {
+    if text.width() <= max_width {
+        return text.to_string();
+    }
+
+    let mut result = String::new();
+    let mut width = 0;
+    for c in text.chars() {
+        let char_width = c.width().unwrap_or(0);
+        if width + char_width + 1 > max_width {
+            break;
+        }
+        width += char_width;
+        result.push(c);
+    }
+    if max_width > 0 {
+        result.push('…');
+    }
+    result
+}
+
 pub trait Pane<T: TaskColors> {
     fn create_block(&self, title: &str, active: bool) -> Block {
         let title = Title::from(format!(" {title} ").bold());
@@ -19,7 +45,7 @@ pub trait Pane<T: TaskColors> {
             .title(title.alignment(Alignment::Center));
 
         if active {
-            block.border_style(Style::default().fg(T::highlight_border()))
+            block.border_style(T::active_border())
         } else {
             block
         }
@@ -27,14 +53,26 @@ pub trait Pane<T: TaskColors> {
 
     fn render(&mut self, frame: &mut Frame, chunk: Rect, data: &Data, active: bool);
     fn handle_key_event(&mut self, _key_event: KeyEvent, _data: &mut Data) -> Option<Box<dyn Popup<T>>> {None}
+    fn handle_mouse_event(&mut self, _mouse_event: MouseEvent, _data: &mut Data) {}
 
     fn enter(&mut self) {}
     fn leave(&mut self) {}
 }
 
+/// Where on screen `H`, `M` and `L` go
+enum Fraction {
+    Top,
+    Middle,
+    Bottom,
+}
+
 pub struct TasksPane {
     table_state: RefCell<TableState>,
     show_numbers: bool,
+    /// Where each sortable header was last drawn, for mouse clicks
+    header_cells: Vec<(Rect, SortColumn)>,
+    /// Table rows the last frame had room for, what a page is
+    viewport: usize,
 }
 
 impl TasksPane {
@@ -44,50 +82,169 @@ impl TasksPane {
         Self {
             table_state: RefCell::new(TableState::default().with_selected(0)),
             show_numbers: false,
+            header_cells: vec![],
+            viewport: 0,
+        }
+    }
+
+    const CONFLICT_MARK: &str = "⚠";
+    const TODAY_BADGE: &str = "•";
+    /// After the name of a task whose description is still to be written
+    const STUB_MARK: &str = " ✎";
+    /// Room for the widest status symbol, "!!"
+    const SYMBOL_WIDTH: usize = 2;
+    /// Heads the tasks without a deadline, wherever they are
+    const UNDATED_HEADER: &str = "— no deadline —";
+
+    /// Header labels, with the column each one sorts by
+    fn columns(&self, data: &Data) -> Vec<(&'static str, Option<SortColumn>)> {
+        let mut columns = vec![(" ", Some(SortColumn::Starred))];
+        if !data.conflicts.is_empty() { columns.push((" ", None)) }
+        if self.show_numbers { columns.push(("No", None)) }
+        columns.extend([
+            ("Subject", Some(SortColumn::Subject)),
+            ("Name", Some(SortColumn::Name)),
+            ("Time Left", Some(SortColumn::Deadline)),
+        ]);
+        columns
+    }
+
+    /// The label with the sort direction when the list is ordered by its column
+    fn header_label(label: &str, column: Option<SortColumn>, data: &Data) -> String {
+        let arrow = data.sort_mode.arrow();
+        match (column == Some(data.sort_mode.column), label.trim().is_empty()) {
+            (false, _) => label.to_string(),
+            (true, true) => arrow.to_string(),
+            (true, false) => format!("{label} {arrow}"),
         }
     }
 
-    fn make_header(&self) -> Row {
-        let mut headers = vec![];
-        headers.push(" ");
-        if self.show_numbers { headers.push("No") }
-        headers.extend(["Subject", "Name", "Time Left"]);
-        headers.into_iter().collect::<Row>()
+    fn make_header(&self, data: &Data) -> Row {
+        self.columns(data)
+            .into_iter()
+            .map(|(label, column)| Self::header_label(label, column, data))
+            .collect::<Row>()
     }
 
-    fn make_row<T: TaskColors>(&self, i: usize, task: &Task, highlighted: bool) -> Row {
+    /// Screen areas of the sortable headers, laid out the way `Table` lays out the body
+    fn locate_header(&self, data: &Data, area: Rect) -> Vec<(Rect, SortColumn)> {
+        let inner = Rect::new(area.x + 1, area.y + 1, area.width.saturating_sub(2), 1);
+        let selection = if data.index.is_some() { Self::BAR.width() as u16 } else { 0 };
+        let [_, columns_area] = Layout::horizontal([Constraint::Length(selection), Constraint::Fill(0)]).areas(inner);
+        let cells = Layout::horizontal(self.make_constraints(data)).spacing(1).split(columns_area);
+        cells
+            .iter()
+            .zip(self.columns(data))
+            .filter_map(|(cell, (_, column))| column.map(|x| (*cell, x)))
+            .collect()
+    }
+
+    fn make_row<T: TaskColors>(&self, i: usize, task: &Task, data: &Data, now: &DateTime<FixedOffset>, conflict: Option<bool>, highlighted: bool) -> Row {
+        let overdue = data.config.ui.overdue_emphasis.modifier();
+        let (status, delta) = (data.status(task, now), task.get_delta(now));
+        let style = T::task_style(task, &status, delta, overdue, data.config.ui.status_symbols);
+
         let mut cells = vec![];
-        cells.push(if task.starred {"*".to_string()} else {" ".to_string()});
-        if self.show_numbers { cells.push(i.to_string()) }
-        cells.extend([task.subject().to_string(), task.name().to_string(), task.delta()]);
-        let row = cells.into_iter().collect::<Row>().fg(T::task_color(task));
+        // Status symbol, star and today badge share the first column
+        let badge = match task.is_due_today(now) {
+            true => Span::from(Self::TODAY_BADGE).fg(T::today_accent()),
+            false => Span::from(" "),
+        };
+        let mut first = vec![Span::from(if task.starred {"*"} else {" "}), badge];
+        if data.config.ui.status_symbols {
+            let symbol = status_symbol(task, &status, delta);
+            first.insert(0, Span::from(format!("{symbol:<width$}", width = Self::SYMBOL_WIDTH)));
+        }
+        cells.push(Cell::from(Line::from(first)));
+        if let Some(conflict) = conflict {
+            cells.push(Cell::from(if conflict {Self::CONFLICT_MARK} else {" "}));
+        }
+        if self.show_numbers { cells.push(Cell::from(i.to_string())) }
+        let subject = data.config.subject_abbrev.abbreviate(task.subject());
+        let mut name = vec![Span::from(task.name().to_string())];
+        if task.is_stub() {
+            name.push(Span::from(Self::STUB_MARK).dim());
+        }
+        cells.extend([
+            // The status colors the rest of the row
+            Cell::from(Span::from(subject.to_string()).fg(T::subject_color(task.subject(), &data.config.subject_colors))),
+            Cell::from(Line::from(name)),
+            Cell::from(task.delta_at(now)),
+        ]);
+        let row = Row::new(cells).style(style);
 
         if highlighted {
-            row.add_modifier(Modifier::REVERSED)
+            row.add_modifier(data.config.ui.selection.modifier())
         } else {
             row
         }
     }
 
     fn make_rows<T: TaskColors>(&self, data: &Data) -> Vec<Row> {
-        data
-            .iter()
+        let show_conflicts = !data.conflicts.is_empty();
+        // One instant for the whole frame, so rows never disagree about what today is
+        let now = data.now();
+        let mut rows: Vec<Row> = data
+            .visible()
             .enumerate()
-            .map(|(i, task)| self.make_row::<T>(i, task, i == data.index.unwrap()))
-            .collect()
+            .map(|(i, (underlying, task))| {
+                let conflict = show_conflicts.then(|| data.in_conflict(underlying));
+                self.make_row::<T>(i, task, data, &now, conflict, data.index == Some(i))
+            })
+            .collect();
+        if let Some(at) = Self::undated_start(data) {
+            rows.insert(at, self.undated_header(data));
+        }
+        rows
+    }
+
+    /// Position among the visible tasks of the first incomplete one without a deadline.
+    /// Sorting keeps them together, completed ones going last, so the header goes right above it.
+    fn undated_start(data: &Data) -> Option<usize> {
+        data.iter().position(|x| x.time.is_none() && !x.complete)
+    }
+
+    /// Row the given visible task is drawn on, one further down below the undated header
+    fn row_of(data: &Data, index: usize) -> usize {
+        match Self::undated_start(data) {
+            Some(at) if index >= at => index + 1,
+            _ => index,
+        }
+    }
+
+    fn undated_header(&self, data: &Data) -> Row<'static> {
+        let mut cells = vec![Cell::from(""); self.columns(data).len()];
+        // Under Name, the widest column
+        let name = cells.len() - 2;
+        cells[name] = Cell::from(Self::UNDATED_HEADER);
+        Row::new(cells).dim()
     }
 
     fn make_constraints(&self, data: &Data) -> Vec<Constraint> {
         let index_len = data.len().to_string().len();
+        let now = data.now();
         let (subject_len, name_len, delta_len) = data
             .iter()
-            .map(|task| (task.subject(), task.name(), task.delta()))
-            .map(|(s, n, d)| (s.width(), n.width(), d.as_str().width()))
+            .map(|task| {
+                let mark = if task.is_stub() { Self::STUB_MARK.width() } else { 0 };
+                // The bucket header says what the ∞ would, so undated tasks don't widen Time Left
+                let delta = if task.time.is_some() { task.delta_at(&now) } else { String::new() };
+                (data.config.subject_abbrev.abbreviate(task.subject()), task.name().width() + mark, delta)
+            })
+            // Columns, not bytes
+            .map(|(s, n, d)| (s.width(), n, d.width()))
             .fold((usize::MIN, usize::MIN, usize::MIN), |(ms, mn, md), (s, n, d)| (ms.max(s), mn.max(n), md.max(d)));
 
         let mut constraints = vec![];
-        constraints.push(Constraint::Length(1));
+        let symbol_len = if data.config.ui.status_symbols { Self::SYMBOL_WIDTH } else { 0 };
+        constraints.push(Constraint::Length((symbol_len + 1 + Self::TODAY_BADGE.width()) as u16));
+        if !data.conflicts.is_empty() { constraints.push(Constraint::Length(Self::CONFLICT_MARK.width() as u16)) }
         if self.show_numbers { constraints.push(Constraint::Max(index_len as u16 + 1))}
+        // The direction arrow must not be cut off a narrow subject column
+        let subject_len = match data.sort_mode.column {
+            SortColumn::Subject => subject_len.max(Self::header_label("Subject", Some(SortColumn::Subject), data).width()),
+            _ => subject_len,
+        };
         constraints.push(Constraint::Length(subject_len as u16 + 1));
         constraints.push(Constraint::Min(name_len as u16 + 1));
         constraints.push(Constraint::Min(delta_len as u16 + 1));
@@ -104,7 +261,7 @@ impl TasksPane {
             self.make_rows::<T>(data),
             self.make_constraints(data)
         )
-        .header(self.make_header())
+        .header(self.make_header(data))
         // .row_highlight_style(selected_row_style)
         // .column_highlight_style(selected_col_style)
         // .highlight_style(highlight_style)
@@ -117,20 +274,20 @@ impl TasksPane {
         t
     }
 
+    // Moves go by task rather than by table row, which steps over the undated header
+
     fn first(&self, data: &mut Data) {
-        self.table_state.borrow_mut().select_first();
-        data.index = self.table_state.borrow().selected();
+        data.index = Some(0);
     }
 
     fn last(&self, data: &mut Data) {
-        self.table_state.borrow_mut().select_last();
-        data.index = self.table_state.borrow().selected().map(|x| x.min(data.len() - 1));
+        data.index = Some(data.len().saturating_sub(1));
     }
 
     fn next(&self, data: &mut Data) {
-        let i = match self.table_state.borrow().selected() {
+        let i = match data.index {
             Some(i) => {
-                if i >= data.len() - 1 {
+                if i >= data.len().saturating_sub(1) {
                     0
                 } else {
                     i + 1
@@ -138,63 +295,160 @@ impl TasksPane {
             }
             None => 0,
         };
-        self.table_state.borrow_mut().select(Some(i));
         data.index = Some(i);
     }
 
     fn previous(&self, data: &mut Data) {
-        let i = match self.table_state.borrow().selected() {
+        let i = match data.index {
             Some(i) => {
                 if i == 0 {
-                    data.len() - 1
+                    data.len().saturating_sub(1)
                 } else {
                     i - 1
                 }
             }
             None => 0,
         };
-        self.table_state.borrow_mut().select(Some(i));
         data.index = Some(i);
     }
 
-    fn toggle_numbers(&mut self) { self.show_numbers = !self.show_numbers; }
+    /// `by` tasks down, or up when negative. Like `j` and `k`, a move past either end from
+    /// the end itself comes around to the other one, a move from elsewhere stops at the end.
+    fn jump(&self, data: &mut Data, by: isize) {
+        let last = data.len().saturating_sub(1);
+        let i = match data.index {
+            Some(i) if by > 0 && i >= last => 0,
+            Some(0) if by < 0 => last,
+            Some(i) => i.saturating_add_signed(by).min(last),
+            None => 0,
+        };
+        data.index = Some(i);
+    }
 
-    fn remove<T: TaskColors>(&mut self, data: &mut Data) -> Option<Box<dyn Popup<T>>> {
-        if let Some(task) = data.index.and_then(|x| data.tasks.get(x)) {
-            return if task.is_default() {
-                data.tasks.remove(data.index.unwrap());
-                None
-            }
-            else {
-                let closure_popup = ClosurePopup {
-                    text: format!("Would you like to remove task \"{}: {}\"", &task.subject, &task.name),
-                    payload: Box::new(|data, _key_event| {
-                        data.tasks.remove(data.index.unwrap());
-                        PopupAction::Close
-                    }),
-                    confirmation: Box::new(|key_event: &KeyEvent| {key_event.code == KeyCode::Char('d')}),
-                    cancellation: Box::new(|key_event: &KeyEvent| {key_event.code == KeyCode::Esc}),
-                    _marker: PhantomData,
-                };
-                Some(Box::new(closure_popup))
-            }
+    /// Half a page, at least a task
+    fn half_page(&self) -> isize {
+        (self.viewport / 2).max(1) as isize
+    }
+
+    fn page(&self) -> isize {
+        self.viewport.max(1) as isize
+    }
+
+    /// Task drawn on the given table row, the one below for the undated header
+    fn task_at_row(data: &Data, row: usize) -> usize {
+        let index = match Self::undated_start(data) {
+            Some(at) if row > at => row - 1,
+            _ => row,
+        };
+        index.min(data.len().saturating_sub(1))
+    }
+
+    /// Table rows, the undated header included
+    fn row_count(data: &Data) -> usize {
+        data.len() + usize::from(Self::undated_start(data).is_some())
+    }
+
+    /// Scrolls just enough for the selected task to be on screen, never past a full last page.
+    /// The table would follow the selection on its own only once drawn, which `H`, `M` and `L`
+    /// in between would read wrong.
+    fn reveal(&self, data: &Data) {
+        let viewport = self.viewport.max(1);
+        let mut state = self.table_state.borrow_mut();
+        let mut offset = state.offset().min(Self::row_count(data).saturating_sub(viewport));
+        // The first task brings the undated header above it along
+        let row = data.index.map(|x| if x == 0 { 0 } else { Self::row_of(data, x) });
+        match row {
+            Some(row) if row < offset => offset = row,
+            Some(row) if row >= offset + viewport => offset = row + 1 - viewport,
+            _ => {}
         }
-        None
+        *state.offset_mut() = offset;
+    }
+
+    /// Along the right border next to the rows, when they don't all fit
+    fn render_scrollbar(&self, frame: &mut Frame, chunk: Rect, data: &Data) {
+        let rows = Self::row_count(data);
+        if rows <= self.viewport {
+            return;
+        }
+        // Below the top border and the header
+        let area = Rect { y: chunk.y + 2, height: self.viewport as u16, ..chunk };
+        let mut state = ScrollbarState::new(rows - self.viewport + 1)
+            .position(self.table_state.borrow().offset())
+            .viewport_content_length(self.viewport);
+        // The track is the border itself
+        let scrollbar = Scrollbar::new(ScrollbarOrientation::VerticalRight)
+            .track_symbol(Some("│"))
+            .begin_symbol(None)
+            .end_symbol(None);
+        frame.render_stateful_widget(scrollbar, area, &mut state);
     }
+
+    /// Selects the task at the top, middle or bottom of what is on screen, `at` of the way down
+    fn on_screen(&self, data: &mut Data, at: Fraction) {
+        let rows = Self::row_count(data);
+        let top = self.table_state.borrow().offset();
+        let bottom = (top + self.viewport.max(1)).min(rows).saturating_sub(1).max(top);
+        let row = match at {
+            Fraction::Top => top,
+            Fraction::Middle => (top + bottom) / 2,
+            Fraction::Bottom => bottom,
+        };
+        data.index = Some(Self::task_at_row(data, row));
+    }
+
+    fn title(&self, data: &Data, width: u16) -> String {
+        let next = data.config.ui.show_next_deadline.then(|| data.next_deadline()).flatten().map(|task| {
+            format!(
+                "— next: {} {} in {}",
+                data.config.subject_abbrev.abbreviate(task.subject()),
+                task.name(),
+                task.delta_short_at(&data.now()).unwrap_or_default()
+            )
+        });
+        // Corners and the padding spaces around the title
+        TitleBadges::new("Tasks")
+            .with_badges(data.title_badges())
+            .with_note(next)
+            .render(width.saturating_sub(4) as usize)
+    }
+
+    fn toggle_numbers(&mut self) { self.show_numbers = !self.show_numbers; }
+
+}
+
+/// Deletes the selected task, see `delete_task`
+fn remove_selected<T: TaskColors>(data: &mut Data) -> Option<Box<dyn Popup<T>>> {
+    let id = data.selected()?.id;
+    delete_task(data, id)
 }
 
 
 impl<T: TaskColors> Pane<T> for TasksPane {
     fn render(&mut self, frame: &mut Frame, chunk: Rect, data: &Data, active: bool) {
-        self.table_state.borrow_mut().select(data.index);
+        // Borders and the header row
+        self.viewport = chunk.height.saturating_sub(3).into();
+        self.table_state.borrow_mut().select(data.index.map(|x| Self::row_of(data, x)));
+        self.reveal(data);
+        let title = self.title(data, chunk.width);
         let table = self
             .table::<T>(data)
-            .block(<TasksPane as Pane<T>>::create_block(self, "Tasks", active));
+            .block(<TasksPane as Pane<T>>::create_block(self, &title, active));
         frame.render_stateful_widget(table, chunk, &mut *self.table_state.borrow_mut());
+        self.render_scrollbar(frame, chunk, data);
+        self.header_cells = self.locate_header(data, chunk);
     }
 
     fn handle_key_event(&mut self, key_event: KeyEvent, data: &mut Data) -> Option<Box<dyn Popup<T>>> {
-        match key_event.code {
+        let control = key_event.modifiers.contains(KeyModifiers::CONTROL);
+        let popup: Option<Box<dyn Popup<T>>> = match key_event.code {
+            KeyCode::Char('d') if control => {self.jump(data, self.half_page()); None}
+            KeyCode::Char('u') if control => {self.jump(data, -self.half_page()); None}
+            KeyCode::Char('f') if control => {self.jump(data, self.page()); None}
+            KeyCode::Char('b') if control => {self.jump(data, -self.page()); None}
+            KeyCode::Char('H') => {self.on_screen(data, Fraction::Top); None}
+            KeyCode::Char('M') => {self.on_screen(data, Fraction::Middle); None}
+            KeyCode::Char('L') => {self.on_screen(data, Fraction::Bottom); None}
             KeyCode::Char('G') => {self.last(data); None}
             KeyCode::Char('g') => {self.first(data); None}
             KeyCode::Char('j') => {self.next(data); None}
@@ -202,8 +456,38 @@ impl<T: TaskColors> Pane<T> for TasksPane {
             KeyCode::Char('i') => {self.toggle_numbers(); None}
             KeyCode::Char('c') => {data.toggle_task_status(); None}
             KeyCode::Char('s') => {data.toggle_task_star(); None}
-            KeyCode::Char('d') => {self.remove(data)}
+            KeyCode::Char('d') => {remove_selected(data)}
+            KeyCode::Char('1') => {data.cycle_sort(SortColumn::Starred); None}
+            KeyCode::Char('2') => {data.cycle_sort(SortColumn::Subject); None}
+            KeyCode::Char('3') => {data.cycle_sort(SortColumn::Name); None}
+            KeyCode::Char('4') => {data.cycle_sort(SortColumn::Deadline); None}
+            KeyCode::Char('5') => {data.cycle_sort(SortColumn::Updated); None}
+            KeyCode::Enter | KeyCode::Char('K') => {
+                let task = data.selected()?;
+                Some(Box::new(DetailPopup::new(
+                    task.clone(),
+                    data.now(),
+                    data.config.ui.date_display_format.clone(),
+                    data.config.ui.date_names(),
+                    data.config.ui.display_zone(),
+                    data.status_policy(),
+                )))
+            }
             _ => None
+        };
+        self.reveal(data);
+        popup
+    }
+
+    fn handle_mouse_event(&mut self, mouse_event: MouseEvent, data: &mut Data) {
+        if mouse_event.kind != MouseEventKind::Down(MouseButton::Left) {
+            return;
+        }
+        let clicked = self.header_cells
+            .iter()
+            .find(|(cell, _)| cell.contains(Position::new(mouse_event.column, mouse_event.row)));
+        if let Some((_, column)) = clicked {
+            data.cycle_sort(*column);
         }
     }
 }
@@ -216,49 +500,151 @@ enum DescriptionEntry {
     Description,
 }
 
+/// The description as last drawn, so an unchanged one is not wrapped again every frame
+struct WrappedDescription {
+    id: u64,
+    width: u16,
+    /// What was wrapped, already cut to `DESCRIPTION_RENDER_LIMIT`
+    text: String,
+    lines: Vec<String>,
+}
+
 #[derive(Default)]
 pub struct DescriptionPane {
-    current_entry: Option<DescriptionEntry>
+    current_entry: Option<DescriptionEntry>,
+    wrapped: Option<WrappedDescription>,
+}
+
+/// At most `limit` bytes of `text`, cut on a character boundary
+fn capped(text: &str, limit: usize) -> &str {
+    let mut end = limit.min(text.len());
+    while !text.is_char_boundary(end) {
+        end -= 1;
+    }
+    &text[..end]
+}
+
+/// Lines of `text` no wider than `width`, broken between words where they allow
+fn wrap_lines(text: &str, width: usize) -> Vec<String> {
+    let width = width.max(1);
+    let mut lines = Vec::new();
+    for line in text.lines() {
+        let mut current = String::new();
+        let mut current_width = 0;
+        for word in line.split_inclusive(' ') {
+            if current_width + word.trim_end_matches(' ').width() > width && current_width > 0 {
+                current.truncate(current.trim_end_matches(' ').len());
+                lines.push(std::mem::take(&mut current));
+                current_width = 0;
+            }
+            for c in word.chars() {
+                let c_width = c.width().unwrap_or(0);
+                if current_width + c_width > width {
+                    // Spaces at the end of a full line are dropped, a long word goes on
+                    if c == ' ' {
+                        continue;
+                    }
+                    lines.push(std::mem::take(&mut current));
+                    current_width = 0;
+                }
+                current.push(c);
+                current_width += c_width;
+            }
+        }
+        lines.push(current);
+    }
+    lines
 }
 
 impl DescriptionPane {
+    /// Shown when there are no tasks at all
+    const EMPTY_HINTS: [&str; 4] = ["No tasks yet", "", "p  add a task", "?  show all keys"];
+    /// Deadlines listed under "Next up"
+    const UPCOMING: usize = 3;
+
     fn render_header<T: TaskColors>(&self, frame: &mut Frame, chunk: Rect, task: &Task, active: bool) {
         let mut header = Line::raw(format!("{}: {}", task.subject(), task.name()))
             .alignment(Alignment::Center)
             .add_modifier(Modifier::BOLD)
-            .fg(T::highlight_desc());
+            .fg(T::task_override(task).unwrap_or_else(T::highlight_desc));
 
-        if active { header = header.bg(tailwind::GRAY.c700) };
+        if active { header = header.patch_style(Style { fg: None, ..T::active_entry() }) };
 
         frame.render_widget(header, chunk);
     }
 
-    fn render_deadline<T: TaskColors>(&self, frame: &mut Frame, chunk: Rect, task: &Task, active: bool) {
+    fn render_deadline<T: TaskColors>(&self, frame: &mut Frame, chunk: Rect, task: &Task, data: &Data, active: bool) {
+        let ui = &data.config.ui;
         let date_str = task.
             time
-            .map(|x| x.to_rfc2822())
+            .map(|x| format_date(&x, &ui.date_display_format, &ui.date_names(), &ui.display_zone()))
             .unwrap_or("None".to_string());
 
-        let mut date_span = Span::raw(format!("Deadline: {date_str}"));
+        let mut lines = vec![Line::raw(format!("Deadline: {date_str}"))];
+        // A relative deadline says what it follows, and why it has no date when broken
+        if let Some(anchor) = &task.anchor {
+            let rule = anchor.describe(&data.tasks);
+            let rule = match data.broken_anchors.iter().find(|x| x.id == task.id) {
+                Some(broken) => format!("Follows: {rule}, but {}", broken.problem.describe()),
+                None => format!("Follows: {rule}"),
+            };
+            lines.push(Line::raw(rule).dim());
+        }
 
-        if active { date_span = date_span.bg(tailwind::GRAY.c700) };
+        let mut text = Text::from(lines);
 
-        frame.render_widget(date_span, chunk);
+        if active { text = text.patch_style(T::active_entry()) };
+
+        frame.render_widget(text, chunk);
+    }
+
+    fn render_updated(&self, frame: &mut Frame, chunk: Rect, task: &Task, now: &DateTime<FixedOffset>, policy: &StatusPolicy) {
+        let updated = task.last_updated().map(|x| format_ago(&x, now)).unwrap_or("unknown".to_string());
+        // Says as much as the age, and why the task is urgent
+        let mut text = match policy.reason(task, now) {
+            Some(reason) => format!("Escalated: {reason}"),
+            None => format!("Updated: {updated}"),
+        };
+        if task.time_spent > TimeDelta::zero() {
+            text += &format!(" · Time spent: {}", format_spent(task.time_spent));
+        }
+        frame.render_widget(Span::raw(text).dim(), chunk);
     }
 
-    fn render_description<T: TaskColors>(&self, frame: &mut Frame, chunk: Rect, task: &Task, active: bool) {
+    fn render_description<T: TaskColors>(&mut self, frame: &mut Frame, chunk: Rect, task: &Task, active: bool) {
         let inner_chunks = Layout::vertical([
             Constraint::Length(1), Constraint::Fill(1)
         ]).split(chunk);
         let label_chunk = inner_chunks[0];
-        let description_chunk = Layout::horizontal([Constraint::Fill(1)]).horizontal_margin(1).split(inner_chunks[1])[0];
+        let mut description_chunk = Layout::horizontal([Constraint::Fill(1)]).horizontal_margin(1).split(inner_chunks[1])[0];
+
+        let text = capped(&task.description, constants::DESCRIPTION_RENDER_LIMIT);
+        let truncated = text.len() < task.description.len();
+        if truncated {
+            let notice_chunk = Rect { y: description_chunk.bottom().saturating_sub(1), height: description_chunk.height.min(1), ..description_chunk };
+            description_chunk.height = description_chunk.height.saturating_sub(1);
+            frame.render_widget(Span::raw("… truncated, press e to view full").dim(), notice_chunk);
+        }
+
+        let fresh = self.wrapped.as_ref().is_some_and(|x| x.id == task.id && x.width == description_chunk.width && x.text == text);
+        if !fresh {
+            self.wrapped = Some(WrappedDescription {
+                id: task.id,
+                width: description_chunk.width,
+                text: text.to_string(),
+                lines: wrap_lines(text, description_chunk.width.into()),
+            });
+        }
+        let lines = &self.wrapped.as_ref().unwrap().lines;
+        // Only what fits is handed to the paragraph
+        let visible = lines.iter().take(description_chunk.height.into()).map(|x| Line::raw(x.as_str()));
 
         let mut span_label = Span::raw("Description");
-        let mut span_description = Paragraph::new(Text::from(task.description.as_str())).wrap( Wrap { trim: false} );
+        let mut span_description = Paragraph::new(Text::from_iter(visible));
 
         if active {
-            span_label = span_label.bg(tailwind::GRAY.c700);
-            span_description = span_description.bg(tailwind::GRAY.c700);
+            span_label = span_label.patch_style(T::active_entry());
+            span_description = span_description.style(T::active_entry());
         }
 
         frame.render_widget(span_label, label_chunk);
@@ -284,6 +670,42 @@ impl DescriptionPane {
             }
         });
     }
+
+    /// In place of a task when none is selected: the starred ones and what is due next,
+    /// or how to start when there are no tasks at all
+    fn render_empty(frame: &mut Frame, chunk: Rect, data: &Data) {
+        if data.tasks.is_empty() {
+            let lines: Vec<Line> = Self::EMPTY_HINTS.iter().map(|x| Line::raw(*x)).collect();
+            frame.render_widget(Paragraph::new(lines).dim(), chunk);
+            return;
+        }
+
+        let now = data.now();
+        let width = chunk.width as usize;
+        let line = |task: &Task| {
+            let subject = data.config.subject_abbrev.abbreviate(task.subject());
+            Line::raw(truncate(&format!("{subject}  {}  {}", task.name(), task.delta_at(&now)), width))
+        };
+
+        // Soonest first, the undated ones last
+        let mut pinned: Vec<&Task> = data.query().starred(true).complete(false).iter().collect();
+        pinned.sort_by_key(|x| (x.time.is_none(), x.time));
+        let mut upcoming: Vec<&Task> = data.query().complete(false).due_after(now).iter().collect();
+        upcoming.sort_by_key(|x| x.time);
+        upcoming.truncate(Self::UPCOMING);
+
+        let mut lines = vec![Line::raw("Pinned").bold()];
+        match pinned.is_empty() {
+            true => lines.push(Line::raw("Nothing starred, s stars a task").dim()),
+            false => lines.extend(pinned.into_iter().map(line)),
+        }
+        lines.extend([Line::raw(""), Line::raw("Next up").bold()]);
+        match upcoming.is_empty() {
+            true => lines.push(Line::raw("Nothing due").dim()),
+            false => lines.extend(upcoming.into_iter().map(line)),
+        }
+        frame.render_widget(Paragraph::new(lines), chunk);
+    }
 }
 
 impl<T: TaskColors> Pane<T> for DescriptionPane {
@@ -297,35 +719,38 @@ impl<T: TaskColors> Pane<T> for DescriptionPane {
             Constraint::Fill(1)
         ]).margin(2).split(chunk)[0];
 
+        let task = match data.selected() {
+            Some(task) => task,
+            None => return Self::render_empty(frame, inner, data),
+        };
+
         let inner_chunks = Layout::vertical([
             Constraint::Length(2),  // Name
-            Constraint::Length(1),  // Deadline
+            Constraint::Length(if task.anchor.is_some() { 2 } else { 1 }),  // Deadline, and what it follows
+            Constraint::Length(1),  // Updated
             Constraint::Fill(1)     // Description
         ]).split(inner);
 
-        let task = match data.index.and_then(|x| data.get(x)) {
-            Some(task) => task,
-            None => return,
+        // Focused before an entry was picked, the one `enter` would pick shows
+        let entry = self.current_entry.clone().or(active.then(DescriptionEntry::default));
+        let (header_active, deadline_active, description_active) = match entry {
+            Some(DescriptionEntry::Header) => (true, false, false),
+            Some(DescriptionEntry::Deadline) => (false, true, false),
+            Some(DescriptionEntry::Description) => (false, false, true),
+            None => (false, false, false),
         };
 
-        let (header_active, deadline_active, description_active) = match self
-            .current_entry.clone()
-            {
-                Some(DescriptionEntry::Header) => (true, false, false),
-                Some(DescriptionEntry::Deadline) => (false, true, false),
-                Some(DescriptionEntry::Description) => (false, false, true),
-                _ => (false, false, false),
-            };
-
         self.render_header::<T>(frame, inner_chunks[0], task, header_active);
-        self.render_deadline::<T>(frame, inner_chunks[1], task, deadline_active);
-        self.render_description::<T>(frame, inner_chunks[2], task, description_active);
+        self.render_deadline::<T>(frame, inner_chunks[1], task, data, deadline_active);
+        self.render_updated(frame, inner_chunks[2], task, &data.now(), &data.status_policy());
+        self.render_description::<T>(frame, inner_chunks[3], task, description_active);
     }
 
-    fn handle_key_event(&mut self, key_event: KeyEvent, _data: &mut Data) -> Option<Box<dyn Popup<T>>> {
+    fn handle_key_event(&mut self, key_event: KeyEvent, data: &mut Data) -> Option<Box<dyn Popup<T>>> {
         match key_event.code {
             KeyCode::Char('j') => self.next(),
             KeyCode::Char('k') => self.previous(),
+            KeyCode::Char('d') => return remove_selected(data),
             _ => ()
         };
         None
@@ -339,3 +764,85 @@ impl<T: TaskColors> Pane<T> for DescriptionPane {
         self.current_entry = None;
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::config::Config;
+    use crate::ui::colors::StandardTaskColors;
+
+    use ratatui::{backend::TestBackend, style::Color, Terminal};
+
+    #[test]
+    fn focused_description_pane_highlights_an_entry() {
+        let mut data = Data::new(vec![Task { subject: "Algo".to_string(), name: "HW1".to_string(), ..Task::default() }]);
+        data.index = Some(0);
+        let mut terminal = Terminal::new(TestBackend::new(40, 10)).unwrap();
+        let header_bg = |pane: &mut DescriptionPane, terminal: &mut Terminal<TestBackend>, active| {
+            terminal.draw(|frame| {
+                <DescriptionPane as Pane<StandardTaskColors>>::render(pane, frame, frame.area(), &data, active)
+            }).unwrap();
+            terminal.backend().buffer()[(20, 2)].bg
+        };
+
+        let mut pane = DescriptionPane::default();
+        assert_eq!(header_bg(&mut pane, &mut terminal, false), Color::Reset);
+        assert_eq!(header_bg(&mut pane, &mut terminal, true), StandardTaskColors::active_entry_bg());
+        <DescriptionPane as Pane<StandardTaskColors>>::enter(&mut pane);
+        pane.next();
+        assert_eq!(header_bg(&mut pane, &mut terminal, true), Color::Reset);
+    }
+
+    #[test]
+    fn descriptions_wrap_between_words() {
+        assert_eq!(wrap_lines("one two three\n\nfour", 8), ["one two", "three", "", "four"]);
+        assert_eq!(wrap_lines("abcdefghij", 4), ["abcd", "efgh", "ij"]);
+        assert_eq!(capped("añb", 2), "a");
+    }
+
+    #[test]
+    fn huge_description_renders_capped_and_wrapped_once() {
+        let description = "A line of a log pasted by accident.\n".repeat(64 * 1024);
+        let task = Task { id: 1, subject: "Algo".to_string(), name: "HW1".to_string(), description, ..Task::default() };
+        let mut data = Data::new(vec![task.clone()]);
+        data.index = Some(0);
+        let mut terminal = Terminal::new(TestBackend::new(60, 20)).unwrap();
+        let mut pane = DescriptionPane::default();
+        let mut draw = |pane: &mut DescriptionPane| {
+            terminal.draw(|frame| {
+                <DescriptionPane as Pane<StandardTaskColors>>::render(pane, frame, frame.area(), &data, false)
+            }).unwrap();
+            format!("{:?}", terminal.backend().buffer())
+        };
+
+        let screen = draw(&mut pane);
+        assert!(screen.contains("truncated, press e to view full"));
+        assert!(screen.contains("A line of a log pasted by accident."));
+        let wrapped = pane.wrapped.as_ref().unwrap();
+        assert_eq!(wrapped.text.len(), constants::DESCRIPTION_RENDER_LIMIT);
+        assert!(wrapped.lines.len() <= constants::DESCRIPTION_RENDER_LIMIT / "A line".len());
+
+        // The next frames draw the lines already wrapped, a fresh wrap would be a new vector
+        let lines = wrapped.lines.as_ptr();
+        draw(&mut pane);
+        assert_eq!(pane.wrapped.as_ref().unwrap().lines.as_ptr(), lines);
+        assert_eq!(data.tasks[0].description, task.description);
+    }
+
+    #[test]
+    fn long_subjects_are_abbreviated_in_the_table() {
+        let config = toml::from_str::<Config>(r#"
+            [subject_abbrev]
+            "Introduction to Distributed Systems" = "IDS"
+        "#).unwrap();
+        let task = |subject: &str| Task { subject: subject.to_string(), name: "HW".to_string(), time: None, ..Task::default() };
+        let data = Data::with_config(vec![task("Introduction to Distributed Systems"), task("Logic")], config);
+
+        let constraints = TasksPane::new().make_constraints(&data);
+        // Star with the today badge, subject, name, time left; "Logic" is the longest subject shown
+        assert_eq!(constraints[1], Constraint::Length("Logic".len() as u16 + 1));
+        assert_eq!(data.config.subject_abbrev.expand("ids"), "Introduction to Distributed Systems");
+        assert_eq!(data.config.subject_abbrev.expand("Logic"), "Logic");
+    }
+}
@@ -0,0 +1,251 @@
+use std::marker::PhantomData;
+
+use super::app::Data;
+use super::colors::TaskColors;
+use super::popups::{fitted_rect, Popup, PopupAction};
+use super::textarea::TextArea;
+use crate::uni::completion::complete;
+use crate::uni::dates::format_date;
+use crate::uni::paste::{parse_lines, PastedTask};
+use crate::uni::task::Task;
+
+use ratatui::prelude::*;
+use ratatui::crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use ratatui::widgets::{Block, BorderType, Clear, Padding, Paragraph};
+
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Step {
+    Text,
+    Preview,
+    Subject,
+}
+
+/// A parsed line and whether it becomes a task
+struct Choice {
+    task: PastedTask,
+    /// Name and deadline, taken when the text is parsed
+    label: String,
+    keep: bool,
+}
+
+/// A list pasted or typed in, a task per line: the lines are read with their deadlines,
+/// previewed to tick off the ones that are not tasks, and added under one subject.
+pub struct PasteImportPopup<T: TaskColors> {
+    text: TextArea,
+    choices: Vec<Choice>,
+    selected: usize,
+    subjects: Vec<String>,
+    subject: TextArea,
+    step: Step,
+    error: Option<String>,
+    _marker: PhantomData<T>,
+}
+
+impl<T: TaskColors> PasteImportPopup<T> {
+    pub fn new(subjects: Vec<String>) -> Self {
+        Self {
+            text: TextArea::new(""),
+            choices: vec![],
+            selected: 0,
+            subjects,
+            subject: TextArea::single_line(""),
+            step: Step::Text,
+            error: None,
+            _marker: PhantomData,
+        }
+    }
+
+    /// Reads the text into choices, all of them kept
+    fn parse(&mut self, data: &Data) -> Result<(), String> {
+        let names = data.config.ui.date_names();
+        let zone = data.config.ui.display_zone();
+        let tasks = parse_lines(&self.text.text(), &data.now(), data.config.deadline_time(), &names);
+        if tasks.is_empty() {
+            return Err("No tasks found, one per line".to_string());
+        }
+        self.choices = tasks
+            .into_iter()
+            .map(|task| {
+                let due = task.time.map(|x| format_date(&x, "%a %d %b %H:%M", &names, &zone)).unwrap_or("no deadline".to_string());
+                let label = format!("{} · {due}", task.name);
+                Choice { task, label, keep: true }
+            })
+            .collect();
+        self.selected = 0;
+        Ok(())
+    }
+
+    fn kept(&self) -> usize {
+        self.choices.iter().filter(|x| x.keep).count()
+    }
+
+    /// The existing subject the typed one completes to
+    fn suggestion(&self) -> Option<&str> {
+        let query = self.subject.text();
+        match query.trim().is_empty() {
+            true => None,
+            false => complete(&self.subjects, &query).into_iter().next(),
+        }
+    }
+
+    /// Adds the kept tasks under the subject, or says why not
+    fn submit(&self, data: &mut Data) -> Result<(), String> {
+        let subject = data.config.subject_abbrev.expand(self.subject.text().trim()).to_string();
+        if subject.is_empty() {
+            return Err("The subject can't be empty".to_string());
+        }
+        let tasks: Vec<Task> = self.choices
+            .iter()
+            .filter(|x| x.keep)
+            .map(|x| Task {
+                subject: subject.clone(),
+                name: x.task.name.clone(),
+                time: x.task.time,
+                ..Task::new_by(&data.clock)
+            })
+            .collect();
+        let count = tasks.len();
+        data.add_all(tasks);
+        data.messages.info(format!("Added {count} task(s) to {subject}"));
+        Ok(())
+    }
+
+    fn handle_text_key(&mut self, key_event: &KeyEvent, data: &Data) -> PopupAction<T> {
+        match key_event.code {
+            KeyCode::Esc => return PopupAction::Close,
+            KeyCode::Char('s') if key_event.modifiers.contains(KeyModifiers::CONTROL) => match self.parse(data) {
+                Ok(()) => self.step = Step::Preview,
+                Err(error) => self.error = Some(error),
+            },
+            _ => {
+                self.text.handle_key_event(key_event);
+            },
+        }
+        PopupAction::None
+    }
+
+    fn handle_preview_key(&mut self, key_event: &KeyEvent) {
+        match key_event.code {
+            KeyCode::Esc => self.step = Step::Text,
+            KeyCode::Char('j') | KeyCode::Down => self.selected = (self.selected + 1).min(self.choices.len().saturating_sub(1)),
+            KeyCode::Char('k') | KeyCode::Up => self.selected = self.selected.saturating_sub(1),
+            KeyCode::Char(' ') => {
+                if let Some(choice) = self.choices.get_mut(self.selected) {
+                    choice.keep = !choice.keep;
+                }
+            },
+            KeyCode::Enter if self.kept() == 0 => self.error = Some("Nothing to add, Space ticks a line".to_string()),
+            KeyCode::Enter => self.step = Step::Subject,
+            _ => (),
+        }
+    }
+
+    fn handle_subject_key(&mut self, key_event: &KeyEvent, data: &mut Data) -> PopupAction<T> {
+        match key_event.code {
+            KeyCode::Esc => self.step = Step::Preview,
+            KeyCode::Tab => {
+                if let Some(subject) = self.suggestion() {
+                    self.subject = TextArea::single_line(subject);
+                }
+            },
+            KeyCode::Enter => match self.submit(data) {
+                Ok(()) => return PopupAction::Close,
+                Err(error) => self.error = Some(error),
+            },
+            _ => {
+                self.subject.handle_key_event(key_event);
+            },
+        }
+        PopupAction::None
+    }
+
+    fn choice_lines(&self) -> Vec<Line<'_>> {
+        self.choices
+            .iter()
+            .enumerate()
+            .map(|(i, choice)| {
+                let mark = if choice.keep { "[x]" } else { "[ ]" };
+                let line = Line::from(format!("{mark} {}", choice.label));
+                match (i == self.selected, choice.keep) {
+                    (true, _) => line.fg(T::highlight_desc()).bold(),
+                    (false, true) => line,
+                    (false, false) => line.dim(),
+                }
+            })
+            .collect()
+    }
+
+    fn footer(&self) -> Line<'_> {
+        if let Some(error) = &self.error {
+            return Line::from(error.as_str()).fg(T::error());
+        }
+        match self.step {
+            Step::Text => Line::from("A task per line, deadlines anywhere · Ctrl-s preview · Esc cancel").dim(),
+            Step::Preview => Line::from("jk move · Space add/skip · Enter choose the subject · Esc back").dim(),
+            Step::Subject => Line::from("Tab complete · Enter add · Esc back").dim(),
+        }
+    }
+}
+
+impl<T: TaskColors> Popup<T> for PasteImportPopup<T> {
+    fn size(&self) -> (u16, u16) {
+        (70, 90)
+    }
+    fn title(&self) -> Line<'_> { Line::from(" Paste tasks ").fg(T::highlight_desc()) }
+    fn paragraph(&self) -> Paragraph<'_> {
+        Paragraph::new(self.choice_lines())
+    }
+    fn handle_key_event(&mut self, key_event: &KeyEvent, data: &mut Data) -> PopupAction<T> {
+        self.error = None;
+        match self.step {
+            Step::Text => self.handle_text_key(key_event, data),
+            Step::Preview => {
+                self.handle_preview_key(key_event);
+                PopupAction::None
+            },
+            Step::Subject => self.handle_subject_key(key_event, data),
+        }
+    }
+    fn render(&self, frame: &mut Frame, area: Rect) {
+        let area = fitted_rect(self.size().0.min(area.width), area.height, self.size().1, area);
+        let popup_block = Block::bordered()
+            .border_type(BorderType::Rounded)
+            .title_alignment(Alignment::Center)
+            .title(self.title())
+            .padding(Padding::uniform(1));
+        let inner = popup_block.inner(area);
+        frame.render_widget(Clear, area);
+        frame.render_widget(popup_block, area);
+
+        let subject_height = if self.step == Step::Subject { 4 } else { 0 };
+        let [body, subject, _, footer] = Layout::vertical([
+            Constraint::Fill(1),
+            Constraint::Length(subject_height),
+            Constraint::Length(1),
+            Constraint::Length(1),
+        ]).areas(inner);
+
+        match self.step {
+            Step::Text => {
+                let block = Block::bordered().border_type(BorderType::Rounded).border_style(T::active_border());
+                self.text.render(frame, body, block, true);
+            },
+            Step::Preview | Step::Subject => {
+                // Scrolled so the selected line stays in view
+                let scroll = self.selected.saturating_sub(body.height.saturating_sub(1) as usize) as u16;
+                frame.render_widget(self.paragraph().scroll((scroll, 0)), body);
+            },
+        }
+        if self.step == Step::Subject {
+            let [field, hint] = Layout::vertical([Constraint::Length(3), Constraint::Length(1)]).areas(subject);
+            let title = format!(" Subject of the {} task(s) ", self.kept());
+            let block = Block::bordered().border_type(BorderType::Rounded).border_style(T::active_border()).title(title);
+            self.subject.render(frame, field, block, true);
+            if let Some(suggestion) = self.suggestion() {
+                frame.render_widget(Paragraph::new(Line::from(format!(" › {suggestion}")).dim()), hint);
+            }
+        }
+        frame.render_widget(Paragraph::new(self.footer()), footer);
+    }
+}
@@ -1,12 +1,21 @@
+use std::cell::Cell;
 use std::marker::PhantomData;
 
-use super::{app::Data, colors::TaskColors};
+use super::{app::Data, colors::TaskColors, commands};
+use crate::uni::export::ExportOptions;
+use crate::uni::detail::{format_detail, wrap};
+use crate::uni::dates::DisplayZone;
+use crate::uni::locale::DateNames;
+use crate::uni::policy::StatusPolicy;
+use crate::uni::task::Task;
+
+use chrono::{DateTime, FixedOffset};
 
 use ratatui::prelude::*;
-use ratatui::crossterm::event::KeyEvent;
-use ratatui::widgets::{Block, BorderType, Padding, Paragraph, Wrap};
+use ratatui::crossterm::event::{KeyCode, KeyEvent};
+use ratatui::widgets::{Block, BorderType, Clear, Padding, Paragraph, Wrap};
 
-fn centered_rect(max_x: u16, percent_y: u16, r: Rect) -> Rect {
+pub fn centered_rect(max_x: u16, percent_y: u16, r: Rect) -> Rect {
     // Cut the given rectangle into three vertical pieces
     let popup_layout = Layout::default()
         .direction(Direction::Vertical)
@@ -28,17 +37,37 @@ fn centered_rect(max_x: u16, percent_y: u16, r: Rect) -> Rect {
         .split(popup_layout[1])[1] // Return the middle chunk
 }
 
-pub enum PopupAction {
+/// Centered area `width` wide and tall enough for `lines` of text plus borders and padding,
+/// never taller than `max_percent` of `area`
+pub fn fitted_rect(width: u16, lines: u16, max_percent: u16, area: Rect) -> Rect {
+    let height = (lines + 4).min(area.height * max_percent / 100);
+    let [_, area, _] = Layout::vertical([Constraint::Fill(1), Constraint::Length(height), Constraint::Fill(1)]).areas(area);
+    let [_, area, _] = Layout::horizontal([Constraint::Fill(1), Constraint::Length(width), Constraint::Fill(1)]).areas(area);
+    area
+}
+
+pub enum PopupAction<T: TaskColors> {
     Close,
+    /// Leave the app, dropping unsaved changes
     Exit,
+    /// Leave the app once the tasks are saved, which only the app does
+    SaveAndExit,
     None,
+    /// Close this one and show another in its place
+    Open(Box<dyn Popup<T>>),
+    /// Close this one and start the weekly review
+    Review,
+    /// Read the tasks file again, dropping unsaved changes
+    Reload,
+    /// Save over whatever the tasks file holds now, then leave if `exit`
+    Overwrite { exit: bool },
 }
 
 pub trait Popup<T: TaskColors> {
     fn size(&self) -> (u16, u16);
     fn title(&self) -> Line { Line::from(" Warning ").fg(T::highlight_desc()) }
     fn paragraph(&self) -> Paragraph;
-    fn handle_key_event(&mut self, key_event: &KeyEvent, data: &mut Data) -> PopupAction;
+    fn handle_key_event(&mut self, key_event: &KeyEvent, data: &mut Data) -> PopupAction<T>;
     fn render(&self, frame: &mut Frame, area: Rect) {
         let popup_block = Block::bordered()
             .border_type(BorderType::Rounded)
@@ -50,28 +79,69 @@ pub trait Popup<T: TaskColors> {
             .block(popup_block);
 
         let area = centered_rect(self.size().0, self.size().1, area);
+        frame.render_widget(Clear, area);
         frame.render_widget(paragraph, area);
     }
 }
 
 pub struct ClosurePopup<T: TaskColors> {
     pub text: String,
-    pub payload: Box<dyn FnMut(&mut Data, &KeyEvent) -> PopupAction>,
+    pub payload: Box<dyn FnMut(&mut Data, &KeyEvent) -> PopupAction<T>>,
     pub confirmation: Box<dyn Fn(&KeyEvent) -> bool>,
     pub cancellation: Box<dyn Fn(&KeyEvent) -> bool>,
     pub _marker: PhantomData<T>
 }
 
+impl<T: TaskColors> ClosurePopup<T> {
+    /// Informational popup dismissed with Enter, Esc or q
+    pub fn message(text: String) -> Self {
+        Self {
+            text,
+            payload: Box::new(|_data, _key_event| PopupAction::Close),
+            confirmation: Box::new(|key_event: &KeyEvent| {
+                [KeyCode::Enter, KeyCode::Esc, KeyCode::Char('q')].contains(&key_event.code)
+            }),
+            cancellation: Box::new(|_key_event: &KeyEvent| false),
+            _marker: PhantomData,
+        }
+    }
+}
+
+/// Deletes the task with `id`, after asking unless it was never filled in. The task is
+/// taken by id, the list may re-sort before the answer.
+pub fn delete_task<T: TaskColors>(data: &mut Data, id: u64) -> Option<Box<dyn Popup<T>>> {
+    let task = data.tasks.iter().find(|x| x.id == id)?;
+    if task.is_default() {
+        data.remove_tasks(&[id]);
+        return None;
+    }
+    let title = format!("{}: {}", &task.subject, &task.name);
+    Some(Box::new(ClosurePopup {
+        text: format!("Would you like to remove task \"{title}\""),
+        payload: Box::new(move |data, _key_event| {
+            if !data.tasks.iter().any(|x| x.id == id) {
+                let message = format!("\"{title}\": task no longer exists");
+                return PopupAction::Open(Box::new(ClosurePopup::message(message)));
+            }
+            data.remove_tasks(&[id]);
+            PopupAction::Close
+        }),
+        confirmation: Box::new(|key_event: &KeyEvent| {key_event.code == KeyCode::Char('d')}),
+        cancellation: Box::new(|key_event: &KeyEvent| {key_event.code == KeyCode::Esc}),
+        _marker: PhantomData,
+    }))
+}
+
 impl<'a, T: TaskColors> Popup<T> for ClosurePopup<T> {
     fn size(&self) -> (u16, u16) {
-        return (65, 25)
+        (65, 80)
     }
     fn paragraph(&self) -> Paragraph {
         let exit_text = Text::raw(&self.text);
         Paragraph::new(exit_text)
             .wrap(Wrap { trim: false })
     }
-    fn handle_key_event(&mut self, key_event: &KeyEvent, data: &mut Data) -> PopupAction {
+    fn handle_key_event(&mut self, key_event: &KeyEvent, data: &mut Data) -> PopupAction<T> {
         if (self.confirmation)(key_event) {
             (self.payload)(data, key_event)
         } else if (self.cancellation)(key_event) {
@@ -80,4 +150,205 @@ impl<'a, T: TaskColors> Popup<T> for ClosurePopup<T> {
             PopupAction::None
         }
     }
+    fn render(&self, frame: &mut Frame, area: Rect) {
+        let (max_width, max_percent) = self.size();
+        let width = max_width.min(area.width);
+        let lines = wrap(&self.text, width.saturating_sub(4) as usize).len() as u16;
+
+        let popup_block = Block::bordered()
+            .border_type(BorderType::Rounded)
+            .title_alignment(Alignment::Center)
+            .title(self.title())
+            .padding(Padding::uniform(1));
+
+        let area = fitted_rect(width, lines, max_percent, area);
+        frame.render_widget(Clear, area);
+        frame.render_widget(self.paragraph().block(popup_block), area);
+    }
+}
+
+/// What an `InputPopup` does with the entered line
+pub type Submit<T> = Box<dyn FnMut(&mut Data, &str) -> PopupAction<T>>;
+
+/// Single line of text handed to `submit` on Enter
+pub struct InputPopup<T: TaskColors> {
+    title: &'static str,
+    prompt: String,
+    input: String,
+    submit: Submit<T>,
+}
+
+impl<T: TaskColors> InputPopup<T> {
+    pub fn new(title: &'static str, prompt: String, submit: Submit<T>) -> Self {
+        Self {
+            title,
+            prompt,
+            input: String::new(),
+            submit,
+        }
+    }
+}
+
+impl<T: TaskColors> Popup<T> for InputPopup<T> {
+    fn size(&self) -> (u16, u16) {
+        (65, 20)
+    }
+    fn title(&self) -> Line<'_> { Line::from(format!(" {} ", self.title)).fg(T::highlight_desc()) }
+    fn paragraph(&self) -> Paragraph<'_> {
+        Paragraph::new(format!("{}{}▏", self.prompt, self.input))
+            .wrap(Wrap { trim: false })
+    }
+    fn handle_key_event(&mut self, key_event: &KeyEvent, data: &mut Data) -> PopupAction<T> {
+        match key_event.code {
+            KeyCode::Char(c) => self.input.push(c),
+            KeyCode::Backspace => { self.input.pop(); },
+            KeyCode::Enter => return (self.submit)(data, self.input.trim()),
+            KeyCode::Esc => return PopupAction::Close,
+            _ => (),
+        }
+        PopupAction::None
+    }
+    fn render(&self, frame: &mut Frame, area: Rect) {
+        // One line of text, whatever the frame height
+        let popup_block = Block::bordered()
+            .border_type(BorderType::Rounded)
+            .title_alignment(Alignment::Center)
+            .title(self.title())
+            .padding(Padding::horizontal(1));
+
+        let [_, area, _] = Layout::vertical([Constraint::Fill(1), Constraint::Length(3), Constraint::Fill(1)]).areas(area);
+        let [_, area, _] = Layout::horizontal([Constraint::Fill(1), Constraint::Max(self.size().0), Constraint::Fill(1)]).areas(area);
+        frame.render_widget(Clear, area);
+        frame.render_widget(self.paragraph().block(popup_block), area);
+    }
+}
+
+/// Path to export the visible tasks to; Tab lets the completed ones in
+pub struct ExportPopup<T: TaskColors> {
+    input: String,
+    options: ExportOptions,
+    _marker: PhantomData<T>,
+}
+
+impl<T: TaskColors> ExportPopup<T> {
+    pub fn new() -> Self {
+        Self {
+            input: String::new(),
+            options: ExportOptions::default(),
+            _marker: PhantomData,
+        }
+    }
+}
+
+impl<T: TaskColors> Popup<T> for ExportPopup<T> {
+    fn size(&self) -> (u16, u16) {
+        (65, 20)
+    }
+    fn title(&self) -> Line<'_> { Line::from(" Export ").fg(T::highlight_desc()) }
+    fn paragraph(&self) -> Paragraph<'_> {
+        let completed = if self.options.include_completed { "included" } else { "left out" };
+        Paragraph::new(vec![
+            Line::from(format!("Path (.md, .csv, .html, anything else plain): {}▏", self.input)),
+            Line::from(format!("Completed tasks {completed}, Tab to change")).dim(),
+        ])
+    }
+    fn handle_key_event(&mut self, key_event: &KeyEvent, data: &mut Data) -> PopupAction<T> {
+        match key_event.code {
+            KeyCode::Char(c) => self.input.push(c),
+            KeyCode::Backspace => { self.input.pop(); },
+            KeyCode::Tab => self.options.include_completed = !self.options.include_completed,
+            KeyCode::Enter => return commands::export(data, self.input.trim(), &self.options),
+            KeyCode::Esc => return PopupAction::Close,
+            _ => (),
+        }
+        PopupAction::None
+    }
+    fn render(&self, frame: &mut Frame, area: Rect) {
+        let popup_block = Block::bordered()
+            .border_type(BorderType::Rounded)
+            .title_alignment(Alignment::Center)
+            .title(self.title())
+            .padding(Padding::horizontal(1));
+
+        let [_, area, _] = Layout::vertical([Constraint::Fill(1), Constraint::Length(4), Constraint::Fill(1)]).areas(area);
+        let [_, area, _] = Layout::horizontal([Constraint::Fill(1), Constraint::Max(self.size().0), Constraint::Fill(1)]).areas(area);
+        frame.render_widget(Clear, area);
+        frame.render_widget(self.paragraph().block(popup_block), area);
+    }
+}
+
+/// Read-only view of a whole task, scrolled with j/k
+pub struct DetailPopup<T: TaskColors> {
+    task: Task,
+    now: DateTime<FixedOffset>,
+    date_format: String,
+    names: DateNames,
+    zone: DisplayZone,
+    policy: StatusPolicy,
+    scroll: u16,
+    // Known only once rendered
+    width: Cell<usize>,
+    max_scroll: Cell<u16>,
+    _marker: PhantomData<T>
+}
+
+impl<T: TaskColors> DetailPopup<T> {
+    const MAX_PERCENT: u16 = 80;
+
+    pub fn new(task: Task, now: DateTime<FixedOffset>, date_format: String, names: DateNames, zone: DisplayZone, policy: StatusPolicy) -> Self {
+        Self {
+            task,
+            now,
+            date_format,
+            names,
+            zone,
+            policy,
+            scroll: 0,
+            width: Cell::new(60),
+            max_scroll: Cell::new(0),
+            _marker: PhantomData,
+        }
+    }
+}
+
+impl<T: TaskColors> Popup<T> for DetailPopup<T> {
+    fn size(&self) -> (u16, u16) {
+        (Self::MAX_PERCENT, Self::MAX_PERCENT)
+    }
+    fn title(&self) -> Line<'_> { Line::from(" Task ").fg(T::highlight_desc()) }
+    fn paragraph(&self) -> Paragraph<'_> {
+        Paragraph::new(format_detail(&self.task, &self.now, self.width.get(), &self.date_format, &self.names, &self.zone, &self.policy))
+            .scroll((self.scroll.min(self.max_scroll.get()), 0))
+    }
+    fn handle_key_event(&mut self, key_event: &KeyEvent, data: &mut Data) -> PopupAction<T> {
+        match key_event.code {
+            KeyCode::Char('d') => return match delete_task(data, self.task.id) {
+                Some(popup) => PopupAction::Open(popup),
+                None => PopupAction::Close,
+            },
+            KeyCode::Char('j') => self.scroll = (self.scroll + 1).min(self.max_scroll.get()),
+            KeyCode::Char('k') => self.scroll = self.scroll.saturating_sub(1),
+            KeyCode::Esc | KeyCode::Enter | KeyCode::Char('q') => return PopupAction::Close,
+            _ => (),
+        }
+        PopupAction::None
+    }
+    fn render(&self, frame: &mut Frame, area: Rect) {
+        // Borders and padding take two columns/rows on each side
+        let max_width = area.width * Self::MAX_PERCENT / 100;
+        self.width.set(max_width.saturating_sub(4) as usize);
+
+        let lines = format_detail(&self.task, &self.now, self.width.get(), &self.date_format, &self.names, &self.zone, &self.policy).lines().count() as u16;
+        let area = fitted_rect(max_width, lines, Self::MAX_PERCENT, area);
+        self.max_scroll.set(lines.saturating_sub(area.height.saturating_sub(4)));
+
+        let popup_block = Block::bordered()
+            .border_type(BorderType::Rounded)
+            .title_alignment(Alignment::Center)
+            .title(self.title())
+            .padding(Padding::uniform(1));
+
+        frame.render_widget(Clear, area);
+        frame.render_widget(self.paragraph().block(popup_block), area);
+    }
 }
@@ -0,0 +1,306 @@
+use std::marker::PhantomData;
+
+use super::app::Data;
+use super::colors::TaskColors;
+use super::date_picker::{DatePicker, Pick};
+use super::popups::{fitted_rect, ClosurePopup, Popup, PopupAction};
+use super::textarea::TextArea;
+use crate::uni::completion::complete;
+use crate::uni::dates::{format_date, parse_deadline};
+use crate::uni::task::Task;
+use crate::uni::validation::past_deadline_warning;
+
+use ratatui::prelude::*;
+use ratatui::crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use ratatui::widgets::{Block, BorderType, Clear, Padding, Paragraph};
+
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Field {
+    Subject,
+    Name,
+    Deadline,
+}
+
+/// Subject, name and deadline in one go. The subject completes against the existing ones,
+/// and anything that looks like a mistake takes a second Enter.
+pub struct QuickAddPopup<T: TaskColors> {
+    subjects: Vec<String>,
+    subject: TextArea,
+    name: TextArea,
+    deadline: TextArea,
+    focus: Field,
+    suggestions: Vec<String>,
+    selected: usize,
+    /// Shown until the next edit, Enter again goes ahead anyway
+    pending: Option<String>,
+    confirmed: Vec<String>,
+    error: Option<String>,
+    /// Ctrl-d on the deadline picks it from a calendar instead
+    picker: Option<DatePicker>,
+    _marker: PhantomData<T>,
+}
+
+impl<T: TaskColors> QuickAddPopup<T> {
+    const MAX_SUGGESTIONS: usize = 5;
+
+    pub fn new(subjects: Vec<String>) -> Self {
+        let mut popup = Self {
+            subjects,
+            subject: TextArea::single_line(""),
+            name: TextArea::single_line(""),
+            deadline: TextArea::single_line(""),
+            focus: Field::Subject,
+            suggestions: vec![],
+            selected: 0,
+            pending: None,
+            confirmed: vec![],
+            error: None,
+            picker: None,
+            _marker: PhantomData,
+        };
+        popup.refresh_suggestions();
+        popup
+    }
+
+    fn refresh_suggestions(&mut self) {
+        let query = self.subject.text();
+        self.suggestions = match query.trim().is_empty() {
+            true => vec![],
+            false => complete(&self.subjects, &query)
+                .into_iter()
+                .take(Self::MAX_SUGGESTIONS)
+                .map(str::to_string)
+                .collect(),
+        };
+        self.selected = 0;
+    }
+
+    fn is_known_subject(&self, subject: &str) -> bool {
+        self.subjects.iter().any(|x| x.to_lowercase() == subject.to_lowercase())
+    }
+
+    fn accept_suggestion(&mut self) {
+        if let Some(subject) = self.suggestions.get(self.selected) {
+            self.subject = TextArea::single_line(subject);
+        }
+        self.suggestions.clear();
+        self.focus = Field::Name;
+    }
+
+    fn next_field(&mut self, forward: bool) {
+        self.focus = match (self.focus, forward) {
+            (Field::Subject, true) | (Field::Deadline, false) => Field::Name,
+            (Field::Name, true) | (Field::Subject, false) => Field::Deadline,
+            (Field::Deadline, true) | (Field::Name, false) => Field::Subject,
+        };
+    }
+
+    /// Either the task, or the reason to stay open: an error or something to confirm
+    fn submit(&mut self, data: &Data) -> Option<Task> {
+        let subject = data.config.subject_abbrev.expand(&self.subject.text()).to_string();
+        let name = self.name.text().trim().to_string();
+        if name.is_empty() {
+            self.error = Some("The name can't be empty".to_string());
+            self.focus = Field::Name;
+            return None;
+        }
+
+        let now = data.now();
+        let deadline = self.deadline.text();
+        let time = match deadline.trim() {
+            "" => None,
+            text => match parse_deadline(text, &now, data.config.deadline_time()) {
+                Ok(time) => Some(time),
+                Err(error) => {
+                    self.error = Some(error);
+                    self.focus = Field::Deadline;
+                    return None;
+                },
+            },
+        };
+
+        let task = Task {
+            subject,
+            name,
+            time,
+            description: String::new(),
+            ..Task::new_now()
+        };
+
+        let mut warnings = vec![];
+        if let Some(similar) = data.find_similar(&task).first() {
+            let due = similar.time
+                .map(|x| format!(", due {}", format_date(&x, "%a %d %b", &data.config.ui.date_names(), &data.config.ui.display_zone())))
+                .unwrap_or_default();
+            warnings.push(format!("Similar task exists: {} — {}{due}. Enter to create anyway", similar.subject, similar.name));
+        }
+        let subject = &task.subject;
+        if !subject.is_empty() && !self.is_known_subject(subject) {
+            warnings.push(format!("\"{subject}\" is a new subject — Enter to create it"));
+        }
+        if let Some(warning) = past_deadline_warning(None, task.time, &now, &data.config.validation) {
+            warnings.push(format!("{warning} — Enter to keep it"));
+        }
+        if let Some(warning) = warnings.into_iter().find(|x| !self.confirmed.contains(x)) {
+            self.confirmed.push(warning.clone());
+            self.pending = Some(warning);
+            return None;
+        }
+        Some(task)
+    }
+
+    /// Lets the picker have the key, filling in the deadline once something is picked
+    fn handle_picker_key(&mut self, key_event: &KeyEvent, data: &Data) {
+        let Some(picker) = self.picker.as_mut() else { return };
+        match picker.handle_key_event(key_event, &data.tasks) {
+            Pick::Done(time) => {
+                self.deadline = TextArea::single_line(&time.format("%Y-%m-%d %H:%M").to_string());
+                self.picker = None;
+            },
+            Pick::Cancelled => self.picker = None,
+            Pick::Pending => (),
+        }
+    }
+
+    fn text(&mut self) -> &mut TextArea {
+        match self.focus {
+            Field::Subject => &mut self.subject,
+            Field::Name => &mut self.name,
+            Field::Deadline => &mut self.deadline,
+        }
+    }
+
+    fn block(&self, title: &'static str, field: Field) -> Block<'static> {
+        let block = Block::bordered().border_type(BorderType::Rounded).title(format!(" {title} "));
+        match self.focus == field {
+            true => block.border_style(T::active_border()),
+            false => block.dim(),
+        }
+    }
+}
+
+impl<T: TaskColors> Popup<T> for QuickAddPopup<T> {
+    fn size(&self) -> (u16, u16) {
+        (60, 80)
+    }
+    fn title(&self) -> Line<'_> { Line::from(" New task ").fg(T::highlight_desc()) }
+    fn paragraph(&self) -> Paragraph<'_> {
+        Paragraph::default()
+    }
+    fn handle_key_event(&mut self, key_event: &KeyEvent, data: &mut Data) -> PopupAction<T> {
+        if self.picker.is_some() {
+            self.handle_picker_key(key_event, data);
+            return PopupAction::None;
+        }
+        let completing = self.focus == Field::Subject && !self.suggestions.is_empty();
+        match key_event.code {
+            KeyCode::Esc => return PopupAction::Close,
+            KeyCode::Char('d') if self.focus == Field::Deadline && key_event.modifiers.contains(KeyModifiers::CONTROL) => {
+                let now = data.now();
+                let current = parse_deadline(self.deadline.text().trim(), &now, data.config.deadline_time()).ok();
+                self.picker = Some(DatePicker::new(current, now, data.config.deadline_time(), &data.tasks)
+                    .with_status_symbols(data.config.ui.status_symbols)
+                    .with_names(data.config.ui.date_names())
+                    .with_policy(data.status_policy())
+                    .with_day_limit(data.config.validation.max_tasks_per_day)
+                    .with_filter(data.config.ui.calendar_include));
+            },
+            KeyCode::Enter => {
+                if let Some(mut task) = self.submit(data) {
+                    let template = data.template_description(&task.subject, &task.name);
+                    if let Ok(Some(description)) = &template {
+                        task.description = description.clone();
+                    }
+                    data.add(task);
+                    return match template {
+                        Err(error) => PopupAction::Open(Box::new(ClosurePopup::message(error))),
+                        Ok(_) => PopupAction::Close,
+                    };
+                }
+                return PopupAction::None;
+            },
+            KeyCode::Tab if completing => self.accept_suggestion(),
+            KeyCode::Down if completing => self.selected = (self.selected + 1) % self.suggestions.len(),
+            KeyCode::Up if completing => {
+                self.selected = (self.selected + self.suggestions.len() - 1) % self.suggestions.len();
+            },
+            KeyCode::Tab => self.next_field(true),
+            KeyCode::BackTab => self.next_field(false),
+            _ => {
+                if !self.text().handle_key_event(key_event) {
+                    return PopupAction::None;
+                }
+                if self.focus == Field::Subject {
+                    self.refresh_suggestions();
+                }
+            },
+        }
+        self.pending = None;
+        self.confirmed.clear();
+        self.error = None;
+        PopupAction::None
+    }
+    fn render(&self, frame: &mut Frame, area: Rect) {
+        if let Some(picker) = &self.picker {
+            let area = fitted_rect(self.size().0.min(area.width), picker.height(), 90, area);
+            let popup_block = Block::bordered()
+                .border_type(BorderType::Rounded)
+                .title_alignment(Alignment::Center)
+                .title(Line::from(" New task · deadline ").fg(T::highlight_desc()))
+                .padding(Padding::uniform(1));
+            let inner = popup_block.inner(area);
+            frame.render_widget(Clear, area);
+            frame.render_widget(popup_block, area);
+            picker.render::<T>(frame, inner);
+            return;
+        }
+        let suggestions = self.suggestions.len() as u16;
+        // Three boxed fields, the suggestions and the message line
+        let lines = 3 * 3 + suggestions + 1;
+        let area = fitted_rect(self.size().0.min(area.width), lines, self.size().1, area);
+
+        let popup_block = Block::bordered()
+            .border_type(BorderType::Rounded)
+            .title_alignment(Alignment::Center)
+            .title(self.title())
+            .padding(Padding::uniform(1));
+        let inner = popup_block.inner(area);
+        frame.render_widget(Clear, area);
+        frame.render_widget(popup_block, area);
+
+        let [subject, completions, name, deadline, footer] = Layout::vertical([
+            Constraint::Length(3),
+            Constraint::Length(suggestions),
+            Constraint::Length(3),
+            Constraint::Length(3),
+            Constraint::Length(1),
+        ]).areas(inner);
+
+        self.subject.render(frame, subject, self.block("Subject", Field::Subject), self.focus == Field::Subject);
+        let completion_lines = self.suggestions
+            .iter()
+            .enumerate()
+            .map(|(i, x)| match i == self.selected {
+                true => Line::from(format!(" › {x}")).fg(T::highlight_desc()),
+                false => Line::from(format!("   {x}")).dim(),
+            })
+            .collect::<Vec<_>>();
+        frame.render_widget(Paragraph::new(completion_lines), completions);
+        self.name.render(frame, name, self.block("Name", Field::Name), self.focus == Field::Name);
+        self.deadline.render(
+            frame,
+            deadline,
+            self.block("Deadline: 2025-05-12 18:00, tomorrow, +3d", Field::Deadline),
+            self.focus == Field::Deadline,
+        );
+
+        let footer_line = match (&self.error, &self.pending) {
+            (Some(error), _) => Line::from(error.as_str()).fg(T::error()),
+            (None, Some(pending)) => Line::from(pending.as_str()).fg(T::highlight_desc()),
+            (None, None) if self.focus == Field::Deadline => Line::from("Ctrl-d calendar · Enter add · Esc cancel").dim(),
+            (None, None) => Line::from("Tab complete / next · Enter add · Esc cancel").dim(),
+        };
+        frame.render_widget(Paragraph::new(footer_line), footer);
+    }
+}
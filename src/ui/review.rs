@@ -0,0 +1,232 @@
+use std::marker::PhantomData;
+
+use super::app::Data;
+use super::colors::TaskColors;
+use super::date_picker::{DatePicker, Pick};
+use super::popups::{fitted_rect, ClosurePopup, Popup, PopupAction};
+use crate::uni::detail::format_detail;
+use crate::uni::task::Task;
+
+use chrono::TimeDelta;
+use ratatui::prelude::*;
+use ratatui::crossterm::event::{KeyCode, KeyEvent};
+use ratatui::widgets::{Block, BorderType, Clear, Padding, Paragraph};
+
+
+/// What a key did to the review
+pub enum ReviewAction<T: TaskColors> {
+    None,
+    /// Show this over the review, which carries on once it closes
+    Open(Box<dyn Popup<T>>),
+    /// Walked through or left with Esc, the decisions made stay
+    Done,
+}
+
+#[derive(Default)]
+struct Tally {
+    kept: usize,
+    completed: usize,
+    rescheduled: usize,
+    deleted: usize,
+    someday: usize,
+}
+
+/// Every incomplete task left alone for a while, one at a time. All decisions of one
+/// review undo together.
+pub struct Review<T: TaskColors> {
+    ids: Vec<u64>,
+    position: usize,
+    picker: Option<DatePicker>,
+    checkpointed: bool,
+    tally: Tally,
+    _marker: PhantomData<T>,
+}
+
+impl<T: TaskColors> Review<T> {
+    /// None when nothing went untouched for `days`
+    pub fn new(data: &Data, days: i64) -> Option<Self> {
+        let now = data.now();
+        let stale = TimeDelta::days(days);
+        let ids = data.query()
+            .complete(false)
+            .matching(|x| x.last_updated().is_none_or(|t| now - t >= stale))
+            .iter()
+            .map(|x| x.id)
+            .collect::<Vec<_>>();
+        (!ids.is_empty()).then_some(Self {
+            ids,
+            position: 0,
+            picker: None,
+            checkpointed: false,
+            tally: Tally::default(),
+            _marker: PhantomData,
+        })
+    }
+
+    /// The task under review, skipping any that disappeared meanwhile
+    fn current<'a>(&mut self, data: &'a Data) -> Option<&'a Task> {
+        while let Some(id) = self.ids.get(self.position) {
+            if let Some(task) = data.tasks.iter().find(|x| x.id == *id) {
+                return Some(task);
+            }
+            self.tally.deleted += 1;
+            self.position += 1;
+        }
+        None
+    }
+
+    /// Only the first decision checkpoints, so one undo takes back the whole review
+    fn decide(&mut self, data: &mut Data) {
+        if !std::mem::replace(&mut self.checkpointed, true) {
+            data.checkpoint();
+        }
+    }
+
+    fn update(&mut self, data: &mut Data, id: u64, change: impl FnOnce(&mut Task)) {
+        self.decide(data);
+        data.update(id, change);
+        self.position += 1;
+    }
+
+    pub fn handle_key_event(&mut self, key_event: &KeyEvent, data: &mut Data) -> ReviewAction<T> {
+        let Some(task) = self.current(data) else { return ReviewAction::Done };
+        let (id, time) = (task.id, task.time);
+
+        if let Some(picker) = self.picker.as_mut() {
+            match picker.handle_key_event(key_event, &data.tasks) {
+                Pick::Done(time) => {
+                    self.picker = None;
+                    self.tally.rescheduled += 1;
+                    self.update(data, id, |x| x.time = Some(time));
+                },
+                Pick::Cancelled => self.picker = None,
+                Pick::Pending => (),
+            }
+            return self.carry_on(data);
+        }
+
+        match key_event.code {
+            KeyCode::Esc => return ReviewAction::Done,
+            KeyCode::Char('k') => {
+                self.tally.kept += 1;
+                self.position += 1;
+            },
+            KeyCode::Char('c') => {
+                self.tally.completed += 1;
+                self.update(data, id, |x| x.set_complete(true));
+            },
+            KeyCode::Char('z') => {
+                self.tally.someday += 1;
+                self.update(data, id, |x| x.time = None);
+            },
+            KeyCode::Char('r') => {
+                let picker = DatePicker::new(time, data.now(), data.config.deadline_time(), &data.tasks)
+                    .with_status_symbols(data.config.ui.status_symbols)
+                    .with_names(data.config.ui.date_names())
+                    .with_filter(data.config.ui.calendar_include);
+                self.picker = Some(picker);
+            },
+            KeyCode::Char('d') => {
+                self.decide(data);
+                return ReviewAction::Open(Box::new(Self::delete_popup(task_title(data, id), id)));
+            },
+            _ => (),
+        }
+        self.carry_on(data)
+    }
+
+    fn carry_on(&mut self, data: &Data) -> ReviewAction<T> {
+        match self.current(data) {
+            Some(_) => ReviewAction::None,
+            None => ReviewAction::Done,
+        }
+    }
+
+    /// The review picks up a deletion by the task being gone
+    fn delete_popup(title: String, id: u64) -> ClosurePopup<T> {
+        ClosurePopup {
+            text: format!("Delete \"{title}\"? (y/n)"),
+            payload: Box::new(move |data: &mut Data, key_event: &KeyEvent| {
+                if matches!(key_event.code, KeyCode::Char('y') | KeyCode::Enter) {
+                    data.tasks.retain(|x| x.id != id);
+                    data.validate();
+                }
+                PopupAction::Close
+            }),
+            confirmation: Box::new(|key_event: &KeyEvent| {
+                [KeyCode::Enter, KeyCode::Char('y'), KeyCode::Char('n')].contains(&key_event.code)
+            }),
+            cancellation: Box::new(|key_event: &KeyEvent| {key_event.code == KeyCode::Esc}),
+            _marker: PhantomData,
+        }
+    }
+
+    /// What the review did, for the message shown once it is over
+    pub fn summary(&self) -> String {
+        let Tally { kept, completed, rescheduled, deleted, someday } = self.tally;
+        format!(
+            "Reviewed {} of {} task(s): {kept} kept, {completed} completed, {rescheduled} rescheduled, \
+            {deleted} deleted, {someday} moved to someday.",
+            self.position.min(self.ids.len()),
+            self.ids.len(),
+        )
+    }
+
+    pub fn render(&mut self, frame: &mut Frame, area: Rect, data: &Data) {
+        let Some(task) = self.current(data) else { return };
+        let title = Line::from(format!(" Review · task {} of {} ", self.position + 1, self.ids.len()))
+            .bold()
+            .fg(T::highlight_desc());
+        let block = Block::bordered()
+            .border_type(BorderType::Rounded)
+            .border_style(T::active_border())
+            .title_alignment(Alignment::Center)
+            .title(title)
+            .padding(Padding::uniform(1));
+        let inner = block.inner(area);
+        frame.render_widget(Clear, area);
+        frame.render_widget(block, area);
+
+        let [detail, _, footer] = Layout::vertical([
+            Constraint::Fill(1),
+            Constraint::Length(1),
+            Constraint::Length(1),
+        ]).areas(inner);
+        let now = data.now();
+        let text = format_detail(
+            task,
+            &now,
+            detail.width as usize,
+            &data.config.ui.date_display_format,
+            &data.config.ui.date_names(),
+            &data.config.ui.display_zone(),
+            &data.status_policy(),
+        );
+        let overdue = data.config.ui.overdue_emphasis.modifier();
+        let style = T::task_style(task, &data.status(task, &now), task.get_delta(&now), overdue, data.config.ui.status_symbols);
+        frame.render_widget(Paragraph::new(text).style(style), detail);
+        let keys = "k keep · c complete · r reschedule · d delete · z someday (no deadline) · Esc stop";
+        frame.render_widget(Paragraph::new(Line::from(keys).dim()), footer);
+
+        if let Some(picker) = &self.picker {
+            let area = fitted_rect(50.min(inner.width), picker.height(), 90, inner);
+            let block = Block::bordered()
+                .border_type(BorderType::Rounded)
+                .title_alignment(Alignment::Center)
+                .title(Line::from(" Reschedule ").fg(T::highlight_desc()))
+                .padding(Padding::uniform(1));
+            let picker_area = block.inner(area);
+            frame.render_widget(Clear, area);
+            frame.render_widget(block, area);
+            picker.render::<T>(frame, picker_area);
+        }
+    }
+}
+
+fn task_title(data: &Data, id: u64) -> String {
+    data.tasks
+        .iter()
+        .find(|x| x.id == id)
+        .map(|x| format!("{}: {}", x.subject, x.name))
+        .unwrap_or_default()
+}
@@ -0,0 +1,1938 @@
+use std::cell::RefCell;
+use std::io;
+use std::path::PathBuf;
+use std::rc::Rc;
+use std::sync::mpsc::{self, Receiver};
+use std::thread;
+use std::time::Duration;
+
+use super::app::{App, Data, SortColumn, Startup, View};
+use super::colors::{Ansi16, Degraded, MonochromeTaskColors, StandardTaskColors};
+use super::panes::{DescriptionPane, TasksPane};
+use crate::config::{Config, Filters, OverdueEmphasis, SelectionStyle, UndatedPosition};
+use crate::constants;
+use crate::readers::TaskReader;
+use crate::storages::{MemoryStorage, TaskStorage, TomlStorage};
+use crate::uni::anchors::Anchor;
+use crate::uni::clock::Clock;
+use crate::uni::task::Task;
+
+use chrono::{DateTime, FixedOffset, TimeDelta};
+use ratatui::backend::TestBackend;
+use ratatui::buffer::Buffer;
+use ratatui::style::{Color, Modifier};
+use ratatui::crossterm::event::{Event, KeyCode, KeyEvent, KeyModifiers};
+use ratatui::Terminal;
+
+
+fn now() -> DateTime<FixedOffset> {
+    DateTime::parse_from_rfc3339("2025-03-01T12:00:00+00:00").unwrap()
+}
+
+fn tasks() -> Vec<Task> {
+    let task = |id, subject: &str, name: &str, days| Task {
+        id,
+        subject: subject.to_string(),
+        name: name.to_string(),
+        description: format!("About {name}"),
+        time: Some(now() + TimeDelta::days(days)),
+        ..Task::default()
+    };
+    vec![
+        task(1, "Algo", "HW1", 1),
+        task(2, "Physics", "Lab", 5),
+        task(3, "Databases", "Quiz", 10),
+    ]
+}
+
+/// Stands in for the editor
+struct RenamingReader;
+
+impl TaskReader for RenamingReader {
+    fn read(&self, template: &Task, _config: &Config) -> Result<Task, ()> {
+        Ok(Task { name: "Renamed".to_string(), ..template.clone() })
+    }
+}
+
+/// Hands the task back as it was
+struct NoopReader;
+
+impl TaskReader for NoopReader {
+    fn read(&self, template: &Task, _config: &Config) -> Result<Task, ()> {
+        Ok(template.clone())
+    }
+}
+
+/// Types a deadline with the wrong year
+struct PastReader;
+
+impl TaskReader for PastReader {
+    fn read(&self, template: &Task, _config: &Config) -> Result<Task, ()> {
+        Ok(Task { time: Some(now() - TimeDelta::days(142)), ..template.clone() })
+    }
+}
+
+/// Remembers what was written, so saving can be observed
+#[derive(Clone, Default)]
+struct SpyStorage {
+    stored: Rc<RefCell<Vec<Task>>>,
+}
+
+impl SpyStorage {
+    fn sorted(tasks: &[Task]) -> Vec<Task> {
+        let mut tasks = tasks.to_vec();
+        tasks.sort_by_key(|x| x.id);
+        tasks
+    }
+}
+
+impl TaskStorage for SpyStorage {
+    fn should_save(&self, tasks: &Vec<Task>) -> bool {
+        *self.stored.borrow() != Self::sorted(tasks)
+    }
+
+    fn read_without_ids(&self) -> Result<Vec<Task>, io::Error> {
+        Ok(self.stored.borrow().clone())
+    }
+
+    fn write(&self, tasks: &Vec<Task>) -> Result<(), io::Error> {
+        *self.stored.borrow_mut() = Self::sorted(tasks);
+        Ok(())
+    }
+
+    fn backup(&self) -> Result<PathBuf, io::Error> {
+        Err(io::Error::other("Nothing to back up"))
+    }
+}
+
+/// Reads only once `gate` lets it through, like a tasks file on a slow network drive.
+/// Dropping the sender fails the read.
+struct SlowStorage {
+    gate: Receiver<()>,
+}
+
+impl TaskStorage for SlowStorage {
+    fn should_save(&self, _tasks: &Vec<Task>) -> bool {
+        false
+    }
+
+    fn read_without_ids(&self) -> Result<Vec<Task>, io::Error> {
+        self.gate.recv().map_err(|_| io::Error::other("network unreachable"))?;
+        Ok(tasks())
+    }
+
+    fn write(&self, _tasks: &Vec<Task>) -> Result<(), io::Error> {
+        Ok(())
+    }
+
+    fn backup(&self) -> Result<PathBuf, io::Error> {
+        Err(io::Error::other("Nothing to back up"))
+    }
+}
+
+struct Harness {
+    app: App<'static, StandardTaskColors>,
+    terminal: Terminal<TestBackend>,
+}
+
+impl Harness {
+    fn new(storage: impl TaskStorage + 'static) -> Self {
+        Self::with_reader(storage, RenamingReader)
+    }
+
+    fn with_reader(storage: impl TaskStorage + 'static, reader: impl TaskReader + 'static) -> Self {
+        Self::build(storage, reader, Startup::default())
+    }
+
+    /// The usual tasks, opened as if given `startup` on the command line
+    fn starting(startup: Startup) -> Self {
+        Self::build(MemoryStorage::with_tasks(tasks()), RenamingReader, startup)
+    }
+
+    fn build(storage: impl TaskStorage + 'static, reader: impl TaskReader + 'static, startup: Startup) -> Self {
+        // Dates as written, whatever zone the machine running the tests is in
+        let mut config = Config::default();
+        config.ui.display_timezone = "original".to_string();
+        let app = App::builder()
+            .with_storage(Box::new(storage))
+            .with_reader(Box::new(reader))
+            .with_config(config)
+            .with_clock(Clock::Fixed(now()))
+            .with_startup(startup)
+            .build()
+            .unwrap();
+
+        let mut harness = Self {
+            app,
+            terminal: Terminal::new(TestBackend::new(100, 20)).unwrap(),
+        };
+        harness.render();
+        harness
+    }
+
+    /// Up before the tasks are, as `main` starts it
+    fn loading(storage: impl TaskStorage + Send + 'static) -> Self {
+        let mut config = Config::default();
+        config.ui.display_timezone = "original".to_string();
+        let app = App::builder()
+            .with_config(config)
+            .with_clock(Clock::Fixed(now()))
+            .build_loading(Box::new(storage));
+
+        let mut harness = Self {
+            app,
+            terminal: Terminal::new(TestBackend::new(100, 20)).unwrap(),
+        };
+        harness.render();
+        harness
+    }
+
+    /// Gives the reading thread up to a second to show `text`
+    fn wait_for(&mut self, text: &str) {
+        for _ in 0..100 {
+            self.app.check_loading();
+            self.render();
+            if self.screen().contains(text) {
+                return;
+            }
+            thread::sleep(Duration::from_millis(10));
+        }
+        panic!("No {text:?} in\n{}", self.screen());
+    }
+
+    fn render(&mut self) {
+        self.terminal.draw(|frame| self.app.draw(frame)).unwrap();
+    }
+
+    /// Same order as the event loop: handle the key, then draw
+    fn press(&mut self, code: KeyCode) {
+        self.app.handle_key_event(KeyEvent::new(code, KeyModifiers::NONE)).unwrap();
+        self.render();
+    }
+
+    /// Ticks before and after moving the clock on, as the event loop would
+    fn wait(&mut self, seconds: i64) {
+        self.app.tick();
+        self.app.data.clock = Clock::Fixed(self.app.data.now() + TimeDelta::seconds(seconds));
+        self.app.tick();
+        self.render();
+    }
+
+    fn press_ctrl(&mut self, c: char) {
+        self.app.handle_key_event(KeyEvent::new(KeyCode::Char(c), KeyModifiers::CONTROL)).unwrap();
+        self.render();
+    }
+
+    fn type_text(&mut self, text: &str) {
+        for c in text.chars() {
+            self.press(KeyCode::Char(c));
+        }
+    }
+
+    fn lines(&self) -> Vec<String> {
+        let buffer = self.terminal.backend().buffer();
+        (0..buffer.area.height)
+            .map(|y| {
+                (0..buffer.area.width)
+                    .map(|x| buffer[(x, y)].symbol())
+                    .collect::<String>()
+            })
+            .collect()
+    }
+
+    fn screen(&self) -> String {
+        self.lines().join("\n")
+    }
+
+    /// First line inside the borders mentioning `needle`
+    fn row(&self, needle: &str) -> String {
+        self.lines()
+            .into_iter()
+            .find(|x| x.starts_with('│') && x.contains(needle))
+            .unwrap_or_else(|| panic!("No row with {needle:?} in\n{}", self.screen()))
+    }
+
+    fn selected(&self) -> String {
+        self.row(" > ")
+    }
+}
+
+fn memory() -> Harness {
+    Harness::new(MemoryStorage::with_tasks(tasks()))
+}
+
+fn spy() -> (Harness, SpyStorage) {
+    let storage = SpyStorage::default();
+    storage.write(&tasks()).unwrap();
+    (Harness::new(storage.clone()), storage)
+}
+
+#[test]
+fn renders_tasks_with_time_left() {
+    let harness = memory();
+
+    assert!(harness.row("HW1").contains("1 day"));
+    assert!(harness.row("Quiz").contains("10 days"));
+    assert!(harness.screen().contains("next: Algo HW1 in 1d"));
+}
+
+#[test]
+fn startup_flags_open_the_list_filtered_until_cleared() {
+    let mut harness = Harness::starting(Startup { subject: Some("algo".to_string()), urgent: true, ..Startup::default() });
+    assert!(harness.screen().contains("URGENT"));
+    assert!(harness.selected().contains("HW1"));
+    assert!(!harness.screen().contains("Lab"));
+    // Leaving the view keeps the subject, clearing drops it too
+    harness.press(KeyCode::Char('a'));
+    assert!(!harness.screen().contains("URGENT") && !harness.screen().contains("Lab"));
+    harness.type_text(":filter clear");
+    harness.press(KeyCode::Enter);
+    assert!(harness.screen().contains("Lab") && harness.screen().contains("Quiz"));
+
+    let harness = Harness::starting(Startup { search: Some("about QUIZ".to_string()), ..Startup::default() });
+    assert!(harness.selected().contains("Quiz"));
+    assert!(!harness.screen().contains("HW1"));
+
+    let harness = Harness::starting(Startup { select: Some(2), ..Startup::default() });
+    assert!(harness.selected().contains("Lab"));
+
+    let harness = Harness::starting(Startup { subject: Some("Chemistry".to_string()), select: Some(9), ..Startup::default() });
+    assert!(harness.screen().contains("No tasks in Chemistry"));
+    assert!(harness.screen().contains("HW1") && harness.screen().contains("Quiz"));
+}
+
+#[test]
+fn builder_defaults_match_the_full_screen_ui() {
+    let app = App::<StandardTaskColors>::builder().build().unwrap();
+    assert!(app.data.tasks.is_empty());
+    assert!(matches!(app.data.clock, Clock::System));
+    assert_eq!(toml::to_string(&app.data.config).unwrap(), toml::to_string(&Config::default()).unwrap());
+
+    // Same screen as with the panes given explicitly
+    let draw = |mut app: App<'static, StandardTaskColors>| {
+        let mut terminal = Terminal::new(TestBackend::new(100, 20)).unwrap();
+        terminal.draw(|frame| app.draw(frame)).unwrap();
+        terminal.backend().buffer().clone()
+    };
+    let builder = || {
+        App::builder()
+            .with_storage(Box::new(MemoryStorage::with_tasks(tasks())))
+            .with_clock(Clock::Fixed(now()))
+    };
+    assert_eq!(
+        draw(builder().build().unwrap()),
+        draw(builder().with_panes(Box::new(TasksPane::new()), Box::new(DescriptionPane::default())).build().unwrap())
+    );
+}
+
+#[test]
+fn sixteen_color_mode_draws_only_ansi_colors() {
+    let mut tasks = tasks();
+    tasks[0].time = Some(now() - TimeDelta::days(2));
+    tasks[1].time = Some(now() + TimeDelta::hours(3));
+    tasks[2].complete = true;
+    let mut app = App::<StandardTaskColors>::builder()
+        .with_storage(Box::new(MemoryStorage::with_tasks(tasks)))
+        .with_reader(Box::new(RenamingReader))
+        .with_clock(Clock::Fixed(now()))
+        .with_theme::<Degraded<StandardTaskColors, Ansi16>>()
+        .build()
+        .unwrap();
+    app.data.config.ui.filter_preset = Filters::default();
+    let mut terminal = Terminal::new(TestBackend::new(100, 20)).unwrap();
+
+    // Both panes active in turn, the description one with its gray background
+    for code in [KeyCode::Char('j'), KeyCode::Char('l'), KeyCode::Enter] {
+        app.handle_key_event(KeyEvent::new(code, KeyModifiers::NONE)).unwrap();
+        terminal.draw(|frame| app.draw(frame)).unwrap();
+        let buffer = terminal.backend().buffer();
+        let exotic = buffer
+            .content()
+            .iter()
+            .flat_map(|x| [x.fg, x.bg])
+            .find(|x| matches!(x, Color::Rgb(..) | Color::Indexed(_)));
+        assert_eq!(exotic, None);
+    }
+}
+
+#[test]
+fn monochrome_theme_tells_states_apart_without_colors() {
+    let mut tasks = tasks();
+    tasks[0].time = Some(now() - TimeDelta::days(2));
+    tasks[1].time = Some(now() + TimeDelta::hours(30));
+    tasks[2].complete = true;
+    tasks.push(Task { id: 4, subject: "Logic".to_string(), name: "Essay".to_string(), time: Some(now() + TimeDelta::days(9)), ..Task::default() });
+    let mut app = App::<StandardTaskColors>::builder()
+        .with_storage(Box::new(MemoryStorage::with_tasks(tasks)))
+        .with_clock(Clock::Fixed(now()))
+        .with_theme::<MonochromeTaskColors>()
+        .build()
+        .unwrap();
+    app.data.config.ui.filter_preset = Filters::default();
+    app.data.config.ui.overdue_emphasis = OverdueEmphasis::None;
+    let mut terminal = Terminal::new(TestBackend::new(100, 20)).unwrap();
+    let mut draw = |app: &mut App<'static, MonochromeTaskColors>| {
+        terminal.draw(|frame| app.draw(frame)).unwrap();
+        terminal.backend().buffer().clone()
+    };
+    let row = |buffer: &Buffer, y| (0..buffer.area.width / 2).map(|x| buffer[(x, y)].symbol()).collect::<String>();
+    // Style of the name of a task in the table
+    let style = |buffer: &Buffer, name: &str| {
+        let (x, y) = (0..buffer.area.height)
+            .find_map(|y| row(buffer, y).find(name).map(|x| (row(buffer, y)[..x].chars().count() as u16, y)))
+            .unwrap();
+        buffer[(x, y)].style()
+    };
+
+    let buffer = draw(&mut app);
+    let colored = buffer.content().iter().flat_map(|x| [x.fg, x.bg]).find(|x| *x != Color::Reset);
+    assert_eq!(colored, None);
+    // Overdue, panicking, zen and normal, the first one selected
+    let modifiers = ["HW1", "Lab", "Quiz", "Essay"].map(|x| style(&buffer, x).add_modifier);
+    assert_eq!(modifiers, [
+        Modifier::BOLD | Modifier::UNDERLINED | Modifier::REVERSED,
+        Modifier::BOLD,
+        Modifier::DIM,
+        Modifier::empty(),
+    ]);
+
+    app.data.config.ui.selection = SelectionStyle::Marker;
+    app.handle_key_event(KeyEvent::new(KeyCode::Char('j'), KeyModifiers::NONE)).unwrap();
+    let buffer = draw(&mut app);
+    assert_eq!(style(&buffer, "HW1").add_modifier, Modifier::BOLD | Modifier::UNDERLINED);
+    assert_eq!(style(&buffer, "Lab").add_modifier, Modifier::BOLD);
+    assert!((0..buffer.area.height).any(|y| row(&buffer, y).starts_with("│ >") && row(&buffer, y).contains("Lab")));
+}
+
+#[test]
+fn completing_a_task_cheers_for_a_moment() {
+    let mut harness = memory();
+    harness.press(KeyCode::Char('c'));
+    assert!(harness.lines().last().unwrap().contains("Done: Algo — HW1"));
+
+    harness.wait(constants::STATUS_MESSAGE_SECS);
+    assert!(!harness.screen().contains("Done:"));
+    assert!(harness.lines().last().unwrap().contains("completed"));
+
+    harness.press(KeyCode::Char(':'));
+    harness.type_text("today-done");
+    harness.press(KeyCode::Enter);
+    assert!(harness.screen().contains("Completed today: 1"));
+    assert!(harness.screen().contains("12:00  Algo — HW1"));
+}
+
+#[test]
+fn navigation_moves_the_selection_marker() {
+    let mut harness = memory();
+    assert!(harness.selected().contains("HW1"));
+
+    harness.press(KeyCode::Char('j'));
+    assert!(harness.selected().contains("Lab"));
+
+    harness.press(KeyCode::Char('G'));
+    assert!(harness.selected().contains("Quiz"));
+
+    harness.press(KeyCode::Char('g'));
+    assert!(harness.selected().contains("HW1"));
+
+    // Wraps around
+    harness.press(KeyCode::Char('k'));
+    assert!(harness.selected().contains("Quiz"));
+}
+
+#[test]
+fn selection_drives_the_description_pane() {
+    let mut harness = memory();
+    assert!(harness.screen().contains("About HW1"));
+
+    harness.press(KeyCode::Char('j'));
+    assert!(harness.screen().contains("About Lab"));
+}
+
+#[test]
+fn toggles_star_and_completion() {
+    let mut harness = memory();
+
+    harness.press(KeyCode::Char('s'));
+    assert!(harness.row("HW1").contains("*  Algo"));
+    assert!(!harness.row("Lab").contains('*'));
+
+    harness.press(KeyCode::Char('c'));
+    let lines = harness.lines();
+    let position = |name| lines.iter().position(|x| x.contains(name)).unwrap();
+    // Completed tasks sink to the bottom
+    assert!(position("HW1") > position("Quiz"));
+    assert!(harness.app.data.tasks.iter().any(|x| x.name == "HW1" && x.complete));
+}
+
+#[test]
+fn delete_asks_for_confirmation() {
+    let mut harness = memory();
+
+    harness.press(KeyCode::Char('d'));
+    assert!(harness.screen().contains("Would you like to remove task \"Algo: HW1\""));
+
+    harness.press(KeyCode::Esc);
+    assert!(harness.row("HW1").contains("Algo"));
+    assert_eq!(harness.app.data.tasks.len(), 3);
+
+    harness.press(KeyCode::Char('d'));
+    harness.press(KeyCode::Char('d'));
+    assert!(!harness.screen().contains("HW1"));
+    assert_eq!(harness.app.data.tasks.len(), 2);
+}
+
+#[test]
+fn description_pane_deletes_blank_tasks_without_asking() {
+    let mut harness = memory();
+    harness.press(KeyCode::Char('p'));
+    // Due the moment it was added, so already the most overdue
+    harness.press(KeyCode::Char('g'));
+    let blank = harness.app.data.selected().unwrap().id;
+    assert!(harness.app.data.selected().unwrap().is_default());
+
+    harness.press(KeyCode::Char('l'));
+    harness.press(KeyCode::Char('d'));
+    assert!(!harness.screen().contains("Would you like to remove task"));
+    assert!(harness.app.data.tasks.iter().all(|x| x.id != blank));
+    assert_eq!(harness.app.data.tasks.len(), 3);
+
+    harness.press(KeyCode::Char('d'));
+    assert!(harness.screen().contains("Would you like to remove task"));
+    harness.press(KeyCode::Esc);
+    assert_eq!(harness.app.data.tasks.len(), 3);
+}
+
+#[test]
+fn task_view_deletes_the_task_it_shows() {
+    let mut harness = memory();
+    harness.press(KeyCode::Enter);
+    harness.press(KeyCode::Char('d'));
+    assert!(harness.screen().contains("Would you like to remove task \"Algo: HW1\""));
+
+    harness.press(KeyCode::Char('d'));
+    assert!(!harness.screen().contains("HW1"));
+    assert_eq!(harness.app.data.tasks.len(), 2);
+    assert!(harness.app.data.index.is_some());
+}
+
+#[test]
+fn quit_without_changes_exits_right_away() {
+    let mut harness = memory();
+
+    harness.press(KeyCode::Char('q'));
+    assert!(harness.app.is_exiting());
+}
+
+#[test]
+fn quit_with_changes_offers_to_save() {
+    let (mut harness, storage) = spy();
+
+    harness.press(KeyCode::Char('s'));
+    harness.press(KeyCode::Char('q'));
+    assert!(harness.screen().contains("You have unsaved progress. Save it?"));
+    assert!(!harness.app.is_exiting());
+
+    harness.press(KeyCode::Char('y'));
+    assert!(harness.app.is_exiting());
+    assert!(storage.stored.borrow()[0].starred);
+}
+
+#[test]
+fn quit_can_discard_changes() {
+    let (mut harness, storage) = spy();
+
+    harness.press(KeyCode::Char('s'));
+    harness.press(KeyCode::Char('q'));
+    harness.press(KeyCode::Char('n'));
+
+    assert!(harness.app.is_exiting());
+    assert_eq!(*storage.stored.borrow(), tasks());
+}
+
+#[test]
+fn saving_over_an_outside_change_asks_first() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("tasks.toml");
+    TomlStorage::new(path.clone()).write(&tasks()).unwrap();
+    let write_outside = |name: &str| {
+        let mut outside = tasks();
+        outside[1].name = name.to_string();
+        TomlStorage::new(path.clone()).write(&outside).unwrap();
+        std::fs::read_to_string(&path).unwrap()
+    };
+
+    let mut harness = Harness::new(TomlStorage::new(path.clone()));
+    harness.press(KeyCode::Char('s'));
+    let outside = write_outside("Lab (outside)");
+    harness.press(KeyCode::Char('w'));
+    assert!(harness.screen().contains("o    overwrite it with yours"));
+    harness.press(KeyCode::Esc);
+    assert_eq!(std::fs::read_to_string(&path).unwrap(), outside);
+
+    // Reloading takes their version, undo brings back the star
+    harness.press(KeyCode::Char('w'));
+    harness.press(KeyCode::Char('r'));
+    assert!(harness.screen().contains("Lab (outside)"));
+    assert!(harness.app.data.tasks.iter().all(|x| !x.starred));
+    harness.press(KeyCode::Char('u'));
+    assert!(harness.app.data.tasks.iter().any(|x| x.starred));
+
+    write_outside("Lab (again)");
+    harness.press(KeyCode::Char('q'));
+    harness.press(KeyCode::Char('y'));
+    assert!(!harness.app.is_exiting());
+    harness.press(KeyCode::Char('o'));
+    assert!(harness.app.is_exiting());
+    let saved = TomlStorage::new(path.clone()).read().unwrap();
+    assert!(saved.iter().any(|x| x.starred) && saved.iter().all(|x| x.name != "Lab (again)"));
+}
+
+#[test]
+fn slow_storage_shows_a_splash_and_keeps_keys_away() {
+    let (open, gate) = mpsc::channel();
+    let mut harness = Harness::loading(SlowStorage { gate });
+    assert!(harness.screen().contains("Loading tasks…"));
+
+    harness.press(KeyCode::Char('p'));
+    harness.press(KeyCode::Char('d'));
+    assert!(harness.screen().contains("Loading tasks…"));
+    assert!(!harness.app.is_exiting());
+    assert_eq!(harness.app.loaded_summary(), None);
+
+    open.send(()).unwrap();
+    harness.wait_for("Loaded 3 tasks kept in memory");
+    assert!(harness.selected().contains("HW1"));
+    assert_eq!(harness.app.data.tasks, tasks());
+    // Kept as it was for stdout, whatever happens to the tasks after
+    harness.app.data.tasks.pop();
+    assert_eq!(harness.app.loaded_summary(), Some("Loaded 3 tasks kept in memory"));
+}
+
+#[test]
+fn a_damaged_file_opens_on_what_could_be_recovered() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("tasks.toml");
+    std::fs::write(&path, include_str!("../../tests/fixtures/truncated.toml")).unwrap();
+
+    let mut harness = Harness::loading(TomlStorage::new(path.clone()));
+    harness.wait_for("is damaged");
+    assert!(harness.screen().contains("2 of 3 task(s) recovered, 1 lost"));
+    // Only y goes on, anything else but q waits
+    harness.press(KeyCode::Char('n'));
+    assert!(harness.screen().contains("is damaged"));
+    assert!(!harness.app.is_exiting());
+
+    harness.press(KeyCode::Char('y'));
+    assert_eq!(harness.app.data.tasks.len(), 2);
+    // After the usual summary
+    harness.wait(constants::STATUS_MESSAGE_SECS);
+    assert!(harness.screen().contains("2 of 3 task(s) recovered, 1 lost, the damaged file is kept as"));
+    assert!(dir.path().join("tasks.toml.corrupt-20250301-120000").exists());
+    let mut shown = harness.app.data.tasks.clone();
+    shown.sort_by_key(|x| x.id);
+    assert_eq!(TomlStorage::new(path).read().unwrap(), shown);
+}
+
+#[test]
+fn failed_load_only_lets_the_app_quit() {
+    let (open, gate) = mpsc::channel::<()>();
+    let mut harness = Harness::loading(SlowStorage { gate });
+    harness.press(KeyCode::Char('q'));
+    assert!(harness.app.is_exiting());
+
+    let (open_too, gate) = mpsc::channel::<()>();
+    let mut harness = Harness::loading(SlowStorage { gate });
+    drop((open, open_too));
+    harness.wait_for("Could not load the tasks: network unreachable");
+    assert_eq!(harness.app.loaded_summary(), None);
+    harness.press(KeyCode::Enter);
+    harness.press(KeyCode::Char('p'));
+    assert!(harness.screen().contains("network unreachable"));
+    harness.press(KeyCode::Char('q'));
+    assert!(harness.app.is_exiting());
+}
+
+#[test]
+fn every_way_of_saving_writes_the_same_bytes() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("tasks.toml");
+    TomlStorage::new(path.clone()).write(&tasks()).unwrap();
+    let original = std::fs::read_to_string(&path).unwrap();
+
+    let mut harness = Harness::new(TomlStorage::new(path.clone()));
+    harness.press(KeyCode::Char('s'));
+    harness.press(KeyCode::Char('w'));
+    let saved = std::fs::read_to_string(&path).unwrap();
+    let edited = harness.app.data.tasks.clone();
+    assert_ne!(saved, original);
+    assert_eq!(std::fs::read_to_string(dir.path().join("tasks.toml.bak")).unwrap(), original);
+
+    std::fs::write(&path, &original).unwrap();
+    let mut harness = Harness::new(TomlStorage::new(path.clone()));
+    harness.press(KeyCode::Char('s'));
+    harness.press(KeyCode::Char('q'));
+    harness.press(KeyCode::Char('y'));
+    assert!(harness.app.is_exiting());
+    assert_eq!(std::fs::read_to_string(&path).unwrap(), saved);
+
+    // Whatever order the list is in
+    let direct = dir.path().join("direct.toml");
+    TomlStorage::new(direct.clone()).write(&edited.into_iter().rev().collect()).unwrap();
+    assert_eq!(std::fs::read_to_string(&direct).unwrap(), saved);
+}
+
+#[test]
+fn failed_save_keeps_the_app_open() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("tasks.toml");
+    TomlStorage::new(path.clone()).write(&tasks()).unwrap();
+    let mut harness = Harness::new(TomlStorage::new(path));
+    drop(dir);
+
+    harness.press(KeyCode::Char('s'));
+    harness.press(KeyCode::Char('w'));
+    assert!(harness.screen().contains("Could not save"));
+    harness.press(KeyCode::Enter);
+    harness.press(KeyCode::Char('q'));
+    harness.press(KeyCode::Char('y'));
+    assert!(!harness.app.is_exiting());
+    assert!(harness.screen().contains("Could not save"));
+}
+
+#[test]
+fn edit_goes_through_the_reader() {
+    let mut harness = memory();
+
+    harness.press(KeyCode::Char('e'));
+    assert!(harness.app.take_edit_request());
+    assert!(!harness.app.take_edit_request());
+
+    harness.app.edit().unwrap();
+    harness.render();
+    assert!(harness.selected().contains("Renamed"));
+}
+
+#[test]
+fn reader_is_chosen_per_app() {
+    let storage = SpyStorage::default();
+    storage.write(&tasks()).unwrap();
+    let mut harness = Harness::with_reader(storage, NoopReader);
+
+    harness.app.edit().unwrap();
+    harness.render();
+    assert!(harness.selected().contains("HW1"));
+
+    // Nothing changed, so nothing to save
+    harness.press(KeyCode::Char('q'));
+    assert!(harness.app.is_exiting());
+}
+
+fn filter_tasks() -> Vec<Task> {
+    let task = |name: &str, dated: bool, complete: bool| Task {
+        name: name.to_string(),
+        time: dated.then(|| now() + TimeDelta::days(3)),
+        complete,
+        ..Task::default()
+    };
+    vec![
+        task("dated open", true, false),
+        task("dated done", true, true),
+        task("undated open", false, false),
+        task("undated done", false, true),
+    ]
+}
+
+#[test]
+fn every_filter_combination_hides_exactly_its_tasks() {
+    let matrix = [
+        (false, false, vec!["dated open", "dated done", "undated open", "undated done"]),
+        (true, false, vec!["dated open", "undated open"]),
+        (false, true, vec!["dated open", "dated done"]),
+        (true, true, vec!["dated open"]),
+    ];
+
+    for (hide_completed, hide_no_deadline, expected) in matrix {
+        let mut data = Data::new(filter_tasks());
+        data.index = Some(3);
+        if hide_completed { data.toggle_hide_completed() }
+        if hide_no_deadline { data.toggle_hide_no_deadline() }
+
+        let visible = data.iter().map(|x| x.name.as_str()).collect::<Vec<_>>();
+        assert_eq!(visible, expected, "{:?}", data.filters);
+        // The selection is pulled back onto the last visible task
+        assert_eq!(data.index, Some(expected.len() - 1), "{:?}", data.filters);
+    }
+}
+
+#[test]
+fn selection_survives_everything_being_hidden() {
+    let mut data = Data::new(filter_tasks().split_off(3));
+
+    data.toggle_hide_completed();
+    assert_eq!(data.len(), 0);
+    assert_eq!(data.index, None);
+
+    data.toggle_hide_completed();
+    assert_eq!(data.index, Some(0));
+}
+
+#[test]
+fn nothing_selected_shows_starred_and_upcoming_tasks() {
+    let mut harness = memory();
+    harness.app.data.tasks[1].starred = true;
+    harness.type_text(":filter stubs");
+    harness.press(KeyCode::Enter);
+    assert_eq!(harness.app.data.index, None);
+
+    let screen = harness.screen();
+    assert!(screen.contains("Pinned"));
+    assert!(harness.row("Physics  Lab  5 days").contains("││ Physics  Lab  5 days"));
+    assert!(screen.contains("Next up"));
+    assert!(screen.contains("Algo  HW1  1 day"));
+    assert!(screen.contains("Databases  Quiz  10 days"));
+
+    let harness = Harness::new(MemoryStorage::with_tasks(vec![]));
+    assert!(harness.screen().contains("No tasks yet"));
+    assert!(harness.screen().contains("p  add a task"));
+    assert!(!harness.screen().contains("Pinned"));
+}
+
+#[test]
+fn preset_toggles_as_a_whole() {
+    let mut data = Data::new(filter_tasks());
+    data.config.ui.filter_preset = Filters { hide_completed: true, hide_no_deadline: true, ..Filters::default() };
+
+    data.apply_filter_preset();
+    assert_eq!(data.len(), 1);
+
+    data.apply_filter_preset();
+    assert!(data.filters.is_empty());
+
+    // A partially matching state is completed to the preset first
+    data.toggle_hide_completed();
+    data.apply_filter_preset();
+    assert_eq!(data.filters, data.config.ui.filter_preset);
+}
+
+#[test]
+fn selection_accessors_map_the_visible_index_to_the_task() {
+    let mut data = Data::new(tasks());
+    data.filters.subject = Some("Physics".to_string());
+    data.index = Some(0);
+    assert_eq!(data.selected_underlying_index(), Some(1));
+    assert_eq!(data.selected().map(|x| x.name.as_str()), Some("Lab"));
+    data.selected_mut().unwrap().name = "Lab 2".to_string();
+    assert_eq!(data.tasks[1].name, "Lab 2");
+
+    // Past the visible tasks, though not past all of them
+    data.index = Some(2);
+    assert_eq!(data.selected_underlying_index(), None);
+    assert!(data.selected().is_none());
+    data.index = None;
+    assert!(data.selected_mut().is_none());
+
+    let mut empty = Data::new(vec![]);
+    empty.index = Some(0);
+    assert!(empty.selected().is_none());
+}
+
+#[test]
+fn changes_under_a_filter_reach_the_selected_task() {
+    let mut harness = Harness::starting(Startup { subject: Some("Physics".to_string()), ..Startup::default() });
+    harness.press(KeyCode::Char('s'));
+    harness.press(KeyCode::Char('c'));
+    harness.app.edit().unwrap();
+
+    let lab = &harness.app.data.tasks.iter().find(|x| x.id == 2).unwrap();
+    assert!(lab.starred && lab.complete);
+    assert_eq!(lab.name, "Renamed");
+    assert!(harness.app.data.tasks.iter().filter(|x| x.id != 2).all(|x| !x.starred && !x.complete && x.name != "Renamed"));
+}
+
+#[test]
+fn status_bar_shows_the_filters() {
+    let mut harness = memory();
+    assert!(harness.screen().contains("all tasks shown"));
+
+    harness.press(KeyCode::Char('c'));
+    harness.press(KeyCode::Char('C'));
+    harness.wait(constants::STATUS_MESSAGE_SECS);
+    assert!(harness.screen().contains("1 hidden"));
+    assert!(!harness.screen().contains("HW1"));
+}
+
+#[test]
+fn starred_filter_composes_with_the_others() {
+    let mut harness = memory();
+    harness.app.data.tasks[1].starred = true;
+    harness.app.data.tasks[2].starred = true;
+    harness.app.data.tasks[2].complete = true;
+    harness.press(KeyCode::Char('G'));
+    assert!(harness.screen().contains("★ 2"));
+
+    harness.press(KeyCode::Char('*'));
+    assert!(harness.screen().contains("Tasks [★]"));
+    assert!(!harness.screen().contains("HW1"));
+    assert!(harness.selected().contains("Quiz"));
+
+    harness.press(KeyCode::Char('C'));
+    assert!(harness.selected().contains("Lab"));
+    assert!(!harness.screen().contains("Quiz"));
+    assert!(harness.screen().contains("2 hidden"));
+}
+
+#[test]
+fn urgent_view_restores_the_previous_filters() {
+    let mut harness = memory();
+    harness.app.data.tasks[2].starred = true;
+    harness.press(KeyCode::Char('U'));
+    harness.press(KeyCode::Char('j'));
+    let before = harness.app.data.filters.clone();
+
+    harness.press(KeyCode::Char('a'));
+    assert!(harness.screen().contains("Tasks [URGENT]"));
+    // Due tomorrow and starred, the rest is hidden
+    assert!(harness.row("HW1").contains(" > "));
+    assert!(harness.screen().contains("Quiz"));
+    assert!(!harness.screen().contains("Lab"));
+
+    harness.press(KeyCode::Char(' '));
+    assert!(!harness.screen().contains("URGENT"));
+    assert_eq!(harness.app.data.filters, before);
+    assert!(harness.row("HW1").contains(" > "));
+}
+
+#[test]
+fn urgent_view_skips_completed_tasks() {
+    let mut data = Data::new(filter_tasks());
+    data.clock = Clock::Fixed(now() + TimeDelta::days(2));
+
+    data.toggle_view(View::URGENT);
+    let visible = data.iter().map(|x| x.name.as_str()).collect::<Vec<_>>();
+    assert_eq!(visible, vec!["dated open"]);
+}
+
+#[test]
+fn table_title_carries_a_badge_per_state() {
+    let mut harness = memory();
+    let title = |harness: &Harness| harness.lines()[0].clone();
+    assert!(title(&harness).contains(" Tasks — next: Algo HW1 in 1d"));
+
+    harness.press(KeyCode::Char('*'));
+    harness.press(KeyCode::Char('3'));
+    harness.press(KeyCode::Char('3'));
+    assert!(title(&harness).contains(" Tasks [★][sort: name ▼] "));
+
+    harness.press(KeyCode::Char('a'));
+    // The view brings its own filters
+    assert!(title(&harness).contains(" Tasks [URGENT][sort: name ▼] "));
+
+    harness.press(KeyCode::Char('a'));
+    assert!(title(&harness).contains(" Tasks [★][sort: name ▼] "));
+}
+
+#[test]
+fn undated_tasks_left_alone_are_escalated() {
+    let mut tasks = tasks();
+    tasks[1].time = None;
+    tasks[1].updated_at = Some(now() - TimeDelta::days(21));
+    let mut harness = Harness::new(MemoryStorage::with_tasks(tasks));
+
+    harness.press(KeyCode::Char('a'));
+    assert!(!harness.screen().contains("Lab"));
+    harness.press(KeyCode::Char(' '));
+
+    harness.app.data.config.ui.escalate_after_days = Some(14);
+    harness.press(KeyCode::Char('a'));
+    assert!(harness.screen().contains("Lab"));
+    harness.press(KeyCode::Char('j'));
+    assert!(harness.screen().contains("Escalated: no activity for 21 days"));
+}
+
+#[test]
+fn detail_popup_shows_the_whole_task() {
+    let mut harness = memory();
+    harness.press(KeyCode::Char('j'));
+
+    harness.press(KeyCode::Enter);
+    assert!(harness.screen().contains("Subject   Physics"));
+    assert!(harness.screen().contains("About Lab"));
+
+    harness.press(KeyCode::Esc);
+    assert!(harness.selected().contains("Lab"));
+}
+
+#[test]
+fn detail_popup_scrolls_long_descriptions() {
+    let mut harness = memory();
+    harness.app.data.tasks[0].description = (1..=40).map(|x| format!("line {x}")).collect::<Vec<_>>().join("\n");
+
+    // The task list stays drawn behind the popup, so look for the field rather than the column
+    harness.press(KeyCode::Char('K'));
+    assert!(harness.screen().contains("Name      HW1"));
+    assert!(!harness.screen().contains("line 40"));
+
+    for _ in 0..100 {
+        harness.press(KeyCode::Char('j'));
+    }
+    assert!(!harness.screen().contains("Name      HW1"));
+    assert!(harness.screen().contains("line 40"));
+
+    harness.press(KeyCode::Char('q'));
+    assert!(harness.selected().contains("HW1"));
+}
+
+#[test]
+fn palette_shift_is_confirmed_and_undone_at_once() {
+    let mut harness = memory();
+    harness.app.data.tasks.push(Task {
+        id: 4,
+        subject: "Algo".to_string(),
+        name: "HW2".to_string(),
+        time: Some(now() + TimeDelta::days(3)),
+        ..Task::default()
+    });
+    harness.render();
+    let before = harness.app.data.tasks.clone();
+
+    harness.type_text(":shift algo +7d");
+    assert!(harness.screen().contains(":shift algo +7d"));
+    harness.press(KeyCode::Enter);
+    assert!(harness.screen().contains("Shift 2 deadline(s) in algo by +7d?"));
+    assert!(harness.screen().contains("HW1: Sun 02 Mar 2025 12:00 → Sun 09 Mar 2025 12:00"));
+
+    harness.press(KeyCode::Char('y'));
+    assert!(harness.row("HW1").contains("8 days"));
+    assert!(harness.row("HW2").contains("10 days"));
+
+    harness.press(KeyCode::Char('u'));
+    assert_eq!(harness.app.data.tasks, before);
+}
+
+#[test]
+fn relative_deadlines_follow_their_anchor() {
+    let mut harness = memory();
+    harness.app.data.tasks.push(Task {
+        id: 4,
+        subject: "Algo".to_string(),
+        name: "Review".to_string(),
+        time: None,
+        anchor: Some(Anchor { task: 1, offset: TimeDelta::days(2) }),
+        ..Task::default()
+    });
+    harness.render();
+    assert!(harness.row("Review").contains("3 days"));
+    harness.press(KeyCode::Char('j'));
+    assert!(harness.screen().contains("Follows: 2d after Algo: HW1"));
+
+    // Only the anchor is shifted, the review comes along
+    harness.type_text(":shift algo +7d");
+    harness.press(KeyCode::Enter);
+    assert!(harness.screen().contains("Shift 1 deadline(s) in algo by +7d?"));
+    harness.press(KeyCode::Char('y'));
+    assert!(harness.row("Review").contains("10 days"));
+
+    harness.app.data.tasks.retain(|x| x.id != 1);
+    harness.render();
+    harness.press(KeyCode::Char('j'));
+    assert!(harness.selected().contains("Review"));
+    assert!(harness.screen().contains("Deadline: None"));
+    assert!(harness.screen().contains("Follows: 2d after task 1, but there is no such"));
+    assert_eq!(harness.app.data.broken_anchors.len(), 1);
+}
+
+#[test]
+fn palette_reports_bad_input() {
+    let mut harness = memory();
+
+    harness.type_text(":shift Algo soon");
+    harness.press(KeyCode::Enter);
+    assert!(harness.screen().contains("Expected a number before"));
+    harness.press(KeyCode::Esc);
+
+    harness.type_text(":frobnicate");
+    harness.press(KeyCode::Enter);
+    assert!(harness.screen().contains("Unknown command \"frobnicate\""));
+}
+
+#[test]
+fn file_command_echoes_where_the_tasks_came_from() {
+    let mut harness = memory();
+
+    harness.type_text(":file");
+    harness.press(KeyCode::Enter);
+    assert!(harness.screen().contains("Loaded 3 tasks kept in memory"));
+    harness.press(KeyCode::Esc);
+
+    harness.app.data.location = Some(PathBuf::from("/srv/unist/tasks.toml"));
+    assert_eq!(harness.app.data.summary(), "Loaded 3 tasks from /srv/unist/tasks.toml");
+}
+
+#[test]
+fn scrolling_keeps_the_selection_and_the_header_on_screen() {
+    let homework = (1..=100)
+        .map(|x| Task { id: x, subject: "Algo".to_string(), name: format!("HW{x}"), time: Some(now() + TimeDelta::days(x as i64)), ..Task::default() })
+        .collect();
+    let mut harness = Harness::new(MemoryStorage::with_tasks(homework));
+    // 13 rows for the pane, so 10 for tasks
+    harness.terminal.backend_mut().resize(100, 14);
+    harness.render();
+    // The first task drawn says how far the table is scrolled, the header stays above it
+    let top = |harness: &Harness| {
+        let lines = harness.lines();
+        assert!(lines[1].contains("Name"), "{}", harness.screen());
+        lines[2].split_whitespace().find(|x| x.starts_with("HW")).unwrap().to_string()
+    };
+    let bar = |harness: &Harness, row: usize| harness.lines()[row].chars().nth(49).unwrap();
+    assert_eq!(top(&harness), "HW1");
+    assert_eq!((bar(&harness, 2), bar(&harness, 11)), ('█', '│'));
+
+    harness.press(KeyCode::Char('G'));
+    assert_eq!(top(&harness), "HW91");
+    assert!(harness.selected().contains("HW100 "));
+    assert_eq!((bar(&harness, 2), bar(&harness, 11)), ('│', '█'));
+    harness.press(KeyCode::Char('g'));
+    assert_eq!(top(&harness), "HW1");
+
+    // Paging moves the view only as far as the selection needs
+    harness.press_ctrl('f');
+    assert!(harness.selected().contains("HW11 "));
+    assert_eq!(top(&harness), "HW2");
+    harness.press_ctrl('d');
+    assert!(harness.selected().contains("HW16 "));
+    assert_eq!(top(&harness), "HW7");
+    harness.press_ctrl('u');
+    harness.press_ctrl('b');
+    assert!(harness.selected().contains("HW1 "));
+    assert_eq!(top(&harness), "HW1");
+
+    // Keys reading the view see it scrolled before the next frame
+    harness.app.handle_key_event(KeyEvent::new(KeyCode::Char('G'), KeyModifiers::NONE)).unwrap();
+    harness.press(KeyCode::Char('H'));
+    assert!(harness.selected().contains("HW91 "));
+}
+
+#[test]
+fn long_lists_page_by_the_height_of_the_table() {
+    let homework = (1..=40)
+        .map(|x| Task { id: x, subject: "Algo".to_string(), name: format!("HW{x}"), time: Some(now() + TimeDelta::days(x as i64)), ..Task::default() })
+        .collect();
+    let mut harness = Harness::new(MemoryStorage::with_tasks(homework));
+    assert!(harness.screen().contains("1/40"));
+
+    // 19 rows for the pane, less borders and header
+    harness.press_ctrl('d');
+    assert_eq!(harness.app.data.index, Some(8));
+    harness.press_ctrl('f');
+    assert_eq!(harness.app.data.index, Some(24));
+    assert!(harness.screen().contains("25/40"));
+    harness.press_ctrl('u');
+    assert_eq!(harness.app.data.index, Some(16));
+    harness.press_ctrl('b');
+    assert_eq!(harness.app.data.index, Some(0));
+    // From the first task back comes around, as k does
+    harness.press_ctrl('b');
+    assert_eq!(harness.app.data.index, Some(39));
+    harness.press_ctrl('d');
+    assert_eq!(harness.app.data.index, Some(0));
+
+    harness.press(KeyCode::Char('G'));
+    harness.press(KeyCode::Char('H'));
+    assert!(harness.selected().contains("HW25 "));
+    harness.press(KeyCode::Char('M'));
+    assert!(harness.selected().contains("HW32 "));
+    harness.press(KeyCode::Char('L'));
+    assert!(harness.selected().contains("HW40 "));
+    // Ctrl-u pages rather than undoing
+    assert_eq!(harness.app.data.tasks.len(), 40);
+}
+
+#[test]
+fn undo_reverts_toggles_one_by_one() {
+    let mut harness = memory();
+
+    harness.press(KeyCode::Char('s'));
+    harness.press(KeyCode::Char('j'));
+    harness.press(KeyCode::Char('s'));
+    assert_eq!(harness.app.data.tasks.iter().filter(|x| x.starred).count(), 2);
+
+    harness.press(KeyCode::Char('u'));
+    assert_eq!(harness.app.data.tasks.iter().filter(|x| x.starred).count(), 1);
+    harness.press(KeyCode::Char('u'));
+    harness.press(KeyCode::Char('u'));
+    assert_eq!(harness.app.data.tasks, tasks());
+}
+
+#[test]
+fn editing_a_deadline_into_the_past_asks_first() {
+    for (answer, kept) in [('y', true), ('n', false)] {
+        let mut harness = Harness::with_reader(MemoryStorage::with_tasks(tasks()), PastReader);
+
+        harness.press(KeyCode::Char('e'));
+        harness.app.edit().unwrap();
+        harness.render();
+        assert!(harness.screen().contains("Deadline is 142 days in the past — keep it? (y/n)"));
+
+        harness.press(KeyCode::Char(answer));
+        let task = harness.app.data.tasks.iter().find(|x| x.id == 1).unwrap();
+        assert_eq!(task.time == tasks()[0].time, !kept);
+    }
+}
+
+#[test]
+fn shifting_into_the_past_is_pointed_out() {
+    let mut harness = memory();
+
+    harness.type_text(":shift Physics -6d");
+    harness.press(KeyCode::Enter);
+    assert!(harness.screen().contains("is 1 day in the past)"));
+}
+
+#[test]
+fn export_writes_the_visible_tasks_and_asks_before_overwriting() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("week.md");
+    let mut harness = memory();
+    harness.app.data.tasks[1].complete = true;
+    harness.press(KeyCode::Char('C'));
+
+    harness.press_ctrl('e');
+    harness.type_text(path.to_str().unwrap());
+    harness.press(KeyCode::Enter);
+    assert!(harness.screen().contains("Exported 2 task(s)"));
+    let content = std::fs::read_to_string(&path).unwrap();
+    assert!(content.starts_with("- [ ] **Algo**: HW1"));
+    assert!(!content.contains("Lab"));
+
+    harness.press(KeyCode::Enter);
+    harness.press(KeyCode::Char('C'));
+    harness.press_ctrl('e');
+    harness.type_text(path.to_str().unwrap());
+    harness.press(KeyCode::Enter);
+    assert!(harness.screen().contains("already exists"));
+    harness.press(KeyCode::Char('n'));
+    assert_eq!(std::fs::read_to_string(&path).unwrap(), content);
+}
+
+#[test]
+fn export_leaves_completed_tasks_out_unless_tab_lets_them_in() {
+    let dir = tempfile::tempdir().unwrap();
+    let mut harness = memory();
+    harness.app.data.tasks[1].complete = true;
+
+    for (name, tab) in [("default.md", false), ("completed.md", true)] {
+        let path = dir.path().join(name);
+        harness.press_ctrl('e');
+        assert!(harness.screen().contains("Completed tasks left out"));
+        if tab {
+            harness.press(KeyCode::Tab);
+            assert!(harness.screen().contains("Completed tasks included"));
+        }
+        harness.type_text(path.to_str().unwrap());
+        harness.press(KeyCode::Enter);
+        assert_eq!(std::fs::read_to_string(&path).unwrap().contains("Lab"), tab);
+        harness.press(KeyCode::Enter);
+    }
+}
+
+#[test]
+fn quick_add_completes_subjects_and_confirms_new_ones() {
+    let mut harness = memory();
+
+    harness.press(KeyCode::Char('n'));
+    harness.type_text("phy");
+    assert!(harness.screen().contains("› Physics"));
+    harness.press(KeyCode::Tab);
+    harness.type_text("Lab 2");
+    harness.press(KeyCode::Tab);
+    harness.type_text("+2d");
+    harness.press(KeyCode::Enter);
+    let task = harness.app.data.tasks.iter().find(|x| x.name == "Lab 2").unwrap();
+    assert_eq!((task.subject.as_str(), task.time), ("Physics", Some(now() + TimeDelta::days(2))));
+
+    harness.press(KeyCode::Char('n'));
+    harness.type_text("Algoritms");
+    harness.press(KeyCode::BackTab);
+    harness.press(KeyCode::BackTab);
+    harness.type_text("HW2");
+    harness.press(KeyCode::Enter);
+    assert!(harness.screen().contains("\"Algoritms\" is a new subject"));
+    assert_eq!(harness.app.data.tasks.len(), 4);
+    harness.press(KeyCode::Enter);
+    assert!(harness.app.data.tasks.iter().any(|x| x.subject == "Algoritms"));
+}
+
+#[test]
+fn quick_add_puts_a_bare_day_at_the_default_deadline_time() {
+    let mut harness = memory();
+    harness.app.data.config.default_deadline_time = Some("08:30".to_string());
+
+    harness.press(KeyCode::Char('n'));
+    harness.type_text("Physics");
+    harness.press(KeyCode::Tab);
+    harness.type_text("Report");
+    harness.press(KeyCode::Tab);
+    harness.type_text("tomorrow");
+    harness.press(KeyCode::Enter);
+    harness.press(KeyCode::Char('n'));
+    harness.type_text("Physics");
+    harness.press(KeyCode::Tab);
+    harness.type_text("Slides");
+    harness.press(KeyCode::Tab);
+    harness.type_text("tomorrow 17:00");
+    harness.press(KeyCode::Enter);
+
+    let due = |name| harness.app.data.tasks.iter().find(|x| x.name == name).unwrap().time.unwrap().to_rfc3339();
+    assert_eq!(due("Report"), "2025-03-02T08:30:00+00:00");
+    assert_eq!(due("Slides"), "2025-03-02T17:00:00+00:00");
+}
+
+#[test]
+fn finder_previews_the_match_and_selects_it() {
+    let mut harness = memory();
+    harness.press(KeyCode::Char('*'));
+    assert_eq!(harness.app.data.len(), 0);
+
+    harness.press_ctrl('p');
+    assert!(harness.screen().contains("3/3"));
+    harness.type_text("about lab");
+    assert!(harness.screen().contains("1/3"));
+    assert!(harness.screen().contains("Physics — Lab"));
+    assert!(harness.screen().contains("About Lab"));
+
+    // Starred only hid it, so the filter goes
+    harness.press(KeyCode::Enter);
+    assert!(!harness.app.data.filters.only_starred);
+    assert!(harness.selected().contains("Lab"));
+}
+
+#[test]
+fn stubs_are_marked_and_can_be_listed_alone() {
+    let mut tasks = tasks();
+    tasks[1].description = " \n".to_string();
+    let mut harness = Harness::new(MemoryStorage::with_tasks(tasks));
+    assert!(harness.row("Lab").contains("Lab ✎"));
+    assert!(!harness.row("HW1").contains('✎'));
+
+    harness.press(KeyCode::Char(':'));
+    harness.type_text("filter stubs");
+    harness.press(KeyCode::Enter);
+    assert_eq!(harness.app.data.len(), 1);
+    assert!(harness.selected().contains("Lab"));
+    assert!(harness.screen().contains("Tasks [✎]"));
+
+    harness.press(KeyCode::Char(':'));
+    harness.type_text("filter stubs");
+    harness.press(KeyCode::Enter);
+    assert_eq!(harness.app.data.len(), 3);
+}
+
+#[test]
+fn quick_add_points_out_a_likely_duplicate() {
+    let mut harness = memory();
+
+    harness.press(KeyCode::Char('n'));
+    harness.type_text("Algo");
+    harness.press(KeyCode::Tab);
+    harness.type_text("hw 1");
+    harness.press(KeyCode::Enter);
+    assert!(harness.screen().contains("Similar task exists: Algo — HW1, due Sun 02 Mar"));
+    assert_eq!(harness.app.data.tasks.len(), 3);
+
+    harness.press(KeyCode::Enter);
+    assert_eq!(harness.app.data.tasks.len(), 4);
+}
+
+#[test]
+fn pasted_lines_are_previewed_and_added_under_one_subject() {
+    let mut harness = memory();
+
+    harness.press_ctrl('v');
+    // Without bracketed paste a terminal types the text in, newlines as Enter
+    for line in ["- HW3 – Mar 12", "Week 4 overview", "Quiz due tomorrow 10:00"] {
+        harness.type_text(line);
+        harness.press(KeyCode::Enter);
+    }
+    harness.press_ctrl('s');
+    let screen = harness.screen();
+    assert!(screen.contains("[x] HW3 · Wed 12 Mar"), "{screen}");
+    assert!(screen.contains("[x] Week 4 overview · no deadline"));
+    assert!(screen.contains("[x] Quiz · Sun 02 Mar 10:00"));
+
+    harness.press(KeyCode::Char('j'));
+    harness.press(KeyCode::Char(' '));
+    assert!(harness.screen().contains("[ ] Week 4 overview"));
+    harness.press(KeyCode::Enter);
+    harness.press(KeyCode::Enter);
+    assert!(harness.screen().contains("The subject can't be empty"));
+    harness.type_text("phy");
+    harness.press(KeyCode::Tab);
+    harness.press(KeyCode::Enter);
+
+    let mut added: Vec<_> = harness.app.data.tasks.iter().filter(|x| x.id > 3).map(|x| (x.subject.as_str(), x.name.as_str())).collect();
+    added.sort();
+    assert_eq!(added, [("Physics", "HW3"), ("Physics", "Quiz")]);
+    assert!(harness.screen().contains("Added 2 task(s) to Physics"));
+    // Created by the app's clock, not the wall clock
+    let now = harness.app.data.now();
+    assert!(harness.app.data.tasks.iter().filter(|x| x.id > 3).all(|x| x.created_at == Some(now)));
+
+    // One undo takes back the whole paste
+    harness.press(KeyCode::Char('u'));
+    assert_eq!(harness.app.data.tasks.len(), 3);
+
+    harness.press(KeyCode::Char(':'));
+    harness.type_text("paste-import");
+    harness.press(KeyCode::Enter);
+    assert!(harness.screen().contains("Paste tasks"));
+}
+
+#[test]
+fn unsaved_changes_survive_a_crash_through_the_journal() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("tasks.toml");
+    let journal = dir.path().join("tasks.toml.journal");
+    TomlStorage::new(path.clone()).write(&tasks()).unwrap();
+
+    let mut harness = Harness::new(TomlStorage::new(path.clone()));
+    harness.app.update_journal();
+    assert!(!journal.exists());
+    harness.press(KeyCode::Char('c'));
+    harness.app.update_journal();
+    assert!(journal.exists());
+    // Dropped without saving, as a crash would
+    drop(harness);
+
+    let mut harness = Harness::new(TomlStorage::new(path.clone()));
+    assert!(harness.app.data.tasks.iter().all(|x| !x.complete));
+    harness.app.offer_recovery();
+    harness.render();
+    assert!(harness.screen().contains("Recover them?"));
+    harness.press(KeyCode::Char('y'));
+    assert!(harness.app.data.tasks.iter().any(|x| x.complete));
+
+    harness.press(KeyCode::Char('w'));
+    assert!(!journal.exists());
+    assert!(TomlStorage::new(path).read().unwrap().iter().any(|x| x.complete));
+}
+
+#[test]
+fn a_journal_that_cannot_be_dropped_is_an_error_on_leaving() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("tasks.toml");
+    // Stands in for a journal the user may not remove
+    std::fs::create_dir_all(dir.path().join("tasks.toml.journal").join("in the way")).unwrap();
+    TomlStorage::new(path.clone()).write(&tasks()).unwrap();
+
+    let mut harness = Harness::new(TomlStorage::new(path));
+    harness.press(KeyCode::Char('c'));
+    harness.press(KeyCode::Char('q'));
+    let result = harness.app.handle_key_event(KeyEvent::new(KeyCode::Char('n'), KeyModifiers::NONE));
+    assert!(result.is_err());
+}
+
+#[test]
+fn deadlines_can_be_picked_on_a_calendar() {
+    let mut harness = memory();
+
+    harness.press(KeyCode::Char('D'));
+    assert!(harness.screen().contains("Mar 2025"));
+    assert!(harness.screen().contains("Sun 2 Mar — 1 task: 1 due"));
+    harness.type_text("ll");
+    harness.press(KeyCode::Enter);
+    harness.press(KeyCode::Enter);
+    assert_eq!(harness.app.data.tasks[0].time, Some(now() + TimeDelta::days(3)));
+
+    harness.press(KeyCode::Char('n'));
+    harness.type_text("Algo");
+    harness.press(KeyCode::Tab);
+    harness.type_text("Essay");
+    harness.press(KeyCode::Tab);
+    harness.press_ctrl('d');
+    harness.type_text("j");
+    harness.press(KeyCode::Enter);
+    harness.press(KeyCode::Enter);
+    assert!(harness.screen().contains("2025-03-08 23:59"));
+    harness.press(KeyCode::Enter);
+    let task = harness.app.data.tasks.iter().find(|x| x.name == "Essay").unwrap();
+    assert_eq!(task.time, Some(DateTime::parse_from_rfc3339("2025-03-08T23:59:00+00:00").unwrap()));
+}
+
+#[test]
+fn moving_a_deadline_onto_a_full_day_warns() {
+    let mut harness = memory();
+    harness.app.data.config.validation.max_tasks_per_day = Some(1);
+    harness.render();
+
+    // HW1 from Sunday onto Thursday, where Lab is due
+    harness.press(KeyCode::Char('D'));
+    harness.type_text("llll");
+    harness.press(KeyCode::Enter);
+    harness.press(KeyCode::Enter);
+    assert!(harness.screen().contains("Thu 6 Mar now has 2 tasks due, over the limit of 1"));
+
+    // Completing one makes room without a word
+    harness.press(KeyCode::Char('c'));
+    harness.wait(10);
+    assert!(!harness.screen().contains("over the limit"));
+}
+
+#[test]
+fn resizing_recenters_the_popup_over_a_redrawn_background() {
+    let mut harness = memory();
+    harness.press(KeyCode::Char(':'));
+
+    harness.terminal.backend_mut().resize(80, 11);
+    harness.app.handle_event(Event::Resize(80, 11)).unwrap();
+    assert!(harness.app.take_resize());
+    harness.render();
+
+    let lines = harness.lines();
+    let top = lines.iter().position(|x| x.contains("Command")).unwrap();
+    assert_eq!(top, 4);
+    let border = lines[top].chars().collect::<Vec<_>>();
+    let left = border.iter().position(|x| *x == '╭').unwrap();
+    let right = border.len() - 1 - border.iter().rposition(|x| *x == '╮').unwrap();
+    assert!(left > 0 && left.abs_diff(right) <= 1, "{}", lines[top]);
+    assert!(lines[2].contains("Algo"), "{}", harness.screen());
+    assert!(lines[10].contains("? help"));
+}
+
+#[test]
+fn ctrl_c_quits_like_q() {
+    let mut harness = memory();
+    harness.press_ctrl('c');
+    assert!(harness.app.is_exiting());
+    assert!(harness.app.data.tasks.iter().all(|x| !x.complete));
+
+    let storage = SpyStorage::default();
+    storage.write(&tasks()).unwrap();
+    let mut harness = Harness::new(storage);
+    harness.press(KeyCode::Char('c'));
+    harness.press(KeyCode::Char('?'));
+    harness.press_ctrl('c');
+    assert!(harness.screen().contains("You have unsaved progress"));
+    assert!(!harness.app.is_exiting());
+}
+
+#[cfg(unix)]
+#[test]
+fn sigterm_journals_unsaved_changes_and_exits() {
+    use std::sync::Arc;
+    use std::sync::atomic::AtomicBool;
+    use signal_hook::consts::SIGTERM;
+
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("tasks.toml");
+    TomlStorage::new(path.clone()).write(&tasks()).unwrap();
+    let mut harness = Harness::new(TomlStorage::new(path.clone()));
+    let terminate = Arc::new(AtomicBool::new(false));
+    signal_hook::flag::register(SIGTERM, Arc::clone(&terminate)).unwrap();
+    harness.app.terminate_on(terminate);
+
+    harness.press(KeyCode::Char('c'));
+    assert!(!harness.app.check_termination());
+    signal_hook::low_level::raise(SIGTERM).unwrap();
+    assert!(harness.app.check_termination());
+    assert!(harness.app.is_exiting());
+    assert!(dir.path().join("tasks.toml.journal").exists());
+}
+
+#[test]
+fn tasks_due_today_get_a_badge_and_may_go_first() {
+    let mut tasks = tasks();
+    tasks[1].time = Some(now() + TimeDelta::hours(2));
+    tasks[2].time = Some(now() - TimeDelta::days(2));
+    let mut harness = Harness::new(MemoryStorage::with_tasks(tasks));
+    assert!(harness.row("Lab").contains(" • Physics"));
+    assert!(!harness.row("Quiz").contains('•'));
+    assert!(harness.selected().contains("Quiz"));
+
+    harness.app.data.config.ui.today_first = true;
+    harness.render();
+    harness.press(KeyCode::Char('g'));
+    assert!(harness.selected().contains("Lab"));
+}
+
+#[test]
+fn undated_tasks_gather_under_their_own_header() {
+    let mut tasks = tasks();
+    tasks.insert(0, Task { id: 4, subject: "Writing".to_string(), name: "Essay".to_string(), time: None, ..Task::default() });
+    let mut harness = Harness::new(MemoryStorage::with_tasks(tasks));
+    harness.app.data.cycle_sort(SortColumn::Name);
+    harness.render();
+    let lines = harness.lines();
+    let at = |needle| lines.iter().position(|x| x.contains(needle)).unwrap();
+    // Ahead of Lab and Quiz by name, still below them
+    assert!(at("Quiz") < at("— no deadline —") && at("— no deadline —") + 1 == at("Essay"));
+
+    // Moving steps over the header both ways
+    harness.press(KeyCode::Char('g'));
+    harness.type_text("jj");
+    assert!(harness.selected().contains("Quiz"));
+    harness.press(KeyCode::Char('j'));
+    assert!(harness.selected().contains("Essay"));
+    harness.press(KeyCode::Char('k'));
+    assert!(harness.selected().contains("Quiz"));
+
+    harness.app.data.config.ui.undated_position = UndatedPosition::Top;
+    harness.render();
+    harness.press(KeyCode::Char('g'));
+    assert!(harness.selected().contains("Essay"));
+    assert!(harness.lines()[2].contains("— no deadline —"));
+}
+
+#[test]
+fn completed_tasks_go_below_undated_ones() {
+    let mut tasks = tasks();
+    tasks[0].complete = true;
+    tasks.push(Task { id: 4, subject: "Writing".to_string(), name: "Essay".to_string(), time: None, ..Task::default() });
+    let harness = Harness::new(MemoryStorage::with_tasks(tasks));
+    let lines = harness.lines();
+    let at = |needle| lines.iter().position(|x| x.contains(needle)).unwrap();
+    assert!(at("Lab") < at("Quiz") && at("Quiz") < at("— no deadline —"));
+    assert!(at("— no deadline —") + 1 == at("Essay") && at("Essay") < at("HW1"));
+}
+
+#[test]
+fn duplicates_are_pointed_out_and_dropped_at_once() {
+    let mut tasks = tasks();
+    let copy = |id, subject: &str, name: &str, days| Task {
+        id,
+        subject: subject.to_string(),
+        name: name.to_string(),
+        time: Some(now() + TimeDelta::days(days)),
+        ..Task::default()
+    };
+    tasks.push(copy(4, "algo ", "HW1", 1));
+    tasks.push(copy(5, "Algo", "hw1", 1));
+    tasks.push(copy(6, "Physics", "LAB", 12));
+    let mut harness = Harness::new(MemoryStorage::with_tasks(tasks.clone()));
+
+    harness.app.show_duplicates();
+    harness.render();
+    assert!(harness.screen().contains("Algo: HW1 (3 copies)"));
+    assert!(harness.screen().contains("Physics: Lab (2 copies, different deadlines"));
+
+    harness.press(KeyCode::Char('d'));
+    assert!(harness.screen().contains("[keep] #1"));
+    assert!(harness.screen().contains("[drop] #5"));
+    assert!(harness.screen().contains("[keep] #6"));
+    // Keeping the newest copy instead of the oldest
+    harness.press(KeyCode::Char(' '));
+    harness.type_text("jj ");
+    harness.press(KeyCode::Enter);
+
+    let mut ids = harness.app.data.tasks.iter().map(|x| x.id).collect::<Vec<_>>();
+    ids.sort();
+    assert_eq!(ids, [2, 3, 5, 6]);
+    harness.press(KeyCode::Char('u'));
+    assert_eq!(harness.app.data.tasks.len(), tasks.len());
+}
+
+#[test]
+fn dedup_refuses_to_drop_a_whole_group() {
+    let mut tasks = tasks();
+    tasks.push(Task { id: 4, ..tasks[0].clone() });
+    let mut harness = Harness::new(MemoryStorage::with_tasks(tasks));
+
+    harness.app.show_duplicates();
+    harness.press(KeyCode::Char('d'));
+    harness.press(KeyCode::Char(' '));
+    harness.press(KeyCode::Enter);
+    assert!(harness.screen().contains("Keep at least one"));
+    assert_eq!(harness.app.data.tasks.len(), 4);
+
+    harness.press(KeyCode::Esc);
+    harness.app.show_duplicates();
+    harness.press(KeyCode::Esc);
+    assert!(!harness.screen().contains("share a subject"));
+}
+
+#[test]
+fn number_keys_cycle_the_sort_column_and_the_header_shows_it() {
+    let mut harness = memory();
+    let names = |harness: &Harness| harness.app.data.tasks.iter().map(|x| x.name.clone()).collect::<Vec<_>>();
+    assert!(harness.row("Time Left").contains("Time Left ▲"));
+
+    harness.press(KeyCode::Char('3'));
+    assert_eq!(names(&harness), ["HW1", "Lab", "Quiz"]);
+    assert!(harness.row("Name ▲").contains("Time Left "));
+    assert!(!harness.row("Name ▲").contains("Time Left ▲"));
+    harness.press(KeyCode::Char('3'));
+    assert_eq!(names(&harness), ["Quiz", "Lab", "HW1"]);
+    assert!(harness.screen().contains("Name ▼"));
+    harness.press(KeyCode::Char('3'));
+    assert_eq!(names(&harness), ["HW1", "Lab", "Quiz"]);
+    assert!(harness.screen().contains("Time Left ▲"));
+
+    harness.press(KeyCode::Char('j'));
+    harness.press(KeyCode::Char('s'));
+    harness.press(KeyCode::Char('1'));
+    assert_eq!(names(&harness), ["Lab", "HW1", "Quiz"]);
+}
+
+#[test]
+fn clicking_a_header_sorts_by_its_column() {
+    use ratatui::crossterm::event::{MouseButton, MouseEvent, MouseEventKind};
+
+    let mut harness = memory();
+    let header = harness.lines().into_iter().enumerate().find(|(_, x)| x.contains("Subject")).unwrap();
+    let click = |column: usize| Event::Mouse(MouseEvent {
+        kind: MouseEventKind::Down(MouseButton::Left),
+        column: column as u16,
+        row: header.0 as u16,
+        modifiers: KeyModifiers::NONE,
+    });
+    let subject = header.1.chars().collect::<Vec<_>>().windows(7).position(|x| x.iter().collect::<String>() == "Subject").unwrap();
+
+    harness.app.handle_event(click(subject + 2)).unwrap();
+    harness.render();
+    assert!(harness.screen().contains("Subject ▲"));
+    let subjects = harness.app.data.tasks.iter().map(|x| x.subject.as_str()).collect::<Vec<_>>();
+    assert_eq!(subjects, ["Algo", "Databases", "Physics"]);
+
+    // Anywhere else on the header row does nothing
+    harness.app.handle_event(click(0)).unwrap();
+    harness.render();
+    assert!(harness.screen().contains("Subject ▲"));
+}
+
+#[test]
+fn an_edit_lands_on_its_task_even_after_a_reorder() {
+    let mut harness = memory();
+    let original = harness.app.data.tasks[0].clone();
+    let edited = RenamingReader.read(&original, &Config::default()).unwrap();
+
+    // Meanwhile something due sooner shows up in front of it
+    harness.app.data.tasks.insert(0, Task { id: 9, name: "Sooner".to_string(), ..original.clone() });
+    harness.app.write_back(&original, edited);
+    assert_eq!(harness.app.data.tasks[0].name, "Sooner");
+    assert_eq!(harness.app.data.tasks[1].name, "Renamed");
+    assert_eq!(harness.app.data.tasks[1].id, original.id);
+}
+
+#[test]
+fn an_edit_of_a_removed_task_can_be_added_back() {
+    let mut harness = memory();
+    harness.press(KeyCode::Char('G'));
+    let original = harness.app.data.tasks[2].clone();
+    let edited = RenamingReader.read(&original, &Config::default()).unwrap();
+
+    harness.app.data.tasks.retain(|x| x.id != original.id);
+    harness.app.write_back(&original, edited.clone());
+    assert_eq!(harness.app.data.index, Some(1));
+    harness.render();
+    assert!(harness.screen().contains("was removed while it was being edited"));
+    harness.press(KeyCode::Char('y'));
+    let added = harness.app.data.tasks.iter().find(|x| x.name == "Renamed").unwrap();
+    assert!(added.id != 0);
+
+    harness.app.data.tasks.retain(|x| x.name != "Renamed");
+    harness.app.write_back(&original, edited);
+    harness.press(KeyCode::Char('n'));
+    assert_eq!(harness.app.data.tasks.len(), 2);
+}
+
+#[test]
+fn review_walks_through_stale_tasks_as_one_undo() {
+    let mut harness = memory();
+
+    harness.press(KeyCode::Char('R'));
+    assert!(harness.screen().contains("Review · task 1 of 3"));
+    assert!(harness.screen().contains("HW1"));
+    harness.press(KeyCode::Char('k'));
+    harness.press(KeyCode::Char('c'));
+    assert!(harness.screen().contains("Review · task 3 of 3"));
+    harness.press(KeyCode::Char('r'));
+    harness.type_text("l");
+    harness.press(KeyCode::Enter);
+    harness.press(KeyCode::Enter);
+
+    assert!(harness.screen().contains("Reviewed 3 of 3 task(s): 1 kept, 1 completed, 1 rescheduled"));
+    let quiz = harness.app.data.tasks.iter().find(|x| x.name == "Quiz").unwrap();
+    assert_eq!(quiz.time.unwrap().date_naive(), (now() + TimeDelta::days(11)).date_naive());
+    assert!(harness.app.data.tasks.iter().any(|x| x.name == "Lab" && x.complete));
+
+    harness.press(KeyCode::Enter);
+    harness.press(KeyCode::Char('u'));
+    let mut tasks = harness.app.data.tasks.clone();
+    tasks.sort_by_key(|x| x.id);
+    assert_eq!(tasks, self::tasks());
+}
+
+#[test]
+fn review_deletes_after_asking_and_stops_on_esc() {
+    let mut harness = memory();
+    harness.app.data.tasks[2].created_at = Some(now());
+
+    harness.press(KeyCode::Char(':'));
+    harness.type_text("review");
+    harness.press(KeyCode::Enter);
+    assert!(harness.screen().contains("Review · task 1 of 2"));
+    harness.press(KeyCode::Char('d'));
+    assert!(harness.screen().contains("Delete \"Algo: HW1\"?"));
+    harness.press(KeyCode::Char('y'));
+    assert!(harness.screen().contains("Review · task 2 of 2"));
+    harness.press(KeyCode::Char('z'));
+    harness.press(KeyCode::Enter);
+
+    assert!(harness.app.data.tasks.iter().all(|x| x.name != "HW1"));
+    assert!(harness.app.data.tasks.iter().any(|x| x.name == "Lab" && x.time.is_none()));
+
+    for task in harness.app.data.tasks.iter_mut() {
+        task.created_at = Some(now());
+    }
+    harness.press(KeyCode::Char('R'));
+    assert!(harness.screen().contains("Nothing has been left alone for 7 days."));
+}
+
+#[test]
+fn review_keeps_decisions_made_before_esc() {
+    let mut harness = memory();
+
+    harness.press(KeyCode::Char('R'));
+    harness.press(KeyCode::Char('c'));
+    harness.press(KeyCode::Esc);
+    assert!(harness.screen().contains("Reviewed 1 of 3 task(s): 0 kept, 1 completed"));
+    harness.press(KeyCode::Enter);
+    assert!(harness.app.data.tasks.iter().any(|x| x.name == "HW1" && x.complete));
+    assert!(harness.selected().contains("Lab"));
+}
+
+#[test]
+fn changes_are_stamped_shown_and_sortable() {
+    let mut harness = memory();
+    harness.app.data.tasks[0].created_at = Some(now() - TimeDelta::days(3));
+    harness.render();
+    assert!(harness.screen().contains("Updated: 3 days ago"));
+
+    harness.press(KeyCode::Char('j'));
+    harness.press(KeyCode::Char('s'));
+    assert!(harness.screen().contains("Updated: just now"));
+    assert_eq!(harness.app.data.tasks[1].updated_at, Some(now()));
+
+    harness.press(KeyCode::Char('5'));
+    let names = harness.app.data.tasks.iter().map(|x| x.name.as_str()).collect::<Vec<_>>();
+    assert_eq!(names, ["Lab", "HW1", "Quiz"]);
+    assert!(harness.screen().contains("Tasks [sort: last change ▲]"));
+}
+
+#[test]
+fn status_symbols_mark_rows_beyond_their_color() {
+    use ratatui::style::Modifier;
+
+    let mut tasks = tasks();
+    tasks[0].time = Some(now() - TimeDelta::hours(3));
+    tasks[2].set_complete(true);
+    let mut harness = Harness::new(MemoryStorage::with_tasks(tasks));
+    harness.app.data.config.ui.status_symbols = true;
+    harness.render();
+
+    assert!(harness.row("HW1").starts_with("│ > !! • Algo"), "{}", harness.screen());
+    assert!(harness.row("Lab").starts_with("│        Physics"), "{}", harness.screen());
+    assert!(harness.row("Quiz").starts_with("│   ✓    Databases"), "{}", harness.screen());
+
+    let buffer = harness.terminal.backend().buffer();
+    let y = harness.lines().iter().position(|x| x.contains("Quiz")).unwrap() as u16;
+    assert!(buffer[(10, y)].modifier.contains(Modifier::DIM));
+
+    harness.app.data.config.ui.status_symbols = false;
+    harness.render();
+    assert!(harness.row("HW1").starts_with("│ >  • Algo"), "{}", harness.screen());
+}
+
+#[test]
+fn focus_timer_counts_down_and_banks_its_time() {
+    let mut harness = memory();
+    let spent = |harness: &Harness| harness.app.data.tasks.iter().find(|x| x.name == "HW1").unwrap().time_spent;
+
+    harness.press(KeyCode::Char('T'));
+    harness.app.data.clock = Clock::Fixed(now() + TimeDelta::minutes(10));
+    harness.render();
+    assert!(harness.lines()[19].contains("⏱ 15:00 HW1"));
+
+    harness.press(KeyCode::Char('T'));
+    assert!(harness.lines()[19].contains("⏸ 15:00 HW1"));
+    assert_eq!(spent(&harness), TimeDelta::minutes(10));
+    assert!(harness.screen().contains("Time spent: 10m"));
+
+    harness.app.data.clock = Clock::Fixed(now() + TimeDelta::minutes(30));
+    harness.press(KeyCode::Char('T'));
+    assert!(!harness.app.tick());
+    harness.app.data.clock = Clock::Fixed(now() + TimeDelta::minutes(45));
+    assert!(harness.app.tick());
+    harness.render();
+    assert!(harness.screen().contains("Time is up on \"HW1\", 25m spent on it so far."));
+    assert_eq!(spent(&harness), TimeDelta::minutes(25));
+    assert!(!harness.lines()[19].contains("HW1"));
+}
+
+#[test]
+fn quitting_mid_timer_banks_the_minutes() {
+    let (mut harness, storage) = spy();
+    harness.press(KeyCode::Char('T'));
+    harness.app.data.clock = Clock::Fixed(now() + TimeDelta::minutes(7));
+
+    harness.press(KeyCode::Char('q'));
+    assert!(harness.screen().contains("You have unsaved progress"));
+    harness.press(KeyCode::Char('y'));
+    assert!(storage.read().unwrap().iter().any(|x| x.name == "HW1" && x.time_spent == TimeDelta::minutes(7)));
+}
+
+#[test]
+fn new_tasks_start_from_the_description_template() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("template.md");
+    std::fs::write(&path, "## Link\n\n## Notes for {name} ({subject}, {date})\n").unwrap();
+    let mut harness = memory();
+    harness.app.data.config.new_task_template = Some(path.clone());
+
+    harness.press(KeyCode::Char('n'));
+    harness.type_text("Physics");
+    harness.press(KeyCode::Tab);
+    harness.type_text("Report");
+    harness.press(KeyCode::Enter);
+    let task = harness.app.data.tasks.iter().find(|x| x.name == "Report").unwrap();
+    assert_eq!(task.description, "## Link\n\n## Notes for Report (Physics, 2025-03-01)\n");
+
+    // Warned about once, then new tasks go without
+    std::fs::remove_file(&path).unwrap();
+    harness.press(KeyCode::Char('p'));
+    assert!(harness.screen().contains("Could not read the task template"));
+    assert_eq!(harness.app.data.tasks.len(), 5);
+    harness.press(KeyCode::Enter);
+    harness.press(KeyCode::Char('p'));
+    assert!(!harness.screen().contains("Could not read the task template"));
+    assert_eq!(harness.app.data.tasks.len(), 6);
+}
+
+#[test]
+fn delete_takes_the_task_shown_when_asked_despite_a_resort() {
+    let mut harness = memory();
+
+    harness.press(KeyCode::Char('d'));
+    assert!(harness.screen().contains("Would you like to remove task \"Algo: HW1\""));
+    // A live tick re-sorts the list, Quiz now sits where HW1 was
+    harness.app.data.tasks.iter_mut().find(|x| x.name == "Quiz").unwrap().time = Some(now());
+    harness.render();
+    assert_eq!(harness.app.data.tasks[0].name, "Quiz");
+
+    harness.press(KeyCode::Char('d'));
+    let names = harness.app.data.tasks.iter().map(|x| x.name.as_str()).collect::<Vec<_>>();
+    assert_eq!(names, ["Quiz", "Lab"]);
+}
+
+#[test]
+fn delete_of_a_vanished_task_removes_nothing() {
+    let mut harness = memory();
+
+    harness.press(KeyCode::Char('d'));
+    harness.app.data.tasks.retain(|x| x.name != "HW1");
+    harness.render();
+    harness.press(KeyCode::Char('d'));
+
+    assert!(harness.screen().contains("\"Algo: HW1\": task no longer exists"));
+    assert_eq!(harness.app.data.tasks.len(), 2);
+}
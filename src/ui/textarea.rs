@@ -0,0 +1,192 @@
+use ratatui::prelude::*;
+use ratatui::crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use ratatui::widgets::{Block, Paragraph};
+use unicode_width::UnicodeWidthStr;
+
+
+/// Editable text with a cursor, one line or many
+pub struct TextArea {
+    lines: Vec<String>,
+    row: usize,
+    /// In characters, not bytes
+    col: usize,
+    multiline: bool,
+}
+
+impl TextArea {
+    /// Cursor at the end of `text`
+    pub fn new(text: &str) -> Self {
+        let mut lines = text.split('\n').map(str::to_string).collect::<Vec<_>>();
+        if lines.is_empty() {
+            lines.push(String::new());
+        }
+        let row = lines.len() - 1;
+        let col = lines[row].chars().count();
+        Self { lines, row, col, multiline: true }
+    }
+
+    /// Enter is left to the caller and newlines never get in
+    pub fn single_line(text: &str) -> Self {
+        Self { multiline: false, ..Self::new(&text.replace('\n', " ")) }
+    }
+
+    pub fn text(&self) -> String {
+        self.lines.join("\n")
+    }
+
+    /// Row and character column
+    pub fn cursor(&self) -> (usize, usize) {
+        (self.row, self.col)
+    }
+
+    fn line_len(&self, row: usize) -> usize {
+        self.lines[row].chars().count()
+    }
+
+    fn byte(&self, row: usize, col: usize) -> usize {
+        self.lines[row].char_indices().nth(col).map(|(i, _)| i).unwrap_or(self.lines[row].len())
+    }
+
+    fn insert(&mut self, c: char) {
+        let at = self.byte(self.row, self.col);
+        self.lines[self.row].insert(at, c);
+        self.col += 1;
+    }
+
+    fn newline(&mut self) {
+        let at = self.byte(self.row, self.col);
+        let rest = self.lines[self.row].split_off(at);
+        self.lines.insert(self.row + 1, rest);
+        self.row += 1;
+        self.col = 0;
+    }
+
+    fn backspace(&mut self) {
+        if self.col > 0 {
+            self.col -= 1;
+            let at = self.byte(self.row, self.col);
+            self.lines[self.row].remove(at);
+        } else if self.row > 0 {
+            let line = self.lines.remove(self.row);
+            self.row -= 1;
+            self.col = self.line_len(self.row);
+            self.lines[self.row].push_str(&line);
+        }
+    }
+
+    fn delete(&mut self) {
+        if self.col < self.line_len(self.row) {
+            let at = self.byte(self.row, self.col);
+            self.lines[self.row].remove(at);
+        } else if self.row + 1 < self.lines.len() {
+            let line = self.lines.remove(self.row + 1);
+            self.lines[self.row].push_str(&line);
+        }
+    }
+
+    fn left(&mut self) {
+        if self.col > 0 {
+            self.col -= 1;
+        } else if self.row > 0 {
+            self.row -= 1;
+            self.col = self.line_len(self.row);
+        }
+    }
+
+    fn right(&mut self) {
+        if self.col < self.line_len(self.row) {
+            self.col += 1;
+        } else if self.row + 1 < self.lines.len() {
+            self.row += 1;
+            self.col = 0;
+        }
+    }
+
+    fn vertical(&mut self, row: usize) {
+        self.row = row;
+        self.col = self.col.min(self.line_len(row));
+    }
+
+    /// Applies an editing key, returns whether it was one
+    pub fn handle_key_event(&mut self, key_event: &KeyEvent) -> bool {
+        if key_event.modifiers.intersects(KeyModifiers::CONTROL | KeyModifiers::ALT) {
+            return false;
+        }
+        match key_event.code {
+            KeyCode::Char(c) => self.insert(c),
+            KeyCode::Enter if self.multiline => self.newline(),
+            KeyCode::Backspace => self.backspace(),
+            KeyCode::Delete => self.delete(),
+            KeyCode::Left => self.left(),
+            KeyCode::Right => self.right(),
+            KeyCode::Up if self.row > 0 => self.vertical(self.row - 1),
+            KeyCode::Down if self.row + 1 < self.lines.len() => self.vertical(self.row + 1),
+            KeyCode::Home => self.col = 0,
+            KeyCode::End => self.col = self.line_len(self.row),
+            _ => return false,
+        }
+        true
+    }
+
+    /// Draws the text scrolled so the cursor stays in view, placing the terminal cursor when `focused`
+    pub fn render(&self, frame: &mut Frame, area: Rect, block: Block, focused: bool) {
+        let inner = block.inner(area);
+        let (row, col) = self.cursor();
+        let prefix = self.lines[row].chars().take(col).collect::<String>();
+        let x = prefix.width() as u16;
+        let y = row as u16;
+        let scroll = (
+            y.saturating_sub(inner.height.saturating_sub(1)),
+            x.saturating_sub(inner.width.saturating_sub(1)),
+        );
+
+        let paragraph = Paragraph::new(self.text())
+            .scroll(scroll)
+            .block(block);
+        frame.render_widget(paragraph, area);
+
+        if focused {
+            frame.set_cursor_position((inner.x + x - scroll.1, inner.y + y - scroll.0));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn press(area: &mut TextArea, codes: &[KeyCode]) {
+        for code in codes {
+            area.handle_key_event(&KeyEvent::new(*code, KeyModifiers::NONE));
+        }
+    }
+
+    fn type_text(area: &mut TextArea, text: &str) {
+        press(area, &text.chars().map(KeyCode::Char).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn edits_across_lines() {
+        let mut area = TextArea::new("héllo");
+        press(&mut area, &[KeyCode::Left, KeyCode::Left, KeyCode::Enter]);
+        assert_eq!(area.text(), "hél\nlo");
+        assert_eq!(area.cursor(), (1, 0));
+
+        press(&mut area, &[KeyCode::Backspace, KeyCode::Home]);
+        type_text(&mut area, "> ");
+        assert_eq!(area.text(), "> héllo");
+
+        press(&mut area, &[KeyCode::End, KeyCode::Enter]);
+        type_text(&mut area, "world");
+        press(&mut area, &[KeyCode::Up, KeyCode::End, KeyCode::Delete]);
+        assert_eq!(area.text(), "> hélloworld");
+    }
+
+    #[test]
+    fn single_line_leaves_enter_and_shortcuts_alone() {
+        let mut area = TextArea::single_line("HW1");
+        assert!(!area.handle_key_event(&KeyEvent::new(KeyCode::Enter, KeyModifiers::NONE)));
+        assert!(!area.handle_key_event(&KeyEvent::new(KeyCode::Char('s'), KeyModifiers::CONTROL)));
+        assert_eq!(area.text(), "HW1");
+    }
+}
@@ -0,0 +1,65 @@
+use super::app::Data;
+use crate::uni::task::Task;
+
+use chrono::{DateTime, FixedOffset, TimeDelta};
+
+
+/// Countdown while working on one task. Every pause banks the time worked since the
+/// last start into the task, so nothing is lost however the timer ends.
+pub struct FocusTimer {
+    pub id: u64,
+    /// Name as of the start, for the status bar
+    pub name: String,
+    length: TimeDelta,
+    /// Worked before the current run
+    worked: TimeDelta,
+    /// Start of the current run, None while paused
+    running_since: Option<DateTime<FixedOffset>>,
+}
+
+impl FocusTimer {
+    pub fn start(task: &Task, length: TimeDelta, now: DateTime<FixedOffset>) -> Self {
+        Self {
+            id: task.id,
+            name: task.name.clone(),
+            length,
+            worked: TimeDelta::zero(),
+            running_since: Some(now),
+        }
+    }
+
+    pub fn is_running(&self) -> bool {
+        self.running_since.is_some()
+    }
+
+    /// Stops the countdown, adding the current run to the task's time spent
+    pub fn pause(&mut self, data: &mut Data) {
+        let Some(since) = self.running_since.take() else { return };
+        let run = (data.now() - since).max(TimeDelta::zero());
+        self.worked += run;
+        data.update(self.id, |task| task.time_spent += run);
+    }
+
+    pub fn resume(&mut self, now: DateTime<FixedOffset>) {
+        self.running_since.get_or_insert(now);
+    }
+
+    fn worked_at(&self, now: DateTime<FixedOffset>) -> TimeDelta {
+        self.worked + self.running_since.map_or(TimeDelta::zero(), |since| now - since)
+    }
+
+    pub fn remaining(&self, now: DateTime<FixedOffset>) -> TimeDelta {
+        (self.length - self.worked_at(now)).max(TimeDelta::zero())
+    }
+
+    pub fn is_done(&self, now: DateTime<FixedOffset>) -> bool {
+        self.remaining(now) == TimeDelta::zero()
+    }
+
+    /// Countdown and task for the status bar, e.g. "⏱ 18:42 HW1"
+    pub fn label(&self, now: DateTime<FixedOffset>) -> String {
+        let remaining = self.remaining(now);
+        let mark = if self.is_running() { "⏱" } else { "⏸" };
+        format!("{mark} {:02}:{:02} {}", remaining.num_minutes(), remaining.num_seconds() % 60, self.name)
+    }
+}
@@ -0,0 +1,207 @@
+use std::collections::HashMap;
+
+use chrono::{DateTime, FixedOffset, TimeDelta};
+use serde::Serialize;
+
+use crate::uni::dates::{format_duration, parse_duration};
+use crate::uni::task::Task;
+
+
+/// Deadline set relative to another task's, "7 days before the submission". The task's
+/// `time` is what it resolves to, see `resolve`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Anchor {
+    /// Id of the task the deadline follows
+    pub task: u64,
+    /// Added to that task's deadline, negative for before it
+    pub offset: TimeDelta,
+}
+
+impl Anchor {
+    /// From the `relative_to` and `offset` fields files and front matter keep it in,
+    /// None without `relative_to`. A `time` next to `relative_to` is an error, the two
+    /// being ways to set the same deadline.
+    pub fn from_fields(relative_to: Option<u64>, offset: Option<&str>, time: Option<&str>) -> Result<Option<Self>, String> {
+        let Some(task) = relative_to else { return Ok(None) };
+        if time.is_some() {
+            return Err(format!("Both a time and relative_to = {task}, expected one of them"));
+        }
+        let offset = offset.map_or(Ok(TimeDelta::zero()), parse_duration)?;
+        Ok(Some(Self { task, offset }))
+    }
+
+    /// The rule for people to read: "7d before Algo: Submission"
+    pub fn describe(&self, tasks: &[Task]) -> String {
+        let amount = format_duration(self.offset.abs());
+        let anchor = match tasks.iter().find(|x| x.id == self.task) {
+            Some(task) => format!("{}: {}", task.subject, task.name),
+            None => format!("task {}", self.task),
+        };
+        match self.offset {
+            x if x < TimeDelta::zero() => format!("{amount} before {anchor}"),
+            x if x > TimeDelta::zero() => format!("{amount} after {anchor}"),
+            _ => format!("same as {anchor}"),
+        }
+    }
+}
+
+/// Why an anchored task has no deadline
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum AnchorProblem {
+    /// No task has the anchor's id
+    Missing,
+    /// The chain ends at a task without a deadline
+    Undated,
+    /// The chain comes back around to a task already on it
+    Cycle,
+    /// The offset takes the deadline past the dates there are
+    OutOfRange,
+}
+
+impl AnchorProblem {
+    pub fn describe(&self) -> &'static str {
+        match self {
+            AnchorProblem::Missing => "there is no such task",
+            AnchorProblem::Undated => "it has no deadline",
+            AnchorProblem::Cycle => "the anchors go round in a circle",
+            AnchorProblem::OutOfRange => "the offset goes past any date",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct BrokenAnchor {
+    pub id: u64,
+    pub problem: AnchorProblem,
+}
+
+enum State {
+    Unresolved,
+    Visiting,
+    Done(Result<DateTime<FixedOffset>, AnchorProblem>),
+}
+
+/// Sets the deadline of every anchored task from its anchor's, following chains of them.
+/// A task whose anchor is missing, undated or leads back around is left without a deadline
+/// and reported. Idempotent, so it runs again after any deadline changes.
+pub fn resolve(tasks: &mut [Task]) -> Vec<BrokenAnchor> {
+    let positions: HashMap<u64, usize> = tasks.iter().enumerate().map(|(i, x)| (x.id, i)).collect();
+    let mut states: Vec<State> = tasks.iter().map(|_| State::Unresolved).collect();
+    for i in 0..tasks.len() {
+        let _ = deadline(i, tasks, &positions, &mut states);
+    }
+
+    let mut broken = vec![];
+    for (task, state) in tasks.iter_mut().zip(states) {
+        let (Some(_), State::Done(result)) = (task.anchor, state) else { continue };
+        task.time = result.ok();
+        if let Err(problem) = result {
+            broken.push(BrokenAnchor { id: task.id, problem });
+        }
+    }
+    broken
+}
+
+fn deadline(
+    i: usize,
+    tasks: &[Task],
+    positions: &HashMap<u64, usize>,
+    states: &mut [State],
+) -> Result<DateTime<FixedOffset>, AnchorProblem> {
+    match &states[i] {
+        State::Done(result) => return *result,
+        State::Visiting => return Err(AnchorProblem::Cycle),
+        State::Unresolved => (),
+    }
+    let result = match tasks[i].anchor {
+        None => tasks[i].time.ok_or(AnchorProblem::Undated),
+        Some(anchor) => {
+            states[i] = State::Visiting;
+            match positions.get(&anchor.task) {
+                Some(&j) => deadline(j, tasks, positions, states)
+                    .and_then(|x| x.checked_add_signed(anchor.offset).ok_or(AnchorProblem::OutOfRange)),
+                None => Err(AnchorProblem::Missing),
+            }
+        },
+    };
+    states[i] = State::Done(result);
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn at(time: &str) -> Option<DateTime<FixedOffset>> {
+        Some(DateTime::parse_from_rfc3339(time).unwrap())
+    }
+
+    fn task(id: u64, time: Option<DateTime<FixedOffset>>, anchor: Option<(u64, i64)>) -> Task {
+        Task {
+            id,
+            name: format!("T{id}"),
+            subject: "Project".to_string(),
+            time,
+            anchor: anchor.map(|(task, days)| Anchor { task, offset: TimeDelta::days(days) }),
+            ..Task::default()
+        }
+    }
+
+    #[test]
+    fn chains_follow_the_final_deadline() {
+        let mut tasks = vec![
+            task(3, None, Some((2, -2))),
+            task(2, at("2025-01-01T00:00:00Z"), Some((1, -7))),
+            task(1, at("2025-06-30T18:00:00Z"), None),
+        ];
+        assert_eq!(resolve(&mut tasks), []);
+        assert_eq!(tasks[1].time, at("2025-06-23T18:00:00Z"));
+        assert_eq!(tasks[0].time, at("2025-06-21T18:00:00Z"));
+
+        // The submission moves, the draft and the outline with it
+        tasks[2].time = at("2025-07-07T18:00:00Z");
+        resolve(&mut tasks);
+        assert_eq!(tasks[0].time, at("2025-06-28T18:00:00Z"));
+        assert_eq!(tasks[0].anchor.unwrap().describe(&tasks), "2d before Project: T2");
+    }
+
+    #[test]
+    fn cycles_and_missing_or_undated_anchors_are_reported() {
+        let mut tasks = vec![
+            task(1, at("2025-06-30T18:00:00Z"), Some((2, 1))),
+            task(2, None, Some((1, 1))),
+            task(3, None, Some((1, -1))),
+            task(4, None, Some((9, -1))),
+            task(5, None, None),
+            task(6, None, Some((5, 0))),
+        ];
+        let broken = resolve(&mut tasks);
+        let problems: Vec<_> = broken.iter().map(|x| (x.id, x.problem)).collect();
+        assert_eq!(problems, [
+            (1, AnchorProblem::Cycle),
+            (2, AnchorProblem::Cycle),
+            (3, AnchorProblem::Cycle),
+            (4, AnchorProblem::Missing),
+            (6, AnchorProblem::Undated),
+        ]);
+        assert!(tasks.iter().all(|x| x.time.is_none()));
+        assert_eq!(tasks[3].anchor.unwrap().describe(&tasks), "1d before task 9");
+    }
+
+    #[test]
+    fn offsets_past_any_date_are_reported() {
+        let mut tasks = vec![task(1, at("2025-06-30T18:00:00Z"), None), task(2, None, Some((1, -1_000_000_000)))];
+        let broken = resolve(&mut tasks);
+        assert_eq!(broken, [BrokenAnchor { id: 2, problem: AnchorProblem::OutOfRange }]);
+        assert_eq!(tasks[1].time, None);
+    }
+
+    #[test]
+    fn a_time_and_an_anchor_are_one_too_many() {
+        assert_eq!(Anchor::from_fields(Some(1), Some("-7d"), None), Ok(Some(Anchor { task: 1, offset: TimeDelta::days(-7) })));
+        assert_eq!(Anchor::from_fields(None, None, Some("2025-06-30T18:00:00Z")), Ok(None));
+        assert!(Anchor::from_fields(Some(1), Some("-7d"), Some("2025-06-30T18:00:00Z")).is_err());
+        assert!(Anchor::from_fields(Some(1), Some("-100000000000000000w"), None).is_err());
+    }
+}
@@ -1,47 +1,237 @@
-use chrono::{DateTime, Datelike, Local, NaiveDate};
-use crate::uni::task::Task;
+use std::collections::BTreeMap;
+
+use chrono::{DateTime, Datelike, FixedOffset, Local, NaiveDate};
+use crate::config::{CalendarTaskFilter, SubjectAbbrev};
+use crate::uni::dates::local_date;
+use crate::uni::format::status_colored;
+use crate::uni::locale::DateNames;
+use crate::uni::policy::StatusPolicy;
+use crate::uni::task::{Task, TaskStatus};
 use colored::{Colorize, ColoredString, Color};
+use unicode_width::UnicodeWidthStr;
 
 
 pub struct Calendar<'a> {
     date: DateTime<Local>,
-    tasks: &'a Vec<Task>
+    tasks: &'a Vec<Task>,
+    /// Tasks the grid counts and the agenda lists
+    filter: CalendarTaskFilter,
+    /// Leading column with the ISO week of each row
+    week_numbers: bool,
+    /// Agenda ends with the starred tasks that have no deadline
+    anytime: bool,
+    abbrev: SubjectAbbrev,
+    /// Busy days bold, the busiest also underlined, for when colors are not enough
+    symbols: bool,
+    names: DateNames,
+    policy: StatusPolicy,
+    /// Days with more open tasks due are marked as overloaded
+    day_limit: Option<usize>,
+}
+
+/// A month's tasks grouped by the day they are due
+#[derive(Default)]
+pub struct TasksByDay<'a> {
+    pub dated: BTreeMap<NaiveDate, Vec<&'a Task>>,
+    /// Tasks without a deadline, which belong to no day and so to every month
+    pub undated: Vec<&'a Task>,
+}
+
+/// Tasks by the local date they are due on, by deadline within a day. What the calendar
+/// colors days by, and what the day budget counts.
+pub fn tasks_by_date(tasks: &[Task]) -> BTreeMap<NaiveDate, Vec<&Task>> {
+    let mut days: BTreeMap<NaiveDate, Vec<&Task>> = BTreeMap::new();
+    for task in tasks.iter() {
+        if let Some(time) = task.time {
+            days.entry(local_date(&time)).or_default().push(task);
+        }
+    }
+    for tasks in days.values_mut() {
+        tasks.sort_by_key(|x| x.time);
+    }
+    days
+}
+
+/// Tasks due on each day of the month, by deadline, that `filter` lets in as of `now`.
+/// What every view of the calendar counts and lists.
+pub fn tasks_by_day<'t>(
+    tasks: &'t [Task],
+    year: i32,
+    month: u32,
+    filter: CalendarTaskFilter,
+    now: &DateTime<FixedOffset>,
+) -> TasksByDay<'t> {
+    let included = |task: &&Task| filter.includes(task, now);
+    TasksByDay {
+        dated: tasks_by_date(tasks)
+            .into_iter()
+            .filter(|(date, _)| date.year() == year && date.month() == month)
+            .map(|(date, tasks)| (date, tasks.into_iter().filter(included).collect::<Vec<_>>()))
+            .filter(|(_, tasks)| !tasks.is_empty())
+            .collect(),
+        undated: tasks.iter().filter(|x| x.time.is_none()).filter(included).collect(),
+    }
+}
+
+/// What a day holds, for the header above its tasks:
+/// "Tue 13 May — 4 tasks: 1 overdue, 2 due, 1 done, 1 starred"
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DaySummary {
+    pub date: NaiveDate,
+    pub total: usize,
+    /// Open and past the deadline
+    pub overdue: usize,
+    /// Open and still ahead
+    pub due: usize,
+    pub done: usize,
+    /// Open ones among them
+    pub starred: usize,
+    /// The most pressing status of the ones still ahead
+    pub due_status: TaskStatus,
+}
+
+impl DaySummary {
+    /// `tasks` are the ones due on `date`, their status as the policy sees it at `now`
+    pub fn new(tasks: &[&Task], date: NaiveDate, now: &DateTime<FixedOffset>, policy: &StatusPolicy) -> Self {
+        let open = tasks.iter().filter(|x| !x.complete);
+        let (overdue, ahead): (Vec<&Task>, Vec<&Task>) = open.clone().partition(|x| x.time.is_some_and(|x| x < *now));
+        let urgency = |status: &TaskStatus| match status {
+            TaskStatus::Panic => 0,
+            TaskStatus::DueToday => 1,
+            TaskStatus::Normal => 2,
+            TaskStatus::Zen => 3,
+        };
+        Self {
+            date,
+            total: tasks.len(),
+            overdue: overdue.len(),
+            due: ahead.len(),
+            done: tasks.len() - overdue.len() - ahead.len(),
+            starred: open.filter(|x| x.starred).count(),
+            due_status: ahead.iter().map(|x| policy.status(x, now)).min_by_key(urgency).unwrap_or(TaskStatus::Normal),
+        }
+    }
+
+    /// Counts that are not zero, each with the status it is colored by, starred ones plain
+    pub fn parts(&self) -> Vec<(String, Option<TaskStatus>)> {
+        [
+            (self.overdue, "overdue", Some(TaskStatus::Panic)),
+            (self.due, "due", Some(self.due_status)),
+            (self.done, "done", Some(TaskStatus::Zen)),
+            (self.starred, "starred", None),
+        ]
+        .into_iter()
+        .filter(|(count, _, _)| *count > 0)
+        .map(|(count, label, status)| (format!("{count} {label}"), status))
+        .collect()
+    }
+
+    /// "Tue 13 May — 4 tasks: ", what goes in front of the parts
+    pub fn heading(&self, names: &DateNames) -> String {
+        let day = self.date.format(&names.localize("%a %-d %b", &self.date)).to_string();
+        match self.total {
+            0 => format!("{day} — nothing due"),
+            1 => format!("{day} — 1 task: "),
+            total => format!("{day} — {total} tasks: "),
+        }
+    }
 }
 
 impl<'a> Calendar<'a> {
     pub fn new(date: DateTime<Local>, tasks: &'a Vec<Task>) -> Self {
-        Calendar { date, tasks }
+        Calendar {
+            date,
+            tasks,
+            filter: CalendarTaskFilter::default(),
+            week_numbers: false,
+            anytime: false,
+            abbrev: SubjectAbbrev::default(),
+            symbols: false,
+            names: DateNames::default(),
+            policy: StatusPolicy::default(),
+            day_limit: None,
+        }
+    }
+
+    pub fn with_filter(self, filter: CalendarTaskFilter) -> Self {
+        Calendar { filter, ..self }
+    }
+
+    pub fn with_week_numbers(self, week_numbers: bool) -> Self {
+        Calendar { week_numbers, ..self }
+    }
+
+    pub fn with_anytime(self, anytime: bool) -> Self {
+        Calendar { anytime, ..self }
+    }
+
+    /// Agenda lines use the short subject names
+    pub fn with_abbreviations(self, abbrev: SubjectAbbrev) -> Self {
+        Calendar { abbrev, ..self }
+    }
+
+    pub fn with_status_symbols(self, symbols: bool) -> Self {
+        Calendar { symbols, ..self }
+    }
+
+    /// Month and weekday names other than English
+    pub fn with_names(self, names: DateNames) -> Self {
+        Calendar { names, ..self }
+    }
+
+    /// Agenda colors by the configured statuses
+    pub fn with_policy(self, policy: StatusPolicy) -> Self {
+        Calendar { policy, ..self }
+    }
+
+    pub fn with_day_limit(self, day_limit: Option<usize>) -> Self {
+        Calendar { day_limit, ..self }
     }
 
     pub fn render_month_buffer_ym(&self, year: i32, month: u32) -> Vec<String> {
         let (first_day, num_days) = Self::get_month_info_ym(year, month);
-        let weekday_labels = "Mo Tu We Th Fr Sa Su ";
+        let headings = self.names.weekday_headings();
+        let weekday_labels = if self.week_numbers { format!("Wk {headings}") } else { headings };
+        // Every day takes as many columns as the widest weekday heading
+        let day_width = self.names.day_width();
         let mut result = vec![];
 
-        let lspaces = " ".repeat(weekday_labels.len() / 2 - 2);
-        let rspaces = " ".repeat(weekday_labels.len() - lspaces.len() - 3);
+        let width = weekday_labels.width();
+        let month_name = self.names.month_short(month);
+        let lspaces = " ".repeat((width / 2).saturating_sub(month_name.width().div_ceil(2)));
+        let rspaces = " ".repeat(width.saturating_sub(lspaces.len() + month_name.width()));
         result.push(format!(
             "{}{}{}",
             lspaces,
-            Self::get_month_name_m(month),
+            month_name,
             rspaces
         ));
 
-        result.push(weekday_labels.to_owned());
+        result.push(weekday_labels);
 
-        let mut current = "".to_owned();
+        let days = tasks_by_day(self.tasks, year, month, self.filter, &Local::now().fixed_offset()).dated;
+        // ISO 8601, so early January may still be week 52 or 53 and late December week 1
+        let week_label = |day: u32| match self.week_numbers {
+            true => {
+                let week = NaiveDate::from_ymd_opt(year, month, day).unwrap().iso_week().week();
+                format!("{:>2} ", week.to_string().dimmed())
+            },
+            false => "".to_owned(),
+        };
+        let mut current = week_label(1);
 
+        let empty_day = " ".repeat(day_width + 1);
         for _ in 0..first_day {
-            current += "   ";
+            current += &empty_day;
         }
 
         for day in 1..=num_days {
             let date = NaiveDate::from_ymd_opt(year, month, day).expect("Could not set the date");
-            let task_count = self.tasks
-                .iter()
-                .filter(|t| t.time.map_or(false, |d| d.date_naive() == date))
-                .count();
-            let mut colored_day = self.color_day(day, task_count);
+            let due = days.get(&date).map_or(&[][..], |x| x.as_slice());
+            let mut colored_day = match self.overloaded(due) {
+                true => self.color_overloaded_day(day),
+                false => self.color_day(day, due.len()),
+            };
 
             if date == Local::now().date_naive() {
                 colored_day = colored_day.on_color(Color::TrueColor {
@@ -51,10 +241,10 @@ impl<'a> Calendar<'a> {
                 });
             }
 
-            current += format!("{:>2} ", colored_day).as_str();
+            current += format!("{:>day_width$} ", colored_day).as_str();
             if (day + first_day) % 7 == 0 {
                 result.push(current);
-                current = "".to_owned();
+                current = if day < num_days { week_label(day + 1) } else { "".to_owned() };
             }
         }
 
@@ -65,7 +255,7 @@ impl<'a> Calendar<'a> {
 
         let filled = (total as f32 / 7f32).floor() as u32 * 7 + 7;
         let padding_len = (filled - total) as usize;
-        let padding = "   ".repeat(padding_len);
+        let padding = empty_day.repeat(padding_len);
         result.push(current + &padding);
 
         result
@@ -91,6 +281,51 @@ impl<'a> Calendar<'a> {
         }
     }
 
+    /// One line per task due in the displayed month, "12 May: Algorithms — HW3 (due 18:00)",
+    /// colored by status, then the undated starred ones when asked for. Completed ones, when
+    /// the filter lets them in, are dimmed and ticked.
+    pub fn render_agenda_buffer(&self) -> Vec<String> {
+        let now = Local::now().fixed_offset();
+        let days = tasks_by_day(self.tasks, self.date.year(), self.date.month(), self.filter, &now);
+        let color = |line: String, task: &Task| match task.complete {
+            true => format!("{line} ✓").dimmed().to_string(),
+            false => status_colored(&line, &self.policy.status(task, &now)).to_string(),
+        };
+
+        let mut result = vec![];
+        for (date, tasks) in days.dated {
+            for task in tasks {
+                let time = task.time.map(|x| x.with_timezone(&Local).format("%H:%M").to_string()).unwrap_or_default();
+                let line = format!(
+                    "{} {}: {} — {} (due {})",
+                    date.day(),
+                    self.names.month_short(date.month()),
+                    self.abbrev.abbreviate(&task.subject),
+                    task.name,
+                    time,
+                );
+                result.push(color(line, task));
+            }
+        }
+        if self.anytime {
+            for task in days.undated.into_iter().filter(|x| x.starred) {
+                let line = format!("Anytime: {} — {} ★", self.abbrev.abbreviate(&task.subject), task.name);
+                result.push(color(line, task));
+            }
+        }
+        result
+    }
+
+    pub fn render_agenda(&self) {
+        let lines = self.render_agenda_buffer();
+        if lines.is_empty() {
+            println!("Nothing due in {}", self.get_month_name());
+        }
+        for line in lines.iter() {
+            println!("{line}");
+        }
+    }
+
     pub fn render3_ym(&self, year: i32, month: u32) {
         let (previous_year, previous_month) = if self.date.month() == 1 {
             (year - 1, 12u32)
@@ -110,7 +345,7 @@ impl<'a> Calendar<'a> {
             self.render_month_buffer_ym(next_year, next_month)
         ];
 
-        let month_width = months[0][0].len();
+        let month_width = months[0][0].width();
         let max_height = months
             .iter()
             .map(|el| el.len())
@@ -142,7 +377,8 @@ impl<'a> Calendar<'a> {
         }
     }
 
-    fn get_month_info_ym(year: i32, month: u32) -> (u32, u32) {
+    /// Weekday of the 1st counted from Monday, and the number of days
+    pub fn get_month_info_ym(year: i32, month: u32) -> (u32, u32) {
         let current_month_first_day = NaiveDate::from_ymd_opt(year, month, 1)
             .unwrap();
 
@@ -173,35 +409,246 @@ impl<'a> Calendar<'a> {
         Self::get_month_info_ym(year, month)
     }
 
-    fn get_month_name_m(month: u32) -> String {
-        match month {
-            1 => "Jan",
-            2 => "Feb",
-            3 => "Mar",
-            4 => "Apr",
-            5 => "May",
-            6 => "Jun",
-            7 => "Jul",
-            8 => "Aug",
-            9 => "Sep",
-            10 => "Oct",
-            11 => "Nov",
-            12 => "Dec",
-            _ => "Unknown",
-        }.to_owned()
+    fn get_month_name(&self) -> String {
+        self.names.month_short(self.date.month()).to_owned()
     }
 
-    fn get_month_name(&self) -> String {
-        let month = self.date.month();
+    /// Whether more open tasks are due on a day than the limit allows
+    fn overloaded(&self, due: &[&Task]) -> bool {
+        self.day_limit.is_some_and(|x| due.iter().filter(|x| !x.complete).count() > x)
+    }
 
-        Self::get_month_name_m(month)
+    /// A day with more open tasks than the limit, apart from however busy the others are
+    fn color_overloaded_day(&self, day: u32) -> ColoredString {
+        match self.symbols {
+            true => day.to_string().magenta().bold().underline().italic(),
+            false => day.to_string().magenta(),
+        }
     }
 
     fn color_day(&self, day: u32, task_count: usize) -> ColoredString {
-        match task_count {
-            0 => day.to_string().white(),
-            1 | 2 => day.to_string().yellow(),
-            _ => day.to_string().red(),
+        match (task_count, self.symbols) {
+            (0, _) => day.to_string().white(),
+            (1 | 2, false) => day.to_string().yellow(),
+            (1 | 2, true) => day.to_string().yellow().bold(),
+            (_, false) => day.to_string().red(),
+            (_, true) => day.to_string().red().bold().underline(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use chrono::{TimeDelta, TimeZone};
+
+    /// A wall-clock time here, which is what the calendar files tasks by
+    fn local(text: &str) -> DateTime<FixedOffset> {
+        let time = chrono::NaiveDateTime::parse_from_str(text, "%Y-%m-%dT%H:%M").unwrap();
+        Local.from_local_datetime(&time).unwrap().fixed_offset()
+    }
+
+    fn describe(summary: &DaySummary) -> String {
+        let parts = summary.parts().into_iter().map(|(x, _)| x).collect::<Vec<_>>();
+        format!("{}{}", summary.heading(&DateNames::default()), parts.join(", "))
+    }
+
+    #[test]
+    fn day_summary_counts_by_status() {
+        let now = DateTime::parse_from_rfc3339("2025-05-13T12:00:00+02:00").unwrap();
+        let at = |hour| Some(now + TimeDelta::hours(hour));
+        let task = |time, complete, starred| Task { time, complete, starred, ..Task::default() };
+        let tasks = [task(at(-2), false, true), task(at(2), false, false), task(at(8), false, true), task(at(-4), true, true)];
+        let tasks = tasks.iter().collect::<Vec<_>>();
+        let date = now.date_naive();
+
+        let summary = DaySummary::new(&tasks, date, &now, &StatusPolicy::default());
+        assert_eq!(describe(&summary), "Tue 13 May — 4 tasks: 1 overdue, 2 due, 1 done, 2 starred");
+        assert_eq!(summary.parts()[1].1, Some(TaskStatus::DueToday));
+
+        // Tomorrow's count is as pressing as the policy makes it
+        let tomorrow = [task(at(30), false, false)];
+        let tomorrow = tomorrow.iter().collect::<Vec<_>>();
+        let date = date.succ_opt().unwrap();
+        let pressing = DaySummary::new(&tomorrow, date, &now, &StatusPolicy::default());
+        assert_eq!((describe(&pressing).as_str(), pressing.due_status), ("Wed 14 May — 1 task: 1 due", TaskStatus::Panic));
+        let calm = DaySummary::new(&tomorrow, date, &now, &StatusPolicy { threshold: TimeDelta::hours(1), ..StatusPolicy::default() });
+        assert_eq!(calm.due_status, TaskStatus::Normal);
+        assert_eq!(describe(&DaySummary::new(&[], date, &now, &StatusPolicy::default())), "Wed 14 May — nothing due");
+    }
+
+    #[test]
+    fn agenda_lists_the_month_by_day() {
+        colored::control::set_override(false);
+        let at = |x| Some(local(x));
+        let task = |subject: &str, name: &str, time, complete| Task {
+            subject: subject.to_string(),
+            name: name.to_string(),
+            time,
+            complete,
+            ..Task::default()
+        };
+        let tasks = vec![
+            task("Physics", "Lab", at("2025-05-14T09:00"), false),
+            task("Algorithms", "HW3", at("2025-05-12T18:00"), false),
+            task("Algorithms", "HW2", at("2025-05-05T18:00"), true),
+            task("Databases", "Quiz", at("2025-06-01T10:00"), false),
+            task("Databases", "Notes", None, false),
+        ];
+        let date = Local.with_ymd_and_hms(2025, 5, 20, 12, 0, 0).unwrap();
+
+        assert_eq!(
+            Calendar::new(date, &tasks).render_agenda_buffer(),
+            ["12 May: Algorithms — HW3 (due 18:00)", "14 May: Physics — Lab (due 09:00)"],
+        );
+        // Completed ones, when let in, are ticked
+        assert_eq!(
+            Calendar::new(date, &tasks).with_filter(CalendarTaskFilter::Completed).render_agenda_buffer(),
+            [
+                "5 May: Algorithms — HW2 (due 18:00) ✓",
+                "12 May: Algorithms — HW3 (due 18:00)",
+                "14 May: Physics — Lab (due 09:00)",
+            ],
+        );
+    }
+
+    #[test]
+    fn every_view_counts_the_tasks_the_filter_lets_in() {
+        let now = local("2025-05-20T12:00");
+        let done = |days_ago| Some(now - TimeDelta::days(days_ago));
+        let task = |time, completed_at: Option<DateTime<FixedOffset>>| Task {
+            time: Some(local(time)),
+            complete: completed_at.is_some(),
+            completed_at,
+            ..Task::default()
+        };
+        let tasks = vec![
+            task("2025-05-12T18:00", None),
+            task("2025-05-12T20:00", done(2)),
+            task("2025-05-14T09:00", done(30)),
+            Task { complete: true, completed_at: done(1), ..Task::default() },
+        ];
+        let counts = |filter| {
+            let days = tasks_by_day(&tasks, 2025, 5, filter, &now);
+            let dated = days.dated.iter().map(|(date, x)| (date.day(), x.len())).collect::<Vec<_>>();
+            (dated, days.undated.len())
+        };
+
+        assert_eq!(counts(CalendarTaskFilter::Incomplete), (vec![(12, 1)], 0));
+        assert_eq!(counts(CalendarTaskFilter::RecentlyCompleted), (vec![(12, 2)], 1));
+        assert_eq!(counts(CalendarTaskFilter::Completed), (vec![(12, 2), (14, 1)], 1));
+
+        // Completed without a known completion are never recent
+        let unknown = Task { complete: true, ..Task::default() };
+        assert!(!CalendarTaskFilter::RecentlyCompleted.includes(&unknown, &now));
+        assert!(CalendarTaskFilter::Incomplete.includes(&tasks[0], &now));
+    }
+
+    #[test]
+    fn starred_undated_tasks_are_on_the_agenda_anytime() {
+        colored::control::set_override(false);
+        let task = |name: &str, time, starred| Task {
+            subject: "Physics".to_string(),
+            name: name.to_string(),
+            time,
+            starred,
+            ..Task::default()
+        };
+        let tasks = vec![
+            task("Notes", None, true),
+            task("Reading", None, false),
+            task("Lab", Some(local("2025-05-14T09:00")), false),
+        ];
+        let date = Local.with_ymd_and_hms(2025, 5, 20, 12, 0, 0).unwrap();
+
+        assert_eq!(tasks_by_day(&tasks, 2025, 5, CalendarTaskFilter::default(), &local("2025-05-20T12:00")).undated.len(), 2);
+        assert_eq!(Calendar::new(date, &tasks).render_agenda_buffer().len(), 1);
+        assert_eq!(
+            Calendar::new(date, &tasks).with_anytime(true).render_agenda_buffer(),
+            ["14 May: Physics — Lab (due 09:00)", "Anytime: Physics — Notes ★"],
+        );
+    }
+
+    #[test]
+    fn busy_days_gain_text_styles_with_symbols() {
+        use colored::Styles;
+
+        let tasks = vec![];
+        let date = Local.with_ymd_and_hms(2025, 5, 20, 12, 0, 0).unwrap();
+        let plain = Calendar::new(date, &tasks);
+        let symbols = Calendar::new(date, &tasks).with_status_symbols(true);
+
+        assert!(!plain.color_day(1, 3).style().contains(Styles::Bold));
+        assert!(symbols.color_day(1, 1).style().contains(Styles::Bold));
+        assert!(!symbols.color_day(1, 1).style().contains(Styles::Underline));
+        assert!(symbols.color_day(1, 3).style().contains(Styles::Underline));
+        assert!(!symbols.color_day(1, 0).style().contains(Styles::Bold));
+        // Overloaded days go further still
+        assert!(symbols.color_overloaded_day(1).style().contains(Styles::Italic));
+    }
+
+    #[test]
+    fn days_over_the_limit_are_told_by_their_open_tasks() {
+        let due = |complete| Task { complete, ..Task::default() };
+        let (open, done) = (due(false), due(true));
+        let tasks = vec![];
+        let date = Local.with_ymd_and_hms(2025, 5, 20, 12, 0, 0).unwrap();
+        let calendar = Calendar::new(date, &tasks).with_day_limit(Some(1));
+
+        assert!(calendar.overloaded(&[&open, &open]));
+        assert!(!calendar.overloaded(&[&open, &done]));
+        assert!(!Calendar::new(date, &tasks).overloaded(&[&open, &open]));
+    }
+
+    fn weeks(year: i32, month: u32) -> Vec<String> {
+        colored::control::set_override(false);
+        let tasks = vec![];
+        let date = Local.with_ymd_and_hms(year, month, 15, 12, 0, 0).unwrap();
+        let lines = Calendar::new(date, &tasks).with_week_numbers(true).render_month_buffer_ym(year, month);
+
+        assert_eq!(lines[1], "Wk Mo Tu We Th Fr Sa Su ");
+        assert!(lines.iter().all(|x| x.len() == lines[1].len()), "{lines:#?}");
+        lines[2..].iter().map(|x| x[..2].trim().to_string()).collect()
+    }
+
+    #[test]
+    fn other_languages_keep_the_columns_aligned() {
+        use crate::uni::locale::NameOverrides;
+
+        colored::control::set_override(false);
+        let task = Task {
+            subject: "Physique".to_string(),
+            name: "TP".to_string(),
+            time: Some(local("2025-02-14T09:00")),
+            ..Task::default()
+        };
+        let tasks = vec![task];
+        let date = Local.with_ymd_and_hms(2025, 2, 20, 12, 0, 0).unwrap();
+        let overrides = NameOverrides {
+            weekdays_min: ["lun", "mar", "mer", "jeu", "ven", "sam", "dim"].map(String::from).to_vec(),
+            ..NameOverrides::default()
+        };
+
+        for names in [DateNames::builtin("fr").unwrap(), DateNames::resolve("fr", None, &overrides)] {
+            let calendar = Calendar::new(date, &tasks).with_names(names.clone()).with_week_numbers(true);
+            let lines = calendar.render_month_buffer_ym(2025, 2);
+            assert_eq!(lines[1], format!("Wk {}", names.weekday_headings()));
+            assert!(lines.iter().all(|x| x.width() == lines[1].width()), "{lines:#?}");
+            assert_eq!(lines[0].trim(), "févr.");
+            assert_eq!(calendar.render_agenda_buffer(), ["14 févr.: Physique — TP (due 09:00)"]);
         }
+        let wide = Calendar::new(date, &tasks).with_names(DateNames::resolve("fr", None, &overrides));
+        // 1 Feb 2025 is a Saturday
+        assert_eq!(wide.render_month_buffer_ym(2025, 2)[2], " ".repeat(4 * 5) + "  1   2 ");
+    }
+
+    #[test]
+    fn week_numbers_follow_iso_8601_across_new_year() {
+        // 1 Jan 2021 is a Friday, still in week 53 of 2020
+        assert_eq!(weeks(2021, 1), ["53", "1", "2", "3", "4"]);
+        // 30 Dec 2024 is a Monday, already in week 1 of 2025
+        assert_eq!(weeks(2024, 12), ["48", "49", "50", "51", "52", "1"]);
+        assert_eq!(weeks(2026, 1), ["1", "2", "3", "4", "5"]);
     }
 }
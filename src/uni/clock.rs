@@ -0,0 +1,21 @@
+use chrono::{DateTime, FixedOffset, Local};
+
+
+/// Where the UI takes the current time from
+#[derive(Debug, Default, Clone, Copy)]
+pub enum Clock {
+    #[default]
+    System,
+    /// Frozen at one moment, for reproducible rendering
+    #[cfg_attr(not(test), allow(dead_code))]
+    Fixed(DateTime<FixedOffset>),
+}
+
+impl Clock {
+    pub fn now(&self) -> DateTime<FixedOffset> {
+        match self {
+            Clock::System => Local::now().fixed_offset(),
+            Clock::Fixed(time) => *time,
+        }
+    }
+}
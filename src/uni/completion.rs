@@ -0,0 +1,55 @@
+/// Candidates containing `query` as a case-insensitive subsequence, best first:
+/// prefixes, then substrings, then scattered matches with the smallest gaps
+pub fn complete<'a>(candidates: &'a [String], query: &str) -> Vec<&'a str> {
+    let query = query.trim().to_lowercase();
+    let mut matches = candidates
+        .iter()
+        .filter_map(|x| score(&x.to_lowercase(), &query).map(|score| (score, x.as_str())))
+        .collect::<Vec<_>>();
+    matches.sort();
+    matches.into_iter().map(|(_, x)| x).collect()
+}
+
+/// How well `query` matches `candidate`, both lowercase, None when it doesn't.
+/// Lower is better: (kind of match, characters skipped, length)
+pub fn score(candidate: &str, query: &str) -> Option<(u8, usize, usize)> {
+    let len = candidate.chars().count();
+    if candidate.starts_with(query) {
+        return Some((0, 0, len));
+    }
+    if candidate.contains(query) {
+        return Some((1, 0, len));
+    }
+
+    let mut wanted = query.chars().peekable();
+    let mut skipped = 0;
+    let mut started = false;
+    for c in candidate.chars() {
+        match wanted.peek() {
+            Some(&x) if x == c => {
+                wanted.next();
+                started = true;
+            },
+            Some(_) if started => skipped += 1,
+            Some(_) => (),
+            None => break,
+        }
+    }
+    wanted.peek().is_none().then_some((2, skipped, len))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn typos_still_find_the_subject() {
+        let subjects = ["Algorithms", "Linear Algebra", "Physics", "Logic"].map(String::from);
+
+        assert_eq!(complete(&subjects, "alg"), ["Algorithms", "Linear Algebra"]);
+        assert_eq!(complete(&subjects, "Algoritms"), ["Algorithms"]);
+        assert_eq!(complete(&subjects, "lgc"), ["Logic"]);
+        assert_eq!(complete(&subjects, "").len(), 4);
+        assert!(complete(&subjects, "chem").is_empty());
+    }
+}
@@ -0,0 +1,305 @@
+use std::fmt::Write;
+
+use chrono::{DateTime, FixedOffset, Local, NaiveDate, NaiveTime, TimeDelta, TimeZone};
+
+use crate::uni::format::largest_unit;
+use crate::uni::locale::DateNames;
+
+
+/// Absolute dates for people to read, weekday first since that is what planning needs
+pub const DEFAULT_DISPLAY_FORMAT: &str = "%a %d %b %Y %H:%M";
+
+/// Time of a deadline given only as a day, unless `default_deadline_time` says otherwise
+pub const END_OF_DAY: NaiveTime = NaiveTime::from_hms_opt(23, 59, 0).expect("a valid time");
+
+/// The moment a deadline on `day` falls on in `zone`, at `time` or else at `default_time`.
+/// Every place a deadline can be typed or picked goes through here.
+pub fn deadline_on<Tz: TimeZone>(
+    day: NaiveDate,
+    time: Option<NaiveTime>,
+    default_time: NaiveTime,
+    zone: &Tz,
+) -> Option<DateTime<FixedOffset>> {
+    zone.from_local_datetime(&day.and_time(time.unwrap_or(default_time)))
+        .single()
+        .map(|x| x.fixed_offset())
+}
+
+/// The calendar date `time` falls on in `zone`
+pub fn date_in<Tz: TimeZone>(time: &DateTime<FixedOffset>, zone: &Tz) -> NaiveDate {
+    time.with_timezone(zone).date_naive()
+}
+
+/// The calendar date `time` falls on here and now. "Today" and every other day a task is
+/// filed under are these dates, whatever offset the times were written with.
+pub fn local_date(time: &DateTime<FixedOffset>) -> NaiveDate {
+    date_in(time, &Local)
+}
+
+/// Zone absolute dates are shown in, `ui.display_timezone`
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum DisplayZone {
+    /// The system's zone at the moment
+    #[default]
+    Local,
+    /// The offset each time was written with
+    Original,
+    #[cfg(feature = "tz")]
+    Named(chrono_tz::Tz),
+}
+
+impl DisplayZone {
+    /// "local", "original" or an IANA name like "Europe/Berlin". Names need the `tz`
+    /// feature; unknown ones, like any name without it, show local times.
+    pub fn resolve(name: &str) -> Self {
+        match name.trim().to_lowercase().as_str() {
+            "original" => Self::Original,
+            #[cfg(feature = "tz")]
+            "local" => Self::Local,
+            #[cfg(feature = "tz")]
+            _ => name.trim().parse().map_or(Self::Local, Self::Named),
+            #[cfg(not(feature = "tz"))]
+            _ => Self::Local,
+        }
+    }
+
+    /// The same instant on the clock of the zone
+    pub fn show(&self, time: &DateTime<FixedOffset>) -> DateTime<FixedOffset> {
+        match self {
+            Self::Local => time.with_timezone(&Local).fixed_offset(),
+            Self::Original => *time,
+            #[cfg(feature = "tz")]
+            Self::Named(zone) => time.with_timezone(zone).fixed_offset(),
+        }
+    }
+}
+
+/// Formats `time` in `zone` with a user-supplied strftime string and the month and weekday
+/// `names`, falling back to RFC 2822 when the format is invalid
+pub fn format_date(time: &DateTime<FixedOffset>, format: &str, names: &DateNames, zone: &DisplayZone) -> String {
+    let time = &zone.show(time);
+    let mut result = String::new();
+    match write!(result, "{}", time.format(&names.localize(format, time))) {
+        Ok(()) => result,
+        Err(_) => time.to_rfc2822(),
+    }
+}
+
+/// How long before `now` something happened, in its largest whole unit: "3 days ago"
+pub fn format_ago(time: &DateTime<FixedOffset>, now: &DateTime<FixedOffset>) -> String {
+    let ago = *now - *time;
+    match ago.num_minutes() {
+        ..=0 => "just now".to_string(),
+        _ => format!("{} ago", largest_unit(ago)),
+    }
+}
+
+/// Tracked time for people to read: "9h 05m", "40m"
+pub fn format_spent(spent: TimeDelta) -> String {
+    match (spent.num_hours(), spent.num_minutes() % 60) {
+        (0, minutes) => format!("{minutes}m"),
+        (hours, minutes) => format!("{hours}h {minutes:02}m"),
+    }
+}
+
+/// A duration as `parse_duration` reads it back: "-7d", "1d12h", "0m"
+pub fn format_duration(delta: TimeDelta) -> String {
+    let sign = if delta < TimeDelta::zero() { "-" } else { "" };
+    let minutes = delta.num_minutes().abs();
+    let parts = [(minutes / (24 * 60), 'd'), (minutes / 60 % 24, 'h'), (minutes % 60, 'm')];
+    let body: String = parts.iter().filter(|(x, _)| *x > 0).map(|(x, unit)| format!("{x}{unit}")).collect();
+    match body.is_empty() {
+        true => "0m".to_string(),
+        false => format!("{sign}{body}"),
+    }
+}
+
+/// Parses a signed duration like "60d", "+7d", "-1w2d" or "3h30m".
+/// Units: w(eeks), d(ays), h(ours), m(inutes).
+pub fn parse_duration(text: &str) -> Result<TimeDelta, String> {
+    let text = text.trim();
+    let (sign, body) = match text.strip_prefix('-') {
+        Some(rest) => (-1, rest),
+        None => (1, text.strip_prefix('+').unwrap_or(text)),
+    };
+
+    if body.is_empty() {
+        return Err(format!("Empty duration: \"{text}\""));
+    }
+
+    let mut total = TimeDelta::zero();
+    let mut number = String::new();
+    for c in body.chars() {
+        if c.is_ascii_digit() {
+            number.push(c);
+            continue;
+        }
+
+        let value = number
+            .parse::<i64>()
+            .map_err(|_| format!("Expected a number before '{c}' in \"{text}\""))?;
+        number.clear();
+
+        let delta = match c {
+            'w' => TimeDelta::try_weeks(value),
+            'd' => TimeDelta::try_days(value),
+            'h' => TimeDelta::try_hours(value),
+            'm' => TimeDelta::try_minutes(value),
+            _ => return Err(format!("Unknown duration unit '{c}' in \"{text}\"")),
+        };
+        total = delta
+            .and_then(|x| total.checked_add(&x))
+            .ok_or_else(|| format!("Duration too long: \"{text}\""))?;
+    }
+
+    if !number.is_empty() {
+        return Err(format!("Missing unit after {number} in \"{text}\""));
+    }
+
+    Ok(total * sign)
+}
+
+/// Parses a deadline typed by hand: "2025-05-12 18:00", "2025-05-12" (at `default_time`),
+/// "today" or "tomorrow" with an optional "HH:MM", RFC 3339, or a duration from now like "+3d"
+pub fn parse_deadline(
+    text: &str,
+    now: &DateTime<FixedOffset>,
+    default_time: NaiveTime,
+) -> Result<DateTime<FixedOffset>, String> {
+    let text = text.trim();
+    if text.starts_with(['+', '-']) {
+        return now
+            .checked_add_signed(parse_duration(text)?)
+            .ok_or_else(|| format!("\"{text}\" is too far from now"));
+    }
+    if let Ok(time) = DateTime::parse_from_rfc3339(text) {
+        return Ok(time);
+    }
+
+    let (day, time) = match text.split_once(' ') {
+        Some((day, time)) => (day, Some(time.trim())),
+        None => (text, None),
+    };
+    let day = match day.to_lowercase().as_str() {
+        "today" => now.date_naive(),
+        "tomorrow" => now.date_naive() + TimeDelta::days(1),
+        _ => NaiveDate::parse_from_str(day, "%Y-%m-%d").map_err(|_| format!("Unknown date \"{day}\""))?,
+    };
+    let time = match time {
+        Some(time) => Some(NaiveTime::parse_from_str(time, "%H:%M").map_err(|_| format!("Unknown time \"{time}\""))?),
+        None => None,
+    };
+
+    deadline_on(day, time, default_time, now.offset()).ok_or(format!("No such moment: \"{text}\""))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn dates_show_the_weekday() {
+        let time = DateTime::parse_from_rfc3339("2025-05-12T18:00:00+02:00").unwrap();
+
+        let english = DateNames::english();
+        let zone = DisplayZone::Original;
+        assert_eq!(format_date(&time, DEFAULT_DISPLAY_FORMAT, &english, &zone), "Mon 12 May 2025 18:00");
+        assert_eq!(format_date(&time, "%Q", &english, &zone), time.to_rfc2822());
+        let french = DateNames::builtin("fr").unwrap();
+        assert_eq!(format_date(&time, "%A %-d %B, %a %b", &french, &zone), "lundi 12 mai, lun. mai");
+    }
+
+    #[test]
+    fn display_zones_resolve_by_name() {
+        assert_eq!(DisplayZone::resolve("local"), DisplayZone::Local);
+        assert_eq!(DisplayZone::resolve(" Original"), DisplayZone::Original);
+        assert_eq!(DisplayZone::resolve("Nowhere/Special"), DisplayZone::Local);
+        #[cfg(feature = "tz")]
+        assert_eq!(DisplayZone::resolve("Europe/Berlin"), DisplayZone::Named(chrono_tz::Europe::Berlin));
+    }
+
+    #[cfg(feature = "tz")]
+    #[test]
+    fn named_zones_follow_daylight_saving_time() {
+        use chrono_tz::Europe::Berlin;
+
+        // Berlin moved its clocks from 02:00 to 03:00 on 30 March 2025
+        let at = |x| DateTime::parse_from_rfc3339(x).unwrap();
+        let (before, after) = (at("2025-03-30T00:30:00Z"), at("2025-03-30T01:30:00Z"));
+        let names = DateNames::english();
+        let show = |x| format_date(x, "%H:%M %:z", &names, &DisplayZone::Named(Berlin));
+        assert_eq!((show(&before), show(&after)), ("01:30 +01:00".to_string(), "03:30 +02:00".to_string()));
+        assert_eq!(after - before, TimeDelta::hours(1));
+
+        // A deadline just after midnight in summer time belongs to that day, though the
+        // offset of a winter `now` would put it on the day before
+        let deadline = at("2025-04-01T00:30:00+02:00");
+        let now = at("2025-03-29T12:00:00+01:00");
+        assert_eq!(date_in(&deadline, &Berlin), NaiveDate::from_ymd_opt(2025, 4, 1).unwrap());
+        assert_eq!(date_in(&deadline, now.offset()), NaiveDate::from_ymd_opt(2025, 3, 31).unwrap());
+    }
+
+    #[test]
+    fn ago_uses_the_largest_whole_unit() {
+        let now = DateTime::parse_from_rfc3339("2025-05-12T18:00:00+02:00").unwrap();
+
+        assert_eq!(format_ago(&(now - TimeDelta::seconds(30)), &now), "just now");
+        assert_eq!(format_ago(&(now + TimeDelta::hours(1)), &now), "just now");
+        assert_eq!(format_ago(&(now - TimeDelta::minutes(1)), &now), "1 minute ago");
+        assert_eq!(format_ago(&(now - TimeDelta::minutes(150)), &now), "2 hours ago");
+        assert_eq!(format_ago(&(now - TimeDelta::hours(80)), &now), "3 days ago");
+    }
+
+    #[test]
+    fn spent_time_reads_in_hours_and_minutes() {
+        assert_eq!(format_spent(TimeDelta::seconds(59)), "0m");
+        assert_eq!(format_spent(TimeDelta::minutes(40)), "40m");
+        assert_eq!(format_spent(TimeDelta::minutes(545)), "9h 05m");
+    }
+
+    #[test]
+    fn durations_format_the_way_they_parse() {
+        for text in ["-7d", "1d12h", "2h30m", "-45m", "0m"] {
+            assert_eq!(format_duration(parse_duration(text).unwrap()), text);
+        }
+        assert_eq!(format_duration(TimeDelta::weeks(2)), "14d");
+    }
+
+    #[test]
+    fn huge_durations_are_an_error() {
+        for text in ["-1000000000000000w", "9223372036854775807m", "100000000000000d100000000000000d"] {
+            assert_eq!(parse_duration(text), Err(format!("Duration too long: \"{text}\"")));
+        }
+        // Fits a duration, not a date
+        let now = DateTime::parse_from_rfc3339("2025-05-12T18:00:00+02:00").unwrap();
+        assert!(parse_deadline("+10000000000d", &now, END_OF_DAY).is_err());
+    }
+
+    #[test]
+    fn deadlines_parse_from_several_shapes() {
+        let now = DateTime::parse_from_rfc3339("2025-05-12T18:00:00+02:00").unwrap();
+        let at = |x| DateTime::parse_from_rfc3339(x).unwrap();
+
+        let parse = |x| parse_deadline(x, &now, END_OF_DAY);
+
+        assert_eq!(parse("2025-05-20 09:30"), Ok(at("2025-05-20T09:30:00+02:00")));
+        assert_eq!(parse("2025-05-20"), Ok(at("2025-05-20T23:59:00+02:00")));
+        assert_eq!(parse("tomorrow 08:00"), Ok(at("2025-05-13T08:00:00+02:00")));
+        assert_eq!(parse("Today"), Ok(at("2025-05-12T23:59:00+02:00")));
+        assert_eq!(parse("+3d"), Ok(at("2025-05-15T18:00:00+02:00")));
+        assert!(parse("someday").is_err());
+        assert!(parse("2025-05-20 25:00").is_err());
+    }
+
+    #[test]
+    fn days_alone_take_the_default_time_and_typed_times_win() {
+        let now = DateTime::parse_from_rfc3339("2025-05-12T18:00:00+02:00").unwrap();
+        let at = |x| DateTime::parse_from_rfc3339(x).unwrap();
+        let nine = NaiveTime::from_hms_opt(9, 0, 0).unwrap();
+
+        assert_eq!(parse_deadline("2025-05-20", &now, nine), Ok(at("2025-05-20T09:00:00+02:00")));
+        assert_eq!(parse_deadline("tomorrow", &now, nine), Ok(at("2025-05-13T09:00:00+02:00")));
+        assert_eq!(parse_deadline("2025-05-20 17:30", &now, nine), Ok(at("2025-05-20T17:30:00+02:00")));
+        assert_eq!(parse_deadline("+1h", &now, nine), Ok(at("2025-05-12T19:00:00+02:00")));
+    }
+}
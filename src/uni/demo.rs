@@ -0,0 +1,93 @@
+use chrono::{DateTime, FixedOffset, TimeDelta};
+
+use crate::uni::task::Task;
+
+
+pub const DEFAULT_SEED: u64 = 42;
+const COUNT: usize = 20;
+
+const SUBJECTS: [&str; 6] = ["Algorithms", "Physics", "Databases", "English", "Statistics", "Networks"];
+const NAMES: [&str; 10] = [
+    "Homework", "Lab report", "Quiz", "Project milestone", "Reading",
+    "Presentation", "Midterm", "Essay draft", "Problem set", "Peer review",
+];
+
+/// SplitMix64, enough to vary the tasks without pulling in a crate
+struct Rng(u64);
+
+impl Rng {
+    fn next(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9e3779b97f4a7c15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58476d1ce4e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d049bb133111eb);
+        z ^ (z >> 31)
+    }
+
+    fn below(&mut self, bound: u64) -> u64 {
+        self.next() % bound
+    }
+
+    /// True with the given chance in percent
+    fn chance(&mut self, percent: u64) -> bool {
+        self.below(100) < percent
+    }
+}
+
+/// Tasks for trying the app out. The same seed and `now` always give the same tasks,
+/// deadlines are spread from two weeks ago to a month ahead.
+pub fn generate(seed: u64, now: DateTime<FixedOffset>) -> Vec<Task> {
+    let mut rng = Rng(seed);
+
+    (0..COUNT).map(|i| {
+        let subject = SUBJECTS[rng.below(SUBJECTS.len() as u64) as usize];
+        let name = NAMES[rng.below(NAMES.len() as u64) as usize];
+        let time = (!rng.chance(15))
+            .then(|| now + TimeDelta::hours(rng.below(44 * 24) as i64 - 14 * 24));
+        let created_at = now - TimeDelta::hours(rng.below(30 * 24) as i64 + 1);
+        let complete = rng.chance(25);
+
+        Task {
+            id: i as u64 + 1,
+            name: format!("{name} {}", i + 1),
+            description: format!("{name} for {subject}.\nGenerated by the demo mode."),
+            subject: subject.to_string(),
+            time,
+            complete,
+            starred: rng.chance(20),
+            created_at: Some(created_at),
+            completed_at: complete.then_some(now),
+            updated_at: Some(created_at),
+            time_spent: TimeDelta::zero(),
+            color: None,
+            anchor: None,
+        }
+    }).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn now() -> DateTime<FixedOffset> {
+        DateTime::parse_from_rfc3339("2025-03-01T12:00:00+01:00").unwrap()
+    }
+
+    #[test]
+    fn same_seed_gives_same_tasks() {
+        assert_eq!(generate(7, now()), generate(7, now()));
+        assert_ne!(generate(7, now()), generate(8, now()));
+    }
+
+    #[test]
+    fn tasks_cover_the_interesting_cases() {
+        let tasks = generate(DEFAULT_SEED, now());
+
+        assert_eq!(tasks.len(), COUNT);
+        assert!(tasks.iter().any(|x| x.time.is_none()));
+        assert!(tasks.iter().any(|x| x.time.is_some_and(|t| t < now())));
+        assert!(tasks.iter().any(|x| x.time.is_some_and(|t| t > now())));
+        assert!(tasks.iter().any(|x| x.complete));
+        assert!(tasks.iter().any(|x| x.starred));
+    }
+}
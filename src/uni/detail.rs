@@ -0,0 +1,135 @@
+use chrono::{DateTime, FixedOffset, TimeDelta};
+use serde::Serialize;
+use unicode_width::UnicodeWidthStr;
+
+use crate::uni::dates::{format_date, format_spent, DisplayZone};
+use crate::uni::locale::DateNames;
+use crate::uni::policy::StatusPolicy;
+use crate::uni::task::Task;
+
+
+/// Stable, scripting-friendly view of a task, the one schema of every `--format json`.
+/// Fields are only ever added; the optional ones at the end are left out when unset.
+#[derive(Serialize)]
+pub struct TaskView<'a> {
+    pub id: u64,
+    pub name: &'a str,
+    pub subject: &'a str,
+    pub description: &'a str,
+    /// Deadline as RFC 3339, null when there is none
+    pub time: Option<String>,
+    pub complete: bool,
+    pub starred: bool,
+    /// "zen", "normal", "due today" or "panic", under the configured policy
+    pub status: &'static str,
+    /// Until the deadline, negative once it passed
+    pub delta_seconds: Option<i64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub created_at: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub updated_at: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub completed_at: Option<String>,
+    /// Tracked with the focus timer
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub time_spent_seconds: Option<i64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub color: Option<&'a str>,
+}
+
+impl<'a> TaskView<'a> {
+    pub fn new(task: &'a Task, now: &DateTime<FixedOffset>, policy: &StatusPolicy) -> Self {
+        let delta = task.get_delta(now);
+        Self {
+            id: task.id,
+            name: &task.name,
+            subject: &task.subject,
+            description: &task.description,
+            time: task.time.map(|x| x.to_rfc3339()),
+            complete: task.complete,
+            starred: task.starred,
+            status: policy.status(task, now).label(),
+            delta_seconds: delta.map(|x| x.num_seconds()),
+            created_at: task.created_at.map(|x| x.to_rfc3339()),
+            updated_at: task.updated_at.map(|x| x.to_rfc3339()),
+            completed_at: task.completed_at.map(|x| x.to_rfc3339()),
+            time_spent_seconds: (task.time_spent > TimeDelta::zero()).then_some(task.time_spent.num_seconds()),
+            color: task.color.as_deref(),
+        }
+    }
+}
+
+/// Labelled fields of a task, in display order, without the description
+pub fn detail_fields(
+    task: &Task,
+    now: &DateTime<FixedOffset>,
+    date_format: &str,
+    names: &DateNames,
+    zone: &DisplayZone,
+    policy: &StatusPolicy,
+) -> Vec<(&'static str, String)> {
+    let deadline = match task.time {
+        Some(time) => format!("{} ({})", format_date(&time, date_format, names, zone), task.delta_at(now)),
+        None => "None".to_string(),
+    };
+    let yes_no = |x: bool| if x { "yes" } else { "no" }.to_string();
+    let status = match policy.reason(task, now) {
+        Some(reason) => format!("{} ({reason})", policy.status(task, now).label()),
+        None => policy.status(task, now).label().to_string(),
+    };
+
+    let mut fields = vec![
+        ("Id", task.id.to_string()),
+        ("Name", task.name.clone()),
+        ("Subject", task.subject.clone()),
+        ("Deadline", deadline),
+        ("Status", status),
+        ("Complete", yes_no(task.complete)),
+        ("Starred", yes_no(task.starred)),
+    ];
+    if task.time_spent > TimeDelta::zero() {
+        fields.push(("Time spent", format_spent(task.time_spent)));
+    }
+    fields
+}
+
+/// Full plain-text rendering, the description wrapped to `width` columns
+pub fn format_detail(
+    task: &Task,
+    now: &DateTime<FixedOffset>,
+    width: usize,
+    date_format: &str,
+    names: &DateNames,
+    zone: &DisplayZone,
+    policy: &StatusPolicy,
+) -> String {
+    let fields = detail_fields(task, now, date_format, names, zone, policy);
+    let label_width = fields.iter().map(|(x, _)| x.width()).max().unwrap_or(0);
+
+    let mut lines = fields
+        .iter()
+        .map(|(label, value)| format!("{label:<label_width$}  {value}"))
+        .collect::<Vec<_>>();
+    lines.push(String::new());
+    lines.extend(wrap(&task.description, width));
+    lines.join("\n")
+}
+
+/// Greedy word wrap keeping the existing line breaks
+pub fn wrap(text: &str, width: usize) -> Vec<String> {
+    let width = width.max(1);
+    let mut lines = vec![];
+    for paragraph in text.lines() {
+        let mut line = String::new();
+        for word in paragraph.split(' ') {
+            if !line.is_empty() && line.width() + 1 + word.width() > width {
+                lines.push(std::mem::take(&mut line));
+            } else if !line.is_empty() {
+                line.push(' ');
+            }
+            line.push_str(word);
+        }
+        lines.push(line);
+    }
+    lines
+}
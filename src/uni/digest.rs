@@ -0,0 +1,115 @@
+use chrono::{DateTime, FixedOffset, TimeDelta};
+
+use crate::uni::dates::local_date;
+use crate::uni::policy::StatusPolicy;
+use crate::uni::task::Task;
+
+
+/// Open tasks worth a look this morning, each section by deadline
+#[derive(Debug, Default)]
+pub struct Digest<'a> {
+    pub overdue: Vec<&'a Task>,
+    pub today: Vec<&'a Task>,
+    pub tomorrow: Vec<&'a Task>,
+    /// Starred whatever their deadline, so a task may also show up above
+    pub starred: Vec<&'a Task>,
+    /// Undated and untouched long enough for the policy to escalate them
+    pub left_alone: Vec<&'a Task>,
+}
+
+impl<'a> Digest<'a> {
+    pub fn new(tasks: &'a [Task], now: &DateTime<FixedOffset>, policy: &StatusPolicy) -> Self {
+        let mut open = tasks.iter().filter(|x| !x.complete).collect::<Vec<_>>();
+        // Undated last
+        open.sort_by_key(|x| (x.time.is_none(), x.time));
+
+        let today = local_date(now);
+        let tomorrow = today + TimeDelta::days(1);
+        let day = |task: &Task| task.time.map(|x| local_date(&x));
+
+        let mut digest = Self::default();
+        for task in open {
+            match task.time {
+                Some(time) if time < *now => digest.overdue.push(task),
+                Some(_) if day(task) == Some(today) => digest.today.push(task),
+                Some(_) if day(task) == Some(tomorrow) => digest.tomorrow.push(task),
+                _ => (),
+            }
+            if task.starred {
+                digest.starred.push(task);
+            }
+            if policy.idle_days(task, now).is_some() {
+                digest.left_alone.push(task);
+            }
+        }
+        digest
+    }
+
+    /// Section titles with their tasks, in reading order
+    pub fn sections(&self) -> [(&'static str, &[&'a Task]); 5] {
+        [
+            ("Overdue", &self.overdue),
+            ("Due today", &self.today),
+            ("Due tomorrow", &self.tomorrow),
+            ("Starred", &self.starred),
+            ("Left alone", &self.left_alone),
+        ]
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.sections().iter().all(|(_, tasks)| tasks.is_empty())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use chrono::{Local, TimeZone};
+
+    #[test]
+    fn tasks_land_in_their_sections() {
+        let now = Local.with_ymd_and_hms(2025, 5, 12, 9, 0, 0).unwrap().fixed_offset();
+        let task = |name: &str, hours: Option<i64>, starred, complete| Task {
+            name: name.to_string(),
+            time: hours.map(|x| now + TimeDelta::hours(x)),
+            starred,
+            complete,
+            ..Task::default()
+        };
+        let tasks = vec![
+            task("late", Some(-2), false, false),
+            task("done", Some(-2), false, true),
+            task("tonight", Some(12), false, false),
+            task("tomorrow", Some(20), true, false),
+            task("next week", Some(24 * 7), false, false),
+            task("someday", None, true, false),
+        ];
+
+        let digest = Digest::new(&tasks, &now, &StatusPolicy::default());
+        let names = |tasks: &[&Task]| tasks.iter().map(|x| x.name.clone()).collect::<Vec<_>>();
+        assert_eq!(names(&digest.overdue), ["late"]);
+        assert_eq!(names(&digest.today), ["tonight"]);
+        assert_eq!(names(&digest.tomorrow), ["tomorrow"]);
+        assert_eq!(names(&digest.starred), ["tomorrow", "someday"]);
+        assert!(digest.left_alone.is_empty());
+        assert!(!digest.is_empty());
+    }
+
+    #[test]
+    fn escalated_tasks_are_left_alone() {
+        let now = DateTime::parse_from_rfc3339("2025-05-12T09:00:00+02:00").unwrap();
+        let task = |name: &str, days: i64| Task {
+            name: name.to_string(),
+            updated_at: Some(now - TimeDelta::days(days)),
+            time: None,
+            ..Task::default()
+        };
+        let tasks = vec![task("forgotten", 30), task("recent", 2)];
+        let policy = StatusPolicy { escalate_after: Some(TimeDelta::days(14)), ..StatusPolicy::default() };
+
+        let digest = Digest::new(&tasks, &now, &policy);
+        assert_eq!(digest.left_alone.iter().map(|x| x.name.as_str()).collect::<Vec<_>>(), ["forgotten"]);
+        assert!(Digest::new(&tasks, &now, &StatusPolicy::default()).is_empty());
+    }
+}
@@ -0,0 +1,209 @@
+use std::path::Path;
+
+use chrono::{DateTime, FixedOffset, TimeDelta};
+
+use crate::config::SubjectColors;
+use crate::uni::dates::{format_date, DisplayZone};
+use crate::uni::locale::DateNames;
+use crate::uni::overview;
+use crate::uni::query::TaskQuery;
+use crate::uni::task::Task;
+
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExportFormat {
+    Markdown,
+    Csv,
+    Plain,
+    /// Printable overview of the deadlines by month, see `overview::render`
+    Html,
+}
+
+impl ExportFormat {
+    /// Guessed from the extension, plain text unless it is .md, .csv or .html
+    pub fn from_path(path: &Path) -> Self {
+        match path.extension().and_then(|x| x.to_str()).map(str::to_lowercase).as_deref() {
+            Some("md" | "markdown") => Self::Markdown,
+            Some("csv") => Self::Csv,
+            Some("html" | "htm") => Self::Html,
+            _ => Self::Plain,
+        }
+    }
+}
+
+/// Which completed tasks make it into an export; by default none of them
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ExportOptions {
+    pub include_completed: bool,
+    /// Only the ones completed this recently, which lets them in without `include_completed`
+    pub completed_since: Option<TimeDelta>,
+}
+
+impl ExportOptions {
+    /// The tasks of `tasks` the options let through
+    pub fn query<'a>(&self, tasks: &'a [Task], now: DateTime<FixedOffset>) -> TaskQuery<'a> {
+        let query = TaskQuery::new(tasks, now);
+        match (self.include_completed, self.completed_since) {
+            (_, Some(since)) => query.matching(move |x| !x.complete || x.completed_at.is_some_and(|t| t >= now - since)),
+            (true, None) => query,
+            (false, None) => query.complete(false),
+        }
+    }
+}
+
+/// Renders the tasks in the order given, so a filtered and sorted view comes out as shown.
+/// Completed tasks are left out unless `options` let them in; the count is of those rendered.
+/// HTML goes by month and deadline instead, with only the dated tasks and the subjects in
+/// their `subject_colors`.
+#[allow(clippy::too_many_arguments)]
+pub fn export<'a>(
+    tasks: impl IntoIterator<Item = &'a Task>,
+    format: ExportFormat,
+    options: &ExportOptions,
+    now: DateTime<FixedOffset>,
+    date_format: &str,
+    names: &DateNames,
+    zone: &DisplayZone,
+    subject_colors: &SubjectColors,
+) -> (String, usize) {
+    let query = options.query(&[], now);
+    let mut tasks = tasks.into_iter().filter(|x| query.matches(x)).collect::<Vec<_>>();
+    if format == ExportFormat::Html {
+        tasks.retain(|x| x.time.is_some());
+        return (overview::render(&tasks, subject_colors, now, date_format, names, zone), tasks.len());
+    }
+    let count = tasks.len();
+    let deadline = |task: &Task| task.time.map(|x| format_date(&x, date_format, names, zone));
+    let mut lines = vec![];
+    match format {
+        ExportFormat::Markdown => {
+            for task in tasks {
+                let check = if task.complete { "x" } else { " " };
+                let star = if task.starred { " ★" } else { "" };
+                let due = deadline(task).map(|x| format!(" — due {x}")).unwrap_or_default();
+                lines.push(format!("- [{check}] **{}**: {}{star}{due}", task.subject, task.name));
+            }
+        },
+        ExportFormat::Csv => {
+            let header = ["id", "subject", "name", "deadline", "complete", "starred", "description"].map(String::from);
+            let rows = tasks.into_iter().map(|task| [
+                task.id.to_string(),
+                task.subject.clone(),
+                task.name.clone(),
+                task.time.map(|x| x.to_rfc3339()).unwrap_or_default(),
+                task.complete.to_string(),
+                task.starred.to_string(),
+                task.description.clone(),
+            ]);
+            return (csv_table([header].into_iter().chain(rows)), count);
+        },
+        ExportFormat::Html => unreachable!("Rendered as a whole above"),
+        ExportFormat::Plain => {
+            for task in tasks {
+                let due = deadline(task).unwrap_or("no deadline".to_string());
+                lines.push(format!("{}: {} ({due})", task.subject, task.name));
+            }
+        },
+    }
+    let mut content = lines.join("\n");
+    content.push('\n');
+    (content, count)
+}
+
+/// The records as CSV, one per line and quoted only where they have to be
+pub fn csv_table<R: IntoIterator<Item = String>>(records: impl IntoIterator<Item = R>) -> String {
+    let mut writer = csv::Writer::from_writer(vec![]);
+    for record in records {
+        writer.write_record(record).expect("Records written to memory have the same length");
+    }
+    let content = writer.into_inner().expect("Writing to memory can't fail");
+    String::from_utf8(content).expect("Written from strings")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tasks() -> Vec<Task> {
+        vec![
+            Task {
+                id: 1,
+                subject: "Algo".to_string(),
+                name: "HW1".to_string(),
+                description: "Read \"CLRS\", chapter 2".to_string(),
+                time: Some(DateTime::parse_from_rfc3339("2025-05-12T18:00:00+02:00").unwrap()),
+                starred: true,
+                complete: false,
+                ..Task::default()
+            },
+            Task {
+                id: 2,
+                subject: "Physics".to_string(),
+                name: "Lab".to_string(),
+                description: String::new(),
+                time: None,
+                starred: false,
+                complete: true,
+                ..Task::default()
+            },
+        ]
+    }
+
+    #[test]
+    fn formats_follow_the_extension() {
+        assert_eq!(ExportFormat::from_path(Path::new("week.MD")), ExportFormat::Markdown);
+        assert_eq!(ExportFormat::from_path(Path::new("week.csv")), ExportFormat::Csv);
+        assert_eq!(ExportFormat::from_path(Path::new("term.html")), ExportFormat::Html);
+        assert_eq!(ExportFormat::from_path(Path::new("week")), ExportFormat::Plain);
+    }
+
+    fn now() -> DateTime<FixedOffset> {
+        DateTime::parse_from_rfc3339("2025-05-12T09:00:00+02:00").unwrap()
+    }
+
+    fn render(tasks: &[Task], format: ExportFormat, options: &ExportOptions) -> String {
+        export(tasks, format, options, now(), "%a %d %b %Y %H:%M", &DateNames::english(), &DisplayZone::Original, &SubjectColors::default()).0
+    }
+
+    #[test]
+    fn every_format_keeps_the_given_order() {
+        let tasks = tasks();
+        let all = ExportOptions { include_completed: true, ..ExportOptions::default() };
+        let reversed = tasks.iter().rev().cloned().collect::<Vec<_>>();
+
+        assert_eq!(
+            render(&reversed, ExportFormat::Markdown, &all),
+            "- [x] **Physics**: Lab\n- [ ] **Algo**: HW1 ★ — due Mon 12 May 2025 18:00\n",
+        );
+        assert_eq!(
+            render(&tasks, ExportFormat::Csv, &all),
+            "id,subject,name,deadline,complete,starred,description\n\
+            1,Algo,HW1,2025-05-12T18:00:00+02:00,false,true,\"Read \"\"CLRS\"\", chapter 2\"\n\
+            2,Physics,Lab,,true,false,\n",
+        );
+        assert_eq!(
+            render(&tasks, ExportFormat::Plain, &all),
+            "Algo: HW1 (Mon 12 May 2025 18:00)\nPhysics: Lab (no deadline)\n",
+        );
+    }
+
+    #[test]
+    fn completed_tasks_are_left_out_unless_asked_for() {
+        let mut tasks = tasks();
+        tasks[1].completed_at = Some(now() - TimeDelta::days(3));
+        let all = ExportOptions { include_completed: true, ..ExportOptions::default() };
+        let week = ExportOptions { completed_since: Some(TimeDelta::weeks(1)), ..ExportOptions::default() };
+        let day = ExportOptions { completed_since: Some(TimeDelta::days(1)), ..ExportOptions::default() };
+
+        for format in [ExportFormat::Markdown, ExportFormat::Csv, ExportFormat::Plain] {
+            assert!(!render(&tasks, format, &ExportOptions::default()).contains("Lab"));
+            assert!(render(&tasks, format, &all).contains("Lab"));
+            assert!(render(&tasks, format, &week).contains("Lab"));
+            assert!(!render(&tasks, format, &day).contains("Lab"));
+            assert!(render(&tasks, format, &day).contains("HW1"));
+        }
+        let count = |format| export(&tasks, format, &ExportOptions::default(), now(), "", &DateNames::english(), &DisplayZone::Original, &SubjectColors::default()).1;
+        assert_eq!(count(ExportFormat::Plain), 1);
+        assert_eq!(count(ExportFormat::Html), 1);
+    }
+}
@@ -0,0 +1,151 @@
+use chrono::TimeDelta;
+use colored::{ColoredString, Colorize};
+use ratatui::style::Style;
+use ratatui::text::Span;
+
+use crate::ui::colors::TaskColors;
+use crate::uni::task::TaskStatus;
+
+
+/// How much of a time left `format_delta` spells out
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DeltaLength {
+    /// Every unit in words, "1 day 2 hours 5 minutes", for the task list and the prompt
+    Long,
+    /// The largest two units abbreviated, "1d 2h", for titles and the status bar
+    Short,
+}
+
+/// "1 day", "2 days", "-1 day"
+pub fn pluralize_unit(amount: i64, unit: &str) -> String {
+    let plural = if amount.abs() == 1 { "" } else { "s" };
+    format!("{amount} {unit}{plural}")
+}
+
+/// The largest whole unit of a duration that is not zero: "3 days", "5 hours", "1 minute"
+pub fn largest_unit(delta: TimeDelta) -> String {
+    match (delta.num_days(), delta.num_hours()) {
+        (0, 0) => pluralize_unit(delta.num_minutes(), "minute"),
+        (0, hours) => pluralize_unit(hours, "hour"),
+        (days, _) => pluralize_unit(days, "day"),
+    }
+}
+
+/// Time left until a deadline, negative once it passed. Both lengths split it the same way,
+/// each unit carrying the sign, and leave out units that are zero: "1 day 5 minutes" and "1d"
+/// for a day and five minutes, the short form cut to the largest two before dropping zeros.
+/// Under a minute either way is "No time!" in the long form and "0m" in the short one.
+pub fn format_delta(delta: TimeDelta, length: DeltaLength) -> String {
+    let days = delta.num_days();
+    let hours = delta.num_hours() - days * 24;
+    let minutes = delta.num_minutes() - delta.num_hours() * 60;
+    let units = [(days, "day", "d"), (hours, "hour", "h"), (minutes, "minute", "m")];
+
+    match length {
+        DeltaLength::Long if delta.abs() < TimeDelta::minutes(1) => "No time!".to_string(),
+        DeltaLength::Long => units
+            .iter()
+            .filter(|(x, _, _)| *x != 0)
+            .map(|(x, unit, _)| pluralize_unit(*x, unit))
+            .collect::<Vec<_>>()
+            .join(" "),
+        DeltaLength::Short => {
+            let units = units
+                .iter()
+                .skip_while(|(x, _, _)| *x == 0)
+                .take(2)
+                .filter(|(x, _, _)| *x != 0)
+                .map(|(x, _, unit)| format!("{x}{unit}"))
+                .collect::<Vec<_>>();
+            match units.is_empty() {
+                true => "0m".to_string(),
+                false => units.join(" "),
+            }
+        },
+    }
+}
+
+/// `text` in the color of `status` for a terminal, the prompt's and the CLI's counterpart
+/// of `status_span`
+pub fn status_colored(text: &str, status: &TaskStatus) -> ColoredString {
+    match status {
+        TaskStatus::Panic => text.red(),
+        TaskStatus::DueToday => text.bright_red(),
+        TaskStatus::Normal => text.bright_blue(),
+        TaskStatus::Zen => text.white(),
+    }
+}
+
+/// `text` in the theme's color of `status`, for the full-screen UI
+pub fn status_span<T: TaskColors>(text: impl Into<String>, status: &TaskStatus) -> Span<'static> {
+    Span::styled(text.into(), Style::new().fg(T::task_color(status)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::ui::colors::StandardTaskColors;
+
+    /// Minutes, the long form, the short form the replaced formatter gave and the short one
+    /// now. The old short form kept a zero second unit, the new one drops it as the long one
+    /// always did.
+    const CASES: &[(i64, &str, &str, &str)] = &[
+        (0, "No time!", "0m", "0m"),
+        (1, "1 minute", "1m", "1m"),
+        (-1, "-1 minute", "-1m", "-1m"),
+        (59, "59 minutes", "59m", "59m"),
+        (60, "1 hour", "1h 0m", "1h"),
+        (-60, "-1 hour", "-1h 0m", "-1h"),
+        (61, "1 hour 1 minute", "1h 1m", "1h 1m"),
+        (-119, "-1 hour -59 minutes", "-1h -59m", "-1h -59m"),
+        (24 * 60, "1 day", "1d 0h", "1d"),
+        (-24 * 60, "-1 day", "-1d 0h", "-1d"),
+        (24 * 60 + 1, "1 day 1 minute", "1d 0h", "1d"),
+        (25 * 60, "1 day 1 hour", "1d 1h", "1d 1h"),
+        (26 * 60 + 5, "1 day 2 hours 5 minutes", "1d 2h", "1d 2h"),
+        (-(3 * 24 * 60 + 4 * 60 + 5), "-3 days -4 hours -5 minutes", "-3d -4h", "-3d -4h"),
+        (400 * 24 * 60, "400 days", "400d 0h", "400d"),
+    ];
+
+    #[test]
+    fn deltas_read_the_same_in_both_lengths() {
+        for (minutes, long, _, short) in CASES {
+            let delta = TimeDelta::minutes(*minutes);
+            assert_eq!(format_delta(delta, DeltaLength::Long), *long, "{minutes}m");
+            assert_eq!(format_delta(delta, DeltaLength::Short), *short, "{minutes}m");
+        }
+        // Seconds don't count
+        assert_eq!(format_delta(TimeDelta::seconds(-30), DeltaLength::Long), "No time!");
+        assert_eq!(format_delta(TimeDelta::seconds(90), DeltaLength::Short), "1m");
+    }
+
+    #[test]
+    fn the_short_form_only_lost_its_zero_units() {
+        for (minutes, _, before, short) in CASES {
+            let trimmed = before.strip_suffix(" 0m").or(before.strip_suffix(" 0h")).unwrap_or(before);
+            assert_eq!(trimmed, *short, "{minutes}m");
+        }
+    }
+
+    #[test]
+    fn units_are_pluralized_by_their_magnitude() {
+        assert_eq!(pluralize_unit(1, "day"), "1 day");
+        assert_eq!(pluralize_unit(-1, "day"), "-1 day");
+        assert_eq!(pluralize_unit(0, "minute"), "0 minutes");
+        assert_eq!(pluralize_unit(2, "hour"), "2 hours");
+
+        assert_eq!(largest_unit(TimeDelta::minutes(59)), "59 minutes");
+        assert_eq!(largest_unit(TimeDelta::minutes(61)), "1 hour");
+        assert_eq!(largest_unit(TimeDelta::hours(142 * 24 + 5)), "142 days");
+    }
+
+    #[test]
+    fn both_status_colors_follow_the_status() {
+        assert_eq!(status_colored("HW1", &TaskStatus::Panic), "HW1".red());
+        assert_eq!(status_colored("HW1", &TaskStatus::Zen), "HW1".white());
+
+        let span = status_span::<StandardTaskColors>("HW1", &TaskStatus::Panic);
+        assert_eq!(span.style.fg, Some(StandardTaskColors::task_color(&TaskStatus::Panic)));
+    }
+}
@@ -0,0 +1,106 @@
+use chrono::{DateTime, FixedOffset, Local, NaiveDate, NaiveDateTime, NaiveTime, TimeZone, Utc};
+
+use super::dates::deadline_on;
+
+
+/// The parts of a VEVENT an import looks at
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct IcsEvent {
+    pub uid: Option<String>,
+    pub summary: String,
+    pub description: String,
+    pub categories: Vec<String>,
+    /// DTSTART, the deadline for the calendars universities export
+    pub start: Option<DateTime<FixedOffset>>,
+}
+
+/// Joins folded lines back up, a line starting with a space or a tab goes on the one before
+fn unfold(text: &str) -> Vec<String> {
+    let mut lines: Vec<String> = vec![];
+    for line in text.lines() {
+        match (line.strip_prefix([' ', '\t']), lines.last_mut()) {
+            (Some(rest), Some(last)) => last.push_str(rest),
+            _ => lines.push(line.to_string()),
+        }
+    }
+    lines
+}
+
+fn unescape(value: &str) -> String {
+    let mut result = String::new();
+    let mut chars = value.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            result.push(c);
+            continue;
+        }
+        match chars.next() {
+            Some('n') | Some('N') => result.push('\n'),
+            Some(other) => result.push(other),
+            None => result.push('\\'),
+        }
+    }
+    result
+}
+
+/// "20250512T220000Z" in UTC, "20250512T235900" with or without a TZID in local time,
+/// "20250512" at `default_time`. Zones other than the local one are not looked up.
+fn parse_time(value: &str, default_time: NaiveTime) -> Option<DateTime<FixedOffset>> {
+    if let Some(utc) = value.strip_suffix('Z') {
+        let time = NaiveDateTime::parse_from_str(utc, "%Y%m%dT%H%M%S").ok()?;
+        return Some(Utc.from_utc_datetime(&time).with_timezone(&Local).fixed_offset());
+    }
+    if let Ok(time) = NaiveDateTime::parse_from_str(value, "%Y%m%dT%H%M%S") {
+        return deadline_on(time.date(), Some(time.time()), default_time, &Local);
+    }
+    let day = NaiveDate::parse_from_str(value, "%Y%m%d").ok()?;
+    deadline_on(day, None, default_time, &Local)
+}
+
+/// Every VEVENT in an iCalendar file; anything else in it is passed over
+pub fn parse(text: &str, default_time: NaiveTime) -> Vec<IcsEvent> {
+    let mut events = vec![];
+    let mut current: Option<IcsEvent> = None;
+    for line in unfold(text) {
+        let Some((name, value)) = line.split_once(':') else { continue };
+        // Parameters such as TZID or VALUE=DATE come after a semicolon
+        let name = name.split(';').next().unwrap_or_default().to_uppercase();
+        match (name.as_str(), current.as_mut()) {
+            ("BEGIN", None) if value.eq_ignore_ascii_case("VEVENT") => current = Some(IcsEvent::default()),
+            ("END", Some(_)) if value.eq_ignore_ascii_case("VEVENT") => events.extend(current.take()),
+            ("UID", Some(event)) => event.uid = Some(value.to_string()),
+            ("SUMMARY", Some(event)) => event.summary = unescape(value),
+            ("DESCRIPTION", Some(event)) => event.description = unescape(value),
+            ("CATEGORIES", Some(event)) => event.categories.extend(
+                value.split(',').map(|x| unescape(x).trim().to_string()).filter(|x| !x.is_empty()),
+            ),
+            ("DTSTART", Some(event)) => event.start = parse_time(value.trim(), default_time),
+            _ => {},
+        }
+    }
+    events
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::uni::dates::END_OF_DAY;
+
+    #[test]
+    fn reads_events_with_folded_lines_and_escapes() {
+        let text = "BEGIN:VCALENDAR\r\nBEGIN:VEVENT\r\nUID:42@moodle\r\nSUMMARY:CS-401-2025S: Assignment 3\\, part 2 \r\n is due\r\n\
+            DESCRIPTION:Upload the PDF\\nby midnight\r\nCATEGORIES:CS-401-2025S\r\nDTSTART:20250512T220000Z\r\nEND:VEVENT\r\n\
+            BEGIN:VEVENT\r\nSUMMARY:Exam\r\nDTSTART;VALUE=DATE:20250601\r\nEND:VEVENT\r\nEND:VCALENDAR\r\n";
+
+        let events = parse(text, END_OF_DAY);
+        assert_eq!(events.len(), 2);
+        assert_eq!(events[0].uid.as_deref(), Some("42@moodle"));
+        assert_eq!(events[0].summary, "CS-401-2025S: Assignment 3, part 2 is due");
+        assert_eq!(events[0].description, "Upload the PDF\nby midnight");
+        assert_eq!(events[0].categories, ["CS-401-2025S"]);
+        assert_eq!(events[0].start, Some(DateTime::parse_from_rfc3339("2025-05-12T22:00:00Z").unwrap()));
+        let exam = events[1].start.unwrap().with_timezone(&Local);
+        assert_eq!((exam.date_naive().to_string(), exam.time()), ("2025-06-01".to_string(), END_OF_DAY));
+    }
+}
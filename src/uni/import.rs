@@ -0,0 +1,203 @@
+use std::collections::BTreeMap;
+
+use chrono::{DateTime, FixedOffset};
+use regex::Regex;
+
+use super::ics::IcsEvent;
+use super::task::{assign_ids, Task};
+use crate::config::ImportConfig;
+
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Source {
+    /// Any calendar: the first category is the subject
+    Ics,
+    /// Moodle and Canvas: the course code is mapped to a subject
+    Moodle,
+}
+
+/// `ImportConfig` with its patterns compiled
+pub struct ImportRules {
+    course_map: BTreeMap<String, String>,
+    course_pattern: Regex,
+    cleanup: Vec<Regex>,
+    ignore: Vec<Regex>,
+}
+
+impl ImportRules {
+    pub fn from_config(config: &ImportConfig) -> Result<Self, String> {
+        let compile = |pattern: &String| Regex::new(pattern).map_err(|x| format!("Bad pattern \"{pattern}\": {x}"));
+        Ok(Self {
+            course_map: config.course_map.clone(),
+            course_pattern: compile(&config.course_pattern)?,
+            cleanup: config.cleanup.iter().map(compile).collect::<Result<_, _>>()?,
+            ignore: config.ignore.iter().map(compile).collect::<Result<_, _>>()?,
+        })
+    }
+
+    /// The course code and the summary without it
+    fn course_code(&self, event: &IcsEvent) -> (Option<String>, String) {
+        let captures = self.course_pattern.captures(&event.summary);
+        let rest = match &captures {
+            Some(x) => event.summary[x.get(0).map_or(0, |x| x.end())..].to_string(),
+            None => event.summary.clone(),
+        };
+        let code = event.categories.first().cloned()
+            .or_else(|| captures.and_then(|x| x.get(1)).map(|x| x.as_str().to_string()));
+        (code, rest)
+    }
+
+    fn clean(&self, summary: &str) -> String {
+        self.cleanup
+            .iter()
+            .fold(summary.to_string(), |x, pattern| pattern.replace_all(&x, "").into_owned())
+            .trim()
+            .to_string()
+    }
+}
+
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct ImportReport {
+    pub imported: usize,
+    pub updated: usize,
+    /// Ignored, without a deadline, or already there as they are
+    pub skipped: usize,
+    /// Events per course code missing from the map, none of them imported
+    pub unmapped: BTreeMap<String, usize>,
+}
+
+fn same_task(task: &Task, subject: &str, name: &str) -> bool {
+    let normalize = |x: &str| x.trim().to_lowercase();
+    normalize(&task.subject) == normalize(subject) && normalize(&task.name) == normalize(name)
+}
+
+/// Adds the events as tasks. One already there by subject and name gets the event's
+/// deadline and description, so importing the same calendar again only picks up changes.
+pub fn import(
+    tasks: &mut Vec<Task>,
+    events: &[IcsEvent],
+    rules: &ImportRules,
+    source: Source,
+    now: &DateTime<FixedOffset>,
+) -> ImportReport {
+    let mut report = ImportReport::default();
+    for event in events {
+        let (Some(time), false) = (event.start, rules.ignore.iter().any(|x| x.is_match(&event.summary))) else {
+            report.skipped += 1;
+            continue;
+        };
+        let (subject, summary) = match source {
+            Source::Ics => (event.categories.first().cloned().unwrap_or(Task::PLACEHOLDER_SUBJECT.to_string()), event.summary.clone()),
+            Source::Moodle => {
+                let (code, summary) = rules.course_code(event);
+                let code = code.unwrap_or_else(|| "(no course code)".to_string());
+                match rules.course_map.get(&code) {
+                    Some(subject) => (subject.clone(), summary),
+                    None => {
+                        *report.unmapped.entry(code).or_default() += 1;
+                        continue;
+                    },
+                }
+            },
+        };
+        let name = rules.clean(&summary);
+
+        match tasks.iter_mut().find(|x| same_task(x, &subject, &name)) {
+            Some(task) if task.time == Some(time) && (event.description.is_empty() || task.description == event.description) => {
+                report.skipped += 1;
+            },
+            Some(task) => {
+                task.time = Some(time);
+                if !event.description.is_empty() {
+                    task.description = event.description.clone();
+                }
+                task.updated_at = Some(*now);
+                report.updated += 1;
+            },
+            None => {
+                tasks.push(Task {
+                    id: 0,
+                    subject,
+                    name,
+                    description: event.description.clone(),
+                    time: Some(time),
+                    created_at: Some(*now),
+                    updated_at: Some(*now),
+                    ..Task::default()
+                });
+                report.imported += 1;
+            },
+        }
+    }
+    assign_ids(tasks);
+    report
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use chrono::TimeDelta;
+
+    fn now() -> DateTime<FixedOffset> {
+        DateTime::parse_from_rfc3339("2025-05-12T09:00:00+02:00").unwrap()
+    }
+
+    fn event(summary: &str, categories: &[&str], days: i64) -> IcsEvent {
+        IcsEvent {
+            summary: summary.to_string(),
+            categories: categories.iter().map(|x| x.to_string()).collect(),
+            start: Some(now() + TimeDelta::days(days)),
+            ..IcsEvent::default()
+        }
+    }
+
+    fn rules() -> ImportRules {
+        let mut config = ImportConfig::default();
+        config.course_map.insert("CS-401-2025S".to_string(), "Algorithms".to_string());
+        config.ignore.push("(?i)lecture".to_string());
+        ImportRules::from_config(&config).unwrap()
+    }
+
+    #[test]
+    fn moodle_events_are_mapped_cleaned_and_counted() {
+        let events = [
+            event("CS-401-2025S: Assignment 3 is due", &[], 3),
+            event("Quiz 2 closes", &["CS-401-2025S"], 5),
+            event("Quiz 2 opens", &["CS-401-2025S"], 1),
+            event("CS-401-2025S: Lecture, room 101", &[], 1),
+            event("MA-201-2025S: Sheet 4 is due", &[], 2),
+            event("[MA-201-2025S] Sheet 5 is due", &[], 9),
+        ];
+        let mut tasks = vec![];
+
+        let report = import(&mut tasks, &events, &rules(), Source::Moodle, &now());
+        assert_eq!((report.imported, report.updated, report.skipped), (2, 0, 2));
+        assert_eq!(report.unmapped, BTreeMap::from([("MA-201-2025S".to_string(), 2)]));
+        let names = tasks.iter().map(|x| (x.subject.as_str(), x.name.as_str(), x.id)).collect::<Vec<_>>();
+        assert_eq!(names, [("Algorithms", "Assignment 3", 1), ("Algorithms", "Quiz 2", 2)]);
+
+        // Again, with one deadline moved
+        let mut events = events.to_vec();
+        events[0].start = Some(now() + TimeDelta::days(4));
+        let report = import(&mut tasks, &events, &rules(), Source::Moodle, &now());
+        assert_eq!((report.imported, report.updated, report.skipped), (0, 1, 3));
+        assert_eq!(tasks[0].time, Some(now() + TimeDelta::days(4)));
+    }
+
+    #[test]
+    fn plain_calendars_take_the_subject_from_the_category() {
+        let mut tasks = vec![];
+        let events = [event("Essay", &["Writing"], 2), IcsEvent { start: None, ..event("Someday", &[], 0) }];
+
+        let report = import(&mut tasks, &events, &rules(), Source::Ics, &now());
+        assert_eq!((report.imported, report.skipped), (1, 1));
+        assert_eq!((tasks[0].subject.as_str(), tasks[0].name.as_str()), ("Writing", "Essay"));
+    }
+
+    #[test]
+    fn bad_patterns_are_reported() {
+        let config = ImportConfig { cleanup: vec!["(unclosed".to_string()], ..ImportConfig::default() };
+        assert!(ImportRules::from_config(&config).is_err_and(|x| x.contains("(unclosed")));
+    }
+}
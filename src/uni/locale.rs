@@ -0,0 +1,193 @@
+use chrono::Datelike;
+use serde::{Serialize, Deserialize};
+use unicode_width::UnicodeWidthStr;
+
+
+/// Month and weekday names of one language, weeks start on Monday
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DateNames {
+    /// `%B`
+    pub months: Vec<String>,
+    /// `%b`, also the calendar titles and the agenda
+    pub months_short: Vec<String>,
+    /// `%A`
+    pub weekdays: Vec<String>,
+    /// `%a`
+    pub weekdays_short: Vec<String>,
+    /// Calendar column headings
+    pub weekdays_min: Vec<String>,
+    /// Words pointing at a date, "due" or "by", that pasted tasks leave out of their names
+    pub connectives: Vec<String>,
+}
+
+/// Names replacing those of the locale in the config. A list is only used when it has
+/// all 12 months or 7 days, Monday first.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct NameOverrides {
+    pub months: Vec<String>,
+    pub months_short: Vec<String>,
+    pub weekdays: Vec<String>,
+    pub weekdays_short: Vec<String>,
+    pub weekdays_min: Vec<String>,
+}
+
+fn names(text: &str) -> Vec<String> {
+    text.split(' ').map(str::to_string).collect()
+}
+
+impl Default for DateNames {
+    fn default() -> Self {
+        Self::english()
+    }
+}
+
+impl DateNames {
+    pub fn english() -> Self {
+        Self {
+            months: names("January February March April May June July August September October November December"),
+            months_short: names("Jan Feb Mar Apr May Jun Jul Aug Sep Oct Nov Dec"),
+            weekdays: names("Monday Tuesday Wednesday Thursday Friday Saturday Sunday"),
+            weekdays_short: names("Mon Tue Wed Thu Fri Sat Sun"),
+            weekdays_min: names("Mo Tu We Th Fr Sa Su"),
+            connectives: names("due by on until till deadline at before"),
+        }
+    }
+
+    /// Names for a language code like "de" or "fr_FR.UTF-8", None for one not built in
+    pub fn builtin(locale: &str) -> Option<Self> {
+        let language = locale.split(['_', '.', '@', '-']).next().unwrap_or_default().to_lowercase();
+        match language.as_str() {
+            "en" | "c" | "posix" => Some(Self::english()),
+            "de" => Some(Self {
+                months: names("Januar Februar März April Mai Juni Juli August September Oktober November Dezember"),
+                months_short: names("Jan Feb Mär Apr Mai Jun Jul Aug Sep Okt Nov Dez"),
+                weekdays: names("Montag Dienstag Mittwoch Donnerstag Freitag Samstag Sonntag"),
+                weekdays_short: names("Mo Di Mi Do Fr Sa So"),
+                weekdays_min: names("Mo Di Mi Do Fr Sa So"),
+                connectives: names("am bis zum vor fällig abgabe frist"),
+            }),
+            "fr" => Some(Self {
+                months: names("janvier février mars avril mai juin juillet août septembre octobre novembre décembre"),
+                months_short: names("janv. févr. mars avr. mai juin juil. août sept. oct. nov. déc."),
+                weekdays: names("lundi mardi mercredi jeudi vendredi samedi dimanche"),
+                weekdays_short: names("lun. mar. mer. jeu. ven. sam. dim."),
+                weekdays_min: names("lu ma me je ve sa di"),
+                connectives: names("le pour avant au jusqu'au d'ici rendu échéance"),
+            }),
+            _ => None,
+        }
+    }
+
+    /// Names for the `locale` setting: a built-in language, or "system" for the one
+    /// `system` (LC_ALL, LC_TIME or LANG) names. Anything unknown is English.
+    pub fn resolve(locale: &str, system: Option<&str>, overrides: &NameOverrides) -> Self {
+        let locale = match locale {
+            "system" => system.unwrap_or("en"),
+            locale => locale,
+        };
+        let mut names = Self::builtin(locale).unwrap_or_default();
+
+        let replace = |names: &mut Vec<String>, with: &Vec<String>| {
+            if with.len() == names.len() {
+                names.clone_from(with);
+            }
+        };
+        replace(&mut names.months, &overrides.months);
+        replace(&mut names.months_short, &overrides.months_short);
+        replace(&mut names.weekdays, &overrides.weekdays);
+        replace(&mut names.weekdays_short, &overrides.weekdays_short);
+        replace(&mut names.weekdays_min, &overrides.weekdays_min);
+        names
+    }
+
+    /// Short name of `month`, 1 for January
+    pub fn month_short(&self, month: u32) -> &str {
+        self.months_short.get(month as usize - 1).map_or("", String::as_str)
+    }
+
+    /// Columns a calendar day takes, as wide as the widest heading and at least two digits
+    pub fn day_width(&self) -> usize {
+        self.weekdays_min.iter().map(|x| x.width()).chain([2]).max().unwrap_or(2)
+    }
+
+    /// The weekday headings right-aligned to `day_width`, each followed by a space
+    pub fn weekday_headings(&self) -> String {
+        let width = self.day_width();
+        self.weekdays_min
+            .iter()
+            .map(|x| format!("{}{x} ", " ".repeat(width - x.width())))
+            .collect()
+    }
+
+    /// `format` with the names of `date` written out in place of %a, %A, %b, %h and %B,
+    /// ready for chrono to fill in the rest
+    pub fn localize(&self, format: &str, date: &impl Datelike) -> String {
+        let weekday = date.weekday().num_days_from_monday() as usize;
+        let month = date.month0() as usize;
+        let mut result = String::new();
+        let mut chars = format.chars();
+        while let Some(c) = chars.next() {
+            if c != '%' {
+                result.push(c);
+                continue;
+            }
+            let name = match chars.next() {
+                Some('a') => &self.weekdays_short[weekday],
+                Some('A') => &self.weekdays[weekday],
+                Some('b' | 'h') => &self.months_short[month],
+                Some('B') => &self.months[month],
+                Some(other) => {
+                    result.push('%');
+                    result.push(other);
+                    continue;
+                },
+                None => {
+                    result.push('%');
+                    continue;
+                },
+            };
+            // The name goes through chrono too
+            result.push_str(&name.replace('%', "%%"));
+        }
+        result
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use chrono::NaiveDate;
+
+    #[test]
+    fn names_are_written_into_the_format() {
+        let monday = NaiveDate::from_ymd_opt(2025, 3, 3).unwrap();
+        let german = DateNames::builtin("de_DE.UTF-8").unwrap();
+
+        assert_eq!(german.localize("%a %d %b %Y, %A %B %%a %-d", &monday), "Mo %d Mär %Y, Montag März %%a %-d");
+        assert_eq!(monday.format(&german.localize("%a %d %b", &monday)).to_string(), "Mo 03 Mär");
+        assert_eq!(DateNames::english().localize("%a %b %", &monday), "Mon Mar %");
+    }
+
+    #[test]
+    fn locale_setting_and_overrides() {
+        let none = NameOverrides::default();
+        assert_eq!(DateNames::resolve("fr", Some("de_DE"), &none).month_short(5), "mai");
+        assert_eq!(DateNames::resolve("system", Some("de_DE.UTF-8"), &none).month_short(3), "Mär");
+        assert_eq!(DateNames::resolve("system", None, &none), DateNames::english());
+        assert_eq!(DateNames::resolve("tlh", None, &none), DateNames::english());
+
+        let overrides = NameOverrides {
+            weekdays_min: names("Mon Tue Wed Thu Fri Sat Sun"),
+            // Incomplete, so left alone
+            months_short: names("Jan Feb"),
+            ..NameOverrides::default()
+        };
+        let names = DateNames::resolve("en", None, &overrides);
+        assert_eq!(names.month_short(2), "Feb");
+        assert_eq!(names.day_width(), 3);
+        assert_eq!(names.weekday_headings(), "Mon Tue Wed Thu Fri Sat Sun ");
+        assert_eq!(DateNames::english().weekday_headings(), "Mo Tu We Th Fr Sa Su ");
+    }
+}
@@ -0,0 +1,245 @@
+use std::collections::HashMap;
+
+use chrono::{DateTime, FixedOffset};
+
+use crate::uni::anchors::{self, Anchor};
+use crate::uni::task::{assign_ids, Task};
+
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Prefer {
+    Ours,
+    Theirs,
+    /// The version with the later updated_at/completed_at/created_at wins
+    Newer,
+}
+
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct MergeReport {
+    pub added: usize,
+    pub updated: usize,
+    pub skipped: usize,
+}
+
+/// Last moment a task is known to have changed
+fn last_touched(task: &Task) -> Option<DateTime<FixedOffset>> {
+    task.updated_at.max(task.completed_at).max(task.created_at)
+}
+
+fn same_name(task1: &Task, task2: &Task) -> bool {
+    let normalize = |x: &str| x.trim().to_lowercase();
+    normalize(&task1.subject) == normalize(&task2.subject)
+        && normalize(&task1.name) == normalize(&task2.name)
+}
+
+fn find_match(ours: &[Task], theirs: &Task) -> Option<usize> {
+    // Ids are handed out locally, so diverged files may reuse one for unrelated tasks: an id
+    // only counts when the creation time backs it up, or the name does when that is unknown
+    let by_id = (theirs.id != 0).then(|| ours.iter().position(|x| {
+        x.id == theirs.id && match (x.created_at, theirs.created_at) {
+            (Some(created1), Some(created2)) => created1 == created2,
+            _ => same_name(x, theirs),
+        }
+    })).flatten();
+    by_id.or_else(|| ours.iter().position(|x| same_name(x, theirs)))
+}
+
+/// Merges `theirs` into `ours`.
+/// `undecided` picks a side (true = theirs) when `Newer` can not tell the versions apart.
+/// Anchors in `theirs` are in its own id numbering, they are carried over to ours and the
+/// anchored deadlines resolved again afterwards.
+pub fn merge(
+    ours: &mut Vec<Task>,
+    theirs: Vec<Task>,
+    prefer: Prefer,
+    mut undecided: impl FnMut(&Task, &Task) -> bool,
+) -> MergeReport {
+    let mut report = MergeReport::default();
+    let mut added = vec![];
+
+    // Where each of their tasks ends up here, the new ones numbered after ours
+    let matches = theirs.iter().map(|x| find_match(ours, x)).collect::<Vec<_>>();
+    let mut next = ours.iter().map(|x| x.id).max().unwrap_or(0);
+    let new_ids = matches.iter().map(|found| match found {
+        Some(i) => ours[*i].id,
+        None => {
+            next += 1;
+            next
+        },
+    }).collect::<Vec<_>>();
+    // Their ids to ours, what their anchors are rewritten through
+    let ids = theirs.iter().zip(&new_ids)
+        .filter(|(task, _)| task.id != 0)
+        .map(|(task, id)| (task.id, *id))
+        .collect::<HashMap<_, _>>();
+
+    for ((task, found), id) in theirs.into_iter().zip(matches).zip(new_ids) {
+        // An anchor to a task their file doesn't have would point at an unrelated one here
+        let anchor = task.anchor.and_then(|x| Some(Anchor { task: *ids.get(&x.task)?, ..x }));
+        let task = Task { anchor, ..task };
+        let Some(i) = found else {
+            added.push(Task { id, ..task });
+            continue;
+        };
+
+        let ours_task = &ours[i];
+        // Anchored deadlines are only resolved on our side
+        let time = match task.anchor.is_some() && task.anchor == ours_task.anchor {
+            true => ours_task.time,
+            false => task.time,
+        };
+        let identical = *ours_task == Task { id: ours_task.id, time, ..task.clone() };
+        if identical {
+            report.skipped += 1;
+            continue;
+        }
+
+        let take_theirs = match prefer {
+            Prefer::Ours => false,
+            Prefer::Theirs => true,
+            Prefer::Newer => match last_touched(&task).cmp(&last_touched(ours_task)) {
+                std::cmp::Ordering::Greater => true,
+                std::cmp::Ordering::Less => false,
+                std::cmp::Ordering::Equal => undecided(ours_task, &task),
+            },
+        };
+
+        if take_theirs {
+            ours[i] = Task { id: ours[i].id, ..task };
+            report.updated += 1;
+        } else {
+            report.skipped += 1;
+        }
+    }
+
+    report.added = added.len();
+    ours.extend(added);
+    // Any of ours still without an id gets one after the added ones
+    assign_ids(ours);
+    anchors::resolve(ours);
+    report
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn task(id: u64, subject: &str, name: &str) -> Task {
+        Task {
+            id,
+            subject: subject.to_string(),
+            name: name.to_string(),
+            time: None,
+            ..Task::default()
+        }
+    }
+
+    fn at(time: &str) -> Option<DateTime<FixedOffset>> {
+        Some(DateTime::parse_from_rfc3339(time).unwrap())
+    }
+
+    #[test]
+    fn matches_by_id_and_keeps_newer() {
+        let mut ours = vec![Task { created_at: at("2025-01-01T10:00:00Z"), ..task(1, "Algo", "HW1") }];
+        let theirs = vec![Task {
+            description: "updated".to_string(),
+            created_at: at("2025-01-01T10:00:00Z"),
+            completed_at: at("2025-02-01T10:00:00Z"),
+            complete: true,
+            ..task(1, "Algo", "HW1 renamed")
+        }];
+
+        let report = merge(&mut ours, theirs, Prefer::Newer, |_, _| panic!("should not ask"));
+
+        assert_eq!(report, MergeReport { added: 0, updated: 1, skipped: 0 });
+        assert_eq!(ours.len(), 1);
+        assert_eq!(ours[0].name, "HW1 renamed");
+        assert_eq!(ours[0].id, 1);
+    }
+
+    #[test]
+    fn matches_by_name_when_ids_are_missing() {
+        let mut ours = vec![task(1, "Algo", "HW1"), task(2, "Algo", "HW2")];
+        let theirs = vec![
+            Task { description: "theirs".to_string(), ..task(0, " algo", "hw2 ") },
+            task(0, "Algo", "HW3"),
+        ];
+
+        let report = merge(&mut ours, theirs, Prefer::Theirs, |_, _| unreachable!());
+
+        assert_eq!(report, MergeReport { added: 1, updated: 1, skipped: 0 });
+        assert_eq!(ours[1].description, "theirs");
+        assert_eq!(ours[1].id, 2);
+        assert_eq!(ours[2].name, "HW3");
+        assert_eq!(ours[2].id, 3);
+    }
+
+    #[test]
+    fn skips_true_duplicates() {
+        let mut ours = vec![task(1, "Algo", "HW1")];
+        let theirs = vec![task(7, "Algo", "HW1")];
+
+        let report = merge(&mut ours, theirs, Prefer::Theirs, |_, _| unreachable!());
+
+        assert_eq!(report, MergeReport { added: 0, updated: 0, skipped: 1 });
+        assert_eq!(ours, vec![task(1, "Algo", "HW1")]);
+    }
+
+    #[test]
+    fn asks_when_newer_is_undecided() {
+        let mut ours = vec![task(1, "Algo", "HW1")];
+        let theirs = vec![Task { starred: true, ..task(1, "Algo", "HW1") }];
+
+        let mut asked = 0;
+        let report = merge(&mut ours, theirs, Prefer::Newer, |_, _| { asked += 1; false });
+
+        assert_eq!(asked, 1);
+        assert_eq!(report, MergeReport { added: 0, updated: 0, skipped: 1 });
+        assert!(!ours[0].starred);
+    }
+
+    #[test]
+    fn reused_id_of_an_unrelated_task_is_not_a_match() {
+        let mut ours = vec![Task { created_at: at("2025-01-01T10:00:00Z"), ..task(3, "Algo", "HW1") }];
+        let theirs = vec![Task { created_at: at("2025-03-01T10:00:00Z"), ..task(3, "Physics", "Lab") }];
+
+        let report = merge(&mut ours, theirs, Prefer::Newer, |_, _| unreachable!());
+
+        assert_eq!(report, MergeReport { added: 1, updated: 0, skipped: 0 });
+        assert_eq!(ours.iter().map(|x| x.id).collect::<Vec<_>>(), vec![3, 4]);
+    }
+
+    #[test]
+    fn reused_id_without_creation_times_needs_the_same_name() {
+        let mut ours = vec![task(3, "Algo", "HW1")];
+        let theirs = vec![
+            Task { created_at: at("2025-03-01T10:00:00Z"), ..task(3, "Physics", "Lab") },
+            Task { description: "theirs".to_string(), ..task(3, "Algo", "HW1") },
+        ];
+
+        let report = merge(&mut ours, theirs, Prefer::Theirs, |_, _| unreachable!());
+
+        assert_eq!(report, MergeReport { added: 1, updated: 1, skipped: 0 });
+        assert_eq!(ours[0].name, "HW1");
+        assert_eq!(ours[0].description, "theirs");
+        assert_eq!(ours[1].name, "Lab");
+    }
+
+    #[test]
+    fn unchanged_anchored_tasks_are_skipped() {
+        let anchored = |id, anchor| Task {
+            anchor: Some(Anchor { task: anchor, offset: chrono::TimeDelta::days(-2) }),
+            ..task(id, "Algo", "Outline")
+        };
+        let submission = |id| Task { time: at("2025-06-30T18:00:00Z"), ..task(id, "Algo", "Submission") };
+        let mut ours = vec![submission(1), anchored(2, 1)];
+        anchors::resolve(&mut ours);
+        // As read from their file, anchored deadlines unresolved and in their numbering
+        let theirs = vec![submission(7), anchored(8, 7)];
+
+        let report = merge(&mut ours, theirs, Prefer::Theirs, |_, _| unreachable!());
+
+        assert_eq!(report, MergeReport { added: 0, updated: 0, skipped: 2 });
+        assert_eq!(ours[1].time, at("2025-06-28T18:00:00Z"));
+    }
+}
@@ -1,3 +1,26 @@
 pub mod task;
+pub mod anchors;
 pub mod todo;
 pub mod calendar;
+pub mod validation;
+pub mod dates;
+pub mod format;
+pub mod detail;
+pub mod merge;
+pub mod demo;
+pub mod clock;
+pub mod shift;
+pub mod digest;
+pub mod export;
+pub mod overview;
+pub mod completion;
+pub mod query;
+pub mod ordering;
+pub mod report;
+pub mod template;
+pub mod locale;
+pub mod policy;
+pub mod ics;
+pub mod import;
+pub mod paste;
+pub mod reflow;
@@ -0,0 +1,146 @@
+use std::cmp::Ordering;
+
+use chrono::{DateTime, FixedOffset};
+
+use crate::uni::task::Task;
+
+
+/// Where a task goes in the default order, the buckets coming in this order:
+///
+/// 1. overdue incomplete tasks, the most overdue first,
+/// 2. upcoming incomplete tasks, the nearest deadline first,
+/// 3. incomplete tasks without a deadline, in the order they were in,
+/// 4. completed tasks, the most recently completed first, then by deadline.
+///    Those completed before `completed_at` was recorded come after the rest.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Bucket {
+    Overdue,
+    Upcoming,
+    Undated,
+    Completed,
+}
+
+impl Bucket {
+    /// A deadline at exactly `now` is already overdue
+    pub fn of(task: &Task, now: &DateTime<FixedOffset>) -> Self {
+        match task.time {
+            _ if task.complete => Self::Completed,
+            None => Self::Undated,
+            Some(time) if time <= *now => Self::Overdue,
+            Some(_) => Self::Upcoming,
+        }
+    }
+}
+
+/// The default order of tasks as of `now`, the one both the UI and the prompt list them in
+pub fn compare(task1: &Task, task2: &Task, now: &DateTime<FixedOffset>) -> Ordering {
+    let bucket = Bucket::of(task1, now);
+    bucket.cmp(&Bucket::of(task2, now)).then_with(|| match bucket {
+        // Undated ones have None on both sides and keep their order
+        Bucket::Overdue | Bucket::Upcoming | Bucket::Undated => task1.time.cmp(&task2.time),
+        // None is the least, so unknown completions sink below the known ones
+        Bucket::Completed => task2.completed_at
+            .cmp(&task1.completed_at)
+            .then_with(|| by_deadline(task1, task2)),
+    })
+}
+
+/// Dated before undated, then the nearest deadline first
+fn by_deadline(task1: &Task, task2: &Task) -> Ordering {
+    task1.time.is_none().cmp(&task2.time.is_none()).then_with(|| task1.time.cmp(&task2.time))
+}
+
+/// Sorts `tasks` into the default order as of `now`, keeping ties as they were
+pub fn sort(tasks: &mut [Task], now: &DateTime<FixedOffset>) {
+    tasks.sort_by(|task1, task2| compare(task1, task2, now));
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::TimeDelta;
+
+    use super::*;
+
+    fn now() -> DateTime<FixedOffset> {
+        DateTime::parse_from_rfc3339("2025-03-01T12:00:00+01:00").unwrap()
+    }
+
+    fn task(name: &str, days: Option<i64>, completed_days_ago: Option<Option<i64>>) -> Task {
+        Task {
+            name: name.to_string(),
+            time: days.map(|x| now() + TimeDelta::days(x)),
+            complete: completed_days_ago.is_some(),
+            completed_at: completed_days_ago.flatten().map(|x| now() - TimeDelta::days(x)),
+            ..Task::default()
+        }
+    }
+
+    /// Every bucket with several tasks in it, in the expected order
+    fn fixture() -> Vec<Task> {
+        vec![
+            task("overdue by a week", Some(-7), None),
+            task("overdue by a day", Some(-1), None),
+            task("due now", Some(0), None),
+            task("due tomorrow", Some(1), None),
+            task("due next week", Some(7), None),
+            task("undated first", None, None),
+            task("undated second", None, None),
+            task("completed today", Some(-3), Some(Some(0))),
+            task("completed today, undated", None, Some(Some(0))),
+            task("completed yesterday", Some(-10), Some(Some(1))),
+            task("completed long ago, due earlier", Some(-5), Some(None)),
+            task("completed long ago, due later", Some(5), Some(None)),
+            task("completed long ago, undated", None, Some(None)),
+        ]
+    }
+
+    fn names(tasks: &[Task]) -> Vec<&str> {
+        tasks.iter().map(|x| x.name.as_str()).collect()
+    }
+
+    #[test]
+    fn every_bucket_comes_in_order() {
+        let expected = fixture();
+        let buckets: Vec<Bucket> = expected.iter().map(|x| Bucket::of(x, &now())).collect();
+        assert_eq!(buckets, [
+            [Bucket::Overdue; 3].as_slice(),
+            &[Bucket::Upcoming; 2],
+            &[Bucket::Undated; 2],
+            &[Bucket::Completed; 6],
+        ].concat());
+
+        // Whatever the order they start in, the tied undated ones keeping theirs
+        let mut reversed = expected.clone();
+        reversed.reverse();
+        for start in [expected, reversed] {
+            for shift in 0..start.len() {
+                let mut tasks = start.clone();
+                tasks.rotate_left(shift);
+                let undated: Vec<String> = tasks.iter().filter(|x| x.name.starts_with("undated")).map(|x| x.name.clone()).collect();
+                sort(&mut tasks, &now());
+                let mut wanted = names(&fixture()).iter().map(|x| x.to_string()).collect::<Vec<_>>();
+                wanted.splice(5..7, undated);
+                assert_eq!(names(&tasks), wanted, "rotated by {shift}");
+            }
+        }
+    }
+
+    #[test]
+    fn each_pair_compares_by_its_positions() {
+        let tasks = fixture();
+        for (i, task1) in tasks.iter().enumerate() {
+            for (j, task2) in tasks.iter().enumerate() {
+                // Only the two undated ones tie
+                let expected = if (i, j) == (5, 6) || (i, j) == (6, 5) { Ordering::Equal } else { i.cmp(&j) };
+                assert_eq!(compare(task1, task2, &now()), expected, "{} vs {}", task1.name, task2.name);
+            }
+        }
+    }
+
+    #[test]
+    fn completion_outranks_the_deadline() {
+        let overdue_done = task("done", Some(-30), Some(Some(0)));
+        let undated = task("undated", None, None);
+        assert_eq!(compare(&undated, &overdue_done, &now()), Ordering::Less);
+    }
+}
@@ -0,0 +1,197 @@
+use std::collections::BTreeMap;
+
+use chrono::{DateTime, Datelike, FixedOffset, NaiveDate};
+
+use crate::config::SubjectColors;
+use crate::ui::colors::{css_color, StandardTaskColors, TaskColors};
+use crate::uni::calendar::Calendar;
+use crate::uni::dates::{format_date, DisplayZone};
+use crate::uni::locale::DateNames;
+use crate::uni::task::Task;
+
+
+/// The whole page. Styles are inline, so the file prints and opens without anything next to it.
+const PAGE: &str = r#"<!DOCTYPE html>
+<html lang="en">
+<head>
+<meta charset="utf-8">
+<title>Semester overview</title>
+<style>
+@page { size: A4; margin: 1.5cm; }
+body { font: 10pt sans-serif; color: #282828; -webkit-print-color-adjust: exact; print-color-adjust: exact; }
+h1 { font-size: 16pt; margin: 0; }
+.generated { color: #7c6f64; margin: 0 0 1em; }
+.month { break-inside: avoid; margin-bottom: 1.5em; }
+.month h2 { font-size: 12pt; border-bottom: 1px solid #d5c4a1; }
+.columns { display: flex; gap: 2em; align-items: flex-start; }
+.grid { border-collapse: collapse; }
+.grid th, .grid td { width: 2em; height: 2.2em; text-align: center; vertical-align: top; }
+.grid th { color: #7c6f64; font-weight: normal; height: auto; }
+.grid td.due { background: #fbf1c7; font-weight: bold; }
+.tasks { border-collapse: collapse; flex: 1; }
+.tasks th { text-align: left; color: #7c6f64; font-weight: normal; }
+.tasks td { padding: 0.2em 1em 0.2em 0; border-bottom: 1px solid #ebdbb2; }
+.tasks tr.done td { color: #a89984; text-decoration: line-through; }
+.dot { display: inline-block; width: 0.6em; height: 0.6em; border-radius: 50%; }
+</style>
+</head>
+<body>
+<h1>Semester overview</h1>
+<p class="generated">{{generated}}</p>
+{{months}}</body>
+</html>
+"#;
+
+const MONTH: &str = r#"<section class="month">
+<h2>{{name}} {{year}}</h2>
+<div class="columns">
+<table class="grid">
+<tr>{{headings}}</tr>
+{{weeks}}</table>
+<table class="tasks">
+<tr><th>Due</th><th>Subject</th><th>Task</th></tr>
+{{rows}}</table>
+</div>
+</section>
+"#;
+
+const ROW: &str = r#"<tr{{class}}><td>{{due}}</td><td><span class="dot" style="background: {{color}}"></span> {{subject}}</td><td>{{name}}</td></tr>
+"#;
+
+/// A printable page with every dated task of `tasks`, a section per month: a calendar with the
+/// due days marked in their subjects' colors, and the tasks in deadline order. Subjects get the
+/// accents the table gives them. Tasks without a deadline belong to no month and are left out.
+pub fn render(
+    tasks: &[&Task],
+    subject_colors: &SubjectColors,
+    now: DateTime<FixedOffset>,
+    date_format: &str,
+    names: &DateNames,
+    zone: &DisplayZone,
+) -> String {
+    let color = |task: &Task| css_color(StandardTaskColors::subject_color(&task.subject, subject_colors));
+    let mut months: BTreeMap<(i32, u32), Vec<(NaiveDate, &Task)>> = BTreeMap::new();
+    for task in tasks {
+        let Some(time) = task.time else { continue };
+        let date = zone.show(&time).date_naive();
+        months.entry((date.year(), date.month())).or_default().push((date, task));
+    }
+
+    let mut sections = String::new();
+    let mut count = 0;
+    for ((year, month), mut tasks) in months {
+        tasks.sort_by_key(|(_, x)| x.time);
+        count += tasks.len();
+
+        let headings: String = names.weekdays_min.iter().map(|x| format!("<th>{}</th>", escape(x))).collect();
+        let (first, days) = Calendar::get_month_info_ym(year, month);
+        let mut cells = vec!["<td></td>".to_string(); first as usize];
+        for day in 1..=days {
+            let due: Vec<&Task> = tasks.iter().filter(|(date, _)| date.day() == day).map(|(_, x)| *x).collect();
+            cells.push(match due.is_empty() {
+                true => format!("<td>{day}</td>"),
+                false => {
+                    let title = due.iter().map(|x| format!("{}: {}", x.subject, x.name)).collect::<Vec<_>>().join(", ");
+                    let dots: String = due.iter().map(|x| format!(r#"<span class="dot" style="background: {}"></span>"#, color(x))).collect();
+                    format!(r#"<td class="due" title="{}">{day}<br>{dots}</td>"#, escape(&title))
+                },
+            });
+        }
+        cells.resize(cells.len().next_multiple_of(7), "<td></td>".to_string());
+        let weeks: String = cells.chunks(7).map(|x| format!("<tr>{}</tr>\n", x.concat())).collect();
+
+        let rows: String = tasks
+            .iter()
+            .map(|(_, task)| {
+                let due = task.time.map(|x| format_date(&x, date_format, names, zone)).unwrap_or_default();
+                let star = if task.starred { " ★" } else { "" };
+                fill(ROW, &[
+                    ("class", if task.complete { r#" class="done""# } else { "" }),
+                    ("due", &escape(&due)),
+                    ("color", &color(task)),
+                    ("subject", &escape(&task.subject)),
+                    ("name", &format!("{}{star}", escape(&task.name))),
+                ])
+            })
+            .collect();
+
+        let name = names.months.get(month as usize - 1).map_or("", String::as_str);
+        sections += &fill(MONTH, &[
+            ("name", &escape(name)),
+            ("year", &year.to_string()),
+            ("headings", &headings),
+            ("weeks", &weeks),
+            ("rows", &rows),
+        ]);
+    }
+
+    let generated = format!("{count} deadline(s), generated {}", format_date(&now, date_format, names, zone));
+    fill(PAGE, &[("generated", &escape(&generated)), ("months", &sections)])
+}
+
+/// `template` with each `{{key}}` replaced by its value, in one pass so values are never
+/// searched for placeholders themselves
+fn fill(template: &str, values: &[(&str, &str)]) -> String {
+    let mut result = String::new();
+    let mut rest = template;
+    while let Some(start) = rest.find("{{") {
+        let end = start + rest[start..].find("}}").expect("Unclosed placeholder");
+        let key = &rest[start + 2..end];
+        let value = values.iter().find(|(x, _)| *x == key).unwrap_or_else(|| panic!("No value for {key}")).1;
+        result.push_str(&rest[..start]);
+        result.push_str(value);
+        rest = &rest[end + 2..];
+    }
+    result.push_str(rest);
+    result
+}
+
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;").replace('"', "&quot;")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn at(time: &str) -> Option<DateTime<FixedOffset>> {
+        Some(DateTime::parse_from_rfc3339(time).unwrap())
+    }
+
+    fn tasks() -> Vec<Task> {
+        let task = |id, subject: &str, name: &str, time| Task {
+            id,
+            subject: subject.to_string(),
+            name: name.to_string(),
+            time,
+            ..Task::default()
+        };
+        vec![
+            task(1, "Algorithms", "HW2", at("2025-11-03T18:00:00+01:00")),
+            Task { starred: true, ..task(2, "Algorithms", "HW1", at("2025-10-20T18:00:00+02:00")) },
+            task(3, "Physics", "Lab <3>", at("2025-10-20T09:00:00+02:00")),
+            task(4, "Physics", "Reading", None),
+            task(5, "Databases", "Project & report", at("2025-11-28T23:59:00+01:00")),
+        ]
+    }
+
+    #[test]
+    fn page_matches_the_snapshot() {
+        let tasks = tasks();
+        let colors: SubjectColors = toml::from_str(r##"Databases = "#b16286""##).unwrap();
+        let page = render(
+            &tasks.iter().collect::<Vec<_>>(),
+            &colors,
+            at("2025-10-01T12:00:00+02:00").unwrap(),
+            "%a %d %b %H:%M",
+            &DateNames::english(),
+            &DisplayZone::Original,
+        );
+        assert_eq!(page, include_str!("../../tests/fixtures/overview.html"));
+    }
+
+    #[test]
+    fn placeholders_in_values_are_left_alone() {
+        assert_eq!(fill("<{{a}}|{{b}}>", &[("a", "{{b}}"), ("b", "x")]), "<{{b}}|x>");
+    }
+}
@@ -0,0 +1,280 @@
+use chrono::{DateTime, Datelike, FixedOffset, NaiveDate, NaiveTime};
+
+use crate::uni::dates::{deadline_on, parse_deadline};
+use crate::uni::locale::DateNames;
+
+
+/// A line of pasted text read as a task, before it has a subject
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PastedTask {
+    pub name: String,
+    pub time: Option<DateTime<FixedOffset>>,
+}
+
+/// Every task of `text` that has a name left once its bullet and deadline are taken out.
+/// A line with several deadlines apart by commas or semicolons, "HW3 – May 12, Quiz – May 19",
+/// holds a task for each.
+pub fn parse_lines(
+    text: &str,
+    now: &DateTime<FixedOffset>,
+    default_time: NaiveTime,
+    names: &DateNames,
+) -> Vec<PastedTask> {
+    text.lines()
+        .flat_map(|x| split_line(x, now, default_time, names))
+        .filter_map(|x| parse_line(&x, now, default_time, names))
+        .collect()
+}
+
+/// The tasks a line holds. The line is cut at the commas and semicolons, and a part with a
+/// deadline starts a new task when the one before has its own. A part without one stays
+/// with the task before it, so "Lab due 2 Apr, bring a printout" is still one task.
+fn split_line(
+    line: &str,
+    now: &DateTime<FixedOffset>,
+    default_time: NaiveTime,
+    names: &DateNames,
+) -> Vec<String> {
+    let mut tasks: Vec<(String, bool)> = vec![];
+    for part in strip_bullet(line).split_inclusive([',', ';']) {
+        let words: Vec<&str> = part.split_whitespace().collect();
+        let dated = find_deadline(&words, now, default_time, names).is_some();
+        match tasks.last_mut() {
+            Some((task, has_deadline)) if !(dated && *has_deadline) => {
+                task.push_str(part);
+                *has_deadline |= dated;
+            },
+            _ => tasks.push((part.to_string(), dated)),
+        }
+    }
+    tasks.into_iter().map(|(task, _)| task).collect()
+}
+
+/// Where the first deadline in `words` starts, how many words it takes and when it is
+fn find_deadline(
+    words: &[&str],
+    now: &DateTime<FixedOffset>,
+    default_time: NaiveTime,
+    names: &DateNames,
+) -> Option<(usize, usize, DateTime<FixedOffset>)> {
+    (0..words.len())
+        .find_map(|start| deadline_at(&words[start..], now, default_time, names).map(|(time, length)| (start, length, time)))
+}
+
+/// A line copied from a syllabus, a chat or a list, the deadline anywhere in it: whatever
+/// `parse_deadline` takes, or a day and a month by name like "May 12", "12th May 2025" or
+/// "Mar 3 18:00". A date without a year is the next one to come, today included. The first
+/// deadline found wins, and what is left around it, short of bullets, dashes and words
+/// like "due" or the locale's own, is the name.
+pub fn parse_line(
+    line: &str,
+    now: &DateTime<FixedOffset>,
+    default_time: NaiveTime,
+    names: &DateNames,
+) -> Option<PastedTask> {
+    let words: Vec<&str> = strip_bullet(line).split_whitespace().collect();
+    let (name, time) = match find_deadline(&words, now, default_time, names) {
+        Some((start, length, time)) => {
+            let before = trim_connectives(&words[..start], names);
+            let after = &words[start + length..];
+            let name = match after.is_empty() {
+                true => before.join(" "),
+                false => format!("{} {}", before.join(" "), after.join(" ")),
+            };
+            (name, Some(time))
+        },
+        None => (words.join(" "), None),
+    };
+    let name = tidy(&name);
+    (!name.is_empty()).then_some(PastedTask { name, time })
+}
+
+/// The deadline `words` start with and how many words it takes, the longest reading first
+fn deadline_at(
+    words: &[&str],
+    now: &DateTime<FixedOffset>,
+    default_time: NaiveTime,
+    names: &DateNames,
+) -> Option<(DateTime<FixedOffset>, usize)> {
+    if let Some((day, length)) = month_day(words, now, names) {
+        let time = words.get(length).and_then(|x| NaiveTime::parse_from_str(trim_punctuation(x), "%H:%M").ok());
+        let length = length + time.is_some() as usize;
+        return deadline_on(day, time, default_time, now.offset()).map(|x| (x, length));
+    }
+    (1..=words.len().min(2)).rev().find_map(|length| {
+        let text = trim_punctuation(&words[..length].join(" ")).to_string();
+        // A bare dash is a separator, not a duration
+        let duration_like = text.starts_with(['+', '-']) && text.len() > 1;
+        let plausible = duration_like || text.starts_with(|c: char| c.is_ascii_alphanumeric());
+        match plausible {
+            true => parse_deadline(&text, now, default_time).ok().map(|x| (x, length)),
+            false => None,
+        }
+    })
+}
+
+/// "May 12", "12 May", either followed by a year, the words it takes
+fn month_day(words: &[&str], now: &DateTime<FixedOffset>, names: &DateNames) -> Option<(NaiveDate, usize)> {
+    let (month, day) = match (words.first().and_then(|x| month(x, names)), words.get(1).and_then(|x| day(x))) {
+        (Some(month), Some(day)) => (month, day),
+        _ => (month(words.get(1)?, names)?, day(words.first()?)?),
+    };
+    let year = words.get(2).map(|x| trim_punctuation(x)).filter(|x| x.len() == 4).and_then(|x| x.parse().ok());
+    match year {
+        Some(year) => NaiveDate::from_ymd_opt(year, month, day).map(|x| (x, 3)),
+        None => {
+            let today = now.date_naive();
+            let this_year = NaiveDate::from_ymd_opt(today.year(), month, day);
+            let date = match this_year {
+                Some(date) if date >= today => Some(date),
+                _ => NaiveDate::from_ymd_opt(today.year() + 1, month, day),
+            };
+            date.map(|x| (x, 2))
+        },
+    }
+}
+
+/// The month `word` names, in full, shortened or cut to at least three letters
+fn month(word: &str, names: &DateNames) -> Option<u32> {
+    let word = trim_punctuation(word).to_lowercase();
+    if word.chars().count() < 3 {
+        return None;
+    }
+    let position = |list: &[String], matches: &dyn Fn(&str) -> bool| {
+        list.iter().position(|x| matches(x.to_lowercase().trim_end_matches('.')))
+    };
+    position(&names.months_short, &|x| x == word)
+        .or_else(|| position(&names.months, &|x| x.starts_with(&word)))
+        .map(|x| x as u32 + 1)
+}
+
+/// A day of the month, "12" or "12th"
+fn day(word: &str) -> Option<u32> {
+    let word = trim_punctuation(word);
+    let digits = word.trim_end_matches(|c: char| c.is_ascii_alphabetic());
+    let suffix = &word[digits.len()..];
+    if !["", "st", "nd", "rd", "th"].contains(&suffix.to_lowercase().as_str()) {
+        return None;
+    }
+    digits.parse().ok().filter(|x| (1..=31).contains(x))
+}
+
+fn trim_punctuation(text: &str) -> &str {
+    text.trim_matches(|c: char| "()[],;.!?".contains(c))
+}
+
+/// Words before the deadline without the ones pointing at it, "due", "by" and the like
+fn trim_connectives<'a>(words: &'a [&'a str], names: &DateNames) -> &'a [&'a str] {
+    let mut words = words;
+    while let Some((last, rest)) = words.split_last() {
+        let word = trim_punctuation(last).trim_end_matches(':').to_lowercase();
+        match names.connectives.contains(&word) {
+            true => words = rest,
+            false => break,
+        }
+    }
+    words
+}
+
+/// Bullets and numbering a list line starts with
+fn strip_bullet(line: &str) -> &str {
+    let line = line.trim_start();
+    let line = line.trim_start_matches(['-', '*', '•', '·', '–', '—', '>']).trim_start();
+    let line = ["[ ]", "[x]", "[X]"].iter().find_map(|x| line.strip_prefix(x)).unwrap_or(line).trim_start();
+    let digits = line.trim_start_matches(|c: char| c.is_ascii_digit());
+    match digits.len() < line.len() {
+        true => digits.strip_prefix(['.', ')']).filter(|x| x.starts_with(' ')).unwrap_or(line),
+        false => line,
+    }
+}
+
+/// The name without separators left hanging at either end or doubled spaces
+fn tidy(name: &str) -> String {
+    let separators = |c: char| c.is_whitespace() || "-–—:|,;/()[]".contains(c);
+    let name = name.trim_matches(separators).replace("()", "").replace("[]", "");
+    name.split_whitespace().collect::<Vec<_>>().join(" ").trim_matches(separators).to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn now() -> DateTime<FixedOffset> {
+        DateTime::parse_from_rfc3339("2025-03-01T12:00:00+01:00").unwrap()
+    }
+
+    fn parse(line: &str) -> Option<(String, Option<String>)> {
+        let default_time = NaiveTime::from_hms_opt(23, 59, 0).unwrap();
+        parse_line(line, &now(), default_time, &DateNames::english())
+            .map(|x| (x.name, x.time.map(|x| x.format("%Y-%m-%d %H:%M").to_string())))
+    }
+
+    fn task(name: &str, time: Option<&str>) -> Option<(String, Option<String>)> {
+        Some((name.to_string(), time.map(str::to_string)))
+    }
+
+    #[test]
+    fn messy_lines_read_as_tasks() {
+        let cases = [
+            ("HW3 – May 12", task("HW3", Some("2025-05-12 23:59"))),
+            ("  • Quiz 2 (due May 19th)", task("Quiz 2", Some("2025-05-19 23:59"))),
+            ("3) Lab report due 2025-04-02 18:00, bring a printout", task("Lab report bring a printout", Some("2025-04-02 18:00"))),
+            ("- [ ] Read chapter 4 by tomorrow", task("Read chapter 4", Some("2025-03-02 23:59"))),
+            ("Midterm: 14 Apr 2026 09:30", task("Midterm", Some("2026-04-14 09:30"))),
+            ("Project proposal | Sept 8", task("Project proposal", Some("2025-09-08 23:59"))),
+            ("Essay draft +3d", task("Essay draft", Some("2025-03-04 12:00"))),
+            ("Week 3 - problem set", task("Week 3 - problem set", None)),
+            ("Final presentation — Jan 10", task("Final presentation", Some("2026-01-10 23:59"))),
+        ];
+        for (line, expected) in cases {
+            assert_eq!(parse(line), expected, "{line}");
+        }
+    }
+
+    #[test]
+    fn lines_without_a_name_are_skipped() {
+        assert_eq!(parse(""), None);
+        assert_eq!(parse("   - "), None);
+        assert_eq!(parse("May 12"), None);
+        assert_eq!(parse("due: 2025-05-12"), None);
+    }
+
+    #[test]
+    fn look_alikes_are_not_deadlines() {
+        // Too short for a month, a day out of range, a dash on its own
+        assert_eq!(parse("Ma 12 sheet"), task("Ma 12 sheet", None));
+        assert_eq!(parse("March 32 notes"), task("March 32 notes", None));
+        assert_eq!(parse("Reading - 40 pages"), task("Reading - 40 pages", None));
+        // A numbered line keeps a number that is part of its name
+        assert_eq!(parse("2025 review"), task("2025 review", None));
+    }
+
+    #[test]
+    fn month_names_follow_the_locale() {
+        let default_time = NaiveTime::from_hms_opt(18, 0, 0).unwrap();
+        let french = DateNames::builtin("fr").unwrap();
+        let task = parse_line("Partiel le 12 mai", &now(), default_time, &french).unwrap();
+        assert_eq!(task.name, "Partiel");
+        assert_eq!(task.time.unwrap().format("%Y-%m-%d %H:%M").to_string(), "2025-05-12 18:00");
+
+        let all = parse_lines("TP 1 — 3 mars\n\nTP 2 — 10 mars\n", &now(), default_time, &french);
+        assert_eq!(all.iter().map(|x| x.name.as_str()).collect::<Vec<_>>(), ["TP 1", "TP 2"]);
+
+        let german = DateNames::builtin("de").unwrap();
+        let task = parse_line("Klausur am 3. März", &now(), default_time, &german).unwrap();
+        assert_eq!(task.name, "Klausur");
+    }
+
+    #[test]
+    fn a_line_with_several_deadlines_holds_several_tasks() {
+        let default_time = NaiveTime::from_hms_opt(23, 59, 0).unwrap();
+        let tasks = parse_lines("HW3 – May 12, Quiz – May 19", &now(), default_time, &DateNames::english());
+        let read = tasks.iter().map(|x| (x.name.as_str(), x.time.unwrap().format("%Y-%m-%d").to_string())).collect::<Vec<_>>();
+        assert_eq!(read, [("HW3", "2025-05-12".to_string()), ("Quiz", "2025-05-19".to_string())]);
+
+        // Commas that are not between deadlines stay in the task
+        for line in ["Lab report due 2025-04-02 18:00, bring a printout", "Essay, 5 pages; May 12, 2025"] {
+            assert_eq!(parse_lines(line, &now(), default_time, &DateNames::english()).len(), 1, "{line}");
+        }
+    }
+}
@@ -0,0 +1,102 @@
+use chrono::{DateTime, FixedOffset, TimeDelta};
+
+use crate::config::Config;
+use crate::constants;
+use crate::uni::task::{Task, TaskStatus};
+
+
+/// How the status of a task is worked out from the config. Everything showing or filtering
+/// by status goes through here, so the table, calendar, CLI and notifications agree.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct StatusPolicy {
+    /// Deadlines closer than this panic
+    pub threshold: TimeDelta,
+    /// Open tasks without a deadline untouched this long panic too, never when None
+    pub escalate_after: Option<TimeDelta>,
+}
+
+impl Default for StatusPolicy {
+    fn default() -> Self {
+        Self {
+            threshold: TimeDelta::hours(constants::PANIC_THRESHOLD_HOURS),
+            escalate_after: None,
+        }
+    }
+}
+
+impl StatusPolicy {
+    pub fn from_config(config: &Config) -> Self {
+        Self {
+            threshold: TimeDelta::hours(config.ui.panic_threshold_hours),
+            escalate_after: config.ui.escalate_after_days.map(TimeDelta::days),
+        }
+    }
+
+    pub fn status(&self, task: &Task, now: &DateTime<FixedOffset>) -> TaskStatus {
+        if self.idle_days(task, now).is_some() {
+            return TaskStatus::Panic;
+        }
+        task.get_status_within(&task.get_delta(now), self.threshold)
+    }
+
+    /// Whole days since the last change of an open, undated task left alone long enough to
+    /// escalate. A task that was never stamped has no age and stays as it is.
+    pub fn idle_days(&self, task: &Task, now: &DateTime<FixedOffset>) -> Option<i64> {
+        let escalate_after = self.escalate_after?;
+        if task.complete || task.time.is_some() {
+            return None;
+        }
+        let idle = *now - task.last_updated()?;
+        (idle >= escalate_after).then_some(idle.num_days())
+    }
+
+    /// Why the status is what it is, when the deadline alone does not tell
+    pub fn reason(&self, task: &Task, now: &DateTime<FixedOffset>) -> Option<String> {
+        self.idle_days(task, now).map(|x| format!("no activity for {x} days"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn now() -> DateTime<FixedOffset> {
+        DateTime::parse_from_rfc3339("2025-05-12T09:00:00+02:00").unwrap()
+    }
+
+    fn untouched_for(days: i64) -> Task {
+        Task { time: None, updated_at: Some(now() - TimeDelta::days(days)), ..Task::default() }
+    }
+
+    #[test]
+    fn undated_tasks_escalate_once_idle_long_enough() {
+        let policy = StatusPolicy { escalate_after: Some(TimeDelta::days(14)), ..StatusPolicy::default() };
+
+        assert_eq!(policy.status(&untouched_for(13), &now()), TaskStatus::Normal);
+        assert_eq!(policy.status(&untouched_for(14), &now()), TaskStatus::Panic);
+        assert_eq!(policy.reason(&untouched_for(21), &now()).as_deref(), Some("no activity for 21 days"));
+
+        // Done, dated or never stamped tasks are left to the deadline rules
+        let done = Task { complete: true, ..untouched_for(30) };
+        let dated = Task { time: Some(now() + TimeDelta::days(10)), ..untouched_for(30) };
+        let unknown = Task { updated_at: None, created_at: None, ..untouched_for(30) };
+        assert_eq!(policy.status(&done, &now()), TaskStatus::Zen);
+        assert_eq!(policy.status(&dated, &now()), TaskStatus::Normal);
+        assert_eq!(policy.status(&unknown, &now()), TaskStatus::Normal);
+    }
+
+    #[test]
+    fn without_escalation_statuses_are_the_deadline_ones() {
+        let policy = StatusPolicy::default();
+        let tasks = [
+            untouched_for(400),
+            Task { time: Some(now() - TimeDelta::hours(1)), ..Task::default() },
+            Task { time: Some(now() + TimeDelta::days(30)), ..Task::default() },
+            Task { time: Some(now() + TimeDelta::days(1)), complete: true, ..Task::default() },
+        ];
+        for task in &tasks {
+            assert_eq!(policy.status(task, &now()), task.get_status_within(&task.get_delta(&now()), TimeDelta::hours(constants::PANIC_THRESHOLD_HOURS)));
+            assert_eq!(policy.reason(task, &now()), None);
+        }
+    }
+}
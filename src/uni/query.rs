@@ -0,0 +1,244 @@
+use chrono::{DateTime, FixedOffset};
+
+use crate::uni::policy::StatusPolicy;
+use crate::uni::task::{Task, TaskStatus};
+
+
+type Predicate<'a> = Box<dyn Fn(&Task) -> bool + 'a>;
+
+/// Filters over a slice of tasks, built up predicate by predicate and evaluated lazily.
+/// Every predicate given has to match; statuses are measured against one shared `now`.
+pub struct TaskQuery<'a> {
+    tasks: &'a [Task],
+    now: DateTime<FixedOffset>,
+    policy: StatusPolicy,
+    subject: Option<String>,
+    statuses: Option<Vec<TaskStatus>>,
+    due_before: Option<DateTime<FixedOffset>>,
+    due_after: Option<DateTime<FixedOffset>>,
+    due_today: Option<bool>,
+    starred: Option<bool>,
+    complete: Option<bool>,
+    text: Option<String>,
+    ids: Option<Vec<u64>>,
+    custom: Vec<Predicate<'a>>,
+}
+
+impl<'a> TaskQuery<'a> {
+    pub fn new(tasks: &'a [Task], now: DateTime<FixedOffset>) -> Self {
+        Self {
+            tasks,
+            now,
+            policy: StatusPolicy::default(),
+            subject: None,
+            statuses: None,
+            due_before: None,
+            due_after: None,
+            due_today: None,
+            starred: None,
+            complete: None,
+            text: None,
+            ids: None,
+            custom: vec![],
+        }
+    }
+
+    /// Policy the statuses are computed with, threshold included
+    pub fn policy(mut self, policy: StatusPolicy) -> Self {
+        self.policy = policy;
+        self
+    }
+
+    /// Same subject, ignoring case and surrounding whitespace
+    pub fn subject(mut self, subject: &str) -> Self {
+        self.subject = Some(subject.trim().to_lowercase());
+        self
+    }
+
+    /// Any of the given statuses
+    pub fn status(mut self, statuses: impl IntoIterator<Item = TaskStatus>) -> Self {
+        self.statuses = Some(statuses.into_iter().collect());
+        self
+    }
+
+    /// Deadline strictly before `time`, tasks without one never match
+    pub fn due_before(mut self, time: DateTime<FixedOffset>) -> Self {
+        self.due_before = Some(time);
+        self
+    }
+
+    /// Deadline at `time` or later, so `due_before` and `due_after` split the dated tasks
+    pub fn due_after(mut self, time: DateTime<FixedOffset>) -> Self {
+        self.due_after = Some(time);
+        self
+    }
+
+    /// Open and due on today's local date, see `Task::is_due_today`
+    pub fn due_today(mut self, due_today: bool) -> Self {
+        self.due_today = Some(due_today);
+        self
+    }
+
+    pub fn starred(mut self, starred: bool) -> Self {
+        self.starred = Some(starred);
+        self
+    }
+
+    pub fn complete(mut self, complete: bool) -> Self {
+        self.complete = Some(complete);
+        self
+    }
+
+    /// Case-insensitive substring of the subject, the name or the description
+    pub fn text_contains(mut self, text: &str) -> Self {
+        self.text = Some(text.to_lowercase());
+        self
+    }
+
+    pub fn ids(mut self, ids: &[u64]) -> Self {
+        self.ids = Some(ids.to_vec());
+        self
+    }
+
+    /// Anything the named predicates don't cover
+    pub fn matching(mut self, predicate: impl Fn(&Task) -> bool + 'a) -> Self {
+        self.custom.push(Box::new(predicate));
+        self
+    }
+
+    pub fn matches(&self, task: &Task) -> bool {
+        let contains = |field: &str, text: &str| field.to_lowercase().contains(text);
+
+        self.subject.as_ref().is_none_or(|x| task.subject.trim().to_lowercase() == *x)
+            && self.statuses.as_ref().is_none_or(|x| {
+                x.contains(&self.policy.status(task, &self.now))
+            })
+            && self.due_before.is_none_or(|x| task.time.is_some_and(|t| t < x))
+            && self.due_after.is_none_or(|x| task.time.is_some_and(|t| t >= x))
+            && self.due_today.is_none_or(|x| task.is_due_today(&self.now) == x)
+            && self.starred.is_none_or(|x| task.starred == x)
+            && self.complete.is_none_or(|x| task.complete == x)
+            && self.text.as_ref().is_none_or(|x| {
+                contains(&task.subject, x) || contains(&task.name, x) || contains(&task.description, x)
+            })
+            && self.ids.as_ref().is_none_or(|x| x.contains(&task.id))
+            && self.custom.iter().all(|x| x(task))
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &'a Task> + '_ {
+        self.tasks.iter().filter(|x| self.matches(x))
+    }
+
+    pub fn count(&self) -> usize {
+        self.iter().count()
+    }
+
+    /// Positions of the matching tasks in the queried slice
+    pub fn indices(&self) -> impl Iterator<Item = usize> + '_ {
+        self.tasks.iter().enumerate().filter(|(_, x)| self.matches(x)).map(|(i, _)| i)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use chrono::TimeDelta;
+
+    fn now() -> DateTime<FixedOffset> {
+        DateTime::parse_from_rfc3339("2025-05-12T12:00:00+00:00").unwrap()
+    }
+
+    /// Deterministic pseudo-random tasks covering every field the predicates look at
+    fn tasks(count: u64) -> Vec<Task> {
+        let mut seed = 0x2545_f491_4f6c_dd1d_u64;
+        let mut next = move |n: u64| {
+            seed ^= seed << 13;
+            seed ^= seed >> 7;
+            seed ^= seed << 17;
+            seed % n
+        };
+        let subjects = ["Algo", "algo ", "Physics", "Databases", ""];
+        let names = ["HW1", "Lab", "Quiz", "Essay draft"];
+        (1..=count)
+            .map(|id| Task {
+                id,
+                subject: subjects[next(5) as usize].to_string(),
+                name: names[next(4) as usize].to_string(),
+                description: if next(3) == 0 { "read the LAB notes".to_string() } else { String::new() },
+                time: match next(4) {
+                    0 => None,
+                    _ => Some(now() + TimeDelta::hours(next(24 * 20) as i64 - 24 * 10)),
+                },
+                complete: next(3) == 0,
+                starred: next(4) == 0,
+                ..Task::default()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn single_predicates_agree_with_manual_filtering() {
+        let tasks = tasks(300);
+        let query = || TaskQuery::new(&tasks, now());
+        let count = |f: &dyn Fn(&Task) -> bool| tasks.iter().filter(|x| f(x)).count();
+
+        assert_eq!(query().count(), tasks.len());
+        assert_eq!(query().starred(true).count(), count(&|x| x.starred));
+        assert_eq!(query().complete(false).count(), count(&|x| !x.complete));
+        assert_eq!(query().subject("ALGO").count(), count(&|x| x.subject.trim().to_lowercase() == "algo"));
+        assert_eq!(query().text_contains("lab").count(), count(&|x| x.name == "Lab" || !x.description.is_empty()));
+        assert_eq!(query().ids(&[3, 5, 1000]).count(), 2);
+        assert_eq!(query().due_today(true).count(), count(&|x| x.is_due_today(&now())));
+        assert_eq!(
+            query().status([TaskStatus::Zen]).count(),
+            count(&|x| matches!(StatusPolicy::default().status(x, &now()), TaskStatus::Zen)),
+        );
+
+        // The deadline bounds split the dated tasks between them
+        let dated = count(&|x| x.time.is_some());
+        assert_eq!(query().due_before(now()).count() + query().due_after(now()).count(), dated);
+    }
+
+    #[test]
+    fn combined_predicates_agree_with_manual_filtering() {
+        let tasks = tasks(500);
+        let (from, to) = (now() - TimeDelta::days(3), now() + TimeDelta::days(4));
+
+        for starred in [false, true] {
+            for complete in [false, true] {
+                let query = TaskQuery::new(&tasks, now())
+                    .starred(starred)
+                    .complete(complete)
+                    .due_after(from)
+                    .due_before(to)
+                    .status([TaskStatus::Panic, TaskStatus::DueToday]);
+                let manual = tasks
+                    .iter()
+                    .enumerate()
+                    .filter(|(_, x)| {
+                        x.starred == starred
+                            && x.complete == complete
+                            && x.time.is_some_and(|t| from <= t && t < to)
+                            && StatusPolicy::default().status(x, &now()).is_urgent()
+                    })
+                    .collect::<Vec<_>>();
+
+                assert_eq!(query.indices().collect::<Vec<_>>(), manual.iter().map(|(i, _)| *i).collect::<Vec<_>>());
+                assert_eq!(query.iter().collect::<Vec<_>>(), manual.iter().map(|(_, x)| *x).collect::<Vec<_>>());
+            }
+        }
+    }
+
+    #[test]
+    fn custom_predicates_and_the_threshold_narrow_further() {
+        let tasks = tasks(200);
+        let soon = TaskQuery::new(&tasks, now()).status([TaskStatus::Panic]).count();
+        let sooner = TaskQuery::new(&tasks, now()).policy(StatusPolicy { threshold: TimeDelta::hours(1), ..StatusPolicy::default() }).status([TaskStatus::Panic]).count();
+        assert!(sooner <= soon);
+
+        let even = TaskQuery::new(&tasks, now()).starred(true).matching(|x| x.id % 2 == 0);
+        assert!(even.iter().all(|x| x.starred && x.id % 2 == 0));
+        assert_eq!(even.count(), tasks.iter().filter(|x| x.starred && x.id % 2 == 0).count());
+    }
+}
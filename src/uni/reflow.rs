@@ -0,0 +1,203 @@
+use unicode_width::UnicodeWidthStr;
+
+
+/// A piece of Markdown as far as wrapping is concerned
+enum Block {
+    /// Kept exactly as written: code, headings, quotes, tables, blank lines
+    Verbatim(String),
+    /// Words of a paragraph or list item
+    Paragraph {
+        /// Indentation and list marker in front of the first line
+        first: String,
+        /// In front of every other line, so they line up under the text
+        rest: String,
+        text: String,
+        /// Trailing spaces of a hard break
+        suffix: String,
+    },
+}
+
+/// "- ", "  1. " or "* [ ] " in front of a list item, and the text after it
+fn list_item(line: &str) -> Option<(&str, &str)> {
+    let content = line.trim_start();
+    let indent = line.len() - content.len();
+    let digits = content.chars().take_while(char::is_ascii_digit).count();
+    let marker = match content[digits..].chars().next() {
+        Some('.' | ')') if digits > 0 => digits + 1,
+        Some('-' | '*' | '+') if digits == 0 => 1,
+        _ => return None,
+    };
+    if !content[marker..].starts_with(' ') {
+        return None;
+    }
+    let mut end = indent + marker + 1;
+    // A task list checkbox belongs to the marker
+    if ["[ ] ", "[x] ", "[X] "].iter().any(|x| line[end..].starts_with(x)) {
+        end += 4;
+    }
+    Some(line.split_at(end))
+}
+
+/// Lines that stand alone: never joined with their neighbours, nor wrapped
+fn stands_alone(line: &str) -> bool {
+    let content = line.trim_start();
+    ["#", ">", "|", "<", "    ", "\t"].iter().any(|x| content.starts_with(x) || line.starts_with(x))
+        || content.chars().next().is_some_and(|c| content.len() >= 3 && "-*_=".contains(c) && content.chars().all(|x| x == c))
+}
+
+fn fence(line: &str) -> Option<&'static str> {
+    let content = line.trim_start();
+    ["```", "~~~"].into_iter().find(|x| content.starts_with(x))
+}
+
+fn parse(text: &str) -> Vec<Block> {
+    let mut blocks = vec![];
+    let mut open = false;
+    let mut in_fence: Option<&str> = None;
+    for line in text.split('\n') {
+        if let Some(marker) = in_fence {
+            blocks.push(Block::Verbatim(line.to_string()));
+            in_fence = (!line.trim_start().starts_with(marker)).then_some(marker);
+            open = false;
+            continue;
+        }
+        let content = line.trim_end();
+        let suffix = match content.ends_with('\\') || line.ends_with("  ") {
+            true => line[content.len()..].to_string(),
+            false => String::new(),
+        };
+        let joined = match (blocks.last_mut(), open) {
+            (Some(Block::Paragraph { text, suffix: last, .. }), true)
+                if !content.is_empty() && list_item(line).is_none() && fence(line).is_none() && !stands_alone(line.trim_start()) =>
+            {
+                text.push(' ');
+                text.push_str(content.trim_start());
+                *last = suffix.clone();
+                true
+            },
+            _ => false,
+        };
+        if joined {
+            open = last_is_open(&blocks);
+            continue;
+        }
+
+        if let Some(marker) = fence(line) {
+            in_fence = Some(marker);
+            blocks.push(Block::Verbatim(line.to_string()));
+            open = false;
+        } else if let Some((prefix, rest)) = list_item(content) {
+            blocks.push(Block::Paragraph {
+                first: prefix.to_string(),
+                rest: " ".repeat(prefix.width()),
+                text: rest.to_string(),
+                suffix,
+            });
+            open = last_is_open(&blocks);
+        } else if content.is_empty() || stands_alone(line) {
+            blocks.push(Block::Verbatim(line.to_string()));
+            open = false;
+        } else {
+            let indent = &content[..content.len() - content.trim_start().len()];
+            blocks.push(Block::Paragraph {
+                first: indent.to_string(),
+                rest: indent.to_string(),
+                text: content.trim_start().to_string(),
+                suffix,
+            });
+            open = last_is_open(&blocks);
+        }
+    }
+    blocks
+}
+
+/// Whether the next line may still go on the last paragraph, that is no hard break ended it
+fn last_is_open(blocks: &[Block]) -> bool {
+    match blocks.last() {
+        Some(Block::Paragraph { text, suffix, .. }) => suffix.is_empty() && !text.ends_with('\\'),
+        _ => false,
+    }
+}
+
+fn render(blocks: &[Block], width: Option<usize>) -> String {
+    let mut lines = vec![];
+    for block in blocks {
+        let Block::Paragraph { first, rest, text, suffix } = block else {
+            if let Block::Verbatim(line) = block {
+                lines.push(line.clone());
+            }
+            continue;
+        };
+        let mut current = first.clone();
+        let mut empty = true;
+        for word in text.split(' ').filter(|x| !x.is_empty()) {
+            if !empty && width.is_some_and(|x| current.width() + 1 + word.width() > x) {
+                lines.push(current);
+                current = rest.clone();
+                empty = true;
+            }
+            if !empty {
+                current.push(' ');
+            }
+            current.push_str(word);
+            empty = false;
+        }
+        current.push_str(suffix);
+        lines.push(current);
+    }
+    lines.join("\n")
+}
+
+/// Paragraphs and list items broken into lines of at most `width` columns, where the
+/// words allow. Code, headings, quotes, tables and hard breaks stay as they are.
+pub fn wrap(text: &str, width: usize) -> String {
+    render(&parse(text), Some(width))
+}
+
+/// Every paragraph and list item back on one line, what `wrap` undoes
+pub fn unwrap(text: &str) -> String {
+    render(&parse(text), None)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn round_trip(text: &str, width: usize) -> String {
+        let wrapped = wrap(text, width);
+        assert_eq!(unwrap(&wrapped), text, "{wrapped}");
+        wrapped
+    }
+
+    #[test]
+    fn paragraphs_wrap_and_join_back() {
+        let text = "Read chapters seven and eight of the book before the lab.\n\nThen the second paragraph.\n";
+        assert_eq!(
+            round_trip(text, 20),
+            "Read chapters seven\nand eight of the\nbook before the lab.\n\nThen the second\nparagraph.\n"
+        );
+        // Too long a word gets a line of its own
+        assert_eq!(round_trip("See https://example.com/a/very/long/link now", 10), "See\nhttps://example.com/a/very/long/link\nnow");
+    }
+
+    #[test]
+    fn nested_lists_line_up_under_their_text() {
+        let text = "Todo:\n- first item of the list\n  - nested item under it\n- [ ] checkbox item here\n12. numbered item";
+        assert_eq!(
+            round_trip(text, 16),
+            "Todo:\n- first item of\n  the list\n  - nested item\n    under it\n- [ ] checkbox\n      item here\n12. numbered\n    item"
+        );
+    }
+
+    #[test]
+    fn code_headings_and_hard_breaks_stay() {
+        let text = "# A heading that is long\n```rust\nfn main() { println!(\"a long line of code\"); }\n```\n\
+            Line with a hard break  \nnext line\\\nlast line\n\n    indented code line here\n> a quote that is long\n| a | table |\n---";
+        assert_eq!(round_trip(text, 12), text.replace("Line with a hard break  ", "Line with a\nhard break  "));
+    }
+
+    #[test]
+    fn soft_breaks_are_joined() {
+        assert_eq!(unwrap("One\ntwo\n- item\n  more\n\nthree"), "One two\n- item more\n\nthree");
+    }
+}
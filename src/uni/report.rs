@@ -0,0 +1,155 @@
+use std::collections::BTreeMap;
+
+use chrono::{Datelike, TimeDelta};
+
+use crate::uni::dates::format_spent;
+use crate::uni::export::csv_table;
+use crate::uni::task::Task;
+
+
+/// Time tracked with the focus timer, summed per subject and per ISO week. Tasks only
+/// keep their total, so the whole of it counts for the week the task last changed in,
+/// which is when the timer last banked into it.
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct TimeReport {
+    /// Most time first
+    pub subjects: Vec<(String, TimeDelta)>,
+    /// Oldest first as "2025-W20", tasks without any date at the end as "unknown"
+    pub weeks: Vec<(String, TimeDelta)>,
+    pub total: TimeDelta,
+}
+
+impl TimeReport {
+    pub fn new(tasks: &[Task]) -> Self {
+        let tracked = tasks.iter().filter(|x| x.time_spent > TimeDelta::zero());
+
+        let mut subjects = BTreeMap::<&str, TimeDelta>::new();
+        let mut weeks = BTreeMap::<Option<(i32, u32)>, TimeDelta>::new();
+        for task in tracked {
+            *subjects.entry(task.subject.trim()).or_default() += task.time_spent;
+            let week = task.last_updated().map(|x| x.iso_week()).map(|x| (x.year(), x.week()));
+            *weeks.entry(week).or_default() += task.time_spent;
+        }
+
+        let mut subjects = subjects
+            .into_iter()
+            .map(|(subject, spent)| (subject.to_string(), spent))
+            .collect::<Vec<_>>();
+        // Stable, so equal times stay in alphabetical order
+        subjects.sort_by_key(|(_, spent)| std::cmp::Reverse(*spent));
+
+        // None sorts first, but an unknown week reads best last
+        let mut weeks = weeks.into_iter().collect::<Vec<_>>();
+        let unknown = weeks.iter().take_while(|(week, _)| week.is_none()).count();
+        weeks.rotate_left(unknown);
+        let weeks = weeks
+            .into_iter()
+            .map(|(week, spent)| match week {
+                Some((year, week)) => (format!("{year}-W{week:02}"), spent),
+                None => ("unknown".to_string(), spent),
+            })
+            .collect();
+
+        Self {
+            total: subjects.iter().map(|(_, spent)| *spent).sum(),
+            subjects,
+            weeks,
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.subjects.is_empty()
+    }
+
+    /// Both tables with the times right-aligned
+    pub fn table(&self) -> String {
+        if self.is_empty() {
+            return "No time tracked yet, start the focus timer with T.".to_string();
+        }
+        let mut lines = Self::section("Subject", &self.subjects);
+        lines.push(Self::row("Total", &format_spent(self.total), Self::width("Subject", &self.subjects)));
+        lines.push(String::new());
+        lines.extend(Self::section("Week", &self.weeks));
+        lines.join("\n")
+    }
+
+    /// One row per subject and per week, the time in whole minutes
+    pub fn csv(&self) -> String {
+        let header = ["kind", "name", "minutes"].map(String::from);
+        let rows = self.subjects.iter().map(|x| ("subject", x)).chain(self.weeks.iter().map(|x| ("week", x)));
+        let rows = rows.map(|(kind, (name, spent))| [kind.to_string(), name.clone(), spent.num_minutes().to_string()]);
+        csv_table([header].into_iter().chain(rows))
+    }
+
+    fn width(heading: &str, rows: &[(String, TimeDelta)]) -> usize {
+        rows.iter().map(|(name, _)| name.chars().count()).chain([heading.len(), "Total".len()]).max().unwrap_or(0)
+    }
+
+    fn row(name: &str, time: &str, width: usize) -> String {
+        format!("{name:<width$}  {time:>7}")
+    }
+
+    fn section(heading: &str, rows: &[(String, TimeDelta)]) -> Vec<String> {
+        let width = Self::width(heading, rows);
+        let mut lines = vec![Self::row(heading, "Time", width)];
+        lines.extend(rows.iter().map(|(name, spent)| Self::row(name, &format_spent(*spent), width)));
+        lines
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use chrono::DateTime;
+
+    fn task(subject: &str, minutes: i64, updated: Option<&str>) -> Task {
+        Task {
+            subject: subject.to_string(),
+            time_spent: TimeDelta::minutes(minutes),
+            updated_at: updated.map(|x| DateTime::parse_from_rfc3339(x).unwrap()),
+            ..Task::default()
+        }
+    }
+
+    #[test]
+    fn untracked_tasks_are_left_out() {
+        let report = TimeReport::new(&[task("Algorithms", 0, Some("2025-05-12T10:00:00+02:00"))]);
+
+        assert!(report.is_empty());
+        assert_eq!(report, TimeReport::default());
+        assert!(report.table().starts_with("No time tracked yet"));
+        assert_eq!(report.csv(), "kind,name,minutes\n");
+    }
+
+    #[test]
+    fn weeks_start_on_monday() {
+        let tasks = vec![
+            // Sunday and Monday around midnight, one ISO week apart
+            task("Algorithms", 300, Some("2025-05-18T23:30:00+02:00")),
+            task("Algorithms", 240, Some("2025-05-19T00:30:00+02:00")),
+            task("Physics", 40, Some("2025-05-13T09:00:00+02:00")),
+            task("Databases", 40, None),
+            task("Physics", 0, Some("2025-01-01T09:00:00+02:00")),
+        ];
+        let report = TimeReport::new(&tasks);
+
+        assert_eq!(report.subjects, [
+            ("Algorithms".to_string(), TimeDelta::minutes(540)),
+            ("Databases".to_string(), TimeDelta::minutes(40)),
+            ("Physics".to_string(), TimeDelta::minutes(40)),
+        ]);
+        assert_eq!(report.weeks, [
+            ("2025-W20".to_string(), TimeDelta::minutes(340)),
+            ("2025-W21".to_string(), TimeDelta::minutes(240)),
+            ("unknown".to_string(), TimeDelta::minutes(40)),
+        ]);
+        assert_eq!(report.total, TimeDelta::minutes(620));
+
+        let table = report.table();
+        assert!(table.contains("Algorithms   9h 00m\n"), "{table}");
+        assert!(table.contains("Total       10h 20m\n"), "{table}");
+        assert!(report.csv().contains("subject,Algorithms,540\nsubject,Databases,40\n"));
+        assert!(report.csv().ends_with("week,unknown,40\n"));
+    }
+}
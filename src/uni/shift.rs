@@ -0,0 +1,92 @@
+use chrono::{DateTime, FixedOffset, TimeDelta};
+
+use crate::uni::task::Task;
+
+
+/// One deadline to be moved
+#[derive(Debug, PartialEq, Eq)]
+pub struct Shift {
+    pub id: u64,
+    pub before: DateTime<FixedOffset>,
+    pub after: DateTime<FixedOffset>,
+}
+
+/// Deadlines of the incomplete tasks in `subject` (case-insensitive), moved by `delta`.
+/// Completed and undated tasks stay where they are, and anchored ones follow their anchor.
+pub fn plan_shift(tasks: &[Task], subject: &str, delta: TimeDelta) -> Vec<Shift> {
+    let subject = subject.trim().to_lowercase();
+    tasks
+        .iter()
+        .filter(|x| !x.complete && x.anchor.is_none() && x.subject.trim().to_lowercase() == subject)
+        .filter_map(|x| x.time.map(|time| Shift { id: x.id, before: time, after: time + delta }))
+        .collect()
+}
+
+pub fn apply_shift(tasks: &mut [Task], shifts: &[Shift], now: DateTime<FixedOffset>) {
+    for shift in shifts {
+        if let Some(task) = tasks.iter_mut().find(|x| x.id == shift.id) {
+            task.time = Some(shift.after);
+            task.touch(now);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::uni::anchors::{self, Anchor};
+
+    fn at(time: &str) -> Option<DateTime<FixedOffset>> {
+        Some(DateTime::parse_from_rfc3339(time).unwrap())
+    }
+
+    #[test]
+    fn shifts_only_open_dated_tasks_of_the_subject() {
+        let task = |id, subject: &str, time, complete| Task {
+            id,
+            subject: subject.to_string(),
+            time,
+            complete,
+            ..Task::default()
+        };
+        let mut tasks = vec![
+            task(1, "Algorithms", at("2025-05-12T18:00:00Z"), false),
+            task(2, "algorithms ", at("2025-05-14T18:00:00Z"), false),
+            task(3, "Algorithms", at("2025-05-01T18:00:00Z"), true),
+            task(4, "Algorithms", None, false),
+            task(5, "Physics", at("2025-05-12T18:00:00Z"), false),
+        ];
+
+        let shifts = plan_shift(&tasks, "Algorithms", TimeDelta::days(7));
+        assert_eq!(shifts.iter().map(|x| x.id).collect::<Vec<_>>(), vec![1, 2]);
+
+        apply_shift(&mut tasks, &shifts, at("2025-05-10T09:00:00Z").unwrap());
+        assert_eq!(tasks[0].time, at("2025-05-19T18:00:00Z"));
+        assert_eq!(tasks[0].updated_at, at("2025-05-10T09:00:00Z"));
+        assert_eq!(tasks[2].updated_at, None);
+        assert_eq!(tasks[1].time, at("2025-05-21T18:00:00Z"));
+        assert_eq!(tasks[2].time, at("2025-05-01T18:00:00Z"));
+        assert_eq!(tasks[4].time, at("2025-05-12T18:00:00Z"));
+    }
+
+    #[test]
+    fn anchored_tasks_are_left_to_their_anchor() {
+        let submission = Task { id: 1, subject: "Algorithms".to_string(), time: at("2025-05-12T18:00:00Z"), ..Task::default() };
+        let draft = Task {
+            id: 2,
+            subject: "Algorithms".to_string(),
+            anchor: Some(Anchor { task: 1, offset: TimeDelta::days(-7) }),
+            ..Task::default()
+        };
+        let mut tasks = vec![submission, draft];
+        anchors::resolve(&mut tasks);
+
+        let shifts = plan_shift(&tasks, "Algorithms", TimeDelta::days(7));
+        assert_eq!(shifts.iter().map(|x| x.id).collect::<Vec<_>>(), vec![1]);
+
+        apply_shift(&mut tasks, &shifts, at("2025-05-10T09:00:00Z").unwrap());
+        anchors::resolve(&mut tasks);
+        assert_eq!(tasks[1].time, at("2025-05-12T18:00:00Z"));
+    }
+}
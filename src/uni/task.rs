@@ -1,67 +1,174 @@
 use chrono::{DateTime, FixedOffset, Local, TimeDelta};
 
-use crate::constants;
+use crate::uni::anchors::Anchor;
+use crate::uni::clock::Clock;
+use crate::uni::dates::local_date;
+use crate::uni::format::{format_delta, DeltaLength};
 
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum TaskStatus {
+    /// Overdue, or due within the panic threshold
     Panic,
+    /// Still ahead, later on the same day
+    DueToday,
     Normal,
     Zen,
 }
 
+impl TaskStatus {
+    /// Needs attention soon, whichever way
+    pub fn is_urgent(&self) -> bool {
+        matches!(self, TaskStatus::Panic | TaskStatus::DueToday)
+    }
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            TaskStatus::Panic => "panic",
+            TaskStatus::DueToday => "due today",
+            TaskStatus::Normal => "normal",
+            TaskStatus::Zen => "zen",
+        }
+    }
+}
+
 #[derive(Debug, Clone, PartialEq)]
 pub struct Task {
+    /// Stable identifier, 0 until assigned by `assign_ids`
+    pub id: u64,
     pub name: String,
     pub description: String,
     pub subject: String,
     pub time: Option<DateTime<FixedOffset>>,
     pub complete: bool,
     pub starred: bool,
+    pub created_at: Option<DateTime<FixedOffset>>,
+    pub completed_at: Option<DateTime<FixedOffset>>,
+    /// Last change through any of the editing paths, see `touch`
+    pub updated_at: Option<DateTime<FixedOffset>>,
+    /// Tracked with the focus timer
+    pub time_spent: TimeDelta,
+    /// "#b16286" or a color name, shown in place of the status color
+    pub color: Option<String>,
+    /// Deadline following another task's, which `time` is then resolved from
+    pub anchor: Option<Anchor>,
 }
 
 impl Default for Task {
     fn default() -> Self {
         Self {
-            name: "[Name]".to_string(),
-            description: "Description_goes_here".to_string(),
-            subject: "[Subject]".to_string(),
-            time: Some(Local::now().fixed_offset()),
+            id: 0,
+            name: Self::PLACEHOLDER_NAME.to_string(),
+            description: Self::PLACEHOLDER_DESCRIPTION.to_string(),
+            subject: Self::PLACEHOLDER_SUBJECT.to_string(),
+            time: None,
             complete: false,
             starred: false,
+            created_at: None,
+            completed_at: None,
+            updated_at: None,
+            time_spent: TimeDelta::zero(),
+            color: None,
+            anchor: None,
         }
     }
 }
 
 impl Task {
-    pub fn get_delta(&self, target: &DateTime<FixedOffset>) -> Option<TimeDelta> {
-        self.time.clone().map(|v| v - target)
+    pub const PLACEHOLDER_NAME: &str = "[Name]";
+    pub const PLACEHOLDER_SUBJECT: &str = "[Subject]";
+    pub const PLACEHOLDER_DESCRIPTION: &str = "Description_goes_here";
+
+    /// A fresh task created right now, without a deadline
+    pub fn new_now() -> Self {
+        Self::new_by(&Clock::System)
     }
 
-    pub fn get_delta_now(&self) -> Option<TimeDelta> {
-        self.get_delta(&Local::now().fixed_offset())
+    /// As `new_now`, created at the moment `clock` gives
+    pub fn new_by(clock: &Clock) -> Self {
+        let now = clock.now();
+        Self {
+            created_at: Some(now),
+            updated_at: Some(now),
+            ..Self::default()
+        }
     }
 
-    pub fn get_status(&self, duration: &Option<TimeDelta>) -> TaskStatus {
-        if duration.is_none() {
+    /// What `p` adds: the placeholder, created and due at the moment `clock` gives
+    pub fn placeholder_now(clock: &Clock) -> Self {
+        let now = clock.now();
+        Self {
+            time: Some(now),
+            created_at: Some(now),
+            updated_at: Some(now),
+            ..Self::default()
+        }
+    }
+
+    /// Demonstrates the fields to a new user
+    pub fn example() -> Self {
+        Self {
+            name: "Try out unist".to_string(),
+            subject: "Getting started".to_string(),
+            description: "Every task has a subject, a name, an optional deadline and this description.\n\
+                Press `e` to edit this task, `c` to mark it complete, `s` to star it \
+                and `d` to delete it.".to_string(),
+            time: Some(Local::now().fixed_offset() + TimeDelta::days(1)),
+            starred: true,
+            ..Self::new_now()
+        }
+    }
+
+    /// Changes the completion, keeping `completed_at` in line with it
+    pub fn set_complete(&mut self, complete: bool) {
+        if complete && !self.complete {
+            self.completed_at = Some(Local::now().fixed_offset());
+        } else if !complete {
+            self.completed_at = None;
+        }
+        self.complete = complete;
+    }
+
+    /// Records a change made at `now`
+    pub fn touch(&mut self, now: DateTime<FixedOffset>) {
+        self.updated_at = Some(now);
+    }
+
+    /// Last known change, the creation for tasks never changed since
+    pub fn last_updated(&self) -> Option<DateTime<FixedOffset>> {
+        self.updated_at.or(self.created_at)
+    }
+
+    pub fn get_delta(&self, target: &DateTime<FixedOffset>) -> Option<TimeDelta> {
+        self.time.clone().map(|v| v - target)
+    }
+
+    /// Status with deadlines closer than `threshold` panicking, to the minute
+    pub fn get_status_within(&self, duration: &Option<TimeDelta>, threshold: TimeDelta) -> TaskStatus {
+        let (Some(duration), Some(time)) = (*duration, self.time) else {
             return match self.complete {
                 true    => TaskStatus::Zen,
                 false   => TaskStatus::Normal
             }
+        };
+        if self.complete {
+            return TaskStatus::Zen;
         }
 
-        let duration = duration.unwrap();
-
-        if duration.num_days() < constants::DAYS_LEFT as i64 && !self.complete {
+        // The moment `duration` was measured from falls on the deadline's local day
+        let today = local_date(&(time - duration)) == local_date(&time);
+        if duration >= TimeDelta::zero() && today {
+            TaskStatus::DueToday
+        } else if duration < threshold {
             TaskStatus::Panic
-        } else if self.complete {
-            TaskStatus::Zen
         } else {
             TaskStatus::Normal
         }
     }
 
-    pub fn get_status_now(&self) -> TaskStatus {
-        let duration = self.get_delta_now();
-        self.get_status(&duration)
+    /// Still open and due on the local calendar date of `now`, whatever offset the deadline was written in
+    pub fn is_due_today(&self, now: &DateTime<FixedOffset>) -> bool {
+        !self.complete && self.time.is_some_and(|x| local_date(&x) == local_date(now))
     }
 
     pub fn name(&self) -> &str {
@@ -72,55 +179,158 @@ impl Task {
         &self.subject
     }
 
-    fn time_quantity_format(str: &str, num: i32) -> Option<String> {
-        if num == 1 || num == -1 {
-            Some(num.to_string() + " " +  str)
-        } else if num > 1 || num < -1 {
-            Some(num.to_string() + " " + str + "s")
-        } else {
-            None
-        }
-    }
-
     pub fn delta(&self) -> String {
-        if let Some(duration) = self.get_delta_now() {
-            if duration.abs() < TimeDelta::minutes(1) {
-                return "No time!".to_string();
-            }
-            // Format time until the task
-            let days    = duration.num_days() as i32;
-            let hours   = duration.num_hours() as i32 - duration.num_days() as i32 * 24;
-            let minutes = duration.num_minutes() as i32 - duration.num_hours() as i32 * 60;
-
-            let days = Self::time_quantity_format("day", days);
-            let hours = Self::time_quantity_format("hour", hours);
-            let minutes = Self::time_quantity_format("minute", minutes);
-
-            let mut units = vec![];
-            if let Some(days) = days {units.push(days)}
-            if let Some(hours) = hours {units.push(hours)}
-
-            // TODO: Tackle the long/short format
-            let long = true;
-            if long {
-                if let Some(minutes) = minutes {units.push(minutes)}
-            }
+        self.delta_at(&Local::now().fixed_offset())
+    }
 
-            if units.len() == 0 {
-                return "".to_owned();
-            }
+    /// Time left in words, "∞" without a deadline
+    pub fn delta_at(&self, now: &DateTime<FixedOffset>) -> String {
+        self.get_delta(now).map_or("∞".to_owned(), |x| format_delta(x, DeltaLength::Long))
+    }
 
+    /// Compact two-unit form of the time left, e.g. "5h 12m"
+    pub fn delta_short_at(&self, now: &DateTime<FixedOffset>) -> Option<String> {
+        self.get_delta(now).map(|x| format_delta(x, DeltaLength::Short))
+    }
 
-            return units.join(" ");
-        } else {
-            "∞".to_owned()
-        }
+    /// The description is still to be written: empty, blank or the placeholder
+    pub fn is_stub(&self) -> bool {
+        let description = self.description.trim();
+        description.is_empty() || description == Self::PLACEHOLDER_DESCRIPTION
     }
 
+    /// Added and never filled in, whatever its deadline and timestamps
     pub fn is_default(&self) -> bool {
-        let mut default_task = Self::default();
-        default_task.time = self.time.clone();
+        self.name == Self::PLACEHOLDER_NAME
+            && self.subject == Self::PLACEHOLDER_SUBJECT
+            && self.is_stub()
+            && !self.complete
+            && !self.starred
+            && self.completed_at.is_none()
+            && self.time_spent.is_zero()
+            && self.color.is_none()
+            && self.anchor.is_none()
+    }
+}
+
+/// Gives every task without an id a fresh one, keeping the existing ids intact
+pub fn assign_ids(tasks: &mut [Task]) {
+    let next = tasks.iter().map(|x| x.id).max().unwrap_or(0) + 1;
+    for (task, id) in tasks.iter_mut().filter(|x| x.id == 0).zip(next..) {
+        task.id = id;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn status(minutes: i64) -> &'static str {
+        use chrono::TimeZone;
+
+        // Late in the evening here, so anything over a few hours ahead is tomorrow
+        let now = Local.with_ymd_and_hms(2025, 5, 12, 22, 0, 0).unwrap().fixed_offset();
+        let task = Task { time: Some(now + TimeDelta::minutes(minutes)), complete: false, ..Task::default() };
+        task.get_status_within(&task.get_delta(&now), TimeDelta::hours(48)).label()
+    }
+
+    #[test]
+    fn statuses_change_at_the_exact_minute() {
+        assert_eq!(status(-1), "panic");
+        assert_eq!(status(0), "due today");
+        assert_eq!(status(119), "due today");
+        assert_eq!(status(121), "panic");
+        assert_eq!(status(48 * 60 - 1), "panic");
+        assert_eq!(status(48 * 60), "normal");
+        assert_eq!(status(48 * 60 + 1), "normal");
+    }
+
+    #[test]
+    fn today_follows_the_local_calendar() {
+        use chrono::TimeZone;
+
+        let local = |h, m| Local.with_ymd_and_hms(2025, 5, 12, h, m, 0).unwrap().fixed_offset();
+        let now = local(21, 0);
+        let due = |time: DateTime<FixedOffset>| Task { time: Some(time), complete: false, ..Task::default() };
+
+        assert!(due(local(23, 59)).is_due_today(&now));
+        assert!(due(local(8, 0)).is_due_today(&now));
+        // Same instant written in another offset is still tonight here
+        let elsewhere = FixedOffset::east_opt(14 * 3600).unwrap();
+        assert!(due(local(23, 30).with_timezone(&elsewhere)).is_due_today(&now));
+        assert!(!due(local(23, 30) + TimeDelta::hours(1)).is_due_today(&now));
+        assert!(!Task { complete: true, ..due(local(23, 0)) }.is_due_today(&now));
+    }
+
+    #[test]
+    fn deltas_count_real_hours_across_a_clock_change() {
+        // Written in winter time, due in summer time: 9:00 to 9:00 on the wall clock
+        // is two days, yet one hour of it never happened
+        let now = DateTime::parse_from_rfc3339("2025-03-29T09:00:00+01:00").unwrap();
+        let task = Task { time: Some(DateTime::parse_from_rfc3339("2025-03-31T09:00:00+02:00").unwrap()), ..Task::default() };
+
+        assert_eq!(task.get_delta(&now), Some(TimeDelta::hours(47)));
+        assert_eq!(task.delta_at(&now), "1 day 23 hours");
+        assert_eq!(task.delta_short_at(&now).as_deref(), Some("1d 23h"));
+    }
+
+    #[test]
+    fn stubs_are_blank_or_the_placeholder() {
+        let with = |description: &str| Task { description: description.to_string(), ..Task::default() };
+
+        assert!(Task::default().is_stub());
+        assert!(with("").is_stub());
+        assert!(with(" \n\t").is_stub());
+        assert!(with("Description_goes_here\n").is_stub());
+        assert!(!with("Read chapter 3").is_stub());
+        assert!(!with("Description_goes_here, then more").is_stub());
+    }
+
+    #[test]
+    fn default_tasks_are_stubs_nobody_touched() {
+        let default = Task::default();
+        assert!(Task { id: 7, time: None, updated_at: Some(Local::now().fixed_offset()), ..default.clone() }.is_default());
+        assert!(Task { description: String::new(), ..default.clone() }.is_default());
+
+        assert!(!Task { description: "Notes".to_string(), ..default.clone() }.is_default());
+        assert!(!Task { name: "HW1".to_string(), ..default.clone() }.is_default());
+        assert!(!Task { starred: true, ..default.clone() }.is_default());
+        assert!(!Task { time_spent: TimeDelta::minutes(5), ..default }.is_default());
+    }
+
+    #[test]
+    fn the_default_task_does_not_depend_on_the_time() {
+        assert_eq!(Task::default(), Task::default());
+        assert_eq!(Task::default().time, None);
+
+        let now = DateTime::parse_from_rfc3339("2025-03-01T12:00:00+01:00").unwrap();
+        let placeholder = Task::placeholder_now(&Clock::Fixed(now));
+        assert_eq!((placeholder.time, placeholder.created_at, placeholder.updated_at), (Some(now), Some(now), Some(now)));
+        assert!(placeholder.is_default());
+        assert_eq!(placeholder, Task { time: Some(now), created_at: Some(now), updated_at: Some(now), ..Task::default() });
+    }
+
+    #[test]
+    fn a_placeholder_edited_in_any_field_is_no_longer_default() {
+        let now = DateTime::parse_from_rfc3339("2025-03-01T12:00:00+01:00").unwrap();
+        let placeholder = Task::placeholder_now(&Clock::Fixed(now));
+        let edited = [
+            ("name", Task { name: "HW1".to_string(), ..placeholder.clone() }),
+            ("subject", Task { subject: "Algorithms".to_string(), ..placeholder.clone() }),
+            ("description", Task { description: "Chapter 3".to_string(), ..placeholder.clone() }),
+            ("complete", Task { complete: true, ..placeholder.clone() }),
+            ("starred", Task { starred: true, ..placeholder.clone() }),
+            ("completed_at", Task { completed_at: Some(now), ..placeholder.clone() }),
+            ("time_spent", Task { time_spent: TimeDelta::minutes(1), ..placeholder.clone() }),
+            ("color", Task { color: Some("red".to_string()), ..placeholder.clone() }),
+            ("anchor", Task { anchor: Some(Anchor { task: 1, offset: TimeDelta::days(-1) }), ..placeholder.clone() }),
+        ];
+        for (field, task) in edited {
+            assert!(!task.is_default(), "{field}");
+        }
 
-        self == &default_task
+        // Moving the deadline, or the bookkeeping, leaves it a placeholder
+        let moved = Task { id: 4, time: None, updated_at: Some(now + TimeDelta::hours(1)), ..placeholder };
+        assert!(moved.is_default());
     }
 }
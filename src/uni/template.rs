@@ -0,0 +1,83 @@
+use std::fs;
+use std::io;
+
+use chrono::NaiveDate;
+
+use crate::config::Config;
+use crate::paths::expand_tilde;
+
+
+/// Fills in `{subject}`, `{name}` and `{date}` (today, as 2025-05-12) in one pass, so braces
+/// in a value are left as they are
+pub fn substitute(template: &str, subject: &str, name: &str, today: NaiveDate) -> String {
+    let date = today.format("%Y-%m-%d").to_string();
+    let placeholders = [("{subject}", subject), ("{name}", name), ("{date}", date.as_str())];
+    let mut result = String::with_capacity(template.len());
+    let mut rest = template;
+    while let Some(start) = rest.find('{') {
+        result.push_str(&rest[..start]);
+        rest = &rest[start..];
+        match placeholders.iter().find(|(key, _)| rest.starts_with(key)) {
+            Some((key, value)) => {
+                result.push_str(value);
+                rest = &rest[key.len()..];
+            },
+            None => {
+                result.push('{');
+                rest = &rest[1..];
+            },
+        }
+    }
+    result.push_str(rest);
+    result
+}
+
+/// Description of a new task from the `new_task_template` file, None without one configured.
+/// The file is read every time, so changes to it apply right away.
+pub fn new_description(config: &Config, subject: &str, name: &str, today: NaiveDate) -> io::Result<Option<String>> {
+    let Some(path) = &config.new_task_template else { return Ok(None) };
+    let path = expand_tilde(path, dirs::home_dir().as_deref());
+    let template = fs::read_to_string(&path)
+        .map_err(|error| io::Error::new(error.kind(), format!("Could not read the task template {}: {error}", path.display())))?;
+    Ok(Some(substitute(&template, subject, name, today)))
+}
+
+/// Same as `new_description`, for callers that would rather go without than fail
+pub fn new_description_or_none(config: &Config, subject: &str, name: &str, today: NaiveDate) -> Option<String> {
+    new_description(config, subject, name, today).ok().flatten()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn placeholders_are_filled_in() {
+        let today = NaiveDate::from_ymd_opt(2025, 5, 12).unwrap();
+        let template = "# {subject}: {name}\n\nCreated {date}\n\n## Link\n\n## Notes\n{unknown}";
+
+        assert_eq!(
+            substitute(template, "Algorithms", "HW3", today),
+            "# Algorithms: HW3\n\nCreated 2025-05-12\n\n## Link\n\n## Notes\n{unknown}",
+        );
+
+        // Values are not filled in again
+        assert_eq!(substitute("{subject}: {name}", "Set {name}", "{date} {{x}", today), "Set {name}: {date} {{x}");
+    }
+
+    #[test]
+    fn missing_template_is_an_error_not_a_panic() {
+        let today = NaiveDate::from_ymd_opt(2025, 5, 12).unwrap();
+        let dir = tempfile::tempdir().unwrap();
+        let mut config = Config::default();
+        assert_eq!(new_description(&config, "Algo", "HW", today).unwrap(), None);
+
+        config.new_task_template = Some(dir.path().join("missing.md"));
+        let error = new_description(&config, "Algo", "HW", today).unwrap_err();
+        assert!(error.to_string().contains("missing.md"));
+        assert_eq!(new_description_or_none(&config, "Algo", "HW", today), None);
+
+        fs::write(dir.path().join("missing.md"), "{name} for {subject}").unwrap();
+        assert_eq!(new_description(&config, "Algo", "HW", today).unwrap(), Some("HW for Algo".to_string()));
+    }
+}
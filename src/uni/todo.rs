@@ -1,9 +1,5 @@
-use std::cmp::Ordering;
-use std::path::{Path, PathBuf};
 use std::io::{self, stdout, stdin, Write, BufRead};
-use std::fs;
-use chrono::format::Fixed;
-use chrono::{DateTime, Datelike, FixedOffset, Local, TimeDelta, TimeZone, Timelike}; use colored::{Colorize, ColoredString};
+use chrono::{DateTime, Datelike, FixedOffset, Local, NaiveTime, TimeZone, Timelike}; use colored::Colorize;
 
 use crossterm::{
     cursor,
@@ -11,7 +7,14 @@ use crossterm::{
     terminal::{Clear, ClearType},
 };
 
-use crate::{uni::{calendar::Calendar, task::{Task, TaskStatus}}, constants};
+use crate::config::Config;
+use crate::storages::TaskStorage;
+use crate::uni::dates::format_date;
+use crate::uni::format::status_colored;
+use crate::uni::ordering;
+use crate::uni::policy::StatusPolicy;
+use crate::uni::validation;
+use crate::{uni::{calendar::Calendar, query::TaskQuery, task::{assign_ids, Task, TaskStatus}}, constants};
 
 pub fn clear_screen() {
     // println!("\n{BOLD_SEPARATOR}");
@@ -75,7 +78,8 @@ fn ask_number_date(prefix: &str) -> Option<i32> {
     }
 }
 
-fn ask_date() -> Result<Option<DateTime<FixedOffset>>, ()> {
+/// Blank answers keep today, and a blank time is `default_time`
+fn ask_date(default_time: NaiveTime) -> Result<Option<DateTime<FixedOffset>>, ()> {
     let now = Local::now();
     let year = ask_number_date("Year: ").unwrap_or(now.year() as i32);
     let month = ask_number_date("Month: ").unwrap_or(now.month() as i32);
@@ -90,13 +94,16 @@ fn ask_date() -> Result<Option<DateTime<FixedOffset>>, ()> {
         return Err(());
     }
 
-    let hour = ask_number_date("Hour: ").unwrap_or(now.hour() as i32);
+    let typed_hour = ask_number_date("Hour: ");
+    let hour = typed_hour.unwrap_or(default_time.hour() as i32);
     if hour > 23 {
         eprintln!("Invalid hour value: {hour}");
         return Err(());
     }
 
-    let min = ask_number_date("Minute: ").unwrap_or(now.minute() as i32);
+    // A typed hour starts on the hour
+    let default_minute = if typed_hour.is_some() { 0 } else { default_time.minute() as i32 };
+    let min = ask_number_date("Minute: ").unwrap_or(default_minute);
     if min > 59 {
         eprintln!("Invalid minute value: {min}");
     }
@@ -112,54 +119,6 @@ fn ask_date() -> Result<Option<DateTime<FixedOffset>>, ()> {
     }
 }
 
-fn date_format(str: &str, task_status: &TaskStatus) -> ColoredString {
-    match task_status {
-        TaskStatus::Panic => str.red(),
-        TaskStatus::Normal => str.bright_blue(),
-        TaskStatus::Zen => str.white()
-    }
-}
-
-fn time_quantity_format(str: &str, num: i32) -> Option<String> {
-    if num == 1 || num == -1 {
-        Some(num.to_string() + " " +  str)
-    } else if num > 1 || num < -1 {
-        Some(num.to_string() + " " + str + "s")
-    } else {
-        None
-    }
-}
-
-fn duration_label(duration: &Option<TimeDelta>, long: bool) -> String {
-    if let Some(duration) = duration {
-        // Format time until the task
-        let days    = duration.num_days() as i32;
-        let hours   = duration.num_hours() as i32 - duration.num_days() as i32 * 24;
-        let minutes = duration.num_minutes() as i32 - duration.num_hours() as i32 * 60;
-
-        let days = time_quantity_format("day", days);
-        let hours = time_quantity_format("hour", hours);
-        let minutes = time_quantity_format("minute", minutes);
-
-        let mut units = vec![];
-        if let Some(days) = days {units.push(days)}
-        if let Some(hours) = hours {units.push(hours)}
-
-        if long {
-            if let Some(minutes) = minutes {units.push(minutes)}
-        }
-
-        if units.len() == 0 {
-            return "".to_owned();
-        }
-
-
-        return units.join(" ");
-    } else {
-        "∞".to_owned()
-    }
-}
-
 fn prompt_input() -> Option<Vec<String>> {
     let input = ask_with_prefix("> ");
     if input == "" {
@@ -197,22 +156,31 @@ enum TaskLayout {
 }
 
 
-pub struct Todo {
+/// Menu-driven prompts, for terminals the full-screen UI does not work on.
+/// Reads and writes through the same storage as the UI.
+pub struct Todo<'a> {
     tasks: Vec<Task>,
-    filename: PathBuf
+    storage: &'a dyn TaskStorage,
+    config: &'a Config,
 }
 
-impl Todo {
-    pub fn new(tasks: Vec<Task>, filename: PathBuf) -> Self {
-        Self {
-            tasks,
-            filename
-        }
+impl<'a> Todo<'a> {
+    pub fn new(storage: &'a dyn TaskStorage, config: &'a Config) -> io::Result<Self> {
+        Ok(Self {
+            tasks: storage.read()?,
+            storage,
+            config,
+        })
+    }
+
+    /// Same policy as the UI
+    fn status(&self, task: &Task) -> TaskStatus {
+        StatusPolicy::from_config(self.config).status(task, &Local::now().fixed_offset())
     }
 
     pub fn panic_lookup(&self) {
         for task in &self.tasks {
-            if let TaskStatus::Panic = task.get_status_now() {
+            if self.status(task).is_urgent() {
                 println!("\nStuff to do:\n");
                 self.print_tasks(TaskLayout::Panic, false);
                 break;
@@ -223,6 +191,8 @@ impl Todo {
     pub fn run(&mut self) {
         let mut state = PromptState::Start;
         loop {
+            // Whatever the step changes, the day budget is checked the same way
+            let before = self.tasks.clone();
             state = match &state {
                 PromptState::Start => self.start_menu(),
                 PromptState::Add => self.add_menu(),
@@ -230,19 +200,24 @@ impl Todo {
                 PromptState::Delete => self.delete_menu(),
                 PromptState::Check => self.check_menu(),
                 PromptState::Sort => {
-                    self.sort_tasks();
+                    ordering::sort(&mut self.tasks, &Local::now().fixed_offset());
                     self.print_tasks(TaskLayout::Headers, true);
                     PromptState::Start
                 }
                 PromptState::Write => {
-                    match self.save() {
-                        Ok(_) => println!("Successfully written!"),
-                        _ => println!("Could not write!")
-                    }
-                    println!();
+                    self.save();
                     PromptState::Start
                 }
-                PromptState::Quit => break
+                PromptState::Quit => {
+                    if self.storage.should_save(&self.tasks) {
+                        self.save();
+                    }
+                    break
+                }
+            };
+            let names = self.config.ui.date_names();
+            for overload in validation::day_overloads_between(&before, &self.tasks, &self.config.validation) {
+                eprintln!("Warning: {}", overload.describe(&names));
             }
         }
     }
@@ -299,9 +274,19 @@ impl Todo {
                     .map(|s| s.as_str())
                     .unwrap_or("m");
 
-                let calendar = Calendar::new(Local::now(), &self.tasks);
+                let calendar = Calendar::new(Local::now(), &self.tasks)
+                    .with_status_symbols(self.config.ui.status_symbols)
+                    .with_abbreviations(self.config.subject_abbrev.clone())
+                    .with_names(self.config.ui.date_names())
+                    .with_policy(StatusPolicy::from_config(self.config))
+                    .with_day_limit(self.config.validation.max_tasks_per_day)
+                    .with_filter(self.config.ui.calendar_include);
                 match command {
-                    "month" | "m"   => calendar.render(),
+                    "month" | "m"   => {
+                        calendar.render();
+                        println!();
+                        calendar.render_agenda();
+                    },
                     "3"             => calendar.render3(),
                     "year"  | "y"   => calendar.render_year(),
                     _ => ()
@@ -343,7 +328,7 @@ impl Todo {
 
         let time = match date_request.trim().to_lowercase().as_str() {
             "yes" | "y" | "" => {
-                match ask_date() {
+                match ask_date(self.config.deadline_time()) {
                     Ok(date) => date,
                     Err(_) => return PromptState::Start,
                 }
@@ -353,19 +338,20 @@ impl Todo {
 
 
         let complete = ask_with_prefix("Complete: ");
-        let complete: bool = match complete.trim().to_lowercase().parse() {
-            Ok(complete) => complete,
-            Err(_) => false
-        };
+        let complete: bool = complete.trim().to_lowercase().parse().unwrap_or(false);
+
+        let starred = ask_with_prefix("Starred: ");
+        let starred: bool = starred.trim().to_lowercase().parse().unwrap_or(false);
 
-        let task = Task {
+        let mut task = Task {
             name:           name.trim().to_owned(),
             description:    description.trim().to_owned(),
             subject:        subject.trim().to_owned(),
             time,
-            complete,
-            starred: false,
+            starred,
+            ..Task::new_now()
         };
+        task.set_complete(complete);
 
         println!("{}\n{:?}\n{}",
             constants::SEPARATOR,
@@ -375,7 +361,10 @@ impl Todo {
 
         let answer = ask_with_prefix("Are you sure you want to add such task? (Y/n): ");
         match answer.trim().to_lowercase().as_str() {
-            "yes" | "y" | "" => self.tasks.push(task),
+            "yes" | "y" | "" => {
+                self.tasks.push(task);
+                assign_ids(&mut self.tasks);
+            }
             _ => ()
         }
 
@@ -423,7 +412,7 @@ impl Todo {
         match date_request.trim().to_lowercase().as_str() {
             "yes" | "y" | "" => (),
             _ => {
-                prototype.time = match ask_date() {
+                prototype.time = match ask_date(self.config.deadline_time()) {
                     Ok(date) => date,
                     Err(_) => return PromptState::Start,
                 }
@@ -434,10 +423,12 @@ impl Todo {
         let complete = ask_with_prefix("Complete: ");
 
         if !complete.trim().is_empty() {
-            prototype.complete = match complete.trim().to_lowercase().parse() {
-                Ok(complete) => complete,
-                Err(_) => false
-            };
+            prototype.set_complete(complete.trim().to_lowercase().parse().unwrap_or(false));
+        }
+
+        let starred = ask_with_prefix("Starred: ");
+        if !starred.trim().is_empty() {
+            prototype.starred = starred.trim().to_lowercase().parse().unwrap_or(false);
         }
 
         println!("{}\n{:?}\n{}",
@@ -448,7 +439,10 @@ impl Todo {
 
         let answer = ask_with_prefix("Are you sure you want to accept the changes? (Y/n): ");
         match answer.trim().to_lowercase().as_str() {
-            "yes" | "y" | "" => self.tasks[index] = prototype,
+            "yes" | "y" | "" => {
+                prototype.touch(Local::now().fixed_offset());
+                self.tasks[index] = prototype;
+            },
             _ => ()
         }
 
@@ -475,7 +469,9 @@ impl Todo {
         println!("Which task would you like to check?");
 
         if let Some(index) = ask_index(&self.tasks) {
-            self.tasks[index as usize].complete ^= true;
+            let task = &mut self.tasks[index];
+            task.set_complete(!task.complete);
+            task.touch(Local::now().fixed_offset());
         }
 
         self.print_tasks(TaskLayout::Headers, true);
@@ -483,60 +479,21 @@ impl Todo {
         PromptState::Start
     }
 
-    pub fn save(&self) -> io::Result<()> {
+    /// Asks first, reports how the write went
+    fn save(&self) {
         let answer = ask_with_prefix("\nDo you want to save the tasks? (Y/n): ");
-        match answer.trim().to_lowercase().as_str() {
-            "y" | "yes" | "" => {
-                println!("Saving tasks to the {}...", constants::TABLE_NAME);
-                self.write_tasks(&self.filename)
-            }
-            _ => Ok(())
+        if !matches!(answer.trim().to_lowercase().as_str(), "y" | "yes" | "") {
+            return;
         }
-    }
-
-    fn sort_tasks(&mut self) {
-        self.tasks.sort_by(|task1, task2| {
-            if task1.complete && !task2.complete {
-                return Ordering::Less;
-            } else if !task1.complete && task2.complete {
-                return Ordering::Greater;
-            }
-
-            if task1.time.is_some() && task2.time.is_none() {
-                return Ordering::Less;
-            } else if task1.time.is_none() && task2.time.is_some() {
-                return Ordering::Greater;
-            } else if task1.time.is_none() && task2.time.is_none() {
-                return Ordering::Equal;
-            }
-
-            task1.time
-                .unwrap()
-                .partial_cmp(&task2.time.unwrap())
-                .expect("Could not perform the comparison")
-        });
-    }
-
-    fn write_tasks(&self, path_to_file: &Path) -> io::Result<()> {
-        let mut to_write = "".to_owned();
-
-        for task in &self.tasks {
-            to_write.push_str(&task.name);
-            to_write.push(',');
-            to_write.push_str(&task.description);
-            to_write.push(',');
-            to_write.push_str(&task.subject);
-            to_write.push(',');
-            // to_write.push_str(&task.time.to_rfc3339());
-            to_write.push_str(&task.time.map_or("None".to_owned(), |v| v.to_rfc3339()));
-            to_write.push(',');
-            to_write.push_str(&task.complete.to_string());
-            to_write.push('\n');
+        match self.write() {
+            Ok(()) => println!("Successfully written!\n"),
+            Err(error) => eprintln!("Could not write: {error}\n"),
         }
+    }
 
-        fs::write(path_to_file, to_write)?;
-
-        Ok(())
+    /// Through the storage, so the file comes out exactly as the UI writes it
+    pub fn write(&self) -> io::Result<()> {
+        self.storage.write(&self.tasks)
     }
 
     fn print_tasks(&self, task_layout: TaskLayout, clear: bool) {
@@ -546,43 +503,29 @@ impl Todo {
 
         if matches!(task_layout, TaskLayout::Headers) {
             for (i, task) in self.tasks.iter().enumerate() {
-                let duration = task.get_delta_now();
-                let task_status = task.get_status(&duration);
-                let mut name_and_time = task.name.clone();
-                name_and_time.push_str(" (");
-                name_and_time.push_str(&duration_label(&duration, false));
-                name_and_time.push(')');
-
-                let name_and_time = date_format(&name_and_time, &task_status);
-                println!("{}. {}", i + 1, &name_and_time);
+                let star = if task.starred { " ★" } else { "" };
+                let name_and_time = format!("{}{star} ({})", task.name, task.delta());
+                println!("{}. {}", i + 1, status_colored(&name_and_time, &self.status(task)));
             }
             println!("{}", constants::SEPARATOR);
             return;
         }
 
 
-        let predicate: Box<dyn Fn(&Task) -> bool> = match task_layout {
-            TaskLayout::All => Box::new(|_| true),
-            TaskLayout::Normal =>
-                Box::new(
-                    |v| matches!(v.get_status_now(), TaskStatus::Normal)),
-            TaskLayout::Zen =>
-                Box::new(
-                    |v| matches!(v.get_status_now(), TaskStatus::Zen)),
-            TaskLayout::Panic =>
-                Box::new(
-                    |v| matches!(v.get_status_now(), TaskStatus::Panic)),
-            TaskLayout::Relevant =>
-                Box::new(
-                    |v| matches!(v.get_status_now(), TaskStatus::Panic
-                                 | TaskStatus::Normal)),
-            TaskLayout::Headers => Box::new(|_| false)
+        let query = TaskQuery::new(&self.tasks, Local::now().fixed_offset())
+            .policy(StatusPolicy::from_config(self.config));
+        let query = match task_layout {
+            TaskLayout::All => query,
+            TaskLayout::Normal => query.status([TaskStatus::Normal]),
+            TaskLayout::Zen => query.status([TaskStatus::Zen]),
+            TaskLayout::Panic => query.status([TaskStatus::Panic, TaskStatus::DueToday]),
+            TaskLayout::Relevant => query.status([TaskStatus::Panic, TaskStatus::DueToday, TaskStatus::Normal]),
+            TaskLayout::Headers => query.matching(|_| false),
         };
 
-        for task in self.tasks.iter().filter(|v| predicate(v)) {
-            // Get the task status
-            let duration = task.get_delta_now();
-            let task_status = task.get_status(&duration);
+        let names = self.config.ui.date_names();
+        for task in query.iter() {
+            let task_status = self.status(task);
 
             // Format completion status
             let complete_str = match task.complete {
@@ -595,17 +538,43 @@ impl Todo {
             println!("{}", task.description);
             println!("{}", task.subject);
 
-            let duration_text = duration_label(&duration, true);
-            let duration_text = date_format(&duration_text, &task_status);
-            println!("{}", duration_text);
+            println!("{}", status_colored(&task.delta(), &task_status));
 
             if let Some(time) = task.time {
-                println!("{}", time.format("%H:%M %d %B %Y"));
+                println!("{}", format_date(&time, &self.config.ui.date_display_format, &names, &self.config.ui.display_zone()));
             }
 
             // Complete / incomplete
             println!("Completion: {complete_str}");
+            println!("Starred: {}", if task.starred { "yes" } else { "no" });
             println!("{}", constants::SEPARATOR);
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::storages::TomlStorage;
+
+    #[test]
+    fn writes_go_through_the_storage() {
+        let golden = include_str!("../../tests/fixtures/golden.toml");
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("tasks.toml");
+        std::fs::write(&path, golden).unwrap();
+        let storage = TomlStorage::new(path.clone());
+        let config = Config::default();
+
+        let mut todo = Todo::new(&storage, &config).unwrap();
+        assert!(!storage.should_save(&todo.tasks));
+        todo.write().unwrap();
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), golden);
+
+        todo.tasks[0].starred = !todo.tasks[0].starred;
+        assert!(storage.should_save(&todo.tasks));
+        todo.write().unwrap();
+        assert_eq!(storage.read().unwrap()[0].starred, todo.tasks[0].starred);
+    }
+}
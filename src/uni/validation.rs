@@ -0,0 +1,352 @@
+use std::collections::{BTreeMap, HashMap};
+
+use chrono::{DateTime, FixedOffset, NaiveDate, TimeDelta};
+
+use crate::config::ValidationConfig;
+use crate::uni::calendar::tasks_by_date;
+use crate::uni::format::largest_unit;
+use crate::uni::locale::DateNames;
+use crate::uni::task::Task;
+
+
+/// Two incomplete tasks of the same subject due suspiciously close to each other.
+/// Indices point into the validated slice, the earlier task goes first.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DeadlineConflict {
+    pub first: usize,
+    pub second: usize,
+}
+
+impl DeadlineConflict {
+    pub fn involves(&self, index: usize) -> bool {
+        self.first == index || self.second == index
+    }
+
+    pub fn describe(&self, tasks: &[Task]) -> String {
+        let (first, second) = (&tasks[self.first], &tasks[self.second]);
+        let format_time = |task: &Task| task
+            .time
+            .map(|x| x.format("%Y-%m-%d %H:%M").to_string())
+            .unwrap_or_default();
+        format!(
+            "{}: {} ({}) <-> {} ({})",
+            first.subject,
+            first.name,
+            format_time(first),
+            second.name,
+            format_time(second),
+        )
+    }
+}
+
+/// Reason to confirm a deadline set too far in the past, likely a typo in the year.
+/// Every path creating or changing a deadline goes through here; a deadline left as it was
+/// is never questioned, tasks do become overdue on their own.
+pub fn past_deadline_warning(
+    previous: Option<DateTime<FixedOffset>>,
+    deadline: Option<DateTime<FixedOffset>>,
+    now: &DateTime<FixedOffset>,
+    config: &ValidationConfig,
+) -> Option<String> {
+    let deadline = deadline.filter(|x| Some(*x) != previous)?;
+    let ago = *now - deadline;
+    if ago <= TimeDelta::minutes(config.past_deadline_margin_minutes) {
+        return None;
+    }
+
+    Some(format!("Deadline is {} in the past", largest_unit(ago)))
+}
+
+/// A day with more incomplete tasks due than `max_tasks_per_day`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DayOverload {
+    pub date: NaiveDate,
+    pub count: usize,
+    pub limit: usize,
+}
+
+impl DayOverload {
+    /// "Thu 6 Mar now has 4 tasks due, over the limit of 3"
+    pub fn describe(&self, names: &DateNames) -> String {
+        let day = self.date.format(&names.localize("%a %-d %b", &self.date)).to_string();
+        format!("{day} now has {} tasks due, over the limit of {}", self.count, self.limit)
+    }
+}
+
+/// Incomplete tasks due on each local date, days with none left out
+pub fn open_tasks_per_day(tasks: &[Task]) -> BTreeMap<NaiveDate, usize> {
+    tasks_by_date(tasks)
+        .into_iter()
+        .map(|(date, tasks)| (date, tasks.iter().filter(|x| !x.complete).count()))
+        .filter(|(_, count)| *count > 0)
+        .collect()
+}
+
+/// Days a change took over `max_tasks_per_day`, or further over it, given the counts of
+/// `open_tasks_per_day` before and after. Every path adding tasks or moving deadlines goes
+/// through here; a day that was over the limit already and stayed as it was is not brought up again.
+pub fn day_overloads(
+    before: &BTreeMap<NaiveDate, usize>,
+    after: &BTreeMap<NaiveDate, usize>,
+    config: &ValidationConfig,
+) -> Vec<DayOverload> {
+    let Some(limit) = config.max_tasks_per_day else { return vec![] };
+    after
+        .iter()
+        .filter(|(date, count)| **count > limit && **count > before.get(*date).copied().unwrap_or(0))
+        .map(|(date, count)| DayOverload { date: *date, count: *count, limit })
+        .collect()
+}
+
+/// `day_overloads` between two versions of the tasks, for the commands writing them
+pub fn day_overloads_between(before: &[Task], after: &[Task], config: &ValidationConfig) -> Vec<DayOverload> {
+    if config.max_tasks_per_day.is_none() {
+        return vec![];
+    }
+    day_overloads(&open_tasks_per_day(before), &open_tasks_per_day(after), config)
+}
+
+/// Lowercased with runs of whitespace collapsed, "HW  3" and "hw 3" compare equal
+fn normalize(text: &str) -> String {
+    text.split_whitespace().collect::<Vec<_>>().join(" ").to_lowercase()
+}
+
+/// Edit distance counting single-character insertions, deletions and substitutions
+pub fn levenshtein(a: &str, b: &str) -> usize {
+    let b = b.chars().collect::<Vec<_>>();
+    let mut row = (0..=b.len()).collect::<Vec<_>>();
+    for (i, x) in a.chars().enumerate() {
+        let mut diagonal = row[0];
+        row[0] = i + 1;
+        for (j, y) in b.iter().enumerate() {
+            let next = (row[j + 1] + 1).min(row[j] + 1).min(diagonal + usize::from(x != *y));
+            diagonal = row[j + 1];
+            row[j + 1] = next;
+        }
+    }
+    row[b.len()]
+}
+
+/// Incomplete tasks of the same subject whose name is within `duplicate_max_distance` edits of `task`'s.
+/// Names only the numbers in them tell apart, "HW1" and "HW3" or "Lab" and "Lab 2", are
+/// siblings and not duplicates. Empty when duplicate detection is off.
+pub fn find_similar<'a>(tasks: &'a [Task], task: &Task, config: &ValidationConfig) -> Vec<&'a Task> {
+    if !config.warn_duplicates {
+        return vec![];
+    }
+    let (subject, name) = (normalize(&task.subject), normalize(&task.name));
+    tasks
+        .iter()
+        // A task already in the list is not its own duplicate
+        .filter(|x| !x.complete && (task.id == 0 || x.id != task.id))
+        .filter(|x| normalize(&x.subject) == subject)
+        .filter(|x| levenshtein(&normalize(&x.name), &name) <= config.duplicate_max_distance)
+        .filter(|x| !numbered_siblings(&normalize(&x.name), &name))
+        .collect()
+}
+
+/// The same name but for different numbers
+fn numbered_siblings(name1: &str, name2: &str) -> bool {
+    let split = |name: &str| {
+        let (digits, rest): (String, String) = name.chars().partition(|c| c.is_ascii_digit());
+        (digits, rest.split_whitespace().collect::<Vec<_>>().join(" "))
+    };
+    let ((digits1, rest1), (digits2, rest2)) = (split(name1), split(name2));
+    digits1 != digits2 && rest1 == rest2
+}
+
+/// Tasks sharing a subject and a name once case and whitespace are ignored
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DuplicateGroup {
+    /// Oldest first
+    pub ids: Vec<u64>,
+    /// The deadlines differ, as with a homework due every week
+    pub possibly_intentional: bool,
+}
+
+impl DuplicateGroup {
+    pub fn describe(&self, tasks: &[Task]) -> String {
+        let Some(task) = tasks.iter().find(|x| x.id == self.ids[0]) else { return String::new() };
+        let note = if self.possibly_intentional { ", different deadlines — possibly intentional" } else { "" };
+        format!("{}: {} ({} copies{note})", task.subject.trim(), task.name.trim(), self.ids.len())
+    }
+}
+
+/// Every group of same subject and name, exact copies first
+pub fn find_duplicates(tasks: &[Task]) -> Vec<DuplicateGroup> {
+    let mut by_key: HashMap<(String, String), Vec<&Task>> = HashMap::new();
+    for task in tasks {
+        by_key.entry((normalize(&task.subject), normalize(&task.name))).or_default().push(task);
+    }
+
+    let mut groups = by_key
+        .into_values()
+        .filter(|x| x.len() > 1)
+        .map(|mut group| {
+            group.sort_by_key(|x| x.id);
+            DuplicateGroup {
+                possibly_intentional: group.iter().any(|x| x.time != group[0].time),
+                ids: group.iter().map(|x| x.id).collect(),
+            }
+        })
+        .collect::<Vec<_>>();
+    groups.sort_by_key(|x| (x.possibly_intentional, x.ids[0]));
+    groups
+}
+
+pub fn find_deadline_conflicts(tasks: &[Task], config: &ValidationConfig) -> Vec<DeadlineConflict> {
+    let window = TimeDelta::minutes(config.conflict_window_minutes);
+
+    let mut by_subject: HashMap<&str, Vec<usize>> = HashMap::new();
+    for (i, task) in tasks.iter().enumerate() {
+        if !task.complete && task.time.is_some() {
+            by_subject.entry(task.subject.as_str()).or_default().push(i);
+        }
+    }
+
+    let mut conflicts = vec![];
+    for indices in by_subject.values_mut() {
+        indices.sort_by_key(|&i| tasks[i].time);
+
+        // Sorted by time, so every partner of `i` is within the following run
+        for (pos, &i) in indices.iter().enumerate() {
+            let time = tasks[i].time.unwrap();
+            for &j in indices[pos + 1..].iter() {
+                if tasks[j].time.unwrap() - time >= window {
+                    break;
+                }
+                conflicts.push(DeadlineConflict { first: i, second: j });
+            }
+        }
+    }
+
+    conflicts.sort_by_key(|x| (x.first, x.second));
+    conflicts
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn at(time: &str) -> Option<DateTime<FixedOffset>> {
+        Some(DateTime::parse_from_rfc3339(time).unwrap())
+    }
+
+    #[test]
+    fn deadlines_set_in_the_past_need_confirmation() {
+        let now = at("2025-05-12T18:00:00Z").unwrap();
+        let config = ValidationConfig::default();
+        let warning = |previous, deadline| past_deadline_warning(previous, deadline, &now, &config);
+
+        assert_eq!(
+            warning(None, at("2024-12-21T18:00:00Z")).as_deref(),
+            Some("Deadline is 142 days in the past"),
+        );
+        assert_eq!(
+            warning(at("2025-05-20T18:00:00Z"), at("2025-05-12T13:00:00Z")).as_deref(),
+            Some("Deadline is 5 hours in the past"),
+        );
+        // Within the margin, unchanged or absent
+        assert_eq!(warning(None, at("2025-05-12T17:30:00Z")), None);
+        assert_eq!(warning(at("2024-12-21T18:00:00Z"), at("2024-12-21T18:00:00Z")), None);
+        assert_eq!(warning(at("2024-12-21T18:00:00Z"), None), None);
+    }
+
+    #[test]
+    fn days_taken_over_the_limit_are_reported_once() {
+        use chrono::{Local, TimeZone};
+
+        // Noon here, so each task falls on the day it says whatever the machine's zone
+        let noon = |day| Some(Local.with_ymd_and_hms(2025, 3, day, 12, 0, 0).unwrap().fixed_offset());
+        let task = |day, complete| Task { time: noon(day), complete, ..Task::default() };
+        let config = ValidationConfig { max_tasks_per_day: Some(2), ..ValidationConfig::default() };
+        let mut before = vec![task(6, false), task(6, false), task(6, true), task(7, false)];
+
+        // Completed tasks take no room
+        let counts = open_tasks_per_day(&before);
+        assert_eq!(counts.values().copied().collect::<Vec<_>>(), [2, 1]);
+        assert!(day_overloads_between(&before, &before, &config).is_empty());
+
+        let mut after = before.clone();
+        after.push(task(6, false));
+        after.push(task(7, false));
+        let overloads = day_overloads_between(&before, &after, &config);
+        assert_eq!(overloads.len(), 1);
+        assert_eq!(overloads[0].describe(&DateNames::default()), "Thu 6 Mar now has 3 tasks due, over the limit of 2");
+
+        // Already over and left alone, or eased, says nothing; going further over does
+        before = after.clone();
+        after[4].time = noon(8);
+        assert!(day_overloads_between(&before, &after, &config).is_empty());
+        after.push(task(6, false));
+        assert!(day_overloads_between(&before, &after, &config).is_empty());
+        after.push(task(6, false));
+        assert_eq!(day_overloads_between(&before, &after, &config)[0].count, 4);
+
+        // Off without a limit
+        assert!(day_overloads_between(&[], &after, &ValidationConfig::default()).is_empty());
+    }
+
+    #[test]
+    fn near_identical_names_of_the_same_subject_are_similar() {
+        assert_eq!(levenshtein("kitten", "sitting"), 3);
+        assert_eq!(levenshtein("", "hw"), 2);
+
+        let task = |id, subject: &str, name: &str, complete| Task {
+            id,
+            subject: subject.to_string(),
+            name: name.to_string(),
+            complete,
+            ..Task::default()
+        };
+        let tasks = vec![
+            task(1, "Algorithms", "HW 3", false),
+            task(2, "Algorithms", "HW 3", true),
+            task(3, "Physics", "HW 3", false),
+            task(4, "Algorithms", "Exam", false),
+        ];
+        let config = ValidationConfig::default();
+        let similar = |name| find_similar(&tasks, &task(0, " algorithms", name, false), &config)
+            .iter()
+            .map(|x| x.id)
+            .collect::<Vec<_>>();
+
+        assert_eq!(similar("hw  3"), [1]);
+        assert_eq!(similar("HW3"), [1]);
+        assert!(similar("Project").is_empty());
+        // Numbered siblings are not duplicates, a typo next to a number still is
+        assert!(similar("HW 4").is_empty());
+        assert!(similar("Exam 2").is_empty());
+        assert_eq!(similar("HV 3"), [1]);
+        assert!(find_similar(&tasks, &tasks[0], &config).is_empty());
+
+        let off = ValidationConfig { warn_duplicates: false, ..ValidationConfig::default() };
+        assert!(find_similar(&tasks, &task(0, "Algorithms", "HW 3", false), &off).is_empty());
+    }
+
+    #[test]
+    fn duplicates_ignore_case_and_whitespace() {
+        let task = |id, subject: &str, name: &str, time| Task {
+            id,
+            subject: subject.to_string(),
+            name: name.to_string(),
+            time: at(time),
+            ..Task::default()
+        };
+        let tasks = vec![
+            task(1, "Physics", "Lab", "2025-05-12T18:00:00Z"),
+            task(2, "Algo", "Homework", "2025-05-12T18:00:00Z"),
+            task(3, "physics ", "LAB  ", "2025-05-12T18:00:00Z"),
+            task(4, "Algo", "homework", "2025-05-19T18:00:00Z"),
+            task(5, "Algo", "Homework 2", "2025-05-19T18:00:00Z"),
+        ];
+
+        let groups = find_duplicates(&tasks);
+        assert_eq!(groups, [
+            DuplicateGroup { ids: vec![1, 3], possibly_intentional: false },
+            DuplicateGroup { ids: vec![2, 4], possibly_intentional: true },
+        ]);
+        assert_eq!(groups[0].describe(&tasks), "Physics: Lab (2 copies)");
+        assert!(groups[1].describe(&tasks).contains("possibly intentional"));
+    }
+}